@@ -0,0 +1,34 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+// Exposes the target triple, and its Debian multiarch tuple on Linux, for `OpenOptions::multiarch`.
+
+use std::env;
+
+// Converts a target triple, such as `armv7-unknown-linux-gnueabihf`, to its multiarch tuple, such
+// as `arm-linux-gnueabihf`.
+fn multiarch(target: &str) -> Option<String> {
+	let mut parts = target.split('-');
+	let arch = parts.next()?;
+	let (os, env) = match (parts.next()?, parts.next()?, parts.next()) {
+		(_, "linux", Some(env)) => ("linux", env),
+		("linux", env, None) => ("linux", env),
+		_ => return None,
+	};
+	let arch = match arch {
+		"i586" | "i686" => "i386",
+		"riscv64gc" => "riscv64",
+		arch if arch.starts_with("arm") || arch.starts_with("thumb") => "arm",
+		arch => arch,
+	};
+	Some(format!("{arch}-{os}-{env}"))
+}
+
+fn main() {
+	println!("cargo:rerun-if-changed=build.rs");
+	let target = env::var("TARGET").unwrap();
+	if let Some(tuple) = multiarch(&target) {
+		println!("cargo:rustc-env=DYLINK_MULTIARCH={tuple}");
+	}
+	println!("cargo:rustc-env=DYLINK_TARGET={target}");
+}
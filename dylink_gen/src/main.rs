@@ -0,0 +1,229 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Generates a Rust module of `#[dylink]` declarations from the exports of a dynamic library.
+
+use dylink::img;
+use std::{
+	collections::{
+		BTreeMap,
+		BTreeSet,
+	},
+	env,
+	fmt::Write as _,
+	fs,
+	io,
+	path::PathBuf,
+	process::ExitCode,
+};
+
+const USAGE: &str = "usage: dylink-gen <LIBRARY> [--name <STATIC>] [--abi <ABI>] [--out <FILE>]";
+
+// Keywords are rejected as function names, and raw identifiers would break `link_name` inference.
+const KEYWORDS: &[&str] = &[
+	"Self", "abstract", "as", "async", "await", "become", "box", "break", "const", "continue",
+	"crate", "do", "dyn", "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if",
+	"impl", "in", "let", "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub",
+	"ref", "return", "self", "static", "struct", "super", "trait", "true", "try", "type", "typeof",
+	"unsafe", "unsized", "use", "virtual", "where", "while", "yield",
+];
+
+struct Args {
+	library: PathBuf,
+	name: String,
+	abi: String,
+	out: Option<PathBuf>,
+}
+
+#[derive(Default)]
+struct Report {
+	functions: usize,
+	renamed: Vec<(String, String)>,
+	data: Vec<String>,
+	unnamed: Vec<u32>,
+}
+
+fn parse_args() -> Result<Args, String> {
+	let mut args = env::args_os().skip(1);
+	let mut library = None;
+	let mut name = None;
+	let mut abi = None;
+	let mut out = None;
+	while let Some(arg) = args.next() {
+		match arg.to_str() {
+			Some("--name") => {
+				name = args.next().and_then(|s| s.into_string().ok());
+			}
+			Some("--abi") => {
+				abi = args.next().and_then(|s| s.into_string().ok());
+			}
+			Some("--out") => {
+				out = args.next().map(PathBuf::from);
+			}
+			Some("-h" | "--help") => return Err(USAGE.to_owned()),
+			Some(flag) if flag.starts_with("--") => {
+				return Err(format!("unknown option `{flag}`\n{USAGE}"));
+			}
+			_ if library.is_none() => library = Some(PathBuf::from(arg)),
+			_ => return Err(USAGE.to_owned()),
+		}
+	}
+	let Some(library) = library else {
+		return Err(USAGE.to_owned());
+	};
+	Ok(Args {
+		library,
+		name: name.unwrap_or_else(|| "LIB".to_owned()),
+		abi: abi.unwrap_or_else(|| "C-unwind".to_owned()),
+		out,
+	})
+}
+
+/// Converts an export name into a valid Rust identifier, if it isn't one already.
+fn to_ident(name: &str) -> String {
+	let mut ident: String = name
+		.chars()
+		.map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+		.collect();
+	if ident.is_empty() || ident.starts_with(|c: char| c.is_ascii_digit()) {
+		ident.insert(0, '_');
+	}
+	if KEYWORDS.contains(&ident.as_str()) || ident == "_" {
+		ident.push('_');
+	}
+	ident
+}
+
+fn generate(args: &Args, exports: &[img::Export]) -> (String, Report) {
+	let mut report = Report::default();
+	// deduplicate names, since versioned ELF symbols may be exported more than once.
+	let mut functions = BTreeMap::new();
+	for export in exports {
+		let Some(name) = export.name() else {
+			if let Some(ordinal) = export.ordinal() {
+				report.unnamed.push(ordinal);
+			}
+			continue;
+		};
+		match export.kind() {
+			img::ExportKind::Data | img::ExportKind::Tls => report.data.push(name.to_owned()),
+			_ => {
+				functions.insert(name.to_owned(), to_ident(name));
+			}
+		}
+	}
+	report.data.sort();
+	report.data.dedup();
+	// names that are already identifiers keep them, and renamed functions that would collide with
+	// another function, such as `a.b` with `a_b`, get a numeric suffix.
+	let mut used: BTreeSet<String> = functions
+		.iter()
+		.filter(|(name, ident)| name == ident)
+		.map(|(_, ident)| ident.clone())
+		.collect();
+	for (name, ident) in &mut functions {
+		if name == ident {
+			continue;
+		}
+		let base = ident.clone();
+		let mut suffix = 1;
+		while !used.insert(ident.clone()) {
+			*ident = format!("{base}_{suffix}");
+			suffix += 1;
+		}
+	}
+
+	let file_name = args
+		.library
+		.file_name()
+		.map(|s| s.to_string_lossy().into_owned())
+		.unwrap_or_default();
+	let abi = &args.abi;
+	let lib = &args.name;
+
+	let mut out = String::new();
+	let _ = writeln!(out, "// Generated by dylink-gen from `{file_name}`.");
+	let _ = writeln!(
+		out,
+		"// Signatures are unknown, so every function is declared as `fn()`. Review each declaration before use."
+	);
+	let _ = writeln!(out);
+	let _ = writeln!(out, "use dylink::*;");
+	let _ = writeln!(out);
+	let _ = writeln!(
+		out,
+		"pub static {lib}: sync::LibLock = sync::LibLock::new(&[{file_name:?}]);"
+	);
+	let _ = writeln!(out);
+	let _ = writeln!(out, "#[dylink(library = {lib})]");
+	let _ = writeln!(out, "extern {abi:?} {{");
+	for (name, ident) in &functions {
+		if name == ident {
+			let _ = writeln!(out, "\tpub fn {ident}();");
+			report.functions += 1;
+		}
+	}
+	let _ = writeln!(out, "}}");
+	for (name, ident) in &functions {
+		if name != ident {
+			let _ = writeln!(out);
+			let _ = writeln!(out, "#[dylink(library = {lib}, link_name = {name:?})]");
+			let _ = writeln!(out, "pub extern {abi:?} fn {ident}();");
+			report.functions += 1;
+			report.renamed.push((name.clone(), ident.clone()));
+		}
+	}
+	(out, report)
+}
+
+fn print_report(report: &Report) {
+	eprintln!("emitted {} functions", report.functions);
+	if !report.renamed.is_empty() {
+		eprintln!("renamed {} functions:", report.renamed.len());
+		for (name, ident) in &report.renamed {
+			eprintln!("    {name} -> {ident}");
+		}
+	}
+	if !report.data.is_empty() {
+		eprintln!("skipped {} data exports:", report.data.len());
+		for name in &report.data {
+			eprintln!("    {name}");
+		}
+	}
+	if !report.unnamed.is_empty() {
+		eprintln!(
+			"skipped {} exports without names (ordinals {:?})",
+			report.unnamed.len(),
+			report.unnamed
+		);
+	}
+}
+
+fn run(args: &Args) -> io::Result<()> {
+	let file = img::File::open(&args.library)?;
+	let exports = file.exports()?;
+	let (module, report) = generate(args, &exports);
+	match &args.out {
+		Some(path) => fs::write(path, module)?,
+		None => print!("{module}"),
+	}
+	print_report(&report);
+	Ok(())
+}
+
+fn main() -> ExitCode {
+	let args = match parse_args() {
+		Ok(args) => args,
+		Err(msg) => {
+			eprintln!("{msg}");
+			return ExitCode::FAILURE;
+		}
+	};
+	match run(&args) {
+		Ok(()) => ExitCode::SUCCESS,
+		Err(err) => {
+			eprintln!("dylink-gen: {}: {err}", args.library.display());
+			ExitCode::FAILURE
+		}
+	}
+}
@@ -0,0 +1,55 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::{
+	collections::BTreeSet,
+	fs,
+	process::Command,
+};
+
+#[path = "../../tests/fixtures/mod.rs"]
+mod fixtures;
+
+#[test]
+fn test_generate_fixture() {
+	let out = fixtures::path().with_file_name("fixture_gen.rs");
+	let output = Command::new(env!("CARGO_BIN_EXE_dylink-gen"))
+		.arg(fixtures::path())
+		.args(["--name", "FIXTURE", "--abi", "C"])
+		.arg("--out")
+		.arg(&out)
+		.output()
+		.unwrap();
+	assert!(output.status.success());
+	let module = fs::read_to_string(&out).unwrap();
+	let report = String::from_utf8(output.stderr).unwrap();
+
+	let file_name = format!("{:?}", fixtures::FILE_NAME);
+	assert!(module.contains(&format!(
+		"pub static FIXTURE: sync::LibLock = sync::LibLock::new(&[{file_name}]);"
+	)));
+	assert!(module.contains("#[dylink(library = FIXTURE)]\nextern \"C\" {"));
+	assert!(module.contains("\tpub fn fixture_add();"));
+	assert!(module.contains("\tpub fn FixtureSub();"));
+
+	// data exports are reported rather than declared.
+	assert!(!module.contains("fixture_data"));
+	assert!(report.contains("data exports:\n    fixture_data\n"));
+
+	// every declared function has its own identifier.
+	let idents: Vec<&str> = module
+		.lines()
+		.filter_map(|line| line.trim_start().split_once(" fn ")?.1.strip_suffix("();"))
+		.collect();
+	assert_eq!(idents.iter().collect::<BTreeSet<_>>().len(), idents.len());
+
+	#[cfg(not(target_env = "msvc"))]
+	{
+		assert!(module.contains("\tpub fn fixture_dotted();"));
+		assert!(module.contains(
+			"#[dylink(library = FIXTURE, link_name = \"fixture.dotted\")]\npub extern \"C\" fn \
+			 fixture_dotted_1();"
+		));
+		assert!(report.contains("    fixture.dotted -> fixture_dotted_1\n"));
+	}
+}
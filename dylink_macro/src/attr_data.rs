@@ -8,18 +8,213 @@ use syn::{
 	*,
 };
 
+// The arguments of `dylink`, listed when an argument isn't recognized.
+const KEYWORDS: &[&str] = &[
+	"library",
+	"allow_improper_ctypes",
+	"checked",
+	"cxx_name",
+	"eager",
+	"egl",
+	"fallback",
+	"link_case",
+	"link_name",
+	"link_ordinal",
+	"link_prefix",
+	"link_suffix",
+	"loader",
+	"nullable",
+	"on_error",
+	"opengl",
+	"optional",
+	"ordering",
+	"probe",
+	"shared",
+	"stub",
+	"table",
+	"validate",
+	"version",
+	"vulkan",
+	"win32_flags",
+];
+
+fn expected_kw() -> String {
+	let (last, keywords) = KEYWORDS.split_last().unwrap();
+	format!("Expected `{}`, or `{last}`.", keywords.join("`, `"))
+}
+
+// The case of a symbol name, converted from a `snake_case` item name.
+#[derive(Clone, Copy)]
+pub enum LinkCase {
+	Camel,
+	Pascal,
+	Upper,
+}
+
+impl LinkCase {
+	fn apply(self, name: &str) -> String {
+		if let Self::Upper = self {
+			return name.to_ascii_uppercase();
+		}
+		let mut out = String::with_capacity(name.len());
+		for (i, word) in name.split('_').filter(|word| !word.is_empty()).enumerate() {
+			let mut chars = word.chars();
+			if let Some(first) = chars.next() {
+				match (self, i) {
+					(Self::Camel, 0) => out.push(first),
+					_ => out.push(first.to_ascii_uppercase()),
+				}
+				out.extend(chars);
+			}
+		}
+		out
+	}
+}
+
+// The memory ordering of the loads on the fast path of generated items.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum LoadOrdering {
+	Strict,
+	Relaxed,
+}
+
 pub struct AttrData {
 	pub library: std::result::Result<syn::Path, Span>,
-	pub link_name: Option<(String, Span)>,
+	pub loader: Option<Span>,
+	pub link_name: Option<(Vec<String>, Span)>,
+	// set if `link_name` came from `cxx_name`, along with whether the macro mangled it.
+	pub cxx_name: Option<(Span, bool)>,
+	pub link_prefix: Option<(String, Span)>,
+	pub link_suffix: Option<(String, Span)>,
+	pub link_case: Option<(LinkCase, Span)>,
+	pub version: Option<(String, Span)>,
+	pub link_ordinal: Option<(u16, Span)>,
+	// the path of the stub, along with whether it was passed as `stub` or `fallback`.
+	pub stub: Option<(syn::Path, &'static str)>,
+	pub checked: Option<Span>,
+	pub on_error: Option<syn::Path>,
+	pub table: Option<syn::Path>,
+	pub eager: Option<Span>,
+	pub probe: Option<Span>,
+	pub shared: Option<Span>,
+	pub validate: Option<(String, Span)>,
+	pub win32_flags: Option<(u32, Span)>,
+	pub nullable: Option<Span>,
+	pub optional: Option<Span>,
+	pub ordering: Option<(LoadOrdering, Span)>,
+	pub allow_improper_ctypes: Option<Span>,
+}
+
+impl AttrData {
+	/// Returns the candidate symbol names for an item in the order they're tried, applying
+	/// `link_name`, or the case followed by the prefix and suffix. Never empty.
+	pub fn symbol_names(&self, ident: &Ident) -> Vec<String> {
+		match &self.link_name {
+			Some((names, _)) => names.clone(),
+			None => {
+				let prefix = self.link_prefix.as_ref().map_or("", |(s, _)| s.as_str());
+				let suffix = self.link_suffix.as_ref().map_or("", |(s, _)| s.as_str());
+				let name = match self.link_case {
+					Some((case, _)) => case.apply(&ident.to_string()),
+					None => ident.to_string(),
+				};
+				vec![format!("{prefix}{name}{suffix}")]
+			}
+		}
+	}
+}
+
+// Symbol names can't be empty, contain NUL bytes, or contain whitespace.
+fn validate_symbol_str(key: &str, val: &LitStr) -> Result<String> {
+	let value = val.value();
+	let problem = if value.is_empty() {
+		"be empty"
+	} else if value.contains('\0') {
+		"contain NUL bytes"
+	} else if value.contains(char::is_whitespace) {
+		"contain whitespace"
+	} else {
+		return Ok(value);
+	};
+	Err(Error::new(val.span(), format!("`{key}` cannot {problem}")))
+}
+
+// Returns the symbol name for `cxx_name`, and whether it was mangled here. Names that are already
+// mangled for the Itanium (`_Z`) or MSVC (`?`) ABI are used as is.
+fn mangle_cxx_name(val: &LitStr) -> Result<(String, bool)> {
+	let value = val.value();
+	if value.starts_with("_Z") || value.starts_with('?') {
+		return validate_symbol_str("cxx_name", val).map(|name| (name, false));
+	}
+	#[cfg(feature = "cxx-mangle")]
+	return crate::cxx::mangle(&value)
+		.map(|name| (name, true))
+		.map_err(|err| Error::new(val.span(), format!("invalid `cxx_name`: {err}")));
+	#[cfg(not(feature = "cxx-mangle"))]
+	Err(Error::new(
+		val.span(),
+		"mangling `cxx_name` requires the `cxx-mangle` feature, or pass an already mangled name",
+	))
+}
+
+// Parses flags of `LoadLibraryExW` separated by `|`, such as `LOAD_LIBRARY_SEARCH_SYSTEM32`.
+fn parse_win32_flags(val: &LitStr) -> Result<u32> {
+	const FLAGS: [(&str, u32); 8] = [
+		("LOAD_WITH_ALTERED_SEARCH_PATH", 0x8),
+		("LOAD_LIBRARY_REQUIRE_SIGNED_TARGET", 0x80),
+		("LOAD_LIBRARY_SEARCH_DLL_LOAD_DIR", 0x100),
+		("LOAD_LIBRARY_SEARCH_APPLICATION_DIR", 0x200),
+		("LOAD_LIBRARY_SEARCH_USER_DIRS", 0x400),
+		("LOAD_LIBRARY_SEARCH_SYSTEM32", 0x800),
+		("LOAD_LIBRARY_SEARCH_DEFAULT_DIRS", 0x1000),
+		("LOAD_LIBRARY_SAFE_CURRENT_DIRS", 0x2000),
+	];
+	val.value().split('|').try_fold(0, |bits, flag| {
+		let flag = flag.trim();
+		match FLAGS.iter().find(|(name, _)| *name == flag) {
+			Some((_, bit)) => Ok(bits | bit),
+			None => Err(Error::new(
+				val.span(),
+				format!("unknown or unsupported flag `{flag}` in `win32_flags`"),
+			)),
+		}
+	})
+}
+
+fn is_ident(expr: &Expr, ident: &str) -> bool {
+	matches!(expr, Expr::Path(ExprPath { path, .. }) if path.is_ident(ident))
 }
 
 impl TryFrom<Punctuated<Expr, Token!(,)>> for AttrData {
 	type Error = syn::Error;
 	fn try_from(value: Punctuated<Expr, Token!(,)>) -> Result<Self> {
 		let mut maybe_library: Option<syn::Path> = None;
-		let mut link_name: Option<(String, Span)> = None;
+		let mut loader: Option<Span> = None;
+		let mut link_name: Option<(Vec<String>, Span)> = None;
+		let mut cxx_name: Option<(String, Span, bool)> = None;
+		let mut link_prefix: Option<(String, Span)> = None;
+		let mut link_suffix: Option<(String, Span)> = None;
+		let mut link_case: Option<(LinkCase, Span)> = None;
+		let mut version: Option<(String, Span)> = None;
+		let mut link_ordinal: Option<(u16, Span)> = None;
+		let mut stub: Option<syn::Path> = None;
+		let mut stub_key = "stub";
+		let mut checked: Option<Span> = None;
+		let mut on_error: Option<syn::Path> = None;
+		let mut table: Option<syn::Path> = None;
+		let mut eager: Option<Span> = None;
+		let mut probe: Option<Span> = None;
+		let mut shared: Option<Span> = None;
+		let mut validate: Option<(String, Span)> = None;
+		let mut win32_flags: Option<(u32, Span)> = None;
+		let mut nullable: Option<Span> = None;
+		let mut optional: Option<Span> = None;
+		let mut ordering: Option<(LoadOrdering, Span)> = None;
+		let mut allow_improper_ctypes: Option<Span> = None;
+		let mut egl: Option<Span> = None;
+		let mut opengl: Option<Span> = None;
+		let mut vulkan: Option<Span> = None;
 		let mut errors = vec![];
-		const EXPECTED_KW: &str = "Expected `library`, or `link_name`.";
 
 		for expr in value.iter() {
 			match expr {
@@ -29,12 +224,21 @@ impl TryFrom<Punctuated<Expr, Token!(,)>> for AttrData {
 					let Expr::Path(ExprPath { path, .. }) = assign_left else {
 						unreachable!("internal error when parsing Expr::Assign");
 					};
-					if path.is_ident("library") {
+					if path.is_ident("library") || path.is_ident("loader") {
 						// Branch for syntax: #[dylink(library = <path>)]
+						let is_loader = path.is_ident("loader");
 						match assign_right {
 							Expr::Path(ExprPath { path, .. }) => {
 								if maybe_library.is_none() {
 									maybe_library = Some(path.clone());
+									if is_loader {
+										loader = Some(assign.span());
+									}
+								} else if is_loader || loader.is_some() {
+									errors.push(Error::new(
+										assign.span(),
+										"`library` and `loader` are mutually exclusive",
+									));
 								} else {
 									errors.push(Error::new(
 										assign.span(),
@@ -46,34 +250,370 @@ impl TryFrom<Punctuated<Expr, Token!(,)>> for AttrData {
 						}
 					} else if path.is_ident("link_name") {
 						// Branch for syntax: #[dylink(link_name = <string>)]
+						// or: #[dylink(link_name = any(<string>, ...))]
+						let names = match assign_right {
+							Expr::Lit(ExprLit {
+								lit: Lit::Str(val), ..
+							}) => validate_symbol_str("link_name", val).map(|name| vec![name]),
+							Expr::Call(ExprCall { func, args, .. }) if is_ident(func, "any") => {
+								if args.is_empty() {
+									Err(Error::new(
+										assign_right.span(),
+										"`any` requires at least one name",
+									))
+								} else {
+									args.iter()
+										.map(|arg| match arg {
+											Expr::Lit(ExprLit {
+												lit: Lit::Str(val), ..
+											}) => validate_symbol_str("link_name", val),
+											arg => Err(Error::new(arg.span(), "Expected string.")),
+										})
+										.collect()
+								}
+							}
+							right => Err(Error::new(
+								right.span(),
+								"Expected string, or `any(<string>, ...)`.",
+							)),
+						};
+						match names {
+							Err(err) => errors.push(err),
+							Ok(_) if link_name.is_some() => errors
+								.push(Error::new(assign.span(), "link_name is already defined")),
+							Ok(names) => link_name = Some((names, assign.span())),
+						}
+					} else if let Some(key) = ["link_prefix", "link_suffix", "version"]
+						.into_iter()
+						.find(|key| path.is_ident(key))
+					{
+						// Branch for syntax: #[dylink(link_prefix = <string>)]
+						let slot = match key {
+							"link_prefix" => &mut link_prefix,
+							"link_suffix" => &mut link_suffix,
+							_ => &mut version,
+						};
+						match assign_right {
+							Expr::Lit(ExprLit {
+								lit: Lit::Str(val), ..
+							}) => match validate_symbol_str(key, val) {
+								Err(err) => errors.push(err),
+								Ok(_) if slot.is_some() => errors.push(Error::new(
+									assign.span(),
+									format!("{key} is already defined"),
+								)),
+								Ok(value) => *slot = Some((value, assign.span())),
+							},
+							right => errors.push(Error::new(right.span(), "Expected string.")),
+						}
+					} else if path.is_ident("cxx_name") {
+						// Branch for syntax: #[dylink(cxx_name = <string>)]
+						match assign_right {
+							Expr::Lit(ExprLit {
+								lit: Lit::Str(val), ..
+							}) => match mangle_cxx_name(val) {
+								Err(err) => errors.push(err),
+								Ok(_) if cxx_name.is_some() => errors
+									.push(Error::new(assign.span(), "cxx_name is already defined")),
+								Ok((name, mangled)) => {
+									cxx_name = Some((name, assign.span(), mangled));
+								}
+							},
+							right => errors.push(Error::new(right.span(), "Expected string.")),
+						}
+					} else if path.is_ident("link_case") {
+						// Branch for syntax: #[dylink(link_case = <string>)]
 						match assign_right {
 							Expr::Lit(ExprLit {
 								lit: Lit::Str(val), ..
 							}) => {
-								if link_name.is_none() {
-									link_name = Some((val.value(), assign.span()));
-								} else {
+								let case = match val.value().as_str() {
+									"camel" => Some(LinkCase::Camel),
+									"pascal" => Some(LinkCase::Pascal),
+									"upper" => Some(LinkCase::Upper),
+									_ => None,
+								};
+								match case {
+									None => errors.push(Error::new(
+										val.span(),
+										"Expected `\"camel\"`, `\"pascal\"`, or `\"upper\"`.",
+									)),
+									Some(_) if link_case.is_some() => errors.push(Error::new(
+										assign.span(),
+										"link_case is already defined",
+									)),
+									Some(case) => link_case = Some((case, assign.span())),
+								}
+							}
+							right => errors.push(Error::new(right.span(), "Expected string.")),
+						}
+					} else if path.is_ident("ordering") {
+						// Branch for syntax: #[dylink(ordering = <string>)]
+						match assign_right {
+							Expr::Lit(ExprLit {
+								lit: Lit::Str(val), ..
+							}) => {
+								let value = match val.value().as_str() {
+									"strict" => Some(LoadOrdering::Strict),
+									"relaxed" => Some(LoadOrdering::Relaxed),
+									_ => None,
+								};
+								match value {
+									None => errors.push(Error::new(
+										val.span(),
+										"Expected `\"strict\"` or `\"relaxed\"`.",
+									)),
+									Some(_) if ordering.is_some() => errors.push(Error::new(
+										assign.span(),
+										"ordering is already defined",
+									)),
+									Some(value) => ordering = Some((value, assign.span())),
+								}
+							}
+							right => errors.push(Error::new(right.span(), "Expected string.")),
+						}
+					} else if path.is_ident("validate") {
+						// Branch for syntax: #[dylink(validate = <string>)]
+						match assign_right {
+							Expr::Lit(ExprLit {
+								lit: Lit::Str(val), ..
+							}) => {
+								if val.value().is_empty() {
+									errors
+										.push(Error::new(val.span(), "`validate` cannot be empty"));
+								} else if validate.is_some() {
 									errors.push(Error::new(
 										assign.span(),
-										"link_name is already defined",
+										"validate is already defined",
 									));
+								} else {
+									validate = Some((val.value(), val.span()));
 								}
 							}
 							right => errors.push(Error::new(right.span(), "Expected string.")),
 						}
+					} else if path.is_ident("win32_flags") {
+						// Branch for syntax: #[dylink(win32_flags = <string>)]
+						match assign_right {
+							Expr::Lit(ExprLit {
+								lit: Lit::Str(val), ..
+							}) => match parse_win32_flags(val) {
+								Err(err) => errors.push(err),
+								Ok(_) if win32_flags.is_some() => errors.push(Error::new(
+									assign.span(),
+									"win32_flags is already defined",
+								)),
+								Ok(flags) => win32_flags = Some((flags, assign.span())),
+							},
+							right => errors.push(Error::new(right.span(), "Expected string.")),
+						}
+					} else if path.is_ident("link_ordinal") {
+						// Branch for syntax: #[dylink(link_ordinal = <integer>)]
+						match assign_right {
+							Expr::Lit(ExprLit {
+								lit: Lit::Int(val), ..
+							}) => match val.base10_parse::<u16>() {
+								Err(err) => errors.push(err),
+								Ok(_) if link_ordinal.is_some() => errors.push(Error::new(
+									assign.span(),
+									"link_ordinal is already defined",
+								)),
+								Ok(ordinal) => link_ordinal = Some((ordinal, assign.span())),
+							},
+							right => errors.push(Error::new(right.span(), "Expected integer.")),
+						}
+					} else if let Some(key) = ["fallback", "on_error", "stub", "table"]
+						.into_iter()
+						.find(|key| path.is_ident(key))
+					{
+						// Branch for syntax: #[dylink(stub = <path>)]
+						let slot = match key {
+							"on_error" => &mut on_error,
+							"table" => &mut table,
+							// `fallback` is another name for `stub`.
+							_ => {
+								if stub.is_none() {
+									stub_key = key;
+								}
+								&mut stub
+							}
+						};
+						match assign_right {
+							Expr::Path(ExprPath { path, .. }) => {
+								if slot.is_none() {
+									*slot = Some(path.clone());
+								} else {
+									errors.push(Error::new(
+										assign.span(),
+										format!("{key} is already defined"),
+									));
+								}
+							}
+							right => errors.push(Error::new(right.span(), "Expected path.")),
+						}
 					} else {
-						errors.push(Error::new(assign_left.span(), EXPECTED_KW));
+						errors.push(Error::new(assign_left.span(), expected_kw()));
+					}
+				}
+
+				// Branch for syntax: #[dylink(checked)]
+				Expr::Path(ExprPath { path, .. })
+					if let Some(key) = [
+						"allow_improper_ctypes",
+						"checked",
+						"eager",
+						"egl",
+						"nullable",
+						"opengl",
+						"optional",
+						"probe",
+						"shared",
+						"vulkan",
+					]
+					.into_iter()
+					.find(|key| path.is_ident(key)) =>
+				{
+					let slot = match key {
+						"allow_improper_ctypes" => &mut allow_improper_ctypes,
+						"checked" => &mut checked,
+						"eager" => &mut eager,
+						"egl" => &mut egl,
+						"nullable" => &mut nullable,
+						"opengl" => &mut opengl,
+						"optional" => &mut optional,
+						"probe" => &mut probe,
+						"shared" => &mut shared,
+						_ => &mut vulkan,
+					};
+					if slot.is_none() {
+						*slot = Some(path.span());
+					} else {
+						errors.push(Error::new(path.span(), format!("{key} is already defined")));
 					}
 				}
 
 				// Branch for everything else.
-				expr => errors.push(Error::new(expr.span(), EXPECTED_KW)),
+				expr => errors.push(Error::new(expr.span(), expected_kw())),
+			}
+		}
+		// OpenGL, EGL and Vulkan functions resolve through the proc-address function of the system
+		// library.
+		let mut system_key = None;
+		let system_loaders = [
+			("egl", egl, "sync", "EGL"),
+			("opengl", opengl, "sync", "OPENGL"),
+			("vulkan", vulkan, "vk", "VULKAN"),
+		];
+		for (key, span, module, name) in system_loaders {
+			let Some(span) = span else {
+				continue;
+			};
+			if maybe_library.is_some() {
+				let other = match (system_key, loader) {
+					(Some(other), _) => other,
+					(None, Some(_)) => "loader",
+					(None, None) => "library",
+				};
+				errors.push(Error::new(
+					span,
+					format!("`{key}` and `{other}` are mutually exclusive"),
+				));
+			} else {
+				let (module, name) = (syn::Ident::new(module, span), syn::Ident::new(name, span));
+				maybe_library = Some(syn::parse_quote_spanned!(span=> ::dylink::#module::#name));
+				loader = Some(span);
+				system_key = Some(key);
 			}
 		}
-		if maybe_library.is_none() {
+		if let (Some((_, span, _)), Some(_)) = (&cxx_name, &link_name) {
+			errors.push(Error::new(
+				*span,
+				"`cxx_name` and `link_name` are mutually exclusive",
+			));
+		}
+		for (key, other) in [
+			("link_name", link_name.as_ref().map(|(_, span)| *span)),
+			("cxx_name", cxx_name.as_ref().map(|(_, span, _)| *span)),
+		] {
+			if let (Some((_, span)), Some(_)) = (&link_case, other) {
+				errors.push(Error::new(
+					*span,
+					format!("`link_case` and `{key}` are mutually exclusive"),
+				));
+			}
+		}
+		if let (Some((_, span)), Some(_)) = (&link_ordinal, &cxx_name) {
+			errors.push(Error::new(
+				*span,
+				"`link_ordinal` and `cxx_name` are mutually exclusive",
+			));
+		}
+		if let (Some((_, span)), Some(_)) = (&link_ordinal, &link_name) {
+			errors.push(Error::new(
+				*span,
+				"`link_ordinal` and `link_name` are mutually exclusive",
+			));
+		}
+		if let (Some((_, span)), Some(_)) = (&link_ordinal, &version) {
+			errors.push(Error::new(
+				*span,
+				"`link_ordinal` and `version` are mutually exclusive",
+			));
+		}
+		if let (Some((_, span)), Some(_)) = (&win32_flags, &loader) {
+			errors.push(Error::new(
+				*span,
+				"`win32_flags` can't be used with a `loader`, which opens libraries itself",
+			));
+		}
+		let fallbacks = [
+			("checked", checked),
+			("on_error", on_error.as_ref().map(Spanned::span)),
+			(stub_key, stub.as_ref().map(Spanned::span)),
+			("optional", optional),
+		];
+		let fallbacks: Vec<_> = fallbacks
+			.into_iter()
+			.filter_map(|(key, span)| Some((key, span?)))
+			.collect();
+		if let [(first, _), (second, span), ..] = fallbacks[..] {
+			errors.push(Error::new(
+				span,
+				format!("`{first}` and `{second}` are mutually exclusive"),
+			));
+		}
+		if let Some(table) = &table {
+			// tables are loaded from a library passed at run-time, and can't fail lazily.
+			let library_key = match loader {
+				Some(_) => "loader",
+				None => "library",
+			};
+			let conflicts = [
+				(library_key, maybe_library.as_ref().map(Spanned::span)),
+				("checked", checked),
+				("on_error", on_error.as_ref().map(Spanned::span)),
+				("eager", eager),
+				("probe", probe),
+				("optional", optional),
+				("shared", shared),
+				("win32_flags", win32_flags.as_ref().map(|(_, span)| *span)),
+				("ordering", ordering.as_ref().map(|(_, span)| *span)),
+			];
+			for (key, span) in conflicts {
+				if let Some(span) = span {
+					errors.push(Error::new(
+						span,
+						format!("`table` and `{key}` are mutually exclusive"),
+					));
+				}
+			}
+			if table.get_ident().is_none() {
+				errors.push(Error::new(table.span(), "Expected identifier."));
+			}
+		} else if maybe_library.is_none() {
 			errors.push(Error::new(
 				value.span(),
-				"No library detected. Suggest using: `library = <path>`.",
+				"No library detected. Suggest using: `library = <path>` or `loader = <path>`.",
 			));
 		}
 
@@ -86,12 +626,36 @@ impl TryFrom<Punctuated<Expr, Token!(,)>> for AttrData {
 				Err(main_err)
 			} else {
 				// argument list was empty. this is a problem
-				Err(Error::new(value.span(), EXPECTED_KW))
+				Err(Error::new(value.span(), expected_kw()))
 			}
 		} else {
+			let (link_name, cxx_name) = match cxx_name {
+				Some((name, span, mangled)) => (Some((vec![name], span)), Some((span, mangled))),
+				None => (link_name, None),
+			};
 			Ok(Self {
 				library: maybe_library.ok_or(value.span()),
+				loader,
 				link_name,
+				cxx_name,
+				link_prefix,
+				link_suffix,
+				link_case,
+				version,
+				link_ordinal,
+				stub: stub.map(|stub| (stub, stub_key)),
+				checked,
+				on_error,
+				table,
+				eager,
+				probe,
+				shared,
+				validate,
+				win32_flags,
+				nullable,
+				optional,
+				ordering,
+				allow_improper_ctypes,
 			})
 		}
 	}
@@ -0,0 +1,359 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+// Itanium C++ ABI mangling of plain function declarations, such as `ns::Class::f(const char*)`.
+//
+// Templates, function pointers, and names in `std` aren't supported. Names that aren't
+// keywords are mangled as classes or enums, so typedefs such as `size_t` must be spelled out.
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+	Ident(String),
+	Scope,
+	Star,
+	Amp,
+	AmpAmp,
+	Open,
+	Close,
+	Comma,
+	Ellipsis,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Type {
+	Builtin(&'static str),
+	Named(Vec<String>),
+	Const(Box<Type>),
+	Pointer(Box<Type>),
+	LRef(Box<Type>),
+	RRef(Box<Type>),
+}
+
+fn tokenize(decl: &str) -> Result<Vec<Token>, String> {
+	let mut tokens = Vec::new();
+	let mut chars = decl.char_indices().peekable();
+	while let Some((i, c)) = chars.next() {
+		let token = match c {
+			c if c.is_whitespace() => continue,
+			c if c.is_ascii_alphabetic() || c == '_' => {
+				let mut end = i + c.len_utf8();
+				while let Some(&(j, c)) = chars.peek() {
+					if !(c.is_ascii_alphanumeric() || c == '_') {
+						break;
+					}
+					end = j + c.len_utf8();
+					chars.next();
+				}
+				Token::Ident(decl[i..end].to_owned())
+			}
+			':' if chars.next_if(|&(_, c)| c == ':').is_some() => Token::Scope,
+			'*' => Token::Star,
+			'&' if chars.next_if(|&(_, c)| c == '&').is_some() => Token::AmpAmp,
+			'&' => Token::Amp,
+			'(' => Token::Open,
+			')' => Token::Close,
+			',' => Token::Comma,
+			'.' if decl[i..].starts_with("...") => {
+				chars.next();
+				chars.next();
+				Token::Ellipsis
+			}
+			c => return Err(format!("unsupported character `{c}`")),
+		};
+		tokens.push(token);
+	}
+	Ok(tokens)
+}
+
+// Maps the keywords of a builtin type, in any order, to its code.
+fn builtin(words: &[&str]) -> Result<&'static str, String> {
+	let count = |word| words.iter().filter(|w| **w == word).count();
+	let (unsigned, signed) = (count("unsigned") > 0, count("signed") > 0);
+	let (short, long) = (count("short"), count("long"));
+	let base: Vec<&str> = words
+		.iter()
+		.copied()
+		.filter(|w| !matches!(*w, "unsigned" | "signed" | "short" | "long" | "int"))
+		.collect();
+	let code = match (base.as_slice(), unsigned, signed, short, long) {
+		([], false, _, 0, 0) => "i",
+		([], true, false, 0, 0) => "j",
+		([], false, _, 1, 0) => "s",
+		([], true, false, 1, 0) => "t",
+		([], false, _, 0, 1) => "l",
+		([], true, false, 0, 1) => "m",
+		([], false, _, 0, 2) => "x",
+		([], true, false, 0, 2) => "y",
+		(["char"], false, false, 0, 0) => "c",
+		(["char"], false, true, 0, 0) => "a",
+		(["char"], true, false, 0, 0) => "h",
+		(["void"], false, false, 0, 0) => "v",
+		(["bool"], false, false, 0, 0) => "b",
+		(["wchar_t"], false, false, 0, 0) => "w",
+		(["char8_t"], false, false, 0, 0) => "Du",
+		(["char16_t"], false, false, 0, 0) => "Ds",
+		(["char32_t"], false, false, 0, 0) => "Di",
+		(["float"], false, false, 0, 0) => "f",
+		(["double"], false, false, 0, 0) => "d",
+		(["double"], false, false, 0, 1) => "e",
+		_ => return Err(format!("invalid type `{}`", words.join(" "))),
+	};
+	Ok(code)
+}
+
+fn is_builtin_word(word: &str) -> bool {
+	const WORDS: [&str; 14] = [
+		"void", "bool", "char", "wchar_t", "char8_t", "char16_t", "char32_t", "short", "int",
+		"long", "float", "double", "signed", "unsigned",
+	];
+	WORDS.contains(&word)
+}
+
+struct Parser {
+	tokens: Vec<Token>,
+	pos: usize,
+}
+
+impl Parser {
+	fn peek(&self) -> Option<&Token> {
+		self.tokens.get(self.pos)
+	}
+
+	fn next(&mut self) -> Option<Token> {
+		let token = self.tokens.get(self.pos).cloned();
+		self.pos += 1;
+		token
+	}
+
+	fn eat(&mut self, token: &Token) -> bool {
+		let found = self.peek() == Some(token);
+		if found {
+			self.pos += 1;
+		}
+		found
+	}
+
+	fn ident(&mut self) -> Result<String, String> {
+		match self.next() {
+			Some(Token::Ident(ident)) if ident != "const" && ident != "volatile" => Ok(ident),
+			_ => Err("expected a name".to_owned()),
+		}
+	}
+
+	fn qualified_name(&mut self) -> Result<Vec<String>, String> {
+		self.eat(&Token::Scope);
+		let mut names = vec![self.ident()?];
+		while self.eat(&Token::Scope) {
+			names.push(self.ident()?);
+		}
+		if names[0] == "std" {
+			return Err("names in `std` are not supported".to_owned());
+		}
+		Ok(names)
+	}
+
+	fn ty(&mut self) -> Result<Type, String> {
+		let mut is_const = false;
+		let mut words = Vec::new();
+		let mut name = None;
+		while let Some(token) = self.peek() {
+			match token {
+				Token::Ident(ident) if ident == "const" => {
+					is_const = true;
+					self.pos += 1;
+				}
+				Token::Ident(ident) if ident == "volatile" => {
+					return Err("`volatile` is not supported".to_owned());
+				}
+				Token::Ident(ident) if is_builtin_word(ident) && name.is_none() => {
+					words.push(ident.clone());
+					self.pos += 1;
+				}
+				Token::Ident(_) | Token::Scope if name.is_none() && words.is_empty() => {
+					name = Some(self.qualified_name()?);
+				}
+				_ => break,
+			}
+		}
+		let mut ty = match name {
+			Some(name) => Type::Named(name),
+			None if words.is_empty() => return Err("expected a type".to_owned()),
+			None => Type::Builtin(builtin(
+				&words.iter().map(String::as_str).collect::<Vec<_>>(),
+			)?),
+		};
+		if is_const {
+			ty = Type::Const(Box::new(ty));
+		}
+		loop {
+			ty = match self.peek() {
+				Some(Token::Star) => Type::Pointer(Box::new(ty)),
+				Some(Token::Amp) => Type::LRef(Box::new(ty)),
+				Some(Token::AmpAmp) => Type::RRef(Box::new(ty)),
+				Some(Token::Ident(ident)) if ident == "const" => Type::Const(Box::new(ty)),
+				Some(Token::Ident(ident)) if ident == "volatile" => {
+					return Err("`volatile` is not supported".to_owned());
+				}
+				_ => break,
+			};
+			self.pos += 1;
+		}
+		Ok(ty)
+	}
+}
+
+// Components that were already mangled, which are referred to by index after their first use.
+#[derive(Default)]
+struct Substitutions(Vec<String>);
+
+impl Substitutions {
+	fn find(&self, key: &str) -> Option<String> {
+		const DIGITS: &[u8; 36] = b"0123456789ABCDEFGHIJKLMNOPQRSTUVWXYZ";
+		let index = self.0.iter().position(|k| k == key)?;
+		let Some(mut n) = index.checked_sub(1) else {
+			return Some("S_".to_owned());
+		};
+		let mut digits = Vec::new();
+		loop {
+			digits.push(DIGITS[n % 36]);
+			n /= 36;
+			if n == 0 {
+				break;
+			}
+		}
+		digits.reverse();
+		Some(format!("S{}_", String::from_utf8_lossy(&digits)))
+	}
+
+	fn push(&mut self, key: String) {
+		self.0.push(key);
+	}
+}
+
+fn source_name(name: &str) -> String {
+	format!("{}{name}", name.len())
+}
+
+// Mangles a qualified name. The full name of a function isn't a candidate for substitution, but
+// the full name of a type is.
+fn mangle_name(names: &[String], cv: &str, is_type: bool, subs: &mut Substitutions) -> String {
+	let key = |len: usize| format!("N{}", names[..len].join("::"));
+	if is_type && let Some(sub) = subs.find(&key(names.len())) {
+		return sub;
+	}
+	if names.len() == 1 && cv.is_empty() {
+		if is_type {
+			subs.push(key(1));
+		}
+		return source_name(&names[0]);
+	}
+	let mut out = format!("N{cv}");
+	let mut start = 0;
+	for len in (1..names.len()).rev() {
+		if let Some(sub) = subs.find(&key(len)) {
+			out.push_str(&sub);
+			start = len;
+			break;
+		}
+	}
+	for len in start + 1..=names.len() {
+		out.push_str(&source_name(&names[len - 1]));
+		if len < names.len() || is_type {
+			subs.push(key(len));
+		}
+	}
+	out.push('E');
+	out
+}
+
+fn type_key(ty: &Type) -> String {
+	match ty {
+		Type::Builtin(code) => (*code).to_owned(),
+		Type::Named(names) => format!("N{}", names.join("::")),
+		Type::Const(inner) => format!("K{}", type_key(inner)),
+		Type::Pointer(inner) => format!("P{}", type_key(inner)),
+		Type::LRef(inner) => format!("R{}", type_key(inner)),
+		Type::RRef(inner) => format!("O{}", type_key(inner)),
+	}
+}
+
+fn mangle_type(ty: &Type, subs: &mut Substitutions) -> String {
+	let (prefix, inner) = match ty {
+		Type::Builtin(code) => return (*code).to_owned(),
+		Type::Named(names) => return mangle_name(names, "", true, subs),
+		Type::Const(inner) => ("K", inner),
+		Type::Pointer(inner) => ("P", inner),
+		Type::LRef(inner) => ("R", inner),
+		Type::RRef(inner) => ("O", inner),
+	};
+	let key = type_key(ty);
+	if let Some(sub) = subs.find(&key) {
+		return sub;
+	}
+	let out = format!("{prefix}{}", mangle_type(inner, subs));
+	subs.push(key);
+	out
+}
+
+/// Mangles a function declaration such as `ns::Class::method(int) const` for the Itanium C++ ABI.
+pub fn mangle(decl: &str) -> Result<String, String> {
+	let mut parser = Parser {
+		tokens: tokenize(decl)?,
+		pos: 0,
+	};
+	let name = parser.qualified_name()?;
+	if !parser.eat(&Token::Open) {
+		return Err("expected a parameter list".to_owned());
+	}
+	let mut params = Vec::new();
+	let mut variadic = false;
+	if !parser.eat(&Token::Close) {
+		loop {
+			if parser.eat(&Token::Ellipsis) {
+				variadic = true;
+			} else {
+				// top-level qualifiers of parameters aren't part of the signature.
+				params.push(match parser.ty()? {
+					Type::Const(inner) => *inner,
+					ty => ty,
+				});
+			}
+			match parser.next() {
+				Some(Token::Comma) if !variadic => continue,
+				Some(Token::Close) => break,
+				_ => return Err("expected `,` or `)`".to_owned()),
+			}
+		}
+	}
+	let is_const = match parser.next() {
+		None => false,
+		Some(Token::Ident(ident)) if ident == "const" && parser.peek().is_none() => true,
+		_ => return Err("unexpected tokens after the parameter list".to_owned()),
+	};
+	if is_const && name.len() == 1 {
+		return Err("only member functions can be `const`".to_owned());
+	}
+	if params == [Type::Builtin("v")] {
+		params.clear();
+	} else if params.contains(&Type::Builtin("v")) {
+		return Err("parameters cannot be `void`".to_owned());
+	}
+
+	let mut subs = Substitutions::default();
+	let mut out = String::from("_Z");
+	out.push_str(&mangle_name(
+		&name,
+		if is_const { "K" } else { "" },
+		false,
+		&mut subs,
+	));
+	for param in &params {
+		out.push_str(&mangle_type(param, &mut subs));
+	}
+	if variadic {
+		out.push('z');
+	} else if params.is_empty() {
+		out.push('v');
+	}
+	Ok(out)
+}
@@ -0,0 +1,106 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+// Implementation of `#[derive(DylinkTable)]`.
+
+use proc_macro2::TokenStream as TokenStream2;
+use quote::*;
+use syn::spanned::Spanned;
+
+// Returns whether the type is spelled `Option<extern "abi" fn(..)>`.
+fn is_optional_fn(ty: &syn::Type) -> bool {
+	let syn::Type::Path(ty) = ty else {
+		return false;
+	};
+	let Some(segment) = ty.path.segments.last() else {
+		return false;
+	};
+	let syn::PathArguments::AngleBracketed(args) = &segment.arguments else {
+		return false;
+	};
+	segment.ident == "Option"
+		&& args.args.len() == 1
+		&& matches!(
+			args.args.first(),
+			Some(syn::GenericArgument::Type(syn::Type::BareFn(bare_fn))) if bare_fn.abi.is_some()
+		)
+}
+
+// Returns the symbol name of a field, from its `#[dylink(name = "...")]` attribute, or else its
+// name.
+fn field_name(field: &syn::Field, ident: &syn::Ident) -> syn::Result<syn::LitStr> {
+	let mut name = None;
+	for attr in field
+		.attrs
+		.iter()
+		.filter(|attr| attr.path().is_ident("dylink"))
+	{
+		attr.parse_nested_meta(|meta| {
+			if !meta.path.is_ident("name") {
+				return Err(meta.error("expected `name`"));
+			}
+			if name.is_some() {
+				return Err(meta.error("`name` is already defined"));
+			}
+			name = Some(meta.value()?.parse::<syn::LitStr>()?);
+			Ok(())
+		})?;
+	}
+	Ok(name.unwrap_or_else(|| syn::LitStr::new(&ident.to_string(), ident.span())))
+}
+
+pub(crate) fn derive_table(input: &syn::DeriveInput) -> syn::Result<TokenStream2> {
+	let syn::Data::Struct(data) = &input.data else {
+		return Err(syn::Error::new(
+			input.span(),
+			"`DylinkTable` can only be derived for structs",
+		));
+	};
+	let syn::Fields::Named(fields) = &data.fields else {
+		return Err(syn::Error::new(
+			data.fields.span(),
+			"`DylinkTable` requires named fields",
+		));
+	};
+
+	let mut loads = Vec::new();
+	for field in &fields.named {
+		let ident = field.ident.as_ref().expect("named field");
+		let ty = &field.ty;
+		if !is_optional_fn(ty) {
+			return Err(syn::Error::new(
+				ty.span(),
+				"fields must be function pointers wrapped in `Option`, such as \
+				 `Option<extern \"C\" fn()>`",
+			));
+		}
+		let name = field_name(field, ident)?;
+		// `Option` of a function pointer has the layout of a pointer, with `None` as null.
+		loads.push(quote! {
+			#ident: ::std::mem::transmute::<*const ::dylink::Symbol, #ty>(
+				lib.symbol(#name).unwrap_or(::std::ptr::null())
+			)
+		});
+	}
+
+	let ident = &input.ident;
+	let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+	Ok(quote! {
+		impl #impl_generics #ident #ty_generics #where_clause {
+			/// Resolves each function from `lib`, leaving those that fail to resolve as `None`.
+			///
+			/// The functions must not be called past the lifetime of `lib`.
+			pub fn load(lib: &::dylink::Library) -> Self {
+				unsafe { Self { #(#loads),* } }
+			}
+
+			/// Resolves each function from the library of `lib`, leaving those that fail to resolve
+			/// as `None`.
+			///
+			/// The library is opened if it isn't already.
+			pub fn load_from<O: ::dylink::sync::Open>(lib: &::dylink::sync::LibLock<'_, O>) -> Self {
+				unsafe { Self { #(#loads),* } }
+			}
+		}
+	})
+}
@@ -2,6 +2,10 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 mod attr_data;
+#[cfg(feature = "cxx-mangle")]
+mod cxx;
+mod derive;
+mod validate;
 
 use proc_macro::TokenStream as TokenStream1;
 use proc_macro2::TokenStream as TokenStream2;
@@ -26,9 +30,146 @@ use syn::ForeignItem;
 ///
 /// # Examples
 ///
-/// May currently be used in foreign modules, and foreign functions.
+/// May currently be used in foreign modules, foreign functions, inherent `impl` blocks, and modules
+/// of foreign modules.
 ///
-///```rust
+/// Symbol names default to the item name. They can be overridden with `link_name` on a foreign
+/// function, or transformed with `link_prefix` and `link_suffix`, which is useful for C APIs with
+/// uniformly prefixed names. With `link_case = "camel"`, `"pascal"`, or `"upper"`, `snake_case`
+/// item names are converted to `camelCase`, `PascalCase`, or `UPPER_CASE` before the prefix and
+/// suffix are applied, such as for `vkCreateInstance` or Win32 functions. For symbols that were
+/// renamed across versions of a library, `link_name = any("name", "alias")` tries each name in
+/// order, and binds to the first that resolves. On Windows, a foreign function can be bound by export ordinal with
+/// `link_ordinal` instead. On targets using glibc, a specific symbol version can be bound with
+/// `version`, such as `version = "GLIBC_2.17"`.
+///
+/// By default, a function panics on first call if its symbol fails to resolve, which can be changed
+/// for every generated item at once with `dylink::config::set_failure_policy`. With
+/// `stub = path::to::function`, the thunk is permanently bound to the given function of the
+/// same signature instead, so that callers can degrade gracefully when a library is missing.
+/// `fallback` is accepted in place of `stub`.
+///
+/// On a module, the attribute is applied to every foreign module inside of it, which saves repeating
+/// the same arguments for bindings split across many blocks. The module may only contain foreign
+/// modules and `use` declarations. Paths are resolved inside the module, so `library` must be
+/// nameable from there, such as with `use super::*;`. With `eager` or `shared`, a single `preload`
+/// function or lock covers the items of every block.
+///
+/// In an `impl` block, each method without a body becomes a method bound to the symbol of the same
+/// name, with `self` passed as the first argument. `Self` is replaced by the type of the block, so
+/// it can be used in signatures as usual, such as `extern "C" fn len(&self) -> usize;`.
+///
+/// C++ functions can be bound with `cxx_name`, which accepts an already mangled name, such as
+/// `cxx_name = "_ZN2ns3addEii"`. With the `cxx-mangle` feature of `dylink`, a declaration such as
+/// `cxx_name = "ns::Class::method(int, const char*) const"` is also mangled for the Itanium C++
+/// ABI used everywhere except MSVC targets, where it's an error. Templates aren't supported, and
+/// since typedefs can't be resolved, parameters such as `size_t` must be spelled as the type they
+/// alias. Member functions take `this` as their first parameter.
+///
+/// With `validate = "path/to/library"`, the library is opened while compiling, and an error is
+/// reported for every item whose symbol it doesn't export, so that misspelled names are caught
+/// early. Relative paths are resolved against the directory of the crate being compiled, or else
+/// searched for like a library name. The library's initializers may run in the compiler on Unix.
+///
+/// Signatures are checked for types that aren't FFI-safe, such as `&str`, `&[T]`, `&dyn Trait`,
+/// `String`, or `char`, which are rejected with a suggested replacement. References to sized types
+/// are still accepted. Types defined elsewhere, such as structs without `repr(C)`, are checked by
+/// the `improper_ctypes` lint as an error. Both checks can be disabled with `allow_improper_ctypes`.
+///
+/// Statics in foreign modules are exposed as accessor functions of the same name, which
+/// return `&'static T`, or `*mut T` for `static mut`.
+///
+/// With `win32_flags = "LOAD_LIBRARY_SEARCH_SYSTEM32"`, flags of `LoadLibraryExW` separated by `|`
+/// are required of the `LibLock` on Windows, restricting where the library and its dependencies are
+/// searched for. They're added to the flags of the `LibLock` if the library isn't open yet, or
+/// else the item fails to resolve if the library was opened without them. Other platforms ignore
+/// them. Flags can also be set on the `LibLock` itself with `LibLock::open_flags`.
+///
+/// With `loader = path::to::LOADER` in place of `library`, symbols are resolved through any value
+/// implementing `dylink::sync::Loader`, such as a `Library`, a `LazyLock<LibLock>` or a custom
+/// loader, instead of a `LibLock`.
+///
+/// With `opengl` in place of `library`, functions are resolved from the OpenGL library of the
+/// system through `dylink::sync::OPENGL`, which queries `wglGetProcAddress`, `glXGetProcAddressARB`
+/// or `eglGetProcAddress`, and falls back to the exports of the library, such as for the functions
+/// of OpenGL 1.1 on Windows.
+///
+/// With `egl` in place of `library`, functions are resolved from the EGL library of the system
+/// through `dylink::sync::EGL`, which queries `eglGetProcAddress`. Functions of client APIs, such as
+/// OpenGL ES, fail to resolve until an EGL context is current.
+///
+/// With `vulkan` in place of `library`, functions are resolved from the Vulkan loader of the system
+/// through `dylink::vk::VULKAN`, which queries `vkGetInstanceProcAddr` without an instance for
+/// global functions, and with the instances registered with `dylink::vk::register_instance` for
+/// the others.
+///
+/// With the `unload` feature of `dylink`, each function registers the address it caches with its
+/// `LibLock`, so that `LibLock::unload` can reset them, and they resolve their symbols again from
+/// the reloaded library on their next call. Loaders keep no such registry.
+///
+/// With `on_error = path::to::handler`, a handler of type
+/// `fn(&io::Error) -> Option<*const dylink::Symbol>` is called when a symbol fails to resolve.
+/// It may log the error, and return the address of a substitute to bind to instead, or `None`
+/// to fail as usual.
+///
+/// With `table = Name` on a foreign module, a struct of function pointers named `Name` is
+/// generated instead of free functions. Its `load` function resolves every function from a
+/// `Library` at once, and each function is called through a method of the same name. No statics
+/// are generated, so a `library` isn't used in this mode.
+///
+/// With `eager` on a foreign module, a `pub fn preload() -> io::Result<()>` is also generated in
+/// the enclosing module, which resolves every item up front. Items that aren't preloaded are still
+/// resolved on first use. Since the function name is fixed, only one `eager` module can be declared
+/// per Rust module.
+///
+/// With `shared` on a foreign module, the first call to any of its items resolves every item at
+/// once, while holding a lock shared by the module, instead of each item resolving its own symbol
+/// on its first call. This saves repeated trips through the loader for large APIs. Items that fail
+/// to resolve are retried on their own, and fail as usual. Like `eager`, only one `shared` module
+/// can be declared per Rust module.
+///
+/// With `probe`, a module of the same name is also generated next to each function, containing
+/// `is_loaded() -> bool` and `addr() -> Option<NonNull<()>>`. They resolve the symbol without
+/// panicking or binding a fallback, so callers can check whether an optional function is available
+/// before committing to a code path. Items of the enclosing module are glob imported into the
+/// generated module, so `library` must be nameable from there.
+///
+/// With `optional`, a function whose symbol fails to resolve is bound to a shim that returns the
+/// default value of its return type, or a null pointer, like a weak symbol in C. A module of the same
+/// name is also generated next to each function, containing `resolved() -> bool`, which tells
+/// whether the symbol resolved. This is meant for functions added by later versions of a library,
+/// which older versions installed on the system lack. Return types must implement `Default`, and
+/// statics are unaffected.
+///
+/// With the `trace` feature of `dylink`, every resolution of a symbol by a generated item is
+/// reported to the hook set with `dylink::trace::set_hook`, along with the address it resolved to
+/// and how long it took.
+///
+/// Whether or not the feature is enabled, each symbol an item resolves is reported to the hook set
+/// with `dylink::set_bind_hook`, such as to know which optional parts of a library are used.
+///
+/// With `nullable`, functions returning a raw pointer return `Option<NonNull<T>>` instead, with
+/// `None` in place of a null pointer, for C APIs that signal failure with `NULL`. Other functions,
+/// as well as the function pointers returned for variadic functions, are unaffected.
+///
+/// Each item caches its resolved address in an atomic, which is published with a `Release` store
+/// once the symbol is resolved, and read with an `Acquire` load on every call, so that a thread
+/// finding the address also sees the library as it was left by the thread that resolved it. Several
+/// threads calling an item for the first time may each resolve it, but all of them bind to the same
+/// address. With `ordering = "relaxed"`, calls read the address with a `Relaxed` load instead,
+/// which saves a barrier per call on some weakly-ordered targets, and relies on the loader's own
+/// synchronization for the library to be visible. `ordering = "strict"` is the default.
+///
+/// With `checked`, functions and accessors return an [`io::Result`](std::io::Result) instead of
+/// panicking when the symbol fails to resolve. Because the result wraps the return value, the
+/// generated functions use the Rust ABI.
+///
+/// Variadic functions, such as `printf`, can't forward their arguments on stable Rust, so they're
+/// exposed as accessor functions of the same name instead, which return the resolved function
+/// pointer, such as `unsafe extern "C" fn(*const c_char, ...) -> c_int`. They're called like
+/// `printf()(fmt, value)`. A `stub` can't be used with them.
+///
+///```no_run
 /// use dylink::*;
 /// static FOOBAR: sync::LibLock = sync::LibLock::new(&["foobar.dll"]);
 ///
@@ -41,6 +182,26 @@ use syn::ForeignItem;
 /// // foreign function pattern
 /// #[dylink(library=FOOBAR)]
 /// extern "system-unwind" fn bar();
+///
+/// // resolves `foo_init`
+/// #[dylink(library=FOOBAR, link_prefix="foo_")]
+/// extern "system-unwind" {
+///     fn init();
+/// }
+///
+/// // falls back to `no_qux` if `qux` can't be resolved
+/// extern "system-unwind" fn no_qux() -> i32 {
+///     -1
+/// }
+/// #[dylink(library=FOOBAR, stub=no_qux)]
+/// extern "system-unwind" fn qux() -> i32;
+///
+/// // foreign static pattern
+/// #[dylink(library=FOOBAR)]
+/// extern "C" {
+///     static mut baz: std::ffi::c_int;
+/// }
+/// unsafe { *baz() = 5 };
 ///```
 #[proc_macro_attribute]
 pub fn dylink(args: TokenStream1, input: TokenStream1) -> TokenStream1 {
@@ -53,33 +214,65 @@ pub fn dylink(args: TokenStream1, input: TokenStream1) -> TokenStream1 {
 	match AttrData::try_from(punct) {
 		Ok(attr_data) => {
 			if let Ok(foreign_mod) = syn::parse2::<syn::ItemForeignMod>(input.clone().into()) {
-				if let Some((_, span)) = attr_data.link_name {
+				if let Err(err) = block_check(&attr_data) {
+					return err.into_compile_error().into();
+				}
+				if let Some(table) = &attr_data.table {
+					let mut tokens = parse_table(table, &foreign_mod, &attr_data);
+					tokens.extend(validate_foreign_items(&attr_data, &foreign_mod.items));
+					return tokens.into();
+				}
+				parse_foreign_mods(&[foreign_mod], &attr_data).into()
+			} else if let Ok(item_mod) = syn::parse2::<syn::ItemMod>(input.clone().into()) {
+				if let Err(err) = block_check(&attr_data) {
+					return err.into_compile_error().into();
+				}
+				parse_mod(&item_mod, &attr_data).into()
+			} else if let Ok(item_impl) = syn::parse2::<syn::ItemImpl>(input.clone().into()) {
+				if let Err(err) = block_check(&attr_data) {
+					return err.into_compile_error().into();
+				}
+				let mut tokens = parse_impl(&item_impl, &attr_data);
+				tokens.extend(validate_items(
+					&attr_data,
+					item_impl.items.iter().filter_map(|item| match item {
+						syn::ImplItem::Verbatim(tokens) => {
+							let fn_item = syn::parse2::<syn::ForeignItemFn>(tokens.clone()).ok()?;
+							Some(fn_item.sig.ident)
+						}
+						_ => None,
+					}),
+				));
+				tokens.into()
+			} else if let Ok(foreign_fn) = syn::parse2::<syn::ForeignItemFn>(input.into()) {
+				if let Some(table) = &attr_data.table {
 					return syn::Error::new(
-						span,
-						"`link_name` should be applied to a foreign function",
+						table.span(),
+						"`table` should be applied to a foreign module",
 					)
 					.to_compile_error()
 					.into();
 				}
-
-				let abi = &foreign_mod.abi;
-				foreign_mod
-					.items
-					.iter()
-					.map(|item| match item {
-						ForeignItem::Fn(fn_item) => {
-							parse_fn::<true>(Some(abi), fn_item, &attr_data)
-						}
-						other => quote!(#abi {#other}),
-					})
-					.collect::<TokenStream2>()
-					.into()
-			} else if let Ok(foreign_fn) = syn::parse2::<syn::ForeignItemFn>(input.into()) {
-				parse_fn::<false>(foreign_fn.sig.abi.as_ref(), &foreign_fn, &attr_data).into()
+				for (key, span) in [("eager", attr_data.eager), ("shared", attr_data.shared)] {
+					if let Some(span) = span {
+						return syn::Error::new(
+							span,
+							format!("`{key}` should be applied to a foreign module"),
+						)
+						.to_compile_error()
+						.into();
+					}
+				}
+				let mut tokens =
+					parse_fn::<false>(foreign_fn.sig.abi.as_ref(), &foreign_fn, &attr_data);
+				tokens.extend(parse_probe(&foreign_fn, &attr_data));
+				tokens.extend(validate_items(&attr_data, [&foreign_fn.sig.ident]));
+				tokens.into()
 			} else {
 				syn::Error::new(
 					proc_macro2::Span::call_site(),
-					"expected a foreign function block (`extern { ... }`) or a foreign function declaration",
+					"expected a foreign function block (`extern { ... }`), a module of foreign function \
+					 blocks, an `impl` block, or a foreign function declaration",
 				)
 				.to_compile_error()
 				.into()
@@ -89,11 +282,1070 @@ pub fn dylink(args: TokenStream1, input: TokenStream1) -> TokenStream1 {
 	}
 }
 
+/// Derive macro for structs of optional function pointers.
+///
+/// Each field must be an `Option` of a function pointer, such as `Option<extern "C" fn()>`, and is
+/// bound to the symbol of the same name, or to the name given with `#[dylink(name = "...")]`.
+/// `load` resolves the fields from a `Library`, and `load_from` from a `LibLock`. Unlike
+/// `table`, fields that fail to resolve are left as `None` instead of failing the whole struct, so
+/// that optional functions can be checked individually.
+///
+///```no_run
+/// use dylink::*;
+/// use std::ffi;
+///
+/// #[derive(DylinkTable)]
+/// struct Api {
+///     foo_init: Option<unsafe extern "C" fn() -> ffi::c_int>,
+///     #[dylink(name = "foo_shutdown")]
+///     shutdown: Option<unsafe extern "C" fn()>,
+/// }
+///
+/// let lib = Library::open("libfoo.so")?;
+/// let api = Api::load(&lib);
+/// if let Some(init) = api.foo_init {
+///     unsafe { init() };
+/// }
+/// # Ok::<(), std::io::Error>(())
+///```
+#[proc_macro_derive(DylinkTable, attributes(dylink))]
+pub fn dylink_table(input: TokenStream1) -> TokenStream1 {
+	syn::parse2::<syn::DeriveInput>(input.into())
+		.and_then(|input| derive::derive_table(&input))
+		.unwrap_or_else(syn::Error::into_compile_error)
+		.into()
+}
+
+// Generates the items of foreign modules sharing the same attribute. Each item keeps the ABI of its
+// own block, while `eager` and `shared` cover the items of every block.
+fn parse_foreign_mods(foreign_mods: &[syn::ItemForeignMod], attr_data: &AttrData) -> TokenStream2 {
+	let Some(first) = foreign_mods.first() else {
+		return TokenStream2::default();
+	};
+	let merged = syn::ItemForeignMod {
+		items: foreign_mods
+			.iter()
+			.flat_map(|foreign_mod| foreign_mod.items.iter().cloned())
+			.collect(),
+		..first.clone()
+	};
+	let preload = match attr_data.eager {
+		Some(_) => parse_preload(&merged, attr_data),
+		None => TokenStream2::default(),
+	};
+	let shared = match attr_data.shared {
+		Some(_) => parse_shared(&merged, attr_data),
+		None => TokenStream2::default(),
+	};
+	let validation = validate_foreign_items(attr_data, &merged.items);
+	foreign_mods
+		.iter()
+		.flat_map(|foreign_mod| {
+			let abi = &foreign_mod.abi;
+			foreign_mod.items.iter().map(move |item| match item {
+				ForeignItem::Fn(fn_item) => {
+					let mut tokens = parse_fn::<true>(Some(abi), fn_item, attr_data);
+					tokens.extend(parse_probe(fn_item, attr_data));
+					tokens
+				}
+				ForeignItem::Static(static_item) => parse_static(static_item, attr_data),
+				other => quote!(#abi {#other}),
+			})
+		})
+		.chain([preload, shared, validation])
+		.collect()
+}
+
+// Generates a module whose foreign modules all share the attribute of the module. `use`
+// declarations are kept, since paths in the attribute and signatures are resolved in the module.
+fn parse_mod(item_mod: &syn::ItemMod, attr_data: &AttrData) -> TokenStream2 {
+	let Some((_, items)) = &item_mod.content else {
+		return syn::Error::new(
+			item_mod.semi.span(),
+			"`dylink` can't be applied to a module declared in another file",
+		)
+		.into_compile_error();
+	};
+	if let Some(table) = &attr_data.table {
+		return syn::Error::new(
+			table.span(),
+			"`table` should be applied to a foreign module",
+		)
+		.into_compile_error();
+	}
+	let mut uses = Vec::new();
+	let mut foreign_mods = Vec::new();
+	for item in items {
+		match item {
+			syn::Item::Use(item_use) => uses.push(item_use),
+			syn::Item::ForeignMod(foreign_mod) => {
+				if let Some(attr) = foreign_mod
+					.attrs
+					.iter()
+					.find(|attr| attr.path().is_ident("dylink"))
+				{
+					return syn::Error::new(
+						attr.span(),
+						"`dylink` is already applied to the enclosing module",
+					)
+					.into_compile_error();
+				}
+				foreign_mods.push(foreign_mod.clone());
+			}
+			other => {
+				return syn::Error::new(
+					other.span(),
+					"only foreign modules and `use` declarations are supported in this context",
+				)
+				.into_compile_error();
+			}
+		}
+	}
+	let (inner_attrs, outer_attrs): (Vec<_>, Vec<_>) = item_mod
+		.attrs
+		.iter()
+		.partition(|attr| matches!(attr.style, syn::AttrStyle::Inner(_)));
+	let vis = &item_mod.vis;
+	let mod_token = &item_mod.mod_token;
+	let ident = &item_mod.ident;
+	let generated = parse_foreign_mods(&foreign_mods, attr_data);
+	quote! {
+		#(#outer_attrs)*
+		#vis #mod_token #ident {
+			#(#inner_attrs)*
+			#(#uses)*
+			#generated
+		}
+	}
+}
+
+// Reports the items of foreign modules whose symbols aren't exported by the library passed to
+// `validate`.
+fn validate_foreign_items(attr_data: &AttrData, items: &[ForeignItem]) -> TokenStream2 {
+	validate_items(
+		attr_data,
+		items.iter().filter_map(|item| match item {
+			ForeignItem::Fn(fn_item) => Some(&fn_item.sig.ident),
+			ForeignItem::Static(static_item) => Some(&static_item.ident),
+			_ => None,
+		}),
+	)
+}
+
+// Reports the items whose symbols aren't exported by the library passed to `validate`.
+fn validate_items<I>(attr_data: &AttrData, idents: impl IntoIterator<Item = I>) -> TokenStream2
+where
+	I: std::borrow::Borrow<syn::Ident>,
+{
+	let Some((path, span)) = &attr_data.validate else {
+		return TokenStream2::default();
+	};
+	// ordinals aren't names, so they can't be validated.
+	if attr_data.link_ordinal.is_some() {
+		return TokenStream2::default();
+	}
+	let lib = match validate::Library::open(path) {
+		Ok(lib) => lib,
+		Err(err) => {
+			let msg = format!("failed to open `{path}` for validation: {err}");
+			return syn::Error::new(*span, msg).into_compile_error();
+		}
+	};
+	idents
+		.into_iter()
+		.filter_map(|ident| {
+			let ident = ident.borrow();
+			let names = attr_data.symbol_names(ident);
+			match names.iter().any(|name| lib.has_symbol(name)) {
+				true => None,
+				false => {
+					let names = names.iter().map(|name| format!("`{name}`"));
+					let msg = format!(
+						"{} is not exported by `{path}`",
+						names.collect::<Vec<_>>().join(" or ")
+					);
+					Some(syn::Error::new(ident.span(), msg).into_compile_error())
+				}
+			}
+		})
+		.collect()
+}
+
+// Rejects arguments that only apply to a single function.
+fn block_check(attr_data: &AttrData) -> syn::Result<()> {
+	if let Some((_, span)) = attr_data.link_name {
+		let key = match attr_data.cxx_name {
+			Some(_) => "cxx_name",
+			None => "link_name",
+		};
+		return Err(syn::Error::new(
+			span,
+			format!("`{key}` should be applied to a foreign function"),
+		));
+	}
+	if let Some((_, span)) = attr_data.link_ordinal {
+		return Err(syn::Error::new(
+			span,
+			"`link_ordinal` should be applied to a foreign function",
+		));
+	}
+	if let Some((stub, key)) = &attr_data.stub {
+		return Err(syn::Error::new(
+			stub.span(),
+			format!("`{key}` should be applied to a foreign function"),
+		));
+	}
+	Ok(())
+}
+
+// Replaces `Self` with the type of the `impl` block, since the items generated inside of methods
+// can't refer to it.
+fn replace_self(tokens: TokenStream2, self_ty: &syn::Type) -> TokenStream2 {
+	tokens
+		.into_iter()
+		.map(|tt| match tt {
+			proc_macro2::TokenTree::Ident(ident) if ident == "Self" => self_ty.to_token_stream(),
+			proc_macro2::TokenTree::Group(group) => {
+				let mut new = proc_macro2::Group::new(
+					group.delimiter(),
+					replace_self(group.stream(), self_ty),
+				);
+				new.set_span(group.span());
+				proc_macro2::TokenTree::Group(new).into_token_stream()
+			}
+			tt => tt.into_token_stream(),
+		})
+		.collect()
+}
+
+// Generates methods for the bodiless functions of an inherent `impl` block, which pass `self` as
+// the first argument. Other items are left as they are.
+fn parse_impl(item_impl: &syn::ItemImpl, attr_data: &AttrData) -> TokenStream2 {
+	if let Some((_, path, _)) = &item_impl.trait_ {
+		return syn::Error::new(path.span(), "`trait` implementations are unsupported")
+			.into_compile_error();
+	}
+	if !item_impl.generics.params.is_empty() {
+		return syn::Error::new(
+			item_impl.generics.span(),
+			"generic `impl` blocks are unsupported",
+		)
+		.into_compile_error();
+	}
+	let conflicts = [
+		("table", attr_data.table.as_ref().map(Spanned::span)),
+		("eager", attr_data.eager),
+		("probe", attr_data.probe),
+		("optional", attr_data.optional),
+		("shared", attr_data.shared),
+	];
+	for (key, span) in conflicts {
+		if let Some(span) = span {
+			return syn::Error::new(
+				span,
+				format!("`{key}` cannot be applied to an `impl` block"),
+			)
+			.into_compile_error();
+		}
+	}
+
+	let self_ty = item_impl.self_ty.as_ref();
+	let replace = |ty: &mut Box<syn::Type>| -> syn::Result<()> {
+		**ty = syn::parse2(replace_self(ty.to_token_stream(), self_ty))?;
+		Ok(())
+	};
+	let items = item_impl.items.iter().map(|item| {
+		// syn keeps functions without a body as verbatim tokens.
+		let syn::ImplItem::Verbatim(tokens) = item else {
+			return item.to_token_stream();
+		};
+		let Ok(mut fn_item) = syn::parse2::<syn::ForeignItemFn>(tokens.clone()) else {
+			return item.to_token_stream();
+		};
+		let result = fn_item
+			.sig
+			.inputs
+			.iter_mut()
+			.try_for_each(|input| match input {
+				syn::FnArg::Typed(pat_type) => replace(&mut pat_type.ty),
+				syn::FnArg::Receiver(rec) => replace(&mut rec.ty),
+			});
+		let result = result.and_then(|_| match &mut fn_item.sig.output {
+			syn::ReturnType::Type(_, ty) => replace(ty),
+			syn::ReturnType::Default => Ok(()),
+		});
+		match result {
+			Ok(()) => parse_fn::<false>(fn_item.sig.abi.as_ref(), &fn_item, attr_data),
+			Err(err) => err.into_compile_error(),
+		}
+	});
+
+	let attrs = &item_impl.attrs;
+	let unsafety = &item_impl.unsafety;
+	let impl_token = &item_impl.impl_token;
+	quote! {
+		#(#attrs)*
+		#unsafety #impl_token #self_ty {
+			#(#items)*
+		}
+	}
+}
+
+// Generates a struct of function pointers, which are loaded together from a library.
+fn parse_table(
+	table: &syn::Path,
+	foreign_mod: &syn::ItemForeignMod,
+	attr_data: &AttrData,
+) -> TokenStream2 {
+	let abi = &foreign_mod.abi;
+	let mut lints = Vec::new();
+	let mut fields = Vec::new();
+	let mut loads = Vec::new();
+	let mut methods = Vec::new();
+	for item in &foreign_mod.items {
+		let fn_item = match item {
+			ForeignItem::Fn(fn_item) => fn_item,
+			other => {
+				return syn::Error::new(
+					other.span(),
+					"only functions are supported in `table` mode",
+				)
+				.into_compile_error();
+			}
+		};
+		let sig = &fn_item.sig;
+		if let Err(err) = ffi_check(sig, attr_data) {
+			return err.into_compile_error();
+		}
+		if let Some(token) = &sig.variadic {
+			return syn::Error::new(
+				token.span(),
+				"`table` cannot be used with variadic functions",
+			)
+			.into_compile_error();
+		}
+		let mut params = Vec::new();
+		let mut param_tys = Vec::new();
+		for (i, input) in sig.inputs.iter().enumerate() {
+			let syn::FnArg::Typed(pat_type) = input else {
+				return syn::Error::new(
+					input.span(),
+					"`self` arguments are unsupported in this context",
+				)
+				.into_compile_error();
+			};
+			let param = match pat_type.pat.as_ref() {
+				syn::Pat::Ident(pat_id) => pat_id.ident.clone(),
+				_ => format_ident!("p{i}"),
+			};
+			let ty = &pat_type.ty;
+			param_tys.push(quote!(#param: #ty));
+			params.push(param);
+		}
+		let attrs = &fn_item.attrs;
+		let vis = &fn_item.vis;
+		let ident = &sig.ident;
+		let output = &sig.output;
+		let generics = &sig.generics;
+		// lifetimes of the function become higher-ranked lifetimes of the pointer.
+		let hrtb = match generics.params.is_empty() {
+			true => TokenStream2::default(),
+			false => quote!(for #generics),
+		};
+		let link_names = attr_data.symbol_names(ident);
+		let symbol = match &attr_data.version {
+			Some((version, span)) => {
+				let symbol = first_of(
+					&link_names,
+					|name| quote!(lib.symbol_version(#name, #version)),
+				);
+				quote_spanned! {*span=>
+					{
+						#[cfg(not(all(unix, target_env = "gnu")))]
+						let symbol: ::std::io::Result<*const ::dylink::Symbol> =
+							::std::compile_error!("`version` is only supported on targets using glibc");
+						#[cfg(all(unix, target_env = "gnu"))]
+						let symbol = #symbol;
+						symbol
+					}
+				}
+			}
+			None => first_of(&link_names, |name| quote!(lib.symbol(#name))),
+		};
+
+		let (ret_output, call) =
+			nullable_return(attr_data, output, quote!((self.#ident)(#(#params),*)));
+		lints.push(ffi_lint(Some(abi), sig, attr_data));
+		lints.push(cxx_check(attr_data));
+		fields.push(quote!(#ident: #hrtb unsafe #abi fn (#(#param_tys),*) #output));
+		loads.push(quote!(#ident: ::std::mem::transmute(#symbol?)));
+		methods.push(quote! {
+			#(#attrs)*
+			#[allow(non_snake_case)]
+			#[inline]
+			#vis unsafe fn #ident #generics (&self, #(#param_tys),*) #ret_output {
+				unsafe { #call }
+			}
+		});
+	}
+
+	quote! {
+		#[allow(non_snake_case)]
+		#[derive(Clone, Copy)]
+		pub struct #table {
+			#(#fields),*
+		}
+
+		impl #table {
+			/// Resolves every function of the table from `lib`.
+			///
+			/// The functions must not be called past the lifetime of `lib`.
+			pub fn load(lib: &::dylink::Library) -> ::std::io::Result<Self> {
+				unsafe { Ok(Self { #(#loads),* }) }
+			}
+
+			#(#methods)*
+		}
+
+		const _: () = {
+			#(#lints)*
+		};
+	}
+}
+
+// Rejects types that aren't FFI-safe, such as `&str` or `String`, unless `allow_improper_ctypes` is
+// used. Types the macro can't see into, such as structs without `repr(C)`, are left to `ffi_lint`.
+fn ffi_check(sig: &syn::Signature, attr_data: &AttrData) -> syn::Result<()> {
+	fn check_args(path: &syn::Path) -> syn::Result<()> {
+		path.segments
+			.iter()
+			.filter_map(|segment| match &segment.arguments {
+				syn::PathArguments::AngleBracketed(args) => Some(args),
+				_ => None,
+			})
+			.flat_map(|args| &args.args)
+			.try_for_each(|arg| match arg {
+				syn::GenericArgument::Type(ty) => check(ty),
+				_ => Ok(()),
+			})
+	}
+	fn check(ty: &syn::Type) -> syn::Result<()> {
+		let pointee = match ty {
+			syn::Type::Reference(syn::TypeReference { elem, .. })
+			| syn::Type::Ptr(syn::TypePtr { elem, .. }) => elem.as_ref(),
+			syn::Type::Array(syn::TypeArray { elem, .. })
+			| syn::Type::Group(syn::TypeGroup { elem, .. })
+			| syn::Type::Paren(syn::TypeParen { elem, .. }) => return check(elem),
+			syn::Type::Tuple(tuple) => return tuple.elems.iter().try_for_each(check),
+			syn::Type::TraitObject(_) => {
+				let message =
+					"trait objects are not FFI-safe, since they're passed as fat pointers";
+				return Err(syn::Error::new_spanned(ty, message));
+			}
+			syn::Type::Path(syn::TypePath { path, .. }) => {
+				let ident = path.segments.last().map(|segment| &segment.ident);
+				let message = match ident {
+					Some(ident) if ident == "String" || ident == "CString" => format!(
+						"`{ident}` is not FFI-safe. Pass a `*const c_char` to a NUL terminated \
+						 string instead, such as from `CString::as_ptr`"
+					),
+					Some(ident) if ["Vec", "OsString", "PathBuf"].iter().any(|s| ident == s) => {
+						format!(
+							"`{ident}` is not FFI-safe. Pass a pointer and length instead, such as \
+							 `ptr: *const u8, len: usize`"
+						)
+					}
+					Some(ident) if ident == "char" && path.segments.len() == 1 => {
+						"`char` is not FFI-safe. Use `u32` for a code point, or `c_char` for a C \
+						 character instead"
+							.to_owned()
+					}
+					_ => return check_args(path),
+				};
+				return Err(syn::Error::new_spanned(ty, message));
+			}
+			_ => return Ok(()),
+		};
+		// the pointee is checked separately, since a pointer to an owned type is still FFI-safe.
+		let message = match pointee {
+			syn::Type::Slice(slice) => format!(
+				"slices are not FFI-safe. Pass a pointer and length instead, such as `ptr: *const \
+				 {}, len: usize`, and rebuild the slice with `std::slice::from_raw_parts`",
+				slice.elem.to_token_stream()
+			),
+			syn::Type::Path(syn::TypePath { path, .. }) if path.is_ident("str") => {
+				"string slices are not FFI-safe. Pass a pointer and length instead, such as `ptr: \
+				 *const u8, len: usize`, or a `*const c_char` if the string is NUL terminated"
+					.to_owned()
+			}
+			syn::Type::TraitObject(_) => {
+				"trait objects are not FFI-safe, since they're passed as fat pointers".to_owned()
+			}
+			syn::Type::Path(syn::TypePath { path, .. }) => return check_args(path),
+			pointee => return check(pointee),
+		};
+		Err(syn::Error::new_spanned(ty, message))
+	}
+	if attr_data.allow_improper_ctypes.is_some() {
+		return Ok(());
+	}
+	for input in &sig.inputs {
+		match input {
+			syn::FnArg::Typed(pat_type) => check(&pat_type.ty)?,
+			syn::FnArg::Receiver(rec) => check(&rec.ty)?,
+		}
+	}
+	match &sig.output {
+		syn::ReturnType::Type(_, ty) => check(ty),
+		syn::ReturnType::Default => Ok(()),
+	}
+}
+
+// Generates a declaration that is never called, so that rustc lints the signature like any other
+// foreign function, and reports types such as structs without `repr(C)`.
+fn ffi_lint(abi: Option<&syn::Abi>, sig: &syn::Signature, attr_data: &AttrData) -> TokenStream2 {
+	let Some(abi) = abi else {
+		return TokenStream2::default();
+	};
+	if attr_data.allow_improper_ctypes.is_some() {
+		return TokenStream2::default();
+	}
+	let param_tys = sig.inputs.iter().map(|input| match input {
+		syn::FnArg::Typed(pat_type) => pat_type.ty.as_ref(),
+		syn::FnArg::Receiver(rec) => rec.ty.as_ref(),
+	});
+	let generics = &sig.generics;
+	let variadic = sig.variadic.as_ref().map(|token| quote!(, #token));
+	let output = &sig.output;
+	let ident = format_ident!("__dylink_ffi_lint_{}", sig.ident);
+	quote! {
+		#[deny(improper_ctypes)]
+		#[allow(clashing_extern_declarations, non_snake_case)]
+		unsafe #abi {
+			fn #ident #generics (#(_: #param_tys),* #variadic) #output;
+		}
+	}
+}
+
+// Checks the library type up front, so mistakes are reported at the `library` argument.
+fn library_check(attr_data: &AttrData, library: &syn::Path) -> TokenStream2 {
+	match attr_data.loader {
+		Some(_) => quote_spanned! {library.span()=>
+			const _: fn() = || {
+				fn check<L: ::dylink::sync::Loader + ?Sized>(_: &L) {}
+				check(&#library);
+			};
+		},
+		None => quote_spanned! {library.span()=>
+			const _: fn() = || {
+				let _: &::dylink::sync::LibLock<'_, _> = &#library;
+			};
+		},
+	}
+}
+
+// Returns the trait or type whose functions are used to look up symbols.
+fn symbol_source(attr_data: &AttrData) -> TokenStream2 {
+	match attr_data.loader {
+		Some(_) => quote!(::dylink::sync::Loader),
+		None => quote!(::dylink::sync::LibLock),
+	}
+}
+
+// Generates a `symbol` binding holding the result of looking up the item in `library`, reporting
+// it to the bind hook if it resolved.
+fn symbol_lookup(attr_data: &AttrData, library: &syn::Path, ident: &syn::Ident) -> TokenStream2 {
+	let lookup = symbol_lookup_flags(attr_data, library, ident);
+	let lookup = if cfg!(feature = "trace") {
+		symbol_trace(attr_data, library, ident, lookup)
+	} else {
+		lookup
+	};
+	let (library_name, item, names) = lookup_names(attr_data, library, ident);
+	quote! {
+		#lookup
+		if let Ok(addr) = &symbol {
+			::dylink::BindEvent::notify(#library_name, #item, &[#(#names),*], *addr);
+		}
+	}
+}
+
+// Returns the library, item, and symbol names reported to the hooks of `dylink`.
+fn lookup_names(
+	attr_data: &AttrData,
+	library: &syn::Path,
+	ident: &syn::Ident,
+) -> (String, String, Vec<String>) {
+	let library_name = library
+		.segments
+		.iter()
+		.map(|segment| segment.ident.to_string())
+		.collect::<Vec<_>>()
+		.join("::");
+	let names = match attr_data.link_ordinal {
+		Some((ordinal, _)) => vec![format!("#{ordinal}")],
+		None => attr_data.symbol_names(ident),
+	};
+	(library_name, ident.to_string(), names)
+}
+
+// Reports the resolution to the hook of `dylink::trace`.
+fn symbol_trace(
+	attr_data: &AttrData,
+	library: &syn::Path,
+	ident: &syn::Ident,
+	lookup: TokenStream2,
+) -> TokenStream2 {
+	let (library_name, item, names) = lookup_names(attr_data, library, ident);
+	quote! {
+		let __dylink_start = ::std::time::Instant::now();
+		#lookup
+		::dylink::trace::resolved(#library_name, #item, &[#(#names),*], &symbol, __dylink_start);
+	}
+}
+
+fn symbol_lookup_flags(
+	attr_data: &AttrData,
+	library: &syn::Path,
+	ident: &syn::Ident,
+) -> TokenStream2 {
+	let lookup = symbol_lookup_inner(attr_data, library, ident);
+	match attr_data.win32_flags {
+		// the flags are required before the lookup, which may be what opens the library.
+		Some((flags, span)) => quote_spanned! {span=>
+			#[cfg(windows)]
+			let flags = ::dylink::sync::LibLock::require_flags(
+				&#library,
+				::dylink::OpenFlags::from_bits(#flags),
+			);
+			#lookup
+			#[cfg(windows)]
+			let symbol = flags.and(symbol);
+		},
+		None => lookup,
+	}
+}
+
+fn symbol_lookup_inner(
+	attr_data: &AttrData,
+	library: &syn::Path,
+	ident: &syn::Ident,
+) -> TokenStream2 {
+	let source = symbol_source(attr_data);
+	if let Some((ordinal, span)) = attr_data.link_ordinal {
+		return quote_spanned! {span=>
+			#[cfg(not(windows))]
+			let symbol: ::std::io::Result<*const ::dylink::Symbol> =
+				::std::compile_error!("`link_ordinal` is only supported on Windows");
+			#[cfg(windows)]
+			let symbol = #source::symbol_ordinal(&#library, #ordinal);
+		};
+	}
+	let link_names = attr_data.symbol_names(ident);
+	let cxx_check = cxx_check(attr_data);
+	let lookup = match &attr_data.version {
+		Some((version, span)) => {
+			let symbol = first_of(
+				&link_names,
+				|name| quote!(#source::symbol_version(&#library, #name, #version)),
+			);
+			quote_spanned! {*span=>
+				#[cfg(not(all(unix, target_env = "gnu")))]
+				let symbol: ::std::io::Result<*const ::dylink::Symbol> =
+					::std::compile_error!("`version` is only supported on targets using glibc");
+				#[cfg(all(unix, target_env = "gnu"))]
+				let symbol = #symbol;
+			}
+		}
+		None => {
+			let symbol = first_of(
+				&link_names,
+				|name| quote!(#source::symbol(&#library, #name)),
+			);
+			quote!(let symbol = #symbol;)
+		}
+	};
+	quote!(#cxx_check #lookup)
+}
+
+// Rejects names mangled by `cxx_name` on MSVC targets, which use a different mangling scheme.
+fn cxx_check(attr_data: &AttrData) -> TokenStream2 {
+	match attr_data.cxx_name {
+		Some((span, true)) => quote_spanned! {span=>
+			#[cfg(target_env = "msvc")]
+			::std::compile_error!(
+				"`cxx_name` can only mangle names for the Itanium C++ ABI, so MSVC targets need an \
+				 already mangled name"
+			);
+		},
+		_ => TokenStream2::default(),
+	}
+}
+
+// Chains the lookups of each candidate name, so that the first to resolve is used. If none do,
+// the error of the last candidate is kept.
+fn first_of(names: &[String], lookup: impl Fn(&str) -> TokenStream2) -> TokenStream2 {
+	let (first, rest) = names.split_first().expect("symbol names are never empty");
+	let mut chain = lookup(first);
+	for name in rest {
+		let next = lookup(name);
+		chain = quote!(#chain.or_else(|_| #next));
+	}
+	chain
+}
+
+// Registers a static caching an address with the `LibLock`, so that `LibLock::unload` can reset it
+// to `init`. Loaders have no such registry.
+fn register_reset(
+	attr_data: &AttrData,
+	library: &syn::Path,
+	slot: TokenStream2,
+	init: TokenStream2,
+) -> TokenStream2 {
+	match attr_data.loader {
+		Some(_) => TokenStream2::default(),
+		None => quote! {
+			::dylink::sync::LibLock::register_reset(&#library, &#slot, #init);
+		},
+	}
+}
+
+// Returns the name of the static caching the address of an item, along with its declaration
+// outside and inside of the generated function. In `eager` and `shared` mode the static is
+// declared outside, so that `preload` or the shared initializer can fill it in.
+fn slot(attr_data: &AttrData, ident: &syn::Ident) -> (syn::Ident, TokenStream2, TokenStream2) {
+	let decl = |slot: &syn::Ident| {
+		quote! {
+			#[allow(non_upper_case_globals)]
+			static #slot: ::std::sync::atomic::AtomicPtr<::std::ffi::c_void> =
+				::std::sync::atomic::AtomicPtr::new(::std::ptr::null_mut());
+		}
+	};
+	match attr_data.eager.or(attr_data.shared) {
+		Some(_) => {
+			let slot = format_ident!("__DYLINK_{}", ident);
+			let outer = decl(&slot);
+			(slot, outer, TokenStream2::default())
+		}
+		None => {
+			let slot = format_ident!("ADDR");
+			let inner = decl(&slot);
+			(slot, TokenStream2::default(), inner)
+		}
+	}
+}
+
+// Generates a `preload` function, which resolves every item of a foreign module up front.
+fn parse_preload(foreign_mod: &syn::ItemForeignMod, attr_data: &AttrData) -> TokenStream2 {
+	let Ok(library) = &attr_data.library else {
+		return TokenStream2::default();
+	};
+	let loads = foreign_mod.items.iter().filter_map(|item| {
+		let ident = match item {
+			ForeignItem::Fn(fn_item) => &fn_item.sig.ident,
+			ForeignItem::Static(static_item) => &static_item.ident,
+			_ => return None,
+		};
+		let (slot, ..) = slot(attr_data, ident);
+		let resolve = symbol_lookup(attr_data, library, ident);
+		let register = register_reset(
+			attr_data,
+			library,
+			slot.to_token_stream(),
+			quote!(::std::ptr::null_mut()),
+		);
+		Some(quote! {
+			if #slot.load(::std::sync::atomic::Ordering::Acquire).is_null() {
+				#resolve
+				#slot.store(symbol?.cast_mut().cast(), ::std::sync::atomic::Ordering::Release);
+				#register
+			}
+		})
+	});
+	quote! {
+		/// Resolves every item of the foreign module up front, so that calls to them can't fail.
+		///
+		/// # Errors
+		///
+		/// Returns the first error encountered while resolving the items.
+		pub fn preload() -> ::std::io::Result<()> {
+			#(#loads)*
+			Ok(())
+		}
+	}
+}
+
+// Generates the initializer of a `shared` module, which resolves every item that isn't resolved yet.
+// Items that fail to resolve are left for their own functions to report.
+fn parse_shared(foreign_mod: &syn::ItemForeignMod, attr_data: &AttrData) -> TokenStream2 {
+	let Ok(library) = &attr_data.library else {
+		return TokenStream2::default();
+	};
+	let loads = foreign_mod.items.iter().filter_map(|item| {
+		let ident = match item {
+			ForeignItem::Fn(fn_item) => &fn_item.sig.ident,
+			ForeignItem::Static(static_item) => &static_item.ident,
+			_ => return None,
+		};
+		let (slot, ..) = slot(attr_data, ident);
+		let resolve = symbol_lookup(attr_data, library, ident);
+		let register = register_reset(
+			attr_data,
+			library,
+			slot.to_token_stream(),
+			quote!(::std::ptr::null_mut()),
+		);
+		Some(quote! {
+			if #slot.load(::std::sync::atomic::Ordering::Acquire).is_null() {
+				#resolve
+				if let Ok(symbol) = symbol {
+					#slot.store(symbol.cast_mut().cast(), ::std::sync::atomic::Ordering::Release);
+					#register
+				}
+			}
+		})
+	});
+	quote! {
+		fn __dylink_shared() {
+			static LOCK: ::std::sync::Mutex<()> = ::std::sync::Mutex::new(());
+			let _guard = LOCK.lock().unwrap_or_else(|err| err.into_inner());
+			#(#loads)*
+		}
+	}
+}
+
+// Returns the ordering of the load that checks whether an item's slot already holds its address.
+//
+// Slots start out null, or pointing at the initializer, and are only ever set to a resolved address
+// with a `Release` store, after the library was opened and the symbol resolved. By default, the
+// fast path loads the slot with `Acquire`, so that a thread finding an address in it also sees every
+// write made while resolving it, such as the state of the library and of the `LibLock`. This is a
+// plain load on x86, and a load-acquire instruction on weakly-ordered targets.
+//
+// With `ordering = "relaxed"`, the fast path loads with `Relaxed` instead, and relies on the call
+// going through the loaded address, which orders it after the load on every target Rust supports.
+// Data published by the loader alongside the symbol is then only visible once the loader itself
+// synchronizes, which it does for the code and data of the library on the platforms it supports.
+//
+// Slow paths, which run under a lock or right after a store, always use `Acquire`.
+fn fast_ordering(attr_data: &AttrData) -> TokenStream2 {
+	match attr_data.ordering {
+		Some((LoadOrdering::Relaxed, _)) => quote!(::std::sync::atomic::Ordering::Relaxed),
+		_ => quote!(::std::sync::atomic::Ordering::Acquire),
+	}
+}
+
+// In `shared` mode, runs the shared initializer before an item is resolved on its own, which then
+// only happens if its symbol failed to resolve.
+fn shared_load(attr_data: &AttrData, slot: &syn::Ident) -> TokenStream2 {
+	match attr_data.shared {
+		Some(_) => quote! {
+			if addr.is_null() {
+				__dylink_shared();
+				addr = #slot.load(Ordering::Acquire);
+			}
+		},
+		None => TokenStream2::default(),
+	}
+}
+
+// Generates a module of the same name as a function, for checking if its symbol can be resolved.
+// With `optional`, it holds `resolved`, and `probe` adds `is_loaded` and `addr`.
+fn parse_probe(fn_item: &syn::ForeignItemFn, attr_data: &AttrData) -> TokenStream2 {
+	let (Some(_), Ok(library)) = (attr_data.probe.or(attr_data.optional), &attr_data.library)
+	else {
+		return TokenStream2::default();
+	};
+	// the module is one level deeper, so paths relative to `self` must go through `super`.
+	let mut library = library.clone();
+	if let Some(first) = library.segments.first_mut()
+		&& first.ident == "self"
+	{
+		first.ident = syn::Ident::new("super", first.ident.span());
+	}
+	let library_check = library_check(attr_data, &library);
+	let ordering = fast_ordering(attr_data);
+	let vis = &fn_item.vis;
+	let ident = &fn_item.sig.ident;
+	let resolve = symbol_lookup(attr_data, &library, ident);
+	let register = register_reset(
+		attr_data,
+		&library,
+		quote!(ADDR),
+		quote!(::std::ptr::null_mut()),
+	);
+	let resolved = attr_data.optional.map(|_| {
+		quote! {
+			#[doc = concat!(
+				"Returns `true` if the symbol resolved, rather than [`", stringify!(#ident),
+				"`](super::", stringify!(#ident), ") being bound to a shim."
+			)]
+			#[inline]
+			pub fn resolved() -> bool {
+				addr().is_some()
+			}
+		}
+	});
+	let (probe, addr_vis) = match attr_data.probe {
+		Some(_) => (
+			quote! {
+				/// Returns `true` if the symbol can be resolved.
+				#[inline]
+				pub fn is_loaded() -> bool {
+					addr().is_some()
+				}
+			},
+			quote!(pub),
+		),
+		None => Default::default(),
+	};
+	quote! {
+		#[doc = concat!("Availability of [`", stringify!(#ident), "`].")]
+		#[allow(non_snake_case)]
+		#vis mod #ident {
+			#[allow(unused_imports)]
+			use super::*;
+
+			#resolved
+			#probe
+
+			/// Returns the address of the symbol, resolving it if needed.
+			#addr_vis fn addr() -> Option<::std::ptr::NonNull<()>> {
+				#library_check
+				use ::std::sync::atomic::{AtomicPtr, Ordering};
+				static ADDR: AtomicPtr<::std::ffi::c_void> = AtomicPtr::new(::std::ptr::null_mut());
+
+				if let Some(addr) = ::std::ptr::NonNull::new(ADDR.load(#ordering)) {
+					return Some(addr.cast());
+				}
+				#resolve
+				let addr = ::std::ptr::NonNull::new(symbol.ok()?.cast_mut().cast::<()>())?;
+				ADDR.store(addr.as_ptr().cast(), Ordering::Release);
+				#register
+				Some(addr)
+			}
+		}
+	}
+}
+
+// With `nullable`, converts the raw pointer returned by a function to `Option<NonNull<T>>`.
+// Returns the output of the generated function, and its call of the function pointer.
+fn nullable_return(
+	attr_data: &AttrData,
+	output: &syn::ReturnType,
+	call: TokenStream2,
+) -> (syn::ReturnType, TokenStream2) {
+	match (attr_data.nullable, output) {
+		(Some(_), syn::ReturnType::Type(_, ty)) if let syn::Type::Ptr(ptr) = ty.as_ref() => {
+			let elem = &ptr.elem;
+			(
+				syn::parse_quote!(-> ::std::option::Option<::std::ptr::NonNull<#elem>>),
+				quote!(::std::ptr::NonNull::new(#call as *mut #elem)),
+			)
+		}
+		_ => (output.clone(), call),
+	}
+}
+
+// Applies the `on_error` handler, and then the failure policy set with `dylink::config`, to a symbol
+// that failed to resolve.
+fn recover(attr_data: &AttrData, ident: &syn::Ident) -> TokenStream2 {
+	let handler = attr_data.on_error.as_ref().map(|handler| {
+		quote_spanned! {handler.span()=>
+			let handler: fn(&::std::io::Error) -> Option<*const ::dylink::Symbol> = #handler;
+			if let Some(symbol) = handler(&err) {
+				return symbol;
+			}
+		}
+	});
+	quote! {
+		let symbol = symbol.unwrap_or_else(|err| {
+			let err = ::std::io::Error::new(
+				err.kind(),
+				format!("failed to load `{}`: {}", stringify!(#ident), err),
+			);
+			#handler
+			::dylink::config::resolve_failed(err)
+		});
+	}
+}
+
+// Foreign statics can't be lazily initialized in place, so an accessor function is generated instead.
+fn parse_static(static_item: &syn::ForeignItemStatic, attr_data: &AttrData) -> TokenStream2 {
+	let library = match attr_data.library {
+		Ok(ref path) => path,
+		Err(span) => {
+			return syn::Error::new(
+				span,
+				"No library detected. Suggest using: `library = <path>` or `loader = <path>`.",
+			)
+			.to_compile_error();
+		}
+	};
+	let library_check = library_check(attr_data, library);
+	let attrs = &static_item.attrs;
+	let vis = &static_item.vis;
+	let ident = &static_item.ident;
+	let ty = &static_item.ty;
+	let resolve = symbol_lookup(attr_data, library, ident);
+	let output = match static_item.mutability {
+		syn::StaticMutability::Mut(_) => quote!(*mut #ty),
+		_ => quote!(&'static #ty),
+	};
+	let (output, unwrap) = match attr_data.checked {
+		Some(_) => (
+			quote!(::std::io::Result<#output>),
+			quote!(let symbol = symbol?;),
+		),
+		None => (output, recover(attr_data, ident)),
+	};
+	let cast = match static_item.mutability {
+		syn::StaticMutability::Mut(_) => quote!(addr.cast::<#ty>()),
+		_ => quote!(&*addr.cast::<#ty>()),
+	};
+	let ret = match attr_data.checked {
+		Some(_) => quote!(Ok(unsafe { #cast })),
+		None => quote!(unsafe { #cast }),
+	};
+	let (slot, outer_slot, inner_slot) = slot(attr_data, ident);
+	let shared = shared_load(attr_data, &slot);
+	let ordering = fast_ordering(attr_data);
+	let register = register_reset(
+		attr_data,
+		library,
+		slot.to_token_stream(),
+		quote!(::std::ptr::null_mut()),
+	);
+	quote! {
+		#outer_slot
+		#(#attrs)*
+		#[allow(non_snake_case)]
+		#[inline]
+		#vis unsafe fn #ident() -> #output {
+			#library_check
+			use ::std::sync::atomic::{AtomicPtr, Ordering};
+			#inner_slot
+
+			let mut addr = #slot.load(#ordering);
+			#shared
+			if addr.is_null() {
+				#resolve
+				#unwrap
+				addr = symbol.cast_mut().cast();
+				#slot.store(addr, Ordering::Release);
+				#register
+			}
+			#ret
+		}
+	}
+}
+
 fn parse_fn<const IS_MOD_ITEM: bool>(
 	abi: Option<&syn::Abi>,
 	fn_item: &syn::ForeignItemFn,
 	attr_data: &AttrData,
 ) -> TokenStream2 {
+	let ffi_lint = ffi_lint(abi, &fn_item.sig, attr_data);
 	let abi = abi.to_token_stream();
 	let fn_name = fn_item.sig.ident.to_token_stream();
 	let vis = fn_item.vis.to_token_stream();
@@ -105,6 +1357,9 @@ fn parse_fn<const IS_MOD_ITEM: bool>(
 				.to_compile_error();
 		}
 	};
+	if let Err(err) = ffi_check(&fn_item.sig, attr_data) {
+		return err.into_compile_error();
+	}
 	// constness makes no sense in this context
 	match &fn_item.sig.constness {
 		None => (),
@@ -188,25 +1443,65 @@ fn parse_fn<const IS_MOD_ITEM: bool>(
 		}
 	}
 
-	let lint;
-	let link_name = match &attr_data.link_name {
-		Some((name, _)) => {
-			lint = TokenStream2::default();
-			name.clone()
-		}
-		None => {
-			lint = quote! {#[allow(non_snake_case)]};
-			fn_name.to_string()
-		}
+	let mut lint = match &attr_data.link_name {
+		Some(_) => TokenStream2::default(),
+		None => quote! {#[allow(non_snake_case)]},
 	};
+	if attr_data.allow_improper_ctypes.is_some() {
+		lint.extend(quote! {#[allow(improper_ctypes_definitions)]});
+	}
+	let resolve = symbol_lookup(attr_data, library, &fn_item.sig.ident);
 
 	// This is mainly useful for applying lifetimes.
 	let generics = &fn_item.sig.generics;
 
-	// variadic compatible ABIs can use this
-	let variadic = match &fn_item.sig.variadic {
-		None => TokenStream2::default(),
-		Some(token) => quote!(, #token),
+	let fallback = match &attr_data.stub {
+		None if attr_data.optional.is_some() => {
+			if let Some(token) = &fn_item.sig.variadic {
+				return syn::Error::new(
+					token.span(),
+					"`optional` cannot be used with variadic functions",
+				)
+				.into_compile_error();
+			}
+			// raw pointers don't implement `Default`, so the shim returns null for them instead.
+			let value = match &fn_item.sig.output {
+				syn::ReturnType::Type(_, ty) => match ty.as_ref() {
+					syn::Type::Ptr(ptr) if ptr.mutability.is_some() => {
+						quote!(::std::ptr::null_mut())
+					}
+					syn::Type::Ptr(_) => quote!(::std::ptr::null()),
+					_ => quote!(::std::default::Default::default()),
+				},
+				syn::ReturnType::Default => TokenStream2::default(),
+			};
+			quote! {
+				let symbol = symbol.unwrap_or_else(|_| {
+					#[allow(unused_variables)]
+					unsafe #abi fn shim #generics (#(#internal_param_ty_list),*) #output {
+						#value
+					}
+					let shim: unsafe #abi fn (#(#internal_param_ty_list),*) #output = shim;
+					shim as *const ::dylink::Symbol
+				});
+			}
+		}
+		Some((stub, key)) => {
+			if let Some(token) = &fn_item.sig.variadic {
+				return syn::Error::new(
+					token.span(),
+					format!("`{key}` cannot be used with variadic functions"),
+				)
+				.into_compile_error();
+			}
+			quote_spanned! {stub.span()=>
+				let symbol = symbol.unwrap_or_else(|_| {
+					let stub: unsafe #abi fn (#(#internal_param_ty_list),*) #output = #stub;
+					stub as *const ::dylink::Symbol
+				});
+			}
+		}
+		None => recover(attr_data, &fn_item.sig.ident),
 	};
 
 	if let Some(token) = &fn_item.sig.asyncness {
@@ -225,30 +1520,163 @@ fn parse_fn<const IS_MOD_ITEM: bool>(
 		.into_compile_error();
 	}
 
+	let mut library_check = library_check(attr_data, library);
+	library_check.extend(ffi_lint);
+
+	let (slot, outer_slot, inner_slot) = slot(attr_data, &fn_item.sig.ident);
+	let shared = shared_load(attr_data, &slot);
+	let ordering = fast_ordering(attr_data);
+	let register = register_reset(
+		attr_data,
+		library,
+		slot.to_token_stream(),
+		quote!(::std::ptr::null_mut()),
+	);
+	// unloading points the thunk back at the initializer, so the symbol is resolved again.
+	let register_init = register_reset(
+		attr_data,
+		library,
+		quote!(FUNC),
+		quote!(initializer as *mut ::std::ffi::c_void),
+	);
+
+	let (ret_output, call) = nullable_return(
+		attr_data,
+		&fn_item.sig.output,
+		quote!(pfn(#(#param_list),*)),
+	);
+
+	// Variadic functions can't be defined on stable, let alone forward their arguments, so an
+	// accessor returning the resolved function pointer is generated instead, like for statics.
+	if let Some(token) = &fn_item.sig.variadic {
+		if fn_item.sig.receiver().is_some() {
+			return syn::Error::new(token.span(), "variadic methods are unsupported")
+				.into_compile_error();
+		}
+		// lifetimes of the function become higher-ranked lifetimes of the pointer.
+		let hrtb = match generics.params.is_empty() {
+			true => TokenStream2::default(),
+			false => quote!(for #generics),
+		};
+		let pfn = quote!(#hrtb unsafe #abi fn (#(#internal_param_ty_list,)* ...) #output);
+		let (ret_ty, unwrap, ret) = match attr_data.checked {
+			Some(_) => (
+				quote!(::std::io::Result<#pfn>),
+				quote!(let symbol = symbol?;),
+				quote!(Ok(::std::mem::transmute::<*mut ::std::ffi::c_void, #pfn>(addr))),
+			),
+			None => (
+				pfn.clone(),
+				fallback,
+				quote!(::std::mem::transmute::<*mut ::std::ffi::c_void, #pfn>(addr)),
+			),
+		};
+		return quote! {
+			#outer_slot
+			#(#fn_attrs)*
+			#lint
+			#[inline]
+			#vis unsafe fn #fn_name () -> #ret_ty {
+				#library_check
+				use ::std::sync::atomic::{AtomicPtr, Ordering};
+				#inner_slot
+
+				let mut addr = #slot.load(#ordering);
+				#shared
+				if addr.is_null() {
+					#resolve
+					#unwrap
+					addr = symbol.cast_mut().cast();
+					#slot.store(addr, Ordering::Release);
+					#register
+				}
+				#ret
+			}
+		};
+	}
+
+	if attr_data.eager.or(attr_data.shared).is_some() && attr_data.checked.is_none() {
+		// The slot is shared with `preload` or the shared initializer, so it can't start out
+		// pointing at the initializer.
+		return quote! {
+			#outer_slot
+			#(#fn_attrs)*
+			#lint
+			#[inline]
+			#vis unsafe #abi fn #generics #fn_name (#(#param_ty_list),*) #ret_output {
+				#library_check
+				use ::std::sync::atomic::{AtomicPtr, Ordering};
+
+				let mut addr = #slot.load(#ordering);
+				#shared
+				if addr.is_null() {
+					#resolve
+					#fallback
+					addr = symbol.cast_mut().cast();
+					#slot.store(addr, Ordering::Release);
+					#register
+				}
+				let pfn : #abi fn (#(#internal_param_ty_list),*) #output = ::std::mem::transmute(addr);
+				#call
+			}
+		};
+	}
+
+	if attr_data.checked.is_some() {
+		let ret_ty = match &ret_output {
+			syn::ReturnType::Default => quote!(()),
+			syn::ReturnType::Type(_, ty) => ty.to_token_stream(),
+		};
+		// The thunk can't forward errors, so the symbol is resolved before it's called instead.
+		return quote! {
+			#outer_slot
+			#(#fn_attrs)*
+			#lint
+			#[inline]
+			#vis unsafe fn #generics #fn_name (#(#param_ty_list),*) -> ::std::io::Result<#ret_ty> {
+				#library_check
+				use ::std::sync::atomic::{AtomicPtr, Ordering};
+				#inner_slot
+
+				let mut addr = #slot.load(#ordering);
+				#shared
+				if addr.is_null() {
+					#resolve
+					addr = symbol?.cast_mut().cast();
+					#slot.store(addr, Ordering::Release);
+					#register
+				}
+				let pfn : #abi fn (#(#internal_param_ty_list),*) #output = ::std::mem::transmute(addr);
+				Ok(#call)
+			}
+		};
+	}
+
 	// According to "The Rustonomicon" foreign functions are assumed unsafe,
 	// so functions are implicitly prepended with `unsafe`
 	quote! {
 		#(#fn_attrs)*
 		#lint
 		#[inline]
-		#vis unsafe #abi fn #generics #fn_name (#(#param_ty_list),* #variadic) #output {
+		#vis unsafe #abi fn #generics #fn_name (#(#param_ty_list),*) #ret_output {
+			#library_check
 			use ::std::sync::atomic::{AtomicPtr, Ordering};
 			static FUNC: AtomicPtr<::std::ffi::c_void> = AtomicPtr::new(
 				initializer as *mut _
 			);
 
-			unsafe #abi fn initializer #generics (#(#internal_param_ty_list),* #variadic) #output {
-				let symbol = ::dylink::sync::LibLock::symbol(&#library, #link_name)
-					.expect(&format!("Dylink Error: failed to load `{}`", stringify!(#fn_name)));
+			unsafe #abi fn initializer #generics (#(#internal_param_ty_list),*) #output {
+				#resolve
+				#fallback
 				FUNC.store(symbol.cast_mut().cast(), Ordering::Release);
+				#register_init
 				let pfn: #abi fn (#(#internal_param_ty_list),*) #output = ::std::mem::transmute(symbol);
 				pfn(#(#internal_param_list),*)
 			}
 
-			let symbol = FUNC.load(Ordering::Acquire);
-			::std::sync::atomic::compiler_fence(Ordering::Acquire);
+			let symbol = FUNC.load(#ordering);
 			let pfn : #abi fn (#(#internal_param_ty_list),*) #output = ::std::mem::transmute(symbol);
-			pfn(#(#param_list),*)
+			#call
 		}
 	}
 }
@@ -0,0 +1,109 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+// The library is loaded into the compiler to look up symbols, so its initializers are run. Handles
+// are never closed, since other invocations of the macro are likely to validate against them too.
+
+use std::{
+	ffi,
+	io,
+	path,
+};
+
+#[cfg(unix)]
+mod c {
+	use std::ffi;
+
+	pub const RTLD_LAZY: ffi::c_int = 0x1;
+	#[cfg(not(any(target_os = "macos", target_os = "ios")))]
+	pub const RTLD_LOCAL: ffi::c_int = 0;
+	#[cfg(any(target_os = "macos", target_os = "ios"))]
+	pub const RTLD_LOCAL: ffi::c_int = 0x4;
+
+	unsafe extern "C" {
+		pub fn dlopen(filename: *const ffi::c_char, flag: ffi::c_int) -> *mut ffi::c_void;
+		pub fn dlerror() -> *const ffi::c_char;
+		pub fn dlsym(handle: *mut ffi::c_void, symbol: *const ffi::c_char) -> *mut ffi::c_void;
+	}
+}
+
+#[cfg(windows)]
+mod c {
+	use std::ffi;
+
+	// initializers of the DLL and its dependencies aren't run with this flag.
+	pub const DONT_RESOLVE_DLL_REFERENCES: u32 = 0x1;
+
+	#[link(name = "kernel32")]
+	unsafe extern "system" {
+		pub fn LoadLibraryExW(
+			lplibfilename: *const u16,
+			hfile: *mut ffi::c_void,
+			dwflags: u32,
+		) -> *mut ffi::c_void;
+		pub fn GetProcAddress(
+			hmodule: *mut ffi::c_void,
+			lpprocname: *const ffi::c_char,
+		) -> *mut ffi::c_void;
+	}
+}
+
+pub struct Library(*mut ffi::c_void);
+
+impl Library {
+	// Relative paths are resolved against the crate being compiled, unless nothing exists there,
+	// in which case the path is passed to the loader to be searched for.
+	pub fn open(path: &str) -> io::Result<Self> {
+		let path = std::env::var_os("CARGO_MANIFEST_DIR")
+			.map(|dir| path::Path::new(&dir).join(path))
+			.filter(|path| path.exists())
+			.unwrap_or_else(|| path.into());
+		unsafe { Self::open_raw(&path) }
+	}
+
+	#[cfg(unix)]
+	unsafe fn open_raw(path: &path::Path) -> io::Result<Self> {
+		use std::os::unix::ffi::OsStrExt;
+		let c_str = ffi::CString::new(path.as_os_str().as_bytes())?;
+		let handle = unsafe { c::dlopen(c_str.as_ptr(), c::RTLD_LAZY | c::RTLD_LOCAL) };
+		if handle.is_null() {
+			let err = unsafe { c::dlerror() };
+			let msg = match err.is_null() {
+				true => "unknown error".into(),
+				false => unsafe { ffi::CStr::from_ptr(err) }.to_string_lossy(),
+			};
+			Err(io::Error::other(msg))
+		} else {
+			Ok(Self(handle))
+		}
+	}
+
+	#[cfg(windows)]
+	unsafe fn open_raw(path: &path::Path) -> io::Result<Self> {
+		use std::os::windows::ffi::OsStrExt;
+		let wide: Vec<u16> = path.as_os_str().encode_wide().chain([0]).collect();
+		let handle = unsafe {
+			c::LoadLibraryExW(
+				wide.as_ptr(),
+				std::ptr::null_mut(),
+				c::DONT_RESOLVE_DLL_REFERENCES,
+			)
+		};
+		if handle.is_null() {
+			Err(io::Error::last_os_error())
+		} else {
+			Ok(Self(handle))
+		}
+	}
+
+	pub fn has_symbol(&self, name: &str) -> bool {
+		let Ok(c_str) = ffi::CString::new(name) else {
+			return false;
+		};
+		#[cfg(unix)]
+		let addr = unsafe { c::dlsym(self.0, c_str.as_ptr()) };
+		#[cfg(windows)]
+		let addr = unsafe { c::GetProcAddress(self.0, c_str.as_ptr()) };
+		!addr.is_null()
+	}
+}
@@ -0,0 +1,86 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::Symbol;
+use std::{
+	path,
+	sync::RwLock,
+};
+
+static HOOK: RwLock<Option<fn(&BindEvent<'_>)>> = RwLock::new(None);
+
+/// The first binding of an item generated with [`dylink`](crate::dylink) to its symbol.
+///
+/// This object is passed to the hook set with [`set_bind_hook`].
+#[derive(Debug)]
+pub struct BindEvent<'a> {
+	library: &'a str,
+	item: &'a str,
+	names: &'a [&'a str],
+	address: *const Symbol,
+}
+
+impl BindEvent<'_> {
+	/// Returns the path of the `library` or `loader` the item resolved its symbol from, as written
+	/// in the attribute.
+	#[inline]
+	pub fn library(&self) -> &str {
+		self.library
+	}
+	/// Returns the name of the generated item.
+	#[inline]
+	pub fn item(&self) -> &str {
+		self.item
+	}
+	/// Returns the symbol names the item binds to, in the order they're tried, or `#n` when bound
+	/// by `link_ordinal`.
+	#[inline]
+	pub fn names(&self) -> &[&str] {
+		self.names
+	}
+	/// Returns the address the item is bound to.
+	#[inline]
+	pub fn address(&self) -> *const Symbol {
+		self.address
+	}
+	/// Returns the path of the image containing the address, which is looked up when called.
+	pub fn path(&self) -> Option<path::PathBuf> {
+		Symbol::image(self.address)?.path().ok()
+	}
+
+	#[doc(hidden)]
+	pub fn notify(library: &str, item: &str, names: &[&str], address: *const Symbol) {
+		let hook = *HOOK.read().unwrap_or_else(|err| err.into_inner());
+		if let Some(hook) = hook {
+			hook(&BindEvent {
+				library,
+				item,
+				names,
+				address,
+			});
+		}
+	}
+}
+
+/// Sets the hook called when an item generated with [`dylink`](crate::dylink) is first bound to its
+/// symbol, replacing the previous hook.
+///
+/// Items report their symbol when it resolves, before caching its address, which happens once per
+/// item unless several threads call it for the first time at once, or its `LibLock` is unloaded.
+/// Items bound to a `stub`, an `optional` shim, or a substitute from `on_error` because their symbol
+/// failed to resolve aren't reported. The hook may be called from any thread, and while the library
+/// of the item is being loaded, so it shouldn't call generated items itself.
+///
+/// # Examples
+///
+/// ```
+/// dylink::set_bind_hook(|event| eprintln!("{} bound to {:p}", event.item(), event.address()));
+/// ```
+pub fn set_bind_hook(hook: fn(&BindEvent<'_>)) {
+	*HOOK.write().unwrap_or_else(|err| err.into_inner()) = Some(hook);
+}
+
+/// Removes the hook set with [`set_bind_hook`], returning it if one was set.
+pub fn take_bind_hook() -> Option<fn(&BindEvent<'_>)> {
+	HOOK.write().unwrap_or_else(|err| err.into_inner()).take()
+}
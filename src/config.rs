@@ -0,0 +1,71 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Process-wide configuration of items generated with [`dylink`](crate::dylink).
+//!
+//! # Examples
+//!
+//! ```
+//! use dylink::config::{self, Policy};
+//!
+//! // log the failure to the crash reporter and exit, rather than unwinding through FFI.
+//! config::set_failure_policy(Policy::Abort);
+//! ```
+
+use crate::Symbol;
+use std::{
+	io,
+	process,
+	sync::RwLock,
+};
+
+static POLICY: RwLock<Policy> = RwLock::new(Policy::Panic);
+
+/// What generated items do when their symbol fails to resolve.
+///
+/// The policy only applies to items that would otherwise panic, so items with a `stub` still bind
+/// to it, and `checked` items still return the error. An `on_error` handler is called first, and
+/// the policy applies if it doesn't return a substitute.
+#[derive(Debug, Clone, Copy, Default)]
+#[non_exhaustive]
+pub enum Policy {
+	/// Panics with the error, which is the default.
+	#[default]
+	Panic,
+	/// Prints the error to standard error, and aborts the process.
+	Abort,
+	/// Calls the hook with the error, which may return the address of a substitute to bind to
+	/// instead, or `None` to panic.
+	///
+	/// The hook has the same signature as an `on_error` handler, and may be called from any thread.
+	ErrorHook(fn(&io::Error) -> Option<*const Symbol>),
+}
+
+/// Sets the policy applied by every generated item when its symbol fails to resolve, replacing the
+/// previous policy.
+pub fn set_failure_policy(policy: Policy) {
+	*POLICY.write().unwrap_or_else(|err| err.into_inner()) = policy;
+}
+
+/// Returns the policy applied when a symbol fails to resolve.
+pub fn failure_policy() -> Policy {
+	*POLICY.read().unwrap_or_else(|err| err.into_inner())
+}
+
+#[doc(hidden)]
+#[track_caller]
+pub fn resolve_failed(err: io::Error) -> *const Symbol {
+	match failure_policy() {
+		Policy::Panic => {}
+		Policy::Abort => {
+			eprintln!("Dylink Error: {err}");
+			process::abort();
+		}
+		Policy::ErrorHook(hook) => {
+			if let Some(symbol) = hook(&err) {
+				return symbol;
+			}
+		}
+	}
+	panic!("Dylink Error: {err}")
+}
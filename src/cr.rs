@@ -0,0 +1,60 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Support for checkpointing and restoring the process, such as with [CRIU].
+//!
+//! A restored process may not have its images mapped at the same addresses as when it was
+//! checkpointed, which invalidates every library handle and symbol address cached by this crate.
+//! Calling [`after_restore`] once the process resumes drops them, so that they're resolved again
+//! lazily on their next use.
+//!
+//! Nothing needs to be done before a checkpoint, other than making sure no other thread is loading
+//! libraries or resolving symbols while it's taken.
+//!
+//! [CRIU]: https://criu.org
+
+use crate::sync::{
+	LibLock,
+	Open,
+};
+use std::io;
+
+/// Drops the state cached before a checkpoint, after the process was restored.
+///
+/// Each `LibLock` forgets its library without closing it, since its handle may no longer be
+/// valid, and every function generated by [`dylink`](crate::dylink) for it is reset, so that the
+/// library is opened again, and symbols are resolved again, when they're next called. The
+/// process-wide state is then gathered again with [`flush_caches`](crate::flush_caches).
+///
+/// `LibLock`s that aren't passed keep their library and symbols.
+///
+/// # Errors
+///
+/// May error if the loaded images can't be enumerated.
+///
+/// # Safety
+///
+/// No other thread may be using the `LibLock`s or any of their generated functions, and
+/// references returned by [`LibLock::get`], as well as addresses of symbols, must no longer be used.
+///
+/// # Examples
+///
+/// ```no_run
+/// use dylink::{cr, sync::LibLock};
+///
+/// static LIBFOO: LibLock = LibLock::new(&["libfoo.so.1"]);
+///
+/// // called by the application once it's notified of the restore.
+/// fn on_restore() {
+///     unsafe { cr::after_restore(&[&LIBFOO]) }.unwrap();
+/// }
+/// ```
+pub unsafe fn after_restore<O: Open>(locks: &[&LibLock<'_, O>]) -> io::Result<()> {
+	for lock in locks {
+		if let Some(lib) = unsafe { lock.reset() } {
+			// closing a handle from before the restore could free an unrelated image.
+			let _ = lib.leak();
+		}
+	}
+	crate::flush_caches()
+}
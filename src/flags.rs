@@ -0,0 +1,113 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::imp;
+use std::ops;
+
+/// Flags controlling how [`Library::open_with`](crate::Library::open_with) loads a library.
+///
+/// The flags are passed to `dlopen` on Unix, and to `LoadLibraryExW` on Windows, so their meaning
+/// differs between platforms. The presets map to the closest flags each platform has:
+///
+/// | Preset | Linux (glibc) | MacOS | Windows |
+/// |---|---|---|---|
+/// | [`DEFAULT`] | `RTLD_NOW \| RTLD_LOCAL` | `RTLD_NOW \| RTLD_LOCAL` | `0` |
+/// | [`PLUGIN`] | `RTLD_NOW \| RTLD_LOCAL \| RTLD_DEEPBIND` | `RTLD_NOW \| RTLD_LOCAL` | `LOAD_LIBRARY_SEARCH_DEFAULT_DIRS` |
+/// | [`SYSTEM`] | `RTLD_NOW \| RTLD_LOCAL` | `RTLD_NOW \| RTLD_LOCAL` | `LOAD_LIBRARY_SEARCH_SYSTEM32` |
+/// | [`INSPECT`] | `RTLD_NOW \| RTLD_LOCAL \| RTLD_NOLOAD` | `RTLD_NOW \| RTLD_LOCAL \| RTLD_NOLOAD` | `LOAD_LIBRARY_AS_DATAFILE \| LOAD_LIBRARY_AS_IMAGE_RESOURCE` |
+///
+/// Other Unix platforms use `RTLD_NOW | RTLD_LOCAL` for every preset, except that `INSPECT` adds
+/// `RTLD_NOLOAD` on those using glibc. Any of them can bind symbols lazily with [`lazy`].
+///
+/// [`DEFAULT`]: Self::DEFAULT
+/// [`PLUGIN`]: Self::PLUGIN
+/// [`SYSTEM`]: Self::SYSTEM
+/// [`INSPECT`]: Self::INSPECT
+/// [`lazy`]: Self::lazy
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OpenFlags(u32);
+
+impl OpenFlags {
+	/// The flags used by [`Library::open`](crate::Library::open).
+	pub const DEFAULT: Self = Self(imp::DEFAULT_FLAGS);
+	/// Flags for plugins, which keep the symbols of the library to itself.
+	///
+	/// On Linux, the library also prefers its own symbols over those of the process, so that a
+	/// plugin bundling a different version of a common library calls its own copy. On Windows, the
+	/// current directory isn't searched for the library or its dependencies.
+	pub const PLUGIN: Self = Self(imp::PLUGIN_FLAGS);
+	/// Flags for system libraries.
+	///
+	/// On Windows, the library and its dependencies are only searched for in the system directory,
+	/// which prevents [preloading attacks]. Unix has no such restriction, so the search path is
+	/// determined by the usual environment variables, and should be guarded by passing an absolute
+	/// path.
+	///
+	/// [preloading attacks]: https://learn.microsoft.com/en-us/windows/win32/dlls/dynamic-link-library-security
+	pub const SYSTEM: Self = Self(imp::SYSTEM_FLAGS);
+	/// Flags for inspecting a library without running its code.
+	///
+	/// On Unix, the library is only opened if it's already loaded, so its initializers never run
+	/// again. On Windows, the library is mapped as a data file, so its initializers aren't run, but
+	/// its symbols can't be resolved either.
+	pub const INSPECT: Self = Self(imp::INSPECT_FLAGS);
+
+	/// Creates flags from the raw flags of the platform, such as `RTLD_LAZY` or
+	/// `LOAD_LIBRARY_SEARCH_APPLICATION_DIR`.
+	#[inline]
+	pub const fn from_bits(bits: u32) -> Self {
+		Self(bits)
+	}
+	/// Returns the raw flags of the platform.
+	#[inline]
+	pub const fn bits(self) -> u32 {
+		self.0
+	}
+	/// Returns these flags with functions bound when they're first called, rather than when the
+	/// library is opened.
+	///
+	/// On Unix, this replaces `RTLD_NOW` with `RTLD_LAZY`, so opening a large library, such as a
+	/// GPU driver, doesn't relocate every function it imports up front. A function the library
+	/// imports that can't be resolved then aborts the process when it's called, rather than
+	/// failing to open the library. This does nothing on Windows, where imports are always bound
+	/// when the library is loaded.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use dylink::{Library, OpenFlags};
+	///
+	/// let lib = Library::open_with("libcuda.so.1", OpenFlags::DEFAULT.lazy()).unwrap();
+	/// ```
+	#[inline]
+	pub const fn lazy(self) -> Self {
+		Self(self.0 & !imp::EAGER_FLAGS | imp::LAZY_FLAGS)
+	}
+	/// Returns `true` if all flags in `other` are contained in `self`.
+	#[inline]
+	pub const fn contains(self, other: Self) -> bool {
+		self.0 & other.0 == other.0
+	}
+}
+
+impl Default for OpenFlags {
+	#[inline]
+	fn default() -> Self {
+		Self::DEFAULT
+	}
+}
+
+impl ops::BitOr for OpenFlags {
+	type Output = Self;
+	#[inline]
+	fn bitor(self, rhs: Self) -> Self::Output {
+		Self(self.0 | rhs.0)
+	}
+}
+
+impl ops::BitOrAssign for OpenFlags {
+	#[inline]
+	fn bitor_assign(&mut self, rhs: Self) {
+		self.0 |= rhs.0
+	}
+}
@@ -0,0 +1,114 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::{
+	Library,
+	Symbol,
+	img,
+};
+use std::{
+	fmt,
+	io,
+	marker,
+	mem,
+	ptr,
+};
+
+/// A typed reference to a global variable exported by a [`Library`].
+///
+/// Accesses are volatile, since the library may change the variable behind the back of the
+/// compiler. This object can be obtained through [`Library::global`].
+pub struct GlobalRef<'a, T> {
+	ptr: ptr::NonNull<T>,
+	_marker: marker::PhantomData<&'a Library>,
+}
+
+unsafe impl<T: Send> Send for GlobalRef<'_, T> {}
+unsafe impl<T: Sync> Sync for GlobalRef<'_, T> {}
+
+impl<T> fmt::Debug for GlobalRef<'_, T> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_tuple("GlobalRef").field(&self.ptr).finish()
+	}
+}
+
+impl<'a, T> GlobalRef<'a, T> {
+	// Checks that the symbol can hold a `T`, with the export table of its image when it knows the
+	// size of the symbol.
+	pub(crate) fn new(name: &str, addr: *const Symbol) -> io::Result<Self> {
+		let ptr = ptr::NonNull::new(addr.cast_mut().cast::<T>()).ok_or(io::Error::new(
+			io::ErrorKind::InvalidData,
+			"global resolved to a null address",
+		))?;
+		if !ptr.as_ptr().is_aligned() {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidData,
+				format!(
+					"global `{name}` isn't aligned to {} bytes",
+					mem::align_of::<T>()
+				),
+			));
+		}
+		if let Some(size) = export_size(name, addr)
+			&& size < mem::size_of::<T>()
+		{
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidData,
+				format!(
+					"global `{name}` is {size} bytes, but the type is {} bytes",
+					mem::size_of::<T>()
+				),
+			));
+		}
+		Ok(Self {
+			ptr,
+			_marker: marker::PhantomData,
+		})
+	}
+
+	/// Returns the address of the global.
+	#[inline]
+	pub fn as_ptr(&self) -> *mut T {
+		self.ptr.as_ptr()
+	}
+
+	/// Reads the current value of the global.
+	#[inline]
+	pub fn read(&self) -> T
+	where
+		T: Copy,
+	{
+		unsafe { self.ptr.read_volatile() }
+	}
+
+	/// Overwrites the global with `value`, without dropping the previous value.
+	///
+	/// # Safety
+	///
+	/// The global must be writable, which isn't the case for `const` globals placed in read-only
+	/// memory, and the library must allow it to be changed from outside of it.
+	#[inline]
+	pub unsafe fn write(&self, value: T)
+	where
+		T: Copy,
+	{
+		unsafe { self.ptr.write_volatile(value) }
+	}
+}
+
+// Returns the size of the exported symbol, if its image records it. Symbols without a type, such as
+// those defined by the linker, are recorded with a size of zero.
+fn export_size(name: &str, addr: *const Symbol) -> Option<usize> {
+	let exports = Symbol::image(addr)?.exports().ok()?;
+	exports
+		.into_iter()
+		.find(|export| {
+			export.name() == Some(name)
+				&& matches!(
+					export.kind(),
+					img::ExportKind::Data | img::ExportKind::Unknown
+				)
+		})?
+		.size()
+		.filter(|&size| size != 0)
+}
@@ -2,36 +2,149 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 use crate::os;
-use crate::weak;
+#[cfg(all(feature = "images", feature = "zeroize"))]
+use crate::scrub::Scrub;
 use std::io;
-use std::iter::FusedIterator;
 use std::path;
-use std::vec;
+
+#[cfg(feature = "images")]
+use crate::weak;
+#[cfg(feature = "images")]
+use std::{
+	collections::HashSet,
+	error,
+	fmt,
+	iter::FusedIterator,
+	marker,
+	ops,
+	vec,
+};
 
 #[cfg(unix)]
 use os::unix as imp;
 #[cfg(windows)]
 use os::windows as imp;
 
+#[cfg(feature = "images")]
+mod file;
+#[cfg(feature = "module-map")]
+mod map;
+#[cfg(feature = "images")]
+mod perf;
+#[cfg(feature = "images")]
+pub(crate) mod raw;
+#[cfg(feature = "images")]
+mod version;
+#[cfg(feature = "images")]
+mod view;
+
+#[cfg_attr(docsrs, doc(cfg(feature = "images")))]
+#[cfg(feature = "images")]
+pub use file::File;
+#[cfg_attr(docsrs, doc(cfg(feature = "module-map")))]
+#[cfg(feature = "module-map")]
+pub use map::{
+	Module,
+	ModuleMap,
+	ModuleRef,
+	Modules,
+};
+#[cfg_attr(docsrs, doc(cfg(feature = "images")))]
+#[cfg(feature = "images")]
+pub use perf::{
+	PageStats,
+	PerfReport,
+};
+#[cfg(feature = "images")]
+pub(crate) use raw::RawImage;
+#[cfg_attr(docsrs, doc(cfg(feature = "images")))]
+#[cfg(feature = "images")]
+pub use version::{
+	Version,
+	VersionInfo,
+};
+#[cfg_attr(docsrs, doc(cfg(feature = "images")))]
+#[cfg(all(unix, feature = "images"))]
+pub use view::{
+	ElfProgramHeader,
+	ElfView,
+	MachoSegment,
+	MachoView,
+};
+#[cfg_attr(docsrs, doc(cfg(feature = "images")))]
+#[cfg(all(windows, feature = "images"))]
+pub use view::{
+	PeSection,
+	PeView,
+};
+
 // This is an iterator and not a vector because the data should be assumed stale.
 /// An iterator over executable images.
 ///
 /// # Safety
 ///
 /// Images retrieved from this iterator should be assumed stale (possibly invalid or unloaded).
+#[cfg_attr(docsrs, doc(cfg(feature = "images")))]
+#[cfg(feature = "images")]
 #[derive(Debug, Clone)]
 pub struct Images {
 	inner: vec::IntoIter<weak::Weak>,
 }
 
+#[cfg(feature = "images")]
 impl Images {
 	/// Takes a snapshot of executable images currently loaded into memory.
 	pub fn now() -> io::Result<Self> {
 		let inner = unsafe { imp::load_objects()?.into_iter() };
 		Ok(Self { inner })
 	}
+
+	/// Compares two snapshots, returning the images loaded and unloaded between them.
+	///
+	/// Images are matched by both base address and path, so an address reused by a different
+	/// image is reported as one image unloaded and another loaded. Images remaining in either
+	/// snapshot are included in the comparison, while images already consumed are not.
+	///
+	/// An image that was unloaded and then reloaded at the same address between the two
+	/// snapshots can't be distinguished from an image that stayed loaded.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use dylink::img::Images;
+	///
+	/// let before = Images::now().unwrap();
+	/// // load or unload libraries here...
+	/// let after = Images::now().unwrap();
+	/// let diff = Images::diff(&before, &after);
+	/// for weak in diff.loaded() {
+	///     println!("loaded: {:?}", weak.path());
+	/// }
+	/// ```
+	pub fn diff(before: &Images, after: &Images) -> ImagesDiff {
+		fn key(weak: &weak::Weak) -> (*const Image, Option<&path::Path>) {
+			(weak.base_addr, weak.path())
+		}
+		let before = before.inner.as_slice();
+		let after = after.inner.as_slice();
+		let old: HashSet<_> = before.iter().map(key).collect();
+		let new: HashSet<_> = after.iter().map(key).collect();
+		ImagesDiff {
+			loaded: after
+				.iter()
+				.filter(|weak| !old.contains(&key(weak)))
+				.cloned()
+				.collect(),
+			unloaded: before
+				.iter()
+				.filter(|weak| !new.contains(&key(weak)))
+				.cloned()
+				.collect(),
+		}
+	}
 }
 
+#[cfg(feature = "images")]
 impl From<Vec<weak::Weak>> for Images {
 	fn from(value: Vec<weak::Weak>) -> Self {
 		Self {
@@ -40,6 +153,7 @@ impl From<Vec<weak::Weak>> for Images {
 	}
 }
 
+#[cfg(feature = "images")]
 impl Iterator for Images {
 	type Item = weak::Weak;
 	#[inline]
@@ -59,6 +173,7 @@ impl Iterator for Images {
 	}
 }
 
+#[cfg(feature = "images")]
 impl DoubleEndedIterator for Images {
 	#[inline]
 	fn next_back(&mut self) -> Option<Self::Item> {
@@ -66,6 +181,7 @@ impl DoubleEndedIterator for Images {
 	}
 }
 
+#[cfg(feature = "images")]
 impl ExactSizeIterator for Images {
 	#[inline]
 	fn len(&self) -> usize {
@@ -73,8 +189,43 @@ impl ExactSizeIterator for Images {
 	}
 }
 
+#[cfg(feature = "images")]
 impl FusedIterator for Images {}
 
+/// The difference between two [`Images`] snapshots.
+///
+/// This object is created by [`Images::diff`].
+#[cfg_attr(docsrs, doc(cfg(feature = "images")))]
+#[cfg(feature = "images")]
+#[derive(Debug, Clone, Default)]
+pub struct ImagesDiff {
+	loaded: Vec<weak::Weak>,
+	unloaded: Vec<weak::Weak>,
+}
+
+#[cfg(feature = "images")]
+impl ImagesDiff {
+	/// Returns the images present in the later snapshot, but not the earlier one.
+	#[inline]
+	pub fn loaded(&self) -> &[weak::Weak] {
+		&self.loaded
+	}
+
+	/// Returns the images present in the earlier snapshot, but not the later one.
+	///
+	/// These images should be assumed unloaded, so they must not be upgraded.
+	#[inline]
+	pub fn unloaded(&self) -> &[weak::Weak] {
+		&self.unloaded
+	}
+
+	/// Returns `true` if no images were loaded or unloaded.
+	#[inline]
+	pub fn is_empty(&self) -> bool {
+		self.loaded.is_empty() && self.unloaded.is_empty()
+	}
+}
+
 /// An opaque object representing an executable image.
 ///
 /// # Platform behavior
@@ -109,13 +260,607 @@ impl Image {
 	pub fn path(&self) -> io::Result<path::PathBuf> {
 		unsafe { imp::hdr_path(self as *const Image) }
 	}
+}
 
+#[cfg_attr(docsrs, doc(cfg(feature = "images")))]
+#[cfg(feature = "images")]
+impl Image {
 	/// Converts this Image to a byte slice.
+	///
+	/// # Errors
+	///
+	/// Errors with [`InvalidData`](io::ErrorKind::InvalidData) and a [`Packed`] inner error if the
+	/// image looks packed, since its headers can't be trusted to describe its memory. See
+	/// [`packed`](Image::packed).
 	pub fn to_bytes(&self) -> io::Result<&[u8]> {
+		if let Some(packed) = self.packed()? {
+			return Err(io::Error::new(io::ErrorKind::InvalidData, packed));
+		}
 		let len = unsafe { imp::hdr_size(self)? };
 		let data = self as *const Image as *const u8;
 		// this is safe because hdr_size checks if the slice is valid.
 		let slice = unsafe { std::slice::from_raw_parts::<u8>(data, len) };
 		Ok(slice)
 	}
+
+	/// Returns how the image diverges from its headers, if it looks like it was compressed by a
+	/// packer such as UPX, or protected by rewriting itself at run time.
+	///
+	/// Packers reserve segments that are both writable and executable to unpack code into, and
+	/// protected modules may leave the headers describing memory that isn't mapped. Tools reading
+	/// such images through their headers would get bogus data, or crash. The check is a heuristic,
+	/// so an image that isn't reported may still be packed.
+	///
+	/// Unmapped segments are only detected on Windows and Linux, where the live mapping can be
+	/// queried.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use dylink::Library;
+	///
+	/// let lib = Library::open("foo.dll").unwrap();
+	/// if let Some(packed) = lib.to_image().unwrap().packed().unwrap() {
+	///     eprintln!("skipping foo.dll: {packed}");
+	/// }
+	/// ```
+	pub fn packed(&self) -> io::Result<Option<Packed>> {
+		let segments = self.segments()?;
+		if let Some(segment) = segments.iter().find(|segment| {
+			let protection = segment.protection();
+			protection.is_writable() && protection.is_executable()
+		}) {
+			return Ok(Some(Packed::WritableCode { addr: segment.addr }));
+		}
+		for segment in segments.iter().filter(|segment| !segment.is_empty()) {
+			match segment.query() {
+				Err(err) if err.kind() == io::ErrorKind::NotFound => {
+					return Ok(Some(Packed::Unmapped { addr: segment.addr }));
+				}
+				Err(err) if err.kind() == io::ErrorKind::Unsupported => break,
+				_ => {}
+			}
+		}
+		Ok(None)
+	}
+
+	/// Returns the memory segments of the image along with their memory protections.
+	///
+	/// The protections are taken from the image headers, which describe how the loader
+	/// initially mapped each segment. Use [`Segment::query`] to compare against the live mapping.
+	///
+	/// # Platform behavior
+	///
+	/// | Platform | Source                         |
+	/// | -------- | ------------------------------ |
+	/// | MacOS    | `LC_SEGMENT`, `LC_SEGMENT_64`  |
+	/// | Windows  | section characteristics        |
+	/// | Linux    | `PT_LOAD` program headers      |
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use dylink::Library;
+	///
+	/// let lib = Library::open("foo.dll").unwrap();
+	/// for seg in lib.to_image().unwrap().segments().unwrap() {
+	///     assert!(!(seg.protection().is_writable() && seg.protection().is_executable()));
+	/// }
+	/// ```
+	pub fn segments(&self) -> io::Result<Vec<Segment>> {
+		self.object().map(|obj| obj.segments())
+	}
+
+	/// Returns the symbols exported by the image.
+	///
+	/// Export addresses are relative to the image base. Use [`Export::rva`] to get the offset.
+	///
+	/// # Platform behavior
+	///
+	/// | Platform | Source                          |
+	/// | -------- | ------------------------------- |
+	/// | MacOS    | `LC_SYMTAB` external symbols    |
+	/// | Windows  | export directory                |
+	/// | Linux    | `.dynsym` defined symbols       |
+	pub fn exports(&self) -> io::Result<Vec<Export>> {
+		let obj = self.object()?;
+		unsafe { obj.exports() }
+	}
+
+	/// Returns the target architecture of the image.
+	pub fn arch(&self) -> io::Result<Arch> {
+		self.object().map(|obj| obj.arch())
+	}
+
+	/// Returns the version information embedded in the image.
+	///
+	/// # Platform behavior
+	///
+	/// | Platform | Source                                             |
+	/// | -------- | -------------------------------------------------- |
+	/// | MacOS    | `LC_ID_DYLIB` current and compatibility versions   |
+	/// | Windows  | `VS_FIXEDFILEINFO` file version                    |
+	/// | Linux    | `DT_SONAME` and `DT_VERDEF` version definitions    |
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use dylink::{Library, img::Version};
+	///
+	/// let lib = Library::open("libcuda.so.1").unwrap();
+	/// let version = lib.to_image().unwrap().version().unwrap();
+	/// if version.current() < Some(Version::new(535, 0, 0)) {
+	///     panic!("driver is too old");
+	/// }
+	/// ```
+	pub fn version(&self) -> io::Result<VersionInfo> {
+		let obj = self.object()?;
+		unsafe { obj.version() }
+	}
+
+	/// Advises the system on how the memory of the image will be accessed.
+	///
+	/// This can be used to warm up an image before it's used in a latency sensitive
+	/// context, or to release the pages of an idle image without unloading it.
+	///
+	/// The advice is applied to each [`Segment`] of the image. To avoid discarding relocated
+	/// or otherwise modified data, [`Advice::DontNeed`] is only applied to segments that aren't writable.
+	///
+	/// # Platform behavior
+	///
+	/// | Platform      | [`WillNeed`](Advice::WillNeed) | [`DontNeed`](Advice::DontNeed) |
+	/// | ------------- | ------------------------------ | ------------------------------ |
+	/// | Windows       | `PrefetchVirtualMemory`        | `VirtualUnlock`                |
+	/// | Linux, MacOS  | `madvise(MADV_WILLNEED)`       | `madvise(MADV_DONTNEED)`       |
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use dylink::{img::Advice, Library};
+	///
+	/// let lib = Library::open("foo.dll").unwrap();
+	/// lib.to_image().unwrap().advise(Advice::WillNeed).unwrap();
+	/// ```
+	#[doc(alias = "madvise", alias = "PrefetchVirtualMemory")]
+	pub fn advise(&self, advice: Advice) -> io::Result<()> {
+		let page_size = imp::page_size();
+		let ranges: Vec<_> = self
+			.segments()?
+			.into_iter()
+			.filter_map(|seg| {
+				let range = seg.range();
+				match advice {
+					// round outwards so the whole segment is covered.
+					Advice::WillNeed => {
+						let start = range.start & !(page_size - 1);
+						let end = range.end.next_multiple_of(page_size);
+						Some(start..end)
+					}
+					// round inwards so neighboring memory is never discarded.
+					Advice::DontNeed if !seg.protection().is_writable() => {
+						let start = range.start.next_multiple_of(page_size);
+						let end = range.end & !(page_size - 1);
+						Some(start..end)
+					}
+					Advice::DontNeed => None,
+				}
+			})
+			.filter(|range| !range.is_empty())
+			.collect();
+		unsafe { imp::advise(&ranges, advice) }
+	}
+
+	/// Summarizes the load cost of the image for performance tuning.
+	///
+	/// # Platform behavior
+	///
+	/// Page statistics are taken from `/proc/self/smaps` on Linux, and `QueryWorkingSetEx` on Windows.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use dylink::Library;
+	///
+	/// let lib = Library::open("foo.dll").unwrap();
+	/// let report = lib.to_image().unwrap().perf_report().unwrap();
+	/// if let Ok(pages) = report.pages() {
+	///     println!("{} private pages, {} shared pages", pages.private(), pages.shared());
+	/// }
+	/// ```
+	pub fn perf_report(&self) -> io::Result<PerfReport> {
+		let obj = self.object()?;
+		let ranges: Vec<_> = obj.segments().iter().map(Segment::range).collect();
+		Ok(PerfReport {
+			base: self as *const Image as usize,
+			preferred_base: obj.preferred_base(),
+			page_size: imp::page_size(),
+			pages: imp::page_stats(&ranges),
+		})
+	}
+
+	/// Returns the thread-local storage of the image, or `None` if it has none.
+	///
+	/// # Errors
+	///
+	/// May error if the image is malformed, or on platforms where thread-local storage can't be
+	/// inspected.
+	///
+	/// # Platform behavior
+	///
+	/// | Platform | Source                      |
+	/// | -------- | --------------------------- |
+	/// | Windows  | TLS directory               |
+	/// | Linux    | `PT_TLS` program header     |
+	/// | MacOS    | unsupported                 |
+	pub fn tls(&self) -> io::Result<Option<Tls>> {
+		let obj = self.object()?;
+		unsafe { obj.tls() }
+	}
+
+	/// Returns the addresses of the TLS callbacks of the image, which the loader calls whenever a
+	/// thread starts or exits.
+	///
+	/// TLS callbacks run before the entry point of the image, so they're a common way of injecting
+	/// code, and worth auditing in images that shouldn't have any. Only PE images have TLS
+	/// callbacks, so this is empty on other platforms.
+	///
+	/// # Errors
+	///
+	/// May error under the same conditions as [`tls`](Self::tls).
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use dylink::Library;
+	///
+	/// let lib = Library::open("foo.dll").unwrap();
+	/// for addr in lib.to_image().unwrap().tls_callbacks().unwrap() {
+	///     println!("TLS callback at {addr:#x}");
+	/// }
+	/// ```
+	pub fn tls_callbacks(&self) -> io::Result<Vec<usize>> {
+		Ok(self.tls()?.map_or_else(Vec::new, |tls| tls.callbacks))
+	}
+
+	/// Returns a typed view of the ELF headers, or `None` if the image isn't an ELF image.
+	#[cfg_attr(docsrs, doc(cfg(unix)))]
+	#[cfg(unix)]
+	pub fn as_elf(&self) -> Option<ElfView<'_>> {
+		match self.object().ok()? {
+			imp::Object::Elf(inner) => Some(ElfView {
+				inner,
+				_marker: marker::PhantomData,
+			}),
+			_ => None,
+		}
+	}
+
+	/// Returns a typed view of the Mach-O headers, or `None` if the image isn't a Mach-O image.
+	#[cfg_attr(docsrs, doc(cfg(unix)))]
+	#[cfg(unix)]
+	pub fn as_macho(&self) -> Option<MachoView<'_>> {
+		match self.object().ok()? {
+			imp::Object::MachO(inner) => Some(MachoView {
+				inner,
+				_marker: marker::PhantomData,
+			}),
+			_ => None,
+		}
+	}
+
+	/// Returns a typed view of the PE headers, or `None` if the headers are invalid.
+	#[cfg_attr(docsrs, doc(cfg(windows)))]
+	#[cfg(windows)]
+	pub fn as_pe(&self) -> Option<PeView<'_>> {
+		self.object().ok().map(|inner| PeView {
+			inner,
+			_marker: marker::PhantomData,
+		})
+	}
+
+	#[inline]
+	fn object(&self) -> io::Result<imp::Object> {
+		unsafe { imp::Object::parse(RawImage::loaded(self)) }
+	}
+}
+
+/// Advice for how the memory of an image will be accessed.
+///
+/// This object is used by [`Image::advise`].
+#[cfg_attr(docsrs, doc(cfg(feature = "images")))]
+#[cfg(feature = "images")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Advice {
+	/// The image will be accessed soon, so its pages should be read ahead.
+	WillNeed,
+	/// The image won't be accessed soon, so its pages may be released.
+	DontNeed,
+}
+
+/// The target architecture of an image.
+#[cfg_attr(docsrs, doc(cfg(feature = "images")))]
+#[cfg(feature = "images")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Arch {
+	/// 32-bit x86.
+	X86,
+	/// 64-bit x86.
+	X86_64,
+	/// 32-bit ARM.
+	Arm,
+	/// 64-bit ARM.
+	AArch64,
+	/// 64-bit RISC-V.
+	RiscV64,
+	/// An architecture not recognized by dylink, containing the raw machine type.
+	Unknown(u32),
+}
+
+/// The kind of item an [`Export`] refers to.
+#[cfg_attr(docsrs, doc(cfg(feature = "images")))]
+#[cfg(feature = "images")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ExportKind {
+	/// Executable code.
+	Function,
+	/// Data, such as a global variable.
+	Data,
+	/// Thread-local data. The address is an offset into the thread-local storage block.
+	Tls,
+	/// The image format doesn't specify the kind of item.
+	Unknown,
+}
+
+/// A symbol exported by an image.
+///
+/// This object can be obtained through [`Image::exports`] or [`File::exports`].
+#[cfg_attr(docsrs, doc(cfg(feature = "images")))]
+#[cfg(feature = "images")]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Export {
+	pub(crate) name: Option<String>,
+	pub(crate) ordinal: Option<u32>,
+	pub(crate) rva: usize,
+	pub(crate) size: Option<usize>,
+	pub(crate) kind: ExportKind,
+	pub(crate) forwarder: Option<String>,
+}
+
+#[cfg(feature = "images")]
+impl Export {
+	/// Returns the name of the export, or [`None`] if it's only exported by ordinal.
+	#[inline]
+	pub fn name(&self) -> Option<&str> {
+		self.name.as_deref()
+	}
+	/// Returns the ordinal of the export. Only Windows uses ordinals.
+	#[inline]
+	pub fn ordinal(&self) -> Option<u32> {
+		self.ordinal
+	}
+	/// Returns the address of the export relative to the image base.
+	#[inline]
+	pub fn rva(&self) -> usize {
+		self.rva
+	}
+	/// Returns the size of the exported item if it's known.
+	#[inline]
+	pub fn size(&self) -> Option<usize> {
+		self.size
+	}
+	/// Returns the kind of the exported item.
+	#[inline]
+	pub fn kind(&self) -> ExportKind {
+		self.kind
+	}
+	/// Returns the target of a forwarded export, such as `NTDLL.RtlAllocateHeap`.
+	/// Only Windows supports forwarded exports.
+	#[inline]
+	pub fn forwarder(&self) -> Option<&str> {
+		self.forwarder.as_deref()
+	}
+}
+
+#[cfg(all(feature = "images", feature = "zeroize"))]
+impl Drop for Export {
+	fn drop(&mut self) {
+		self.name.scrub();
+		self.forwarder.scrub();
+	}
+}
+
+/// Memory protection flags of a [`Segment`].
+#[cfg_attr(docsrs, doc(cfg(feature = "images")))]
+#[cfg(feature = "images")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct Protection(u8);
+
+#[cfg(feature = "images")]
+impl Protection {
+	/// No access.
+	pub const NONE: Self = Self(0);
+	/// Readable memory.
+	pub const READ: Self = Self(1);
+	/// Writable memory.
+	pub const WRITE: Self = Self(2);
+	/// Executable memory.
+	pub const EXECUTE: Self = Self(4);
+
+	/// Returns `true` if the memory is readable.
+	#[inline]
+	pub const fn is_readable(self) -> bool {
+		self.0 & Self::READ.0 != 0
+	}
+	/// Returns `true` if the memory is writable.
+	#[inline]
+	pub const fn is_writable(self) -> bool {
+		self.0 & Self::WRITE.0 != 0
+	}
+	/// Returns `true` if the memory is executable.
+	#[inline]
+	pub const fn is_executable(self) -> bool {
+		self.0 & Self::EXECUTE.0 != 0
+	}
+	/// Returns `true` if all flags in `other` are contained in `self`.
+	#[inline]
+	pub const fn contains(self, other: Self) -> bool {
+		self.0 & other.0 == other.0
+	}
+}
+
+#[cfg(feature = "images")]
+impl ops::BitOr for Protection {
+	type Output = Self;
+	#[inline]
+	fn bitor(self, rhs: Self) -> Self::Output {
+		Self(self.0 | rhs.0)
+	}
+}
+
+#[cfg(feature = "images")]
+impl ops::BitOrAssign for Protection {
+	#[inline]
+	fn bitor_assign(&mut self, rhs: Self) {
+		self.0 |= rhs.0
+	}
+}
+
+/// A mapped address range of an [`Image`].
+///
+/// This object can be obtained through [`Image::segments`].
+#[cfg_attr(docsrs, doc(cfg(feature = "images")))]
+#[cfg(feature = "images")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Segment {
+	pub(crate) addr: usize,
+	pub(crate) len: usize,
+	pub(crate) protection: Protection,
+}
+
+#[cfg(feature = "images")]
+impl Segment {
+	/// Returns the address range covered by the segment.
+	#[inline]
+	pub fn range(&self) -> ops::Range<usize> {
+		self.addr..self.addr + self.len
+	}
+	/// Returns the start address of the segment.
+	#[inline]
+	pub fn as_ptr(&self) -> *const u8 {
+		self.addr as *const u8
+	}
+	/// Returns the size of the segment in bytes.
+	#[inline]
+	pub fn len(&self) -> usize {
+		self.len
+	}
+	/// Returns `true` if the segment has a length of 0.
+	#[inline]
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+	/// Returns the memory protection declared by the image headers.
+	#[inline]
+	pub fn protection(&self) -> Protection {
+		self.protection
+	}
+	/// Queries the live memory protection at the start of the segment.
+	///
+	/// This may differ from [`protection`](Segment::protection) if the protection was changed
+	/// after loading (such as by `RELRO` or `VirtualProtect`).
+	///
+	/// # Platform-specific Behavior
+	///
+	/// Uses `VirtualQuery` on Windows, and `/proc/self/maps` on Linux. Returns an error
+	/// of kind [`Unsupported`](io::ErrorKind::Unsupported) on other platforms.
+	pub fn query(&self) -> io::Result<Protection> {
+		unsafe { imp::query_protection(self.as_ptr()) }
+	}
+}
+
+/// How an [`Image`] diverges from its headers, as returned by [`Image::packed`].
+#[cfg_attr(docsrs, doc(cfg(feature = "images")))]
+#[cfg(feature = "images")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Packed {
+	/// A segment is declared both writable and executable, which is how packers reserve room to
+	/// unpack code into.
+	WritableCode {
+		/// The address of the segment.
+		addr: usize,
+	},
+	/// A segment described by the headers isn't mapped.
+	Unmapped {
+		/// The address of the segment.
+		addr: usize,
+	},
+}
+
+#[cfg(feature = "images")]
+impl fmt::Display for Packed {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::WritableCode { addr } => {
+				write!(
+					f,
+					"image looks packed, segment at {addr:#x} is writable code"
+				)
+			}
+			Self::Unmapped { addr } => {
+				write!(
+					f,
+					"image diverges from its headers, segment at {addr:#x} isn't mapped"
+				)
+			}
+		}
+	}
+}
+
+#[cfg(feature = "images")]
+impl error::Error for Packed {}
+
+/// The thread-local storage declared by an [`Image`].
+///
+/// Each thread gets its own copy of the storage, which is initialized from the template and then
+/// zero filled up to [`size`](Tls::size).
+///
+/// This object can be obtained through [`Image::tls`].
+#[cfg_attr(docsrs, doc(cfg(feature = "images")))]
+#[cfg(feature = "images")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tls {
+	pub(crate) template: ops::Range<usize>,
+	pub(crate) size: usize,
+	pub(crate) align: usize,
+	pub(crate) callbacks: Vec<usize>,
+}
+
+#[cfg(feature = "images")]
+impl Tls {
+	/// Returns the address range of the initialization template.
+	#[inline]
+	pub fn template(&self) -> ops::Range<usize> {
+		self.template.clone()
+	}
+	/// Returns the size of each thread's copy of the storage, including the zero filled part.
+	#[inline]
+	pub fn size(&self) -> usize {
+		self.size
+	}
+	/// Returns the alignment of each thread's copy of the storage.
+	#[inline]
+	pub fn align(&self) -> usize {
+		self.align
+	}
+	/// Returns the addresses of the TLS callbacks, in the order they're called.
+	///
+	/// This is always empty on platforms other than Windows.
+	#[inline]
+	pub fn callbacks(&self) -> &[usize] {
+		&self.callbacks
+	}
 }
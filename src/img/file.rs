@@ -0,0 +1,164 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#[cfg(windows)]
+use super::PeView;
+use super::{
+	Arch,
+	Export,
+	RawImage,
+	VersionInfo,
+	imp,
+};
+#[cfg(unix)]
+use super::{
+	ElfView,
+	MachoView,
+};
+use crate::scrub::Scrub;
+use std::{
+	fs,
+	io,
+	marker,
+	path,
+	ptr,
+};
+
+/// An executable image file mapped into memory without being loaded.
+///
+/// Unlike [`Library::open`](crate::Library::open), opening a `File` never executes code
+/// from the image, which makes it suitable for inspecting untrusted candidates before loading them.
+///
+/// Only the image format native to the current platform is supported.
+///
+/// # Platform behavior
+///
+/// The file is memory-mapped as read-only. If the file is truncated by another process
+/// while it is mapped, accessing it may terminate the program.
+#[derive(Debug)]
+pub struct File {
+	addr: ptr::NonNull<u8>,
+	len: usize,
+	path: path::PathBuf,
+}
+
+unsafe impl Send for File {}
+unsafe impl Sync for File {}
+
+impl File {
+	/// Attempts to map an image file into memory.
+	///
+	/// # Errors
+	///
+	/// May error if the file cannot be opened or mapped, or if the file isn't an executable image.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use dylink::img;
+	///
+	/// let file = img::File::open("foo.dll").unwrap();
+	/// let has_foo = file.exports().unwrap().iter().any(|e| e.name() == Some("foo"));
+	/// ```
+	pub fn open<P: AsRef<path::Path>>(path: P) -> io::Result<Self> {
+		let path = path.as_ref();
+		let file = fs::File::open(path)?;
+		let (addr, len) = unsafe { imp::map_file(&file)? };
+		let this = Self {
+			addr,
+			len,
+			path: path.to_path_buf(),
+		};
+		// validate the headers up front.
+		this.object()?;
+		Ok(this)
+	}
+
+	/// Returns the magic number as a byte slice.
+	/// On windows the slice is length 2, on unix slice is length 4.
+	pub fn magic(&self) -> &[u8] {
+		let len: usize = if cfg!(windows) { 2 } else { 4 };
+		&self.to_bytes()[..len.min(self.len)]
+	}
+
+	/// Returns the path the file was opened with.
+	#[inline]
+	pub fn path(&self) -> &path::Path {
+		&self.path
+	}
+
+	/// Returns the contents of the file.
+	#[inline]
+	pub fn to_bytes(&self) -> &[u8] {
+		unsafe { std::slice::from_raw_parts(self.addr.as_ptr(), self.len) }
+	}
+
+	/// Returns the symbols exported by the image.
+	///
+	/// See [`Image::exports`](super::Image::exports) for more information.
+	pub fn exports(&self) -> io::Result<Vec<Export>> {
+		let obj = self.object()?;
+		unsafe { obj.exports() }
+	}
+
+	/// Returns the version information embedded in the image.
+	///
+	/// See [`Image::version`](super::Image::version) for more information.
+	pub fn version(&self) -> io::Result<VersionInfo> {
+		let obj = self.object()?;
+		unsafe { obj.version() }
+	}
+
+	/// Returns the target architecture of the image.
+	pub fn arch(&self) -> io::Result<Arch> {
+		self.object().map(|obj| obj.arch())
+	}
+
+	/// Returns a typed view of the ELF headers, or `None` if the image isn't an ELF image.
+	#[cfg_attr(docsrs, doc(cfg(unix)))]
+	#[cfg(unix)]
+	pub fn as_elf(&self) -> Option<ElfView<'_>> {
+		match self.object().ok()? {
+			imp::Object::Elf(inner) => Some(ElfView {
+				inner,
+				_marker: marker::PhantomData,
+			}),
+			_ => None,
+		}
+	}
+
+	/// Returns a typed view of the Mach-O headers, or `None` if the image isn't a Mach-O image.
+	#[cfg_attr(docsrs, doc(cfg(unix)))]
+	#[cfg(unix)]
+	pub fn as_macho(&self) -> Option<MachoView<'_>> {
+		match self.object().ok()? {
+			imp::Object::MachO(inner) => Some(MachoView {
+				inner,
+				_marker: marker::PhantomData,
+			}),
+			_ => None,
+		}
+	}
+
+	/// Returns a typed view of the PE headers, or `None` if the headers are invalid.
+	#[cfg_attr(docsrs, doc(cfg(windows)))]
+	#[cfg(windows)]
+	pub fn as_pe(&self) -> Option<PeView<'_>> {
+		self.object().ok().map(|inner| PeView {
+			inner,
+			_marker: marker::PhantomData,
+		})
+	}
+
+	#[inline]
+	fn object(&self) -> io::Result<imp::Object> {
+		unsafe { imp::Object::parse(RawImage::file(self.to_bytes())) }
+	}
+}
+
+impl Drop for File {
+	fn drop(&mut self) {
+		unsafe { imp::unmap_file(self.addr, self.len) }
+		self.path.scrub();
+	}
+}
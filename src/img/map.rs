@@ -0,0 +1,305 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use super::{
+	Image,
+	Images,
+};
+use std::{
+	io,
+	ops,
+	path,
+	ptr,
+	sync::{
+		Mutex,
+		Once,
+		atomic::{
+			AtomicBool,
+			AtomicPtr,
+			AtomicUsize,
+			Ordering,
+		},
+	},
+};
+
+#[cfg(unix)]
+use crate::os::unix as imp;
+#[cfg(windows)]
+use crate::os::windows as imp;
+#[cfg(feature = "zeroize")]
+use crate::scrub::Scrub;
+
+/// An image in a [`ModuleMap`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Module {
+	base: usize,
+	range: ops::Range<usize>,
+	path: Option<path::PathBuf>,
+}
+
+impl Module {
+	// Creates a module spanning the segments of the image at `base`.
+	pub(crate) fn new(base: *const Image, len: Option<usize>, path: Option<path::PathBuf>) -> Self {
+		let start = base as usize;
+		let end = match len {
+			Some(len) => start + len,
+			None => unsafe { base.as_ref() }
+				.and_then(|image| image.segments().ok())
+				.and_then(|segments| segments.iter().map(|seg| seg.range().end).max())
+				.unwrap_or(start),
+		};
+		Self {
+			base: start,
+			range: start..end.max(start),
+			path,
+		}
+	}
+
+	/// Returns the base address of the image.
+	#[inline]
+	pub fn base(&self) -> *const Image {
+		self.base as *const Image
+	}
+	/// Returns the address range spanned by the image.
+	#[inline]
+	pub fn range(&self) -> ops::Range<usize> {
+		self.range.clone()
+	}
+	/// Returns the path of the image, if known.
+	#[inline]
+	pub fn path(&self) -> Option<&path::Path> {
+		self.path.as_deref()
+	}
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for Module {
+	fn drop(&mut self) {
+		self.path.scrub();
+	}
+}
+
+// An immutable snapshot of the loaded images, sorted by address.
+#[derive(Debug)]
+struct Snapshot {
+	generation: u64,
+	modules: Vec<Module>,
+}
+
+/// An always available map of the images loaded in the process, for looking up addresses
+/// without taking any locks.
+///
+/// The map is a snapshot that is republished whenever images are loaded or unloaded, so
+/// [`lookup`](Self::lookup) never blocks, allocates, or waits on the loader. This makes it
+/// suitable for samplers and signal handlers.
+///
+/// Readers hold the snapshot they're using through a [`Modules`] or [`ModuleRef`] guard.
+/// Superseded snapshots are freed by the next update of the map that finds no guard alive, so a
+/// guard should be dropped once the lookup is done, rather than kept.
+///
+/// # Platform behavior
+///
+/// On Windows and MacOS, the map is updated by loader notifications. Other platforms have no such
+/// notifications, so [`refresh`](Self::refresh) must be called after images are loaded or unloaded.
+#[derive(Debug)]
+pub struct ModuleMap {
+	current: AtomicPtr<Snapshot>,
+	watched: AtomicBool,
+	// guards that are alive, which may be using any published snapshot.
+	readers: AtomicUsize,
+	// superseded snapshots that readers may still be using, which also serializes updates. They're
+	// boxed, since readers refer to them by address.
+	#[allow(clippy::vec_box)]
+	retired: Mutex<Vec<Box<Snapshot>>>,
+}
+
+static GLOBAL: ModuleMap = ModuleMap {
+	current: AtomicPtr::new(ptr::null_mut()),
+	watched: AtomicBool::new(false),
+	readers: AtomicUsize::new(0),
+	retired: Mutex::new(Vec::new()),
+};
+
+impl ModuleMap {
+	/// Returns the module map of the process, building it on first use.
+	pub fn global() -> &'static ModuleMap {
+		static INIT: Once = Once::new();
+		INIT.call_once(|| {
+			// notifications may be delivered for images that are already loaded.
+			let watched = unsafe { imp::watch_images() };
+			GLOBAL.watched.store(watched, Ordering::Release);
+			let _ = GLOBAL.refresh();
+		});
+		&GLOBAL
+	}
+
+	// Returns the global map without building it, since loader notifications may be delivered
+	// while `global` is still running.
+	pub(crate) fn unbuilt() -> &'static ModuleMap {
+		&GLOBAL
+	}
+
+	/// Returns `true` if the map is updated automatically when images are loaded or unloaded.
+	#[inline]
+	pub fn is_watched(&self) -> bool {
+		self.watched.load(Ordering::Acquire)
+	}
+
+	/// Returns the image containing `addr`.
+	///
+	/// This function takes no locks and doesn't allocate.
+	pub fn lookup(&self, addr: usize) -> Option<ModuleRef<'_>> {
+		let modules = self.modules();
+		let index = modules.position(addr)?;
+		Some(ModuleRef { modules, index })
+	}
+
+	/// Returns every image in the map, sorted by address.
+	///
+	/// This function takes no locks and doesn't allocate.
+	pub fn modules(&self) -> Modules<'_> {
+		self.readers.fetch_add(1, Ordering::SeqCst);
+		let snapshot = self.current.load(Ordering::SeqCst);
+		Modules {
+			map: self,
+			snapshot: unsafe { snapshot.as_ref() },
+		}
+	}
+
+	/// Returns the number of times the map has been republished.
+	pub fn generation(&self) -> u64 {
+		self.modules().generation()
+	}
+
+	/// Rebuilds the map from the images currently loaded in the process.
+	///
+	/// # Errors
+	///
+	/// May error if the loaded images can't be enumerated.
+	pub fn refresh(&self) -> io::Result<()> {
+		// built outside of `update`, so that the images aren't enumerated while updates are blocked.
+		let mut modules: Vec<Module> = Images::now()?
+			.filter(|weak| !weak.to_ptr().is_null())
+			.map(|weak| {
+				Module::new(
+					weak.to_ptr(),
+					None,
+					weak.path().map(path::Path::to_path_buf),
+				)
+			})
+			.collect();
+		modules.sort_unstable_by_key(|module| module.base);
+		modules.dedup_by_key(|module| module.base);
+		self.update(|_| Some(modules));
+		Ok(())
+	}
+
+	// Called by the loader notifications of the platform.
+	#[cfg_attr(not(any(windows, target_os = "macos")), allow(dead_code))]
+	pub(crate) fn loaded(&self, module: Module) {
+		self.update(|modules| {
+			let index = modules.partition_point(|m| m.base < module.base);
+			let mut modules = modules.to_vec();
+			match modules.get(index) {
+				Some(m) if m.base == module.base => modules[index] = module,
+				_ => modules.insert(index, module),
+			}
+			Some(modules)
+		});
+	}
+
+	// Called by the loader notifications of the platform.
+	#[cfg_attr(not(any(windows, target_os = "macos")), allow(dead_code))]
+	pub(crate) fn unloaded(&self, base: *const Image) {
+		self.update(|modules| {
+			let index = modules.iter().position(|m| m.base == base as usize)?;
+			let mut modules = modules.to_vec();
+			modules.remove(index);
+			Some(modules)
+		});
+	}
+
+	// Publishes the modules returned by `f`, and frees the superseded snapshots if no reader is
+	// using them.
+	fn update(&self, f: impl FnOnce(&[Module]) -> Option<Vec<Module>>) {
+		let mut retired = self.retired.lock().unwrap_or_else(|err| err.into_inner());
+		// only updates free snapshots, so the current one stays valid while the lock is held.
+		let current = unsafe { self.current.load(Ordering::SeqCst).as_ref() };
+		let Some(modules) = f(current.map_or(&[], |snapshot| &snapshot.modules)) else {
+			return;
+		};
+		let new = Box::into_raw(Box::new(Snapshot {
+			generation: current.map_or(0, |snapshot| snapshot.generation) + 1,
+			modules,
+		}));
+		let old = self.current.swap(new, Ordering::SeqCst);
+		if !old.is_null() {
+			retired.push(unsafe { Box::from_raw(old) });
+		}
+		// readers that start from now on load the new snapshot, so if none are alive, none can be
+		// using a retired one.
+		if self.readers.load(Ordering::SeqCst) == 0 {
+			retired.clear();
+		}
+	}
+}
+
+/// The images of a [`ModuleMap`], sorted by address, as returned by [`ModuleMap::modules`].
+///
+/// The snapshot of the map is kept alive until the guard is dropped.
+#[derive(Debug)]
+pub struct Modules<'a> {
+	map: &'a ModuleMap,
+	snapshot: Option<&'a Snapshot>,
+}
+
+impl Modules<'_> {
+	/// Returns the image containing `addr`.
+	pub fn lookup(&self, addr: usize) -> Option<&Module> {
+		self.get(self.position(addr)?)
+	}
+
+	/// Returns the number of times the map had been republished when this snapshot was taken.
+	pub fn generation(&self) -> u64 {
+		self.snapshot.map_or(0, |snapshot| snapshot.generation)
+	}
+
+	// Returns the index of the image containing `addr`.
+	fn position(&self, addr: usize) -> Option<usize> {
+		let index = self.partition_point(|module| module.range.start <= addr);
+		let index = index.checked_sub(1)?;
+		self[index].range.contains(&addr).then_some(index)
+	}
+}
+
+impl ops::Deref for Modules<'_> {
+	type Target = [Module];
+	#[inline]
+	fn deref(&self) -> &[Module] {
+		self.snapshot.map_or(&[], |snapshot| &snapshot.modules)
+	}
+}
+
+impl Drop for Modules<'_> {
+	#[inline]
+	fn drop(&mut self) {
+		self.map.readers.fetch_sub(1, Ordering::SeqCst);
+	}
+}
+
+/// An image of a [`ModuleMap`], as returned by [`ModuleMap::lookup`].
+///
+/// The snapshot of the map is kept alive until the guard is dropped.
+#[derive(Debug)]
+pub struct ModuleRef<'a> {
+	modules: Modules<'a>,
+	index: usize,
+}
+
+impl ops::Deref for ModuleRef<'_> {
+	type Target = Module;
+	#[inline]
+	fn deref(&self) -> &Module {
+		&self.modules[self.index]
+	}
+}
@@ -0,0 +1,94 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::io;
+
+/// Page residency statistics of an image.
+///
+/// All counts are measured in pages of [`PerfReport::page_size`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct PageStats {
+	pub(crate) mappings: usize,
+	pub(crate) resident: usize,
+	pub(crate) private: usize,
+	pub(crate) shared: usize,
+	pub(crate) large: usize,
+}
+
+impl PageStats {
+	/// Returns the number of distinct memory mappings backing the image.
+	/// Each mapping may need its own TLB entries.
+	#[inline]
+	pub fn mappings(&self) -> usize {
+		self.mappings
+	}
+	/// Returns the number of pages currently resident in physical memory.
+	#[inline]
+	pub fn resident(&self) -> usize {
+		self.resident
+	}
+	/// Returns the number of resident pages private to this process, such as
+	/// pages modified by relocations.
+	#[inline]
+	pub fn private(&self) -> usize {
+		self.private
+	}
+	/// Returns the number of resident pages shared with other processes.
+	#[inline]
+	pub fn shared(&self) -> usize {
+		self.shared
+	}
+	/// Returns the number of resident pages backed by large (huge) pages.
+	#[inline]
+	pub fn large(&self) -> usize {
+		self.large
+	}
+}
+
+/// A performance summary of an image.
+///
+/// This object can be obtained through [`Image::perf_report`](super::Image::perf_report).
+#[derive(Debug)]
+pub struct PerfReport {
+	pub(crate) base: usize,
+	pub(crate) preferred_base: usize,
+	pub(crate) page_size: usize,
+	pub(crate) pages: io::Result<PageStats>,
+}
+
+impl PerfReport {
+	/// Returns the address the image was loaded at.
+	#[inline]
+	pub fn base(&self) -> usize {
+		self.base
+	}
+	/// Returns the address the image was linked to load at.
+	#[inline]
+	pub fn preferred_base(&self) -> usize {
+		self.preferred_base
+	}
+	/// Returns `true` if the image was loaded at a different address than it was linked for.
+	///
+	/// Position independent images (which include all ELF shared objects and Mach-O dylibs)
+	/// are almost always relocated, which is cheap. Relocated PE images must have their
+	/// base relocations applied, which makes the affected pages private.
+	#[inline]
+	pub fn is_relocated(&self) -> bool {
+		self.base != self.preferred_base
+	}
+	/// Returns the size of a page in bytes.
+	#[inline]
+	pub fn page_size(&self) -> usize {
+		self.page_size
+	}
+	/// Returns the page residency statistics of the image.
+	///
+	/// # Errors
+	///
+	/// Returns an error of kind [`Unsupported`](io::ErrorKind::Unsupported) on platforms
+	/// other than Windows and Linux.
+	#[inline]
+	pub fn pages(&self) -> Result<&PageStats, &io::Error> {
+		self.pages.as_ref()
+	}
+}
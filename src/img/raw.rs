@@ -0,0 +1,83 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::{
+	ffi,
+	io,
+	mem,
+	ptr,
+};
+
+/// Raw contents of an executable image, either loaded by the system loader or mapped from a file.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct RawImage {
+	pub ptr: *const u8,
+	pub len: usize,
+	// If the image was loaded by the system, offsets are relative to the image base
+	// and the headers are trusted; otherwise offsets are file offsets and bounds checked.
+	pub loaded: bool,
+}
+
+impl RawImage {
+	#[inline]
+	pub fn loaded(hdr: *const super::Image) -> Self {
+		Self {
+			ptr: hdr.cast(),
+			len: usize::MAX - hdr as usize,
+			loaded: true,
+		}
+	}
+
+	#[inline]
+	pub fn file(data: &[u8]) -> Self {
+		Self {
+			ptr: data.as_ptr(),
+			len: data.len(),
+			loaded: false,
+		}
+	}
+
+	/// Returns a new image starting at `offset` with a length of `len`.
+	#[cfg_attr(windows, allow(dead_code))]
+	pub fn subrange(&self, offset: usize, len: usize) -> io::Result<Self> {
+		match offset.checked_add(len) {
+			Some(end) if end <= self.len => Ok(Self {
+				ptr: self.ptr.wrapping_add(offset),
+				len,
+				loaded: self.loaded,
+			}),
+			_ => Err(out_of_bounds()),
+		}
+	}
+
+	/// Reads a value at `offset` without requiring alignment.
+	pub unsafe fn read<T>(&self, offset: usize) -> io::Result<T> {
+		match offset.checked_add(mem::size_of::<T>()) {
+			Some(end) if end <= self.len => {
+				Ok(unsafe { ptr::read_unaligned(self.ptr.add(offset).cast::<T>()) })
+			}
+			_ => Err(out_of_bounds()),
+		}
+	}
+
+	/// Reads a nul-terminated string at `offset`.
+	pub unsafe fn read_cstr<'a>(&self, offset: usize) -> io::Result<&'a ffi::CStr> {
+		if offset >= self.len {
+			return Err(out_of_bounds());
+		}
+		unsafe {
+			let data = self.ptr.add(offset);
+			if self.loaded {
+				Ok(ffi::CStr::from_ptr(data.cast()))
+			} else {
+				let bytes = std::slice::from_raw_parts(data, self.len - offset);
+				ffi::CStr::from_bytes_until_nul(bytes)
+					.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+			}
+		}
+	}
+}
+
+pub(crate) fn out_of_bounds() -> io::Error {
+	io::Error::new(io::ErrorKind::InvalidData, "image offset is out of bounds")
+}
@@ -0,0 +1,125 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::fmt;
+
+/// A version number embedded in an image.
+///
+/// Versions are compared component-wise, from `major` to `build`.
+///
+/// # Examples
+///
+/// ```
+/// use dylink::img::Version;
+///
+/// assert!(Version::new(535, 54, 3) > Version::new(535, 0, 0));
+/// assert_eq!(Version::new(1, 2, 3).to_string(), "1.2.3");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct Version {
+	major: u32,
+	minor: u32,
+	patch: u32,
+	build: u32,
+}
+
+impl Version {
+	/// Constructs a new `Version` with a build number of zero.
+	#[inline]
+	pub const fn new(major: u32, minor: u32, patch: u32) -> Self {
+		Self {
+			major,
+			minor,
+			patch,
+			build: 0,
+		}
+	}
+
+	#[cfg_attr(not(windows), allow(dead_code))]
+	#[inline]
+	pub(crate) const fn with_build(self, build: u32) -> Self {
+		Self { build, ..self }
+	}
+
+	/// Parses the leading dot separated numbers of `s`, such as `1.7.0` in a shared object name.
+	#[cfg_attr(windows, allow(dead_code))]
+	pub(crate) fn parse_prefix(s: &str) -> Option<Self> {
+		let mut parts = s.split('.').map_while(|part| part.parse::<u32>().ok());
+		let major = parts.next()?;
+		Some(Self {
+			major,
+			minor: parts.next().unwrap_or(0),
+			patch: parts.next().unwrap_or(0),
+			build: parts.next().unwrap_or(0),
+		})
+	}
+
+	/// Returns the major version number.
+	#[inline]
+	pub const fn major(&self) -> u32 {
+		self.major
+	}
+	/// Returns the minor version number.
+	#[inline]
+	pub const fn minor(&self) -> u32 {
+		self.minor
+	}
+	/// Returns the patch version number.
+	#[inline]
+	pub const fn patch(&self) -> u32 {
+		self.patch
+	}
+	/// Returns the build number, which is only used on Windows.
+	#[inline]
+	pub const fn build(&self) -> u32 {
+		self.build
+	}
+}
+
+impl fmt::Display for Version {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}.{}.{}", self.major, self.minor, self.patch)?;
+		if self.build != 0 {
+			write!(f, ".{}", self.build)?;
+		}
+		Ok(())
+	}
+}
+
+/// Version information embedded in an image.
+///
+/// This object can be obtained through [`Image::version`](super::Image::version).
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct VersionInfo {
+	pub(crate) current: Option<Version>,
+	pub(crate) compatibility: Option<Version>,
+	pub(crate) name: Option<String>,
+	pub(crate) definitions: Vec<String>,
+}
+
+impl VersionInfo {
+	/// Returns the version of the image.
+	///
+	/// On Linux this is parsed from the `SONAME`, which often only contains the major version.
+	#[inline]
+	pub fn current(&self) -> Option<Version> {
+		self.current
+	}
+	/// Returns the oldest version the image is compatible with, if recorded.
+	#[inline]
+	pub fn compatibility(&self) -> Option<Version> {
+		self.compatibility
+	}
+	/// Returns the name the image identifies itself by, such as the `SONAME` or install name.
+	#[inline]
+	pub fn name(&self) -> Option<&str> {
+		self.name.as_deref()
+	}
+	/// Returns the symbol versions defined by the image, such as `GLIBC_2.34`.
+	///
+	/// This is only populated for ELF images.
+	#[inline]
+	pub fn definitions(&self) -> &[String] {
+		&self.definitions
+	}
+}
@@ -0,0 +1,154 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::marker;
+
+#[cfg(unix)]
+use std::{
+	fmt,
+	io,
+};
+
+#[cfg(unix)]
+use crate::os::unix::{
+	elf,
+	macho,
+};
+#[cfg(windows)]
+use crate::os::windows::pe;
+
+#[cfg(unix)]
+pub use elf::Phdr as ElfProgramHeader;
+#[cfg(unix)]
+pub use macho::SegmentCommand as MachoSegment;
+#[cfg(windows)]
+pub use pe::Section as PeSection;
+
+/// A typed view of the headers of an ELF image.
+///
+/// This object can be obtained through [`Image::as_elf`](super::Image::as_elf)
+/// or [`File::as_elf`](super::File::as_elf).
+#[cfg_attr(docsrs, doc(cfg(unix)))]
+#[cfg(unix)]
+#[derive(Debug)]
+pub struct ElfView<'a> {
+	pub(crate) inner: elf::Elf,
+	pub(crate) _marker: marker::PhantomData<&'a [u8]>,
+}
+
+#[cfg(unix)]
+impl ElfView<'_> {
+	/// Returns `true` if the image is of class `ELFCLASS64`.
+	#[inline]
+	pub fn is_64(&self) -> bool {
+		self.inner.is_64
+	}
+	/// Returns the machine type of the image, such as `EM_X86_64`.
+	#[inline]
+	pub fn e_machine(&self) -> u16 {
+		self.inner.e_machine
+	}
+	/// Returns the program headers of the image.
+	#[inline]
+	pub fn program_headers(&self) -> &[ElfProgramHeader] {
+		&self.inner.phdrs
+	}
+	/// Returns the entries of the dynamic section as `(d_tag, d_un)` pairs.
+	pub fn dynamic(&self) -> io::Result<Vec<(i64, usize)>> {
+		unsafe { self.inner.dynamic() }
+	}
+	/// Looks up the default version of an exported symbol by name.
+	///
+	/// The `DT_GNU_HASH` or `DT_HASH` table is used if the image has one, so this is much
+	/// faster than searching [`Image::exports`](super::Image::exports).
+	///
+	/// # Errors
+	///
+	/// May error if the image is malformed.
+	pub fn lookup(&self, name: &str) -> io::Result<Option<super::Export>> {
+		unsafe { self.inner.lookup(name) }
+	}
+}
+
+/// A typed view of the headers of a Mach-O image.
+///
+/// This object can be obtained through [`Image::as_macho`](super::Image::as_macho)
+/// or [`File::as_macho`](super::File::as_macho).
+#[cfg_attr(docsrs, doc(cfg(unix)))]
+#[cfg(unix)]
+pub struct MachoView<'a> {
+	pub(crate) inner: macho::MachO,
+	pub(crate) _marker: marker::PhantomData<&'a [u8]>,
+}
+
+#[cfg(unix)]
+impl MachoView<'_> {
+	/// Returns `true` if the image has a `mach_header_64` header.
+	#[inline]
+	pub fn is_64(&self) -> bool {
+		self.inner.is_64
+	}
+	/// Returns the CPU type of the image, such as `CPU_TYPE_ARM64`.
+	#[inline]
+	pub fn cputype(&self) -> i32 {
+		self.inner.cputype
+	}
+	/// Returns the segment load commands of the image.
+	#[inline]
+	pub fn segments(&self) -> &[MachoSegment] {
+		&self.inner.segments
+	}
+}
+
+#[cfg(unix)]
+impl fmt::Debug for MachoView<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.debug_struct("MachoView")
+			.field("is_64", &self.is_64())
+			.field("cputype", &self.cputype())
+			.field("segments", &self.segments())
+			.finish_non_exhaustive()
+	}
+}
+
+/// A typed view of the headers of a PE image.
+///
+/// This object can be obtained through [`Image::as_pe`](super::Image::as_pe)
+/// or [`File::as_pe`](super::File::as_pe).
+#[cfg_attr(docsrs, doc(cfg(windows)))]
+#[cfg(windows)]
+#[derive(Debug)]
+pub struct PeView<'a> {
+	pub(crate) inner: pe::Pe,
+	pub(crate) _marker: marker::PhantomData<&'a [u8]>,
+}
+
+#[cfg(windows)]
+impl PeView<'_> {
+	/// Returns the machine type of the image, such as `IMAGE_FILE_MACHINE_AMD64`.
+	#[inline]
+	pub fn machine(&self) -> u16 {
+		self.inner.machine
+	}
+	/// Returns the address the image was linked to load at.
+	#[inline]
+	pub fn image_base(&self) -> usize {
+		self.inner.imagebase
+	}
+	/// Returns the combined size of the headers, rounded up to the file alignment.
+	#[inline]
+	pub fn size_of_headers(&self) -> usize {
+		self.inner.sizeofheaders
+	}
+	/// Returns the data directories as `(virtual_address, size)` pairs,
+	/// indexed by `IMAGE_DIRECTORY_ENTRY_*`.
+	#[inline]
+	pub fn data_directories(&self) -> &[(usize, usize)] {
+		&self.inner.datadirectory
+	}
+	/// Returns the section headers of the image.
+	#[inline]
+	pub fn sections(&self) -> &[PeSection] {
+		&self.inner.sections
+	}
+}
@@ -0,0 +1,57 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+// Conversions from and to the libraries of `libloading`, for code bases moving between the two.
+
+use crate::{
+	Library,
+	imp,
+};
+#[cfg(unix)]
+use libloading::os::unix::Library as RawLibrary;
+#[cfg(windows)]
+use libloading::os::windows::Library as RawLibrary;
+use std::ptr;
+
+/// Takes ownership of the handle of a `libloading` library.
+///
+/// `libloading` closes its libraries when they're dropped, while a [`Library`] stays open until
+/// it's [closed](Library::close).
+impl From<libloading::Library> for Library {
+	fn from(lib: libloading::Library) -> Self {
+		let handle = RawLibrary::from(lib).into_raw();
+		// `libloading` holds modules as integers on Windows.
+		#[cfg(windows)]
+		let handle = handle as *mut std::ffi::c_void;
+		// `libloading` never holds a null handle.
+		Self(imp::InnerLibrary(
+			ptr::NonNull::new(handle).expect("library handle is null"),
+		))
+	}
+}
+
+/// Gives the handle of a [`Library`] to `libloading`, which closes it when it's dropped.
+impl From<Library> for libloading::Library {
+	fn from(lib: Library) -> Self {
+		let handle = lib.leak();
+		#[cfg(windows)]
+		let handle = handle as isize;
+		unsafe { RawLibrary::from_raw(handle) }.into()
+	}
+}
+
+/// Resolves symbols through `libloading`, so that the `dylink` macro can use libraries it opened
+/// with the `loader` argument.
+#[cfg_attr(docsrs, doc(cfg(feature = "sync")))]
+#[cfg(feature = "sync")]
+impl crate::sync::Loader for libloading::Library {
+	fn symbol(&self, name: &str) -> std::io::Result<*const crate::Symbol> {
+		let name = match std::ffi::CString::new(name) {
+			Ok(name) => crate::scrub::Scrubbed(name),
+			Err(err) => return Err(std::io::Error::new(std::io::ErrorKind::InvalidData, err)),
+		};
+		unsafe { self.get::<*const crate::Symbol>(name.as_bytes_with_nul()) }
+			.map(|symbol| *symbol)
+			.map_err(std::io::Error::other)
+	}
+}
@@ -11,9 +11,46 @@
 //! Complete platform support may vary between functions, however unless otherwise specified, functions
 //! are supported on Windows, Linux, and MacOS.
 //!
+//! # Crate features
+//! The `images` and `sync` features are enabled by default. Without them, only [`Library`],
+//! [`Symbol`], [`Weak`], and the types they use are compiled, for a smaller safe wrapper around the
+//! system loader.
+//!
+//! - `images`: inspection and enumeration of images through [`img`], along with `symbolize`,
+//!   `tools`, and `os::linux`. On Windows, this includes the use of dbghelp.
+//! - `sync`: lazily loaded libraries through `sync::LibLock`, along with the `config` module and
+//!   the bind hook used by the `dylink` macro.
+//! - `async`: `load_async`, and `LibLock::symbol_async`, which load libraries without blocking an
+//!   executor.
+//! - `macro`: the `dylink` attribute and the `DylinkTable` derive. Enables `sync`.
+//! - `cxx-mangle`: mangling of the `cxx_name` argument of the `dylink` macro from a C++
+//!   declaration. Enables `macro`.
+//! - `trace`: the `trace` module, which reports every symbol resolved by items of the `dylink`
+//!   macro. Enables `macro`.
+//! - `module-map`: `img::ModuleMap`, a snapshot of the loaded images whose lookups never block.
+//!   Enables `images`.
+//! - `unload`: `LibLock::unload`, which closes the library of a `LibLock` so it's loaded again on
+//!   its next use. Enables `sync`.
+//! - `registry`: the `registry` function, which lists every `LibLock` that attempted to open its
+//!   library. Enables `sync`.
+//! - `checkpoint`: the `cr` module, which drops cached libraries and symbols after the process was
+//!   restored from a checkpoint. Enables `unload`.
+//! - `zeroize`: the symbol names and paths held by this crate are overwritten with zeros before
+//!   their memory is freed. This covers the temporary strings passed to the system loader, as well
+//!   as the names and paths cached in objects such as [`Weak`] and [`img::Export`]. Copies made by
+//!   the system loader itself, or by the caller, aren't affected.
+//! - `libloading`: conversions between [`Library`] and `libloading::Library`, which also
+//!   implements `sync::Loader` with the `sync` feature, to use the `dylink` macro with libraries
+//!   opened by `libloading`.
+//! - `mock`: `test::MockLoader`, which stands in for a library in tests of code using the
+//!   `dylink` macro. Enables `sync`.
+//!
 //! [`LibLock`]: crate::sync::LibLock
 
+mod scrub;
 mod sealed;
+#[cfg(all(feature = "images", feature = "sync"))]
+mod suggest;
 
 pub mod os;
 #[cfg(unix)]
@@ -21,13 +58,87 @@ use os::unix as imp;
 #[cfg(windows)]
 use os::windows as imp;
 
+#[cfg_attr(docsrs, doc(cfg(feature = "sync")))]
+#[cfg(feature = "sync")]
+pub mod config;
+#[cfg_attr(docsrs, doc(cfg(feature = "checkpoint")))]
+#[cfg(feature = "checkpoint")]
+pub mod cr;
 pub mod img;
+#[cfg_attr(docsrs, doc(cfg(feature = "images")))]
+#[cfg(feature = "images")]
+pub mod symbolize;
+#[cfg_attr(docsrs, doc(cfg(feature = "sync")))]
+#[cfg(feature = "sync")]
 pub mod sync;
+#[cfg_attr(docsrs, doc(cfg(feature = "mock")))]
+#[cfg(feature = "mock")]
+pub mod test;
+#[cfg_attr(docsrs, doc(cfg(feature = "images")))]
+#[cfg(feature = "images")]
+pub mod tools;
+#[cfg_attr(docsrs, doc(cfg(feature = "trace")))]
+#[cfg(feature = "trace")]
+pub mod trace;
+#[cfg_attr(docsrs, doc(cfg(feature = "sync")))]
+#[cfg(feature = "sync")]
+pub mod vk;
 
 mod weak;
 pub use weak::Weak;
 
+#[cfg(feature = "sync")]
+mod bind;
+#[cfg_attr(docsrs, doc(cfg(feature = "sync")))]
+#[cfg(feature = "sync")]
+pub use bind::{
+	BindEvent,
+	set_bind_hook,
+	take_bind_hook,
+};
+
+mod flags;
+pub use flags::OpenFlags;
+
+#[cfg(feature = "images")]
+mod global;
+#[cfg_attr(docsrs, doc(cfg(feature = "images")))]
+#[cfg(feature = "images")]
+pub use global::GlobalRef;
+
+mod options;
+pub use options::OpenOptions;
+
+#[cfg(feature = "registry")]
+mod registry;
+#[cfg_attr(docsrs, doc(cfg(feature = "registry")))]
+#[cfg(feature = "registry")]
+pub use registry::{
+	RegistryEntry,
+	registry,
+};
+
+#[cfg(feature = "libloading")]
+mod interop;
+
+#[cfg(feature = "async")]
+mod task;
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+#[cfg(feature = "async")]
+pub use task::load_async;
+
+mod report;
+pub use report::{
+	LoadError,
+	LoadErrorKind,
+	LoadFailure,
+	LoadReport,
+	SandboxDenied,
+};
+
 mod sym;
+#[allow(deprecated)]
+pub use sym::Sym;
 pub use sym::Symbol;
 
 use std::{
@@ -37,7 +148,10 @@ use std::{
 };
 
 #[cfg(feature = "macro")]
-pub use dylink_macro::dylink;
+pub use dylink_macro::{
+	DylinkTable,
+	dylink,
+};
 
 #[doc = include_str!("../README.md")]
 #[cfg(all(doctest, windows))]
@@ -84,6 +198,106 @@ impl Library {
 	pub fn open<P: AsRef<path::Path>>(path: P) -> io::Result<Self> {
 		unsafe { imp::InnerLibrary::open(path.as_ref().as_os_str()) }.map(Self)
 	}
+	/// Attempts to open a dynamic library file with the given flags.
+	///
+	/// This is otherwise the same as [`open`](Self::open), which uses [`OpenFlags::DEFAULT`].
+	///
+	/// # Errors
+	///
+	/// May error if the library can't be loaded with the flags, such as with
+	/// [`OpenFlags::INSPECT`] on Unix when the library isn't already loaded.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use dylink::{Library, OpenFlags};
+	///
+	/// let lib = Library::open_with("plugin.dll", OpenFlags::PLUGIN).unwrap();
+	/// ```
+	#[doc(alias = "LoadLibraryEx")]
+	#[inline]
+	pub fn open_with<P: AsRef<path::Path>>(path: P, flags: OpenFlags) -> io::Result<Self> {
+		unsafe { imp::InnerLibrary::open_with(path.as_ref().as_os_str(), flags.bits()) }.map(Self)
+	}
+	/// Attempts to open a dynamic library file relative to an open directory.
+	///
+	/// This is useful in capability-based sandboxes, where a process holds handles to the
+	/// directories it may access instead of having access to the whole filesystem. `path` must be
+	/// relative, and may still name a parent directory with `..`, like `openat`. The library is
+	/// otherwise opened like with [`open`](Self::open), but its dependencies are still searched
+	/// for as usual.
+	///
+	/// On Linux, the file is opened with `openat`, and the library is loaded through
+	/// `/proc/self/fd`, which must be mounted. The path reported for the image is then that of the
+	/// descriptor, which no longer exists once this function returns. On Windows, the path of
+	/// the directory is recovered from its handle.
+	///
+	/// # Errors
+	///
+	/// Errors with [`InvalidInput`](io::ErrorKind::InvalidInput) if `path` is absolute, and with
+	/// [`Unsupported`](io::ErrorKind::Unsupported) on Unix platforms other than Linux.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use dylink::Library;
+	/// use std::fs::File;
+	///
+	/// let plugins = File::open("/usr/lib/foo/plugins").unwrap();
+	/// let lib = Library::open_at(&plugins, "bar.so").unwrap();
+	/// ```
+	#[cfg_attr(docsrs, doc(cfg(any(unix, windows))))]
+	#[cfg(unix)]
+	#[doc(alias = "openat")]
+	pub fn open_at<D: std::os::fd::AsFd, P: AsRef<path::Path>>(
+		dir: D,
+		path: P,
+	) -> io::Result<Self> {
+		let path = relative_path(path.as_ref())?;
+		unsafe { imp::InnerLibrary::open_at(dir.as_fd(), path.as_os_str()) }.map(Self)
+	}
+	/// Attempts to open a dynamic library file relative to an open directory.
+	///
+	/// This is useful in capability-based sandboxes, where a process holds handles to the
+	/// directories it may access instead of having access to the whole filesystem. `path` must be
+	/// relative, and may still name a parent directory with `..`, like `openat`. The library is
+	/// otherwise opened like with [`open`](Self::open), but its dependencies are still searched
+	/// for as usual.
+	///
+	/// On Linux, the file is opened with `openat`, and the library is loaded through
+	/// `/proc/self/fd`, which must be mounted. The path reported for the image is then that of the
+	/// descriptor, which no longer exists once this function returns. On Windows, the path of
+	/// the directory is recovered from its handle.
+	///
+	/// # Errors
+	///
+	/// Errors with [`InvalidInput`](io::ErrorKind::InvalidInput) if `path` is absolute, and with
+	/// [`Unsupported`](io::ErrorKind::Unsupported) on Unix platforms other than Linux.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use dylink::Library;
+	/// use std::fs::File;
+	/// use std::os::windows::fs::OpenOptionsExt;
+	///
+	/// // `FILE_FLAG_BACKUP_SEMANTICS` is required to open a directory.
+	/// let plugins = File::options()
+	///     .read(true)
+	///     .custom_flags(0x02000000)
+	///     .open("C:\\Program Files\\Foo\\plugins")
+	///     .unwrap();
+	/// let lib = Library::open_at(&plugins, "bar.dll").unwrap();
+	/// ```
+	#[cfg_attr(docsrs, doc(cfg(any(unix, windows))))]
+	#[cfg(windows)]
+	pub fn open_at<D: std::os::windows::io::AsHandle, P: AsRef<path::Path>>(
+		dir: D,
+		path: P,
+	) -> io::Result<Self> {
+		let path = relative_path(path.as_ref())?;
+		unsafe { imp::InnerLibrary::open_at(dir.as_handle(), path.as_os_str()) }.map(Self)
+	}
 	/// Attempts to return a library handle to the current process.
 	///
 	/// # Panics
@@ -154,6 +368,56 @@ impl Library {
 		unsafe { self.0.symbol(name) }
 	}
 
+	/// Retrieves a symbol from the library by its export ordinal if it exists. The symbol must not
+	/// be used past the lifetime of the library or the symbol will be invalid.
+	///
+	/// This is useful for libraries that export some symbols by ordinal only.
+	///
+	/// # Errors
+	///
+	/// May error if no symbol is exported with the ordinal.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use dylink::Library;
+	///
+	/// let lib = Library::open("foo.dll").unwrap();
+	/// let sym = lib.symbol_ordinal(42).unwrap();
+	/// ```
+	#[cfg_attr(docsrs, doc(cfg(windows)))]
+	#[cfg(windows)]
+	#[doc(alias = "GetProcAddress")]
+	#[inline]
+	pub fn symbol_ordinal(&self, ordinal: u16) -> io::Result<*const Symbol> {
+		unsafe { self.0.symbol_ordinal(ordinal) }
+	}
+
+	/// Retrieves a specific version of a symbol from the library if it exists. The symbol must not
+	/// be used past the lifetime of the library or the symbol will be invalid.
+	///
+	/// This is useful for libraries that ship several versions of a symbol, such as glibc.
+	///
+	/// # Errors
+	///
+	/// May error if the symbol does not exist with the requested version.
+	///
+	/// # Examples
+	///
+	/// ```
+	/// use dylink::Library;
+	///
+	/// let lib = Library::open("libc.so.6").unwrap();
+	/// let sym = lib.symbol_version("clock_gettime", "GLIBC_2.17").unwrap();
+	/// ```
+	#[cfg_attr(docsrs, doc(cfg(all(unix, target_env = "gnu"))))]
+	#[cfg(all(unix, target_env = "gnu"))]
+	#[doc(alias = "dlvsym")]
+	#[inline]
+	pub fn symbol_version(&self, name: &str, version: &str) -> io::Result<*const Symbol> {
+		unsafe { self.0.symbol_version(name, version) }
+	}
+
 	/// Retrieves a symbol from the library if it exists. The difference from [`symbol`] is that this function accepts a raw c-string, which is
 	/// useful to avoid redundant string cloning.
 	///
@@ -165,6 +429,46 @@ impl Library {
 		unsafe { self.0.raw_symbol(name) }
 	}
 
+	/// Retrieves a global variable exported by the library, as a reference that reads and writes it
+	/// as a `T`.
+	///
+	/// The address of the symbol must be aligned for `T`, and if the export table of the library
+	/// records the size of the symbol, such as `.dynsym` on Linux, it must be at least the size of
+	/// `T`. Other platforms don't record sizes, so they're not checked there.
+	///
+	/// # Errors
+	///
+	/// May error if the symbol is not found, or with [`io::ErrorKind::InvalidData`] if it can't hold
+	/// a `T`.
+	///
+	/// # Safety
+	///
+	/// The symbol must be a global variable whose value is valid as a `T`, and which isn't written
+	/// by other threads while it's accessed.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use dylink::Library;
+	///
+	/// #[repr(C)]
+	/// #[derive(Clone, Copy)]
+	/// struct Version {
+	///     major: u8,
+	///     minor: u8,
+	///     patch: u8,
+	/// }
+	///
+	/// let lib = Library::open("libfoo.so").unwrap();
+	/// let version = unsafe { lib.global::<Version>("foo_compiled_version") }.unwrap();
+	/// println!("{}.{}.{}", version.read().major, version.read().minor, version.read().patch);
+	/// ```
+	#[cfg_attr(docsrs, doc(cfg(feature = "images")))]
+	#[cfg(feature = "images")]
+	pub unsafe fn global<T>(&self, name: &str) -> io::Result<GlobalRef<'_, T>> {
+		GlobalRef::new(name, self.symbol(name)?)
+	}
+
 	/// Creates a new `Library` instance that shares the same underlying library handle as the
 	/// existing `Library` instance.
 	///
@@ -218,6 +522,25 @@ impl Library {
 		})
 	}
 
+	/// Blocks until the loader has finished running the initializers of this library.
+	///
+	/// This is useful when a library may still be initializing on another thread, such as when it
+	/// was loaded in the background, and calls into it must be sequenced after its initializers.
+	///
+	/// If called from within an initializer of this library, this returns immediately.
+	///
+	/// # Errors
+	///
+	/// May error if the loader cannot be synchronized with.
+	///
+	/// # Platform behavior
+	///
+	/// On Unix, `dlopen` returns only after initializers have run, so this waits on the loader
+	/// by reopening the library. On Windows, this waits for the loader lock to be released.
+	pub fn wait_until_initialized(&self) -> io::Result<()> {
+		unsafe { self.0.wait_until_initialized() }
+	}
+
 	/// Unloads the dynamic library from memory.
 	///
 	/// This decrements the library's reference count and unloads it when the
@@ -236,6 +559,45 @@ impl Library {
 	}
 }
 
+// Rejects absolute paths passed to `Library::open_at`, which would escape the directory.
+fn relative_path(path: &path::Path) -> io::Result<&path::Path> {
+	match path.components().next() {
+		Some(path::Component::Prefix(_) | path::Component::RootDir) => Err(io::Error::new(
+			io::ErrorKind::InvalidInput,
+			"path must be relative to the directory",
+		)),
+		_ => Ok(path),
+	}
+}
+
+/// Discards the state this crate keeps about the images loaded in the process, so that it's
+/// gathered again from the system.
+///
+/// This is meant for after events the crate can't observe, such as images being closed through
+/// FFI with `dlclose` or `FreeLibrary`, or the process being restored from a checkpoint. The
+/// [`ModuleMap`](img::ModuleMap) is rebuilt if it was built before, and on Windows, dbghelp
+/// re-enumerates the modules it uses for [`symbolize`].
+///
+/// Libraries held by a [`LibLock`](sync::LibLock) and the addresses cached by functions generated
+/// with [`dylink`] are not affected. They stay valid as long as their library is loaded, so a
+/// library closed through FFI must also be reset with `LibLock::unload`.
+///
+/// # Errors
+///
+/// May error if the loaded images can't be enumerated.
+pub fn flush_caches() -> io::Result<()> {
+	#[cfg(all(windows, feature = "images"))]
+	os::windows::refresh_symbols();
+	#[cfg(feature = "module-map")]
+	{
+		let map = img::ModuleMap::unbuilt();
+		if map.generation() != 0 {
+			map.refresh()?;
+		}
+	}
+	Ok(())
+}
+
 /// Creates an `Option<Library>` that may contain a loaded library.
 ///
 /// `lib!` allows `Library`s to be defined with the same syntax as an array expression.
@@ -250,3 +612,21 @@ macro_rules! lib {
 			.find_map(|elem| $crate::Library::open(elem).ok())
 	};
 }
+
+/// Creates a [`LoadReport`] describing which library candidate was loaded, and why the others failed.
+///
+/// `lib_report!` accepts the same syntax as [`lib`], but keeps the attempt trail instead of
+/// discarding it.
+/// ```rust
+/// use dylink::*;
+/// let report = lib_report!["libvulkan.dylib", "libvulkan.1.dylib", "libMoltenVK.dylib"];
+/// if report.library().is_none() {
+///     eprintln!("could not find Vulkan: {report}");
+/// }
+/// ```
+#[macro_export]
+macro_rules! lib_report {
+	($($name:expr),+ $(,)?) => {
+		$crate::LoadReport::open([$($name),+])
+	};
+}
@@ -0,0 +1,105 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::{
+	Library,
+	OpenFlags,
+};
+use std::{
+	io,
+	path,
+};
+
+// The target triple the crate was built for, such as `x86_64-unknown-linux-gnu`.
+const TARGET: &str = env!("DYLINK_TARGET");
+// The Debian multiarch tuple of the target, such as `x86_64-linux-gnu`.
+#[cfg(target_os = "linux")]
+const MULTIARCH: Option<&str> = option_env!("DYLINK_MULTIARCH");
+
+/// Options for opening a library, used when [`Library::open`] isn't enough.
+///
+/// # Examples
+///
+/// ```no_run
+/// use dylink::{OpenFlags, OpenOptions};
+///
+/// let lib = OpenOptions::new()
+///     .flags(OpenFlags::PLUGIN)
+///     .multiarch(true)
+///     .open("plugins/libfoo.so")
+///     .unwrap();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct OpenOptions {
+	flags: OpenFlags,
+	multiarch: bool,
+}
+
+impl OpenOptions {
+	/// Creates options that open a library like [`Library::open`].
+	#[inline]
+	pub const fn new() -> Self {
+		Self {
+			flags: OpenFlags::DEFAULT,
+			multiarch: false,
+		}
+	}
+
+	/// Sets the flags the library is opened with, which are [`OpenFlags::DEFAULT`] unless set.
+	#[inline]
+	pub fn flags(&mut self, flags: OpenFlags) -> &mut Self {
+		self.flags = flags;
+		self
+	}
+
+	/// Sets whether to look for a build of the library for the current architecture first.
+	///
+	/// This is meant for application bundles that ship several architectures in one tree. A path
+	/// such as `<dir>/libfoo.so` is looked for in `<dir>/<target-triple>/libfoo.so` first, such
+	/// as `<dir>/x86_64-unknown-linux-gnu/libfoo.so`, and then on Linux in
+	/// `<dir>/<multiarch-tuple>/libfoo.so`, using the Debian multiarch tuple, such as
+	/// `x86_64-linux-gnu`. A library name without a directory is looked for in
+	/// `/usr/lib/<multiarch-tuple>` and `/lib/<multiarch-tuple>` on Linux instead.
+	///
+	/// The first of these files that exists is opened, and if none do, the path is opened as is.
+	#[inline]
+	pub fn multiarch(&mut self, multiarch: bool) -> &mut Self {
+		self.multiarch = multiarch;
+		self
+	}
+
+	/// Attempts to open a dynamic library file with these options.
+	///
+	/// # Errors
+	///
+	/// May error if the library can't be loaded. If a build for the current architecture was found,
+	/// the error is that of opening it, rather than of the path itself.
+	pub fn open<P: AsRef<path::Path>>(&self, path: P) -> io::Result<Library> {
+		let path = path.as_ref();
+		let candidate = match self.multiarch {
+			true => arch_paths(path).into_iter().find(|path| path.is_file()),
+			false => None,
+		};
+		Library::open_with(candidate.as_deref().unwrap_or(path), self.flags)
+	}
+}
+
+// Returns the paths of the builds of the library for the current architecture, in order.
+fn arch_paths(path: &path::Path) -> Vec<path::PathBuf> {
+	let Some(file_name) = path.file_name() else {
+		return Vec::new();
+	};
+	let dir = path.parent().filter(|dir| !dir.as_os_str().is_empty());
+	let mut dirs = Vec::new();
+	if let Some(dir) = dir {
+		dirs.push(dir.join(TARGET));
+	}
+	#[cfg(target_os = "linux")]
+	if let Some(tuple) = MULTIARCH {
+		match dir {
+			Some(dir) => dirs.push(dir.join(tuple)),
+			None => dirs.extend(["/usr/lib", "/lib"].map(|dir| path::Path::new(dir).join(tuple))),
+		}
+	}
+	dirs.into_iter().map(|dir| dir.join(file_name)).collect()
+}
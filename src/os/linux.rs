@@ -0,0 +1,165 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use super::unix::{
+	c,
+	elf::{
+		VERSYM_HIDDEN,
+		gnu_hash,
+		sysv_hash,
+	},
+	iter_phdr,
+};
+use crate::Symbol;
+use std::{
+	ffi,
+	slice,
+};
+
+#[cfg(target_pointer_width = "32")]
+use c::{
+	Elf32_Dyn as Dyn,
+	Elf32_Sym as Sym,
+};
+#[cfg(target_pointer_width = "64")]
+use c::{
+	Elf64_Dyn as Dyn,
+	Elf64_Sym as Sym,
+};
+
+/// Finds a symbol by walking the dynamic symbol tables of the images loaded in the process.
+///
+/// Unlike [`Library::symbol`](crate::Library::symbol), this doesn't take a reference to any image,
+/// doesn't allocate, and doesn't take the lock held by `dlopen` while an image is loading, which makes
+/// it usable from crash handlers and profilers. Images are searched in load order, and only the
+/// default version of a symbol is considered.
+///
+/// Thread local and `STT_GNU_IFUNC` symbols are skipped, since their values aren't addresses
+/// of the symbols themselves.
+///
+/// # Platform behavior
+///
+/// `dl_iterate_phdr` briefly takes a lock of its own while images are added or removed, so this
+/// function may block if another thread is in the middle of doing so.
+///
+/// # Examples
+///
+/// ```
+/// use dylink::os::linux;
+///
+/// let atoi = linux::find_symbol_in_maps("atoi").unwrap();
+/// assert!(!atoi.is_null());
+/// ```
+pub fn find_symbol_in_maps(name: &str) -> Option<*const Symbol> {
+	let name = name.as_bytes();
+	let mut found = None;
+	unsafe {
+		iter_phdr(|info, _| match lookup(&*info, name) {
+			Some(addr) => {
+				found = Some(addr as *const Symbol);
+				1
+			}
+			None => 0,
+		});
+	}
+	found
+}
+
+unsafe fn lookup(info: &c::dl_phdr_info, name: &[u8]) -> Option<usize> {
+	unsafe {
+		let base = info.dlpi_addr;
+		let phdrs = slice::from_raw_parts(info.dlpi_phdr, info.dlpi_phnum as usize);
+		let dynamic = phdrs.iter().find(|ph| ph.p_type == c::PT_DYNAMIC)?;
+		let mut dynamic = base.wrapping_add(dynamic.p_vaddr as usize) as *const Dyn;
+
+		let (mut hash, mut gnu_hash_table, mut symtab, mut strtab, mut versym) =
+			(None, None, None, None, None);
+		loop {
+			let d = &*dynamic;
+			let d_ptr = d.d_un as usize;
+			// glibc relocates the dynamic section in place, other loaders (and the vDSO) don't.
+			let addr = if d_ptr >= base {
+				d_ptr
+			} else {
+				base.wrapping_add(d_ptr)
+			};
+			match d.d_tag as i64 {
+				c::DT_NULL => break,
+				c::DT_HASH => hash = Some(addr as *const u32),
+				c::DT_GNU_HASH => gnu_hash_table = Some(addr as *const u32),
+				c::DT_SYMTAB => symtab = Some(addr as *const Sym),
+				c::DT_STRTAB => strtab = Some(addr as *const ffi::c_char),
+				c::DT_VERSYM => versym = Some(addr as *const u16),
+				_ => (),
+			}
+			dynamic = dynamic.add(1);
+		}
+		let (symtab, strtab) = (symtab?, strtab?);
+
+		let matches = |index: u32| {
+			let sym = &*symtab.add(index as usize);
+			let bind = sym.st_info >> 4;
+			let kind = sym.st_info & 0xf;
+			if sym.st_shndx == c::SHN_UNDEF
+				|| !matches!(bind, c::STB_GLOBAL | c::STB_WEAK | c::STB_GNU_UNIQUE)
+				|| !matches!(
+					kind,
+					c::STT_FUNC | c::STT_OBJECT | c::STT_COMMON | c::STT_NOTYPE
+				) || versym.is_some_and(|versym| *versym.add(index as usize) & VERSYM_HIDDEN != 0)
+				|| ffi::CStr::from_ptr(strtab.add(sym.st_name as usize)).to_bytes() != name
+			{
+				None
+			} else {
+				Some(base.wrapping_add(sym.st_value as usize))
+			}
+		};
+
+		if let Some(table) = gnu_hash_table {
+			let (nbuckets, symoffset, bloom_size) = (*table, *table.add(1), *table.add(2));
+			if nbuckets == 0 {
+				return None;
+			}
+			// the bloom filter is made of `ElfW_Addr` sized words.
+			let buckets = table
+				.add(4)
+				.cast::<usize>()
+				.add(bloom_size as usize)
+				.cast::<u32>();
+			let chains = buckets.add(nbuckets as usize);
+			let h = gnu_hash(name);
+			let mut index = *buckets.add((h % nbuckets) as usize);
+			if index < symoffset {
+				return None;
+			}
+			loop {
+				let chain = *chains.add((index - symoffset) as usize);
+				if chain | 1 == h | 1
+					&& let Some(addr) = matches(index)
+				{
+					return Some(addr);
+				}
+				if chain & 1 != 0 {
+					return None;
+				}
+				index += 1;
+			}
+		} else if let Some(table) = hash {
+			let nbucket = *table;
+			if nbucket == 0 {
+				return None;
+			}
+			let buckets = table.add(2);
+			let chains = buckets.add(nbucket as usize);
+			let mut index = *buckets.add((sysv_hash(name) % nbucket) as usize);
+			while index != 0 {
+				if let Some(addr) = matches(index) {
+					return Some(addr);
+				}
+				index = *chains.add(index as usize);
+			}
+			None
+		} else {
+			None
+		}
+	}
+}
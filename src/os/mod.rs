@@ -1,8 +1,12 @@
 // SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
+#[cfg_attr(docsrs, doc(cfg(all(target_os = "linux", feature = "images"))))]
+#[cfg(all(target_os = "linux", feature = "images"))]
+pub mod linux;
 #[cfg_attr(docsrs, doc(cfg(unix)))]
 #[cfg(any(unix, docsrs))]
 pub mod unix;
+#[cfg_attr(docsrs, doc(cfg(windows)))]
 #[cfg(windows)]
-pub(crate) mod windows;
+pub mod windows;
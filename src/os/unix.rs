@@ -3,19 +3,37 @@
 
 #![allow(clippy::let_unit_value)]
 
+use crate::scrub::Scrubbed;
 use crate::sealed::Sealed;
 use crate::{
+	LoadError,
+	LoadErrorKind,
+	SandboxDenied,
 	Symbol,
 	img,
-	weak,
+};
+#[cfg(unix)]
+use std::os::fd::BorrowedFd;
+#[cfg(target_os = "linux")]
+use std::os::fd::{
+	AsRawFd,
+	FromRawFd,
+	OwnedFd,
 };
 #[cfg(unix)]
 use std::os::unix::ffi::OsStrExt;
+#[cfg(target_os = "linux")]
+use std::sync::Mutex;
 use std::{
+	env,
 	ffi,
+	fs,
 	io,
 	mem,
-	path::PathBuf,
+	path::{
+		self,
+		PathBuf,
+	},
 	ptr,
 };
 
@@ -28,6 +46,11 @@ use std::sync::{
 	},
 };
 
+#[cfg(feature = "images")]
+use crate::weak;
+#[cfg(feature = "images")]
+use std::ops;
+
 #[cfg(not(any(target_os = "linux", target_os = "macos", target_env = "gnu")))]
 use std::sync::{
 	LockResult,
@@ -35,7 +58,11 @@ use std::sync::{
 	MutexGuard,
 };
 
-mod c;
+pub(crate) mod c;
+#[cfg(feature = "images")]
+pub(crate) mod elf;
+#[cfg(feature = "images")]
+pub(crate) mod macho;
 
 #[cfg(not(any(target_os = "linux", target_os = "macos", target_env = "gnu")))]
 #[inline]
@@ -72,6 +99,160 @@ fn dlopen_fname(fname: &ffi::CStr) -> *const ffi::c_char {
 	fname.as_ptr()
 }
 
+// Flags of the `OpenFlags` presets.
+pub(crate) const DEFAULT_FLAGS: u32 = (c::RTLD_NOW | c::RTLD_LOCAL) as u32;
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+pub(crate) const PLUGIN_FLAGS: u32 = DEFAULT_FLAGS | c::RTLD_DEEPBIND as u32;
+#[cfg(not(all(target_os = "linux", target_env = "gnu")))]
+pub(crate) const PLUGIN_FLAGS: u32 = DEFAULT_FLAGS;
+pub(crate) const SYSTEM_FLAGS: u32 = DEFAULT_FLAGS;
+#[cfg(any(target_os = "macos", target_env = "gnu"))]
+pub(crate) const INSPECT_FLAGS: u32 = DEFAULT_FLAGS | c::RTLD_NOLOAD as u32;
+#[cfg(not(any(target_os = "macos", target_env = "gnu")))]
+pub(crate) const INSPECT_FLAGS: u32 = DEFAULT_FLAGS;
+// Flags removed and added by `OpenFlags::lazy`.
+pub(crate) const EAGER_FLAGS: u32 = c::RTLD_NOW as u32;
+pub(crate) const LAZY_FLAGS: u32 = c::RTLD_LAZY as u32;
+
+// The environment variable listing the directories searched first for libraries opened by name.
+#[cfg(target_os = "macos")]
+const SEARCH_PATH_VAR: &str = "DYLD_LIBRARY_PATH";
+#[cfg(not(target_os = "macos"))]
+const SEARCH_PATH_VAR: &str = "LD_LIBRARY_PATH";
+// The default directories searched for libraries opened by name. Run paths and the cache of the
+// loader aren't covered, which is good enough to find a denied file in most sandboxes.
+const SEARCH_DIRS: [&str; 5] = ["/lib", "/usr/lib", "/lib64", "/usr/lib64", "/usr/local/lib"];
+
+// Classifies a message of `dlerror`, which is all the loaders report about a failure. The formats
+// of glibc, musl and dyld are recognized.
+fn load_error(path: Option<&path::Path>, message: String) -> io::Error {
+	let has = |patterns: &[&str]| patterns.iter().any(|pattern| message.contains(pattern));
+	let kind = if has(&["version `", ", version "]) {
+		LoadErrorKind::VersionMismatch
+	} else if has(&["undefined symbol", "Symbol not found", "symbol not found"]) {
+		LoadErrorKind::UndefinedSymbol
+	} else if has(&[
+		"wrong ELF class",
+		"incompatible architecture",
+		"wrong architecture",
+	]) {
+		LoadErrorKind::WrongArchitecture
+	} else if has(&[
+		"invalid ELF header",
+		"file too short",
+		"not a mach-o file",
+		"Exec format error",
+	]) {
+		LoadErrorKind::InvalidFormat
+	} else if has(&[
+		"No such file",
+		"no such file",
+		"Library not loaded",
+		"image not found",
+	]) {
+		LoadErrorKind::NotFound
+	} else {
+		LoadErrorKind::Other
+	};
+	let dependency = if let Some((_, rest)) = message.split_once("Library not loaded: ") {
+		// dyld names the dependency, followed by the library requiring it on the next line.
+		rest.lines().next().map(|name| name.trim().to_owned())
+	} else if let Some(rest) = message.strip_prefix("Error loading shared library ") {
+		// musl only appends the library requiring it when the dependency failed.
+		rest.split_once(": ")
+			.filter(|_| message.contains("(needed by "))
+			.map(|(name, _)| name.to_owned())
+	} else {
+		// glibc prefixes the file that failed, which is the resolved path of libraries searched
+		// for by name, so only file names are compared.
+		message
+			.split_once(": ")
+			.map(|(name, _)| name)
+			.filter(|name| {
+				path.is_some_and(|path| path::Path::new(name).file_name() != path.file_name())
+			})
+			.map(str::to_owned)
+	};
+	let (kind, io_kind) = match kind {
+		LoadErrorKind::NotFound if dependency.is_some() => {
+			(LoadErrorKind::MissingDependency, io::ErrorKind::NotFound)
+		}
+		LoadErrorKind::NotFound => (kind, io::ErrorKind::NotFound),
+		LoadErrorKind::WrongArchitecture | LoadErrorKind::InvalidFormat => {
+			(kind, io::ErrorKind::InvalidData)
+		}
+		_ => (kind, io::ErrorKind::Other),
+	};
+	LoadError::error(io_kind, kind, message, dependency, None)
+}
+
+// Errors of `dlsym` and `dlvsym` are always about the symbol that was looked up.
+fn symbol_error(message: String) -> io::Error {
+	let kind = if message.contains("version `") || message.contains(", version ") {
+		LoadErrorKind::VersionMismatch
+	} else {
+		LoadErrorKind::UndefinedSymbol
+	};
+	LoadError::error(io::ErrorKind::Other, kind, message, None, None)
+}
+
+// Converts a failure of dlopen into an error. Sandboxes deny access to files with EACCES or EPERM,
+// which the loader only reports as such if no other candidate was found, so the candidates are
+// opened again to find the one that was denied. Landlock doesn't restrict `stat`, so the files
+// have to be opened, rather than only queried.
+fn dlopen_error(path: Option<&ffi::OsStr>, message: String) -> io::Error {
+	let Some(path) = path.map(path::Path::new) else {
+		return load_error(None, message);
+	};
+	let is_denied = |path: &path::Path| {
+		fs::File::open(path).is_err_and(|err| err.kind() == io::ErrorKind::PermissionDenied)
+	};
+	let reported = [
+		"Permission denied",
+		"Operation not permitted",
+		"(errno=1)",
+		"(errno=13)",
+	]
+	.iter()
+	.any(|denied| message.contains(denied));
+	let denied_path = if path.as_os_str().as_bytes().contains(&b'/') {
+		Some(path.to_path_buf()).filter(|path| reported || is_denied(path))
+	} else {
+		env::var_os(SEARCH_PATH_VAR)
+			.iter()
+			.flat_map(env::split_paths)
+			.chain(SEARCH_DIRS.iter().map(PathBuf::from))
+			.map(|dir| dir.join(path))
+			.find(|path| is_denied(path))
+	};
+	if reported || denied_path.is_some() {
+		SandboxDenied::error(denied_path, message)
+	} else {
+		load_error(Some(path), message)
+	}
+}
+
+// Descriptors of the libraries loaded through `/proc/self/fd`, along with their handles.
+#[cfg(target_os = "linux")]
+static FD_LIBRARIES: Mutex<Vec<(usize, OwnedFd)>> = Mutex::new(Vec::new());
+
+// Closes the descriptor of the library `handle` once it was unloaded, since closing a handle only
+// unloads the library when no other handle is left.
+#[cfg(target_os = "linux")]
+fn release_fd(handle: usize) {
+	let mut fds = FD_LIBRARIES.lock().unwrap_or_else(|err| err.into_inner());
+	let Some(index) = fds.iter().position(|(kept, _)| *kept == handle) else {
+		return;
+	};
+	let fd_path = format!("/proc/self/fd/{}\0", fds[index].1.as_raw_fd());
+	let loaded = unsafe { c::dlopen(fd_path.as_ptr().cast(), c::RTLD_LAZY | c::RTLD_NOLOAD) };
+	if loaded.is_null() {
+		fds.swap_remove(index);
+	} else {
+		unsafe { c::dlclose(loaded) };
+	}
+}
+
 #[derive(Debug)]
 #[repr(transparent)]
 pub(crate) struct InnerLibrary(pub ptr::NonNull<ffi::c_void>);
@@ -80,23 +261,75 @@ impl InnerLibrary {
 	unsafe fn open_with_flags(path: Option<&ffi::OsStr>, flag: ffi::c_int) -> io::Result<Self> {
 		let _lock = dylib_guard();
 		unsafe {
-			let c_str = path.map(|p| ffi::CString::new(p.as_bytes())).transpose()?;
+			let c_str = Scrubbed(path.map(|p| ffi::CString::new(p.as_bytes())).transpose()?);
 			let path_ptr = c_str.as_ref().map_or(ptr::null(), |s| s.as_ptr());
 			let handle = c::dlopen(path_ptr, flag);
 			if let Some(ret) = ptr::NonNull::new(handle) {
 				Ok(Self(ret))
 			} else {
 				let err = c_dlerror().unwrap();
-				Err(io::Error::other(err.to_string_lossy()))
+				Err(dlopen_error(path, err.to_string_lossy().into_owned()))
 			}
 		}
 	}
 	pub unsafe fn open(path: &ffi::OsStr) -> io::Result<Self> {
 		unsafe { Self::open_with_flags(Some(path), c::RTLD_NOW | c::RTLD_LOCAL) }
 	}
+	pub unsafe fn open_with(path: &ffi::OsStr, flags: u32) -> io::Result<Self> {
+		unsafe { Self::open_with_flags(Some(path), flags as ffi::c_int) }
+	}
 	pub unsafe fn this() -> io::Result<Self> {
 		unsafe { Self::open_with_flags(None, c::RTLD_NOW | c::RTLD_LOCAL) }
 	}
+	// The library is loaded through the magic link of its descriptor, since dlopen only takes paths.
+	// The loader recognizes loaded libraries by their path, so the descriptor is kept open while the
+	// library is loaded, or else the next library loaded through the same number would be taken for
+	// this one.
+	#[cfg(target_os = "linux")]
+	unsafe fn open_fd(fd: OwnedFd, flags: ffi::c_int) -> io::Result<Self> {
+		let fd_path = format!("/proc/self/fd/{}", fd.as_raw_fd());
+		let lib = unsafe { Self::open_with_flags(Some(fd_path.as_ref()), flags)? };
+		let mut fds = FD_LIBRARIES.lock().unwrap_or_else(|err| err.into_inner());
+		// a library reopened through the same path is already kept.
+		if !fds
+			.iter()
+			.any(|(handle, _)| *handle == lib.0.as_ptr() as usize)
+		{
+			fds.push((lib.0.as_ptr() as usize, fd));
+		}
+		Ok(lib)
+	}
+	#[cfg(target_os = "linux")]
+	pub unsafe fn open_at(dir: BorrowedFd<'_>, path: &ffi::OsStr) -> io::Result<Self> {
+		let c_str = Scrubbed(ffi::CString::new(path.as_bytes())?);
+		let fd = unsafe { c::openat(dir.as_raw_fd(), c_str.as_ptr(), c::O_RDONLY | c::O_CLOEXEC) };
+		if fd < 0 {
+			return Err(io::Error::last_os_error());
+		}
+		let fd = unsafe { OwnedFd::from_raw_fd(fd) };
+		unsafe { Self::open_fd(fd, DEFAULT_FLAGS as ffi::c_int) }
+	}
+	// The library is written to an anonymous file, so nothing is left on the filesystem.
+	#[cfg(all(target_os = "linux", feature = "sync"))]
+	pub unsafe fn open_bytes(name: &str, bytes: &[u8], flags: u32) -> io::Result<Self> {
+		use std::io::Write;
+
+		let c_str = Scrubbed(ffi::CString::new(name)?);
+		let fd = unsafe { c::memfd_create(c_str.as_ptr(), c::MFD_CLOEXEC) };
+		if fd < 0 {
+			return Err(io::Error::last_os_error());
+		}
+		let mut file = fs::File::from(unsafe { OwnedFd::from_raw_fd(fd) });
+		file.write_all(bytes)?;
+		unsafe { Self::open_fd(file.into(), flags as ffi::c_int) }
+	}
+	#[cfg(not(target_os = "linux"))]
+	pub unsafe fn open_at(_: BorrowedFd<'_>, _: &ffi::OsStr) -> io::Result<Self> {
+		Err(io::Error::new(
+			io::ErrorKind::Unsupported,
+			"opening a library relative to a directory is unsupported on this platform",
+		))
+	}
 
 	#[inline]
 	pub unsafe fn raw_symbol(&self, name: &ffi::CStr) -> *const Symbol {
@@ -107,7 +340,7 @@ impl InnerLibrary {
 		let _lock = dylib_guard();
 		unsafe {
 			let c_str = match ffi::CString::new(name) {
-				Ok(s) => s,
+				Ok(s) => Scrubbed(s),
 				Err(err) => return Err(io::Error::new(io::ErrorKind::InvalidData, err)),
 			};
 
@@ -115,12 +348,33 @@ impl InnerLibrary {
 			let handle = self.raw_symbol(&c_str).cast_mut();
 
 			if let Some(err) = c_dlerror() {
-				Err(io::Error::other(err.to_string_lossy()))
+				Err(symbol_error(err.to_string_lossy().into_owned()))
 			} else {
 				Ok(handle)
 			}
 		}
 	}
+	#[cfg(target_env = "gnu")]
+	pub unsafe fn symbol_version(&self, name: &str, version: &str) -> io::Result<*const Symbol> {
+		let _lock = dylib_guard();
+		unsafe {
+			let (c_name, c_version) = match (ffi::CString::new(name), ffi::CString::new(version)) {
+				(Ok(n), Ok(v)) => (Scrubbed(n), Scrubbed(v)),
+				(Err(err), _) | (_, Err(err)) => {
+					return Err(io::Error::new(io::ErrorKind::InvalidData, err));
+				}
+			};
+
+			let _ = c_dlerror(); // clear existing errors
+			let handle = c::dlvsym(self.0.as_ptr(), c_name.as_ptr(), c_version.as_ptr());
+
+			if let Some(err) = c_dlerror() {
+				Err(symbol_error(err.to_string_lossy().into_owned()))
+			} else {
+				Ok(handle.cast())
+			}
+		}
+	}
 	pub(crate) unsafe fn try_clone(&self) -> io::Result<Self> {
 		unsafe {
 			let this = Self::this()?;
@@ -141,6 +395,12 @@ impl InnerLibrary {
 		}
 	}
 
+	// The loader holds its lock while running initializers, so reopening the library
+	// blocks until any in-progress initialization has finished.
+	pub(crate) unsafe fn wait_until_initialized(&self) -> io::Result<()> {
+		unsafe { self.try_clone()?.close() }
+	}
+
 	// This is to handle any platforms that I cannot deal with.
 	#[cfg(not(any(target_env = "gnu", target_os = "macos")))]
 	pub(crate) unsafe fn to_ptr(&self) -> *const img::Image {
@@ -201,9 +461,30 @@ impl InnerLibrary {
 			}
 		}
 	}
+	// The loader lock can't be polled, so this only avoids loading the image again.
+	pub(crate) unsafe fn try_from_ptr(addr: *const img::Image) -> io::Result<Option<Self>> {
+		unsafe {
+			let mut info = mem::MaybeUninit::zeroed();
+			if c::dladdr(addr.cast(), info.as_mut_ptr()) == 0 {
+				return Ok(None);
+			}
+			let info = info.assume_init();
+			let filename = dlopen_fname(ffi::CStr::from_ptr(info.dli_fname));
+			#[cfg(any(target_os = "macos", target_env = "gnu"))]
+			const FLAGS: ffi::c_int = c::RTLD_NOW | c::RTLD_LOCAL | c::RTLD_NOLOAD;
+			#[cfg(not(any(target_os = "macos", target_env = "gnu")))]
+			const FLAGS: ffi::c_int = c::RTLD_NOW | c::RTLD_LOCAL;
+			let handle = c::dlopen(filename, FLAGS);
+			Ok(ptr::NonNull::new(handle).map(Self))
+		}
+	}
 	pub(crate) fn close(self) -> io::Result<()> {
 		match unsafe { c::dlclose(self.0.as_ptr()) } {
-			0 => Ok(()),
+			0 => {
+				#[cfg(target_os = "linux")]
+				release_fd(self.0.as_ptr() as usize);
+				Ok(())
+			}
 			_ => Err(io::Error::last_os_error()),
 		}
 	}
@@ -227,6 +508,38 @@ fn get_image_count() -> &'static AtomicU32 {
 	&IMAGE_COUNT
 }
 
+// Registers dyld callbacks that keep the global module map up to date.
+#[cfg(feature = "module-map")]
+pub(crate) unsafe fn watch_images() -> bool {
+	#[cfg(target_os = "macos")]
+	{
+		extern "C" fn added(mh: *const c::mach_header, _: isize) {
+			let mut info = mem::MaybeUninit::<c::Dl_info>::zeroed();
+			let path = unsafe {
+				if c::dladdr(mh.cast(), info.as_mut_ptr()) != 0
+					&& !(*info.as_ptr()).dli_fname.is_null()
+				{
+					let path = ffi::CStr::from_ptr((*info.as_ptr()).dli_fname);
+					Some(PathBuf::from(ffi::OsStr::from_bytes(path.to_bytes())))
+				} else {
+					None
+				}
+			};
+			img::ModuleMap::unbuilt().loaded(img::Module::new(mh.cast(), None, path));
+		}
+		extern "C" fn removed(mh: *const c::mach_header, _: isize) {
+			img::ModuleMap::unbuilt().unloaded(mh.cast());
+		}
+		unsafe {
+			c::_dyld_register_func_for_add_image(added);
+			c::_dyld_register_func_for_remove_image(removed);
+		}
+		true
+	}
+	#[cfg(not(target_os = "macos"))]
+	false
+}
+
 pub(crate) unsafe fn base_addr(symbol: *const std::ffi::c_void) -> *mut img::Image {
 	unsafe {
 		#[cfg(not(target_os = "aix"))]
@@ -247,6 +560,35 @@ pub(crate) unsafe fn base_addr(symbol: *const std::ffi::c_void) -> *mut img::Ima
 	}
 }
 
+/// Returns the name and address of the symbol containing `addr` using `dladdr`, and whether the
+/// symbol is only the nearest one preceding it.
+#[cfg(feature = "images")]
+pub(crate) unsafe fn nearest_symbol(addr: usize) -> Option<(String, usize, bool)> {
+	#[cfg(not(target_os = "aix"))]
+	unsafe {
+		let mut info = mem::MaybeUninit::<c::Dl_info>::zeroed();
+		if c::dladdr(addr as *const ffi::c_void, info.as_mut_ptr()) == 0 {
+			return None;
+		}
+		let info = info.assume_init();
+		if info.dli_sname.is_null() {
+			return None;
+		}
+		let name = ffi::CStr::from_ptr(info.dli_sname);
+		Some((
+			name.to_string_lossy().into_owned(),
+			info.dli_saddr as usize,
+			false,
+		))
+	}
+	#[cfg(target_os = "aix")]
+	{
+		// aix doesn't have dladdr
+		let _ = addr;
+		None
+	}
+}
+
 #[derive(Debug)]
 pub struct DlInfo {
 	pub dli_fname: ffi::CString,
@@ -280,8 +622,8 @@ impl SymExt for Symbol {
 	}
 }
 
-#[cfg(target_env = "gnu")]
-unsafe fn iter_phdr<F>(mut f: F) -> ffi::c_int
+#[cfg(all(any(target_os = "linux", target_env = "gnu"), feature = "images"))]
+pub(crate) unsafe fn iter_phdr<F>(mut f: F) -> ffi::c_int
 where
 	F: FnMut(*mut c::dl_phdr_info, usize) -> ffi::c_int,
 {
@@ -304,6 +646,7 @@ where
 }
 
 #[cfg(target_env = "gnu")]
+#[cfg(feature = "images")]
 pub(crate) unsafe fn load_objects() -> io::Result<Vec<weak::Weak>> {
 	unsafe {
 		let mut data = Vec::new();
@@ -329,6 +672,7 @@ pub(crate) unsafe fn load_objects() -> io::Result<Vec<weak::Weak>> {
 }
 
 #[cfg(target_os = "macos")]
+#[cfg(feature = "images")]
 pub(crate) unsafe fn load_objects() -> io::Result<Vec<weak::Weak>> {
 	let mut data = Vec::new();
 	let _ = get_image_count().fetch_update(Ordering::SeqCst, Ordering::SeqCst, |image_index| {
@@ -347,6 +691,7 @@ pub(crate) unsafe fn load_objects() -> io::Result<Vec<weak::Weak>> {
 	Ok(data)
 }
 
+#[cfg(feature = "images")]
 pub(crate) unsafe fn hdr_size(hdr: *const img::Image) -> io::Result<usize> {
 	unsafe {
 		const MH_MAGIC: &[u8] = &0xfeedface_u32.to_le_bytes();
@@ -462,3 +807,223 @@ pub(crate) unsafe fn hdr_path(hdr: *const img::Image) -> io::Result<PathBuf> {
 		}
 	}
 }
+
+#[cfg(feature = "images")]
+pub(crate) enum Object {
+	Elf(elf::Elf),
+	MachO(macho::MachO),
+}
+
+#[cfg(feature = "images")]
+impl Object {
+	pub unsafe fn parse(raw: img::RawImage) -> io::Result<Self> {
+		unsafe {
+			match raw.read::<[u8; 4]>(0)? {
+				[0x7f, b'E', b'L', b'F'] => elf::Elf::parse(raw).map(Self::Elf),
+				_ => macho::MachO::parse(raw).map(Self::MachO),
+			}
+		}
+	}
+	pub fn arch(&self) -> img::Arch {
+		match self {
+			Self::Elf(elf) => elf.arch(),
+			Self::MachO(macho) => macho.arch(),
+		}
+	}
+	pub unsafe fn exports(&self) -> io::Result<Vec<img::Export>> {
+		unsafe {
+			match self {
+				Self::Elf(elf) => elf.exports(),
+				Self::MachO(macho) => macho.exports(),
+			}
+		}
+	}
+	/// Returns the address the image was linked to load at.
+	pub fn preferred_base(&self) -> usize {
+		match self {
+			Self::Elf(elf) => elf.min_vaddr,
+			Self::MachO(macho) => macho.text_vmaddr,
+		}
+	}
+	pub fn segments(&self) -> Vec<img::Segment> {
+		match self {
+			Self::Elf(elf) => elf.segments(),
+			Self::MachO(macho) => macho.segments(),
+		}
+	}
+	pub unsafe fn version(&self) -> io::Result<img::VersionInfo> {
+		unsafe {
+			match self {
+				Self::Elf(elf) => elf.version(),
+				Self::MachO(macho) => macho.version(),
+			}
+		}
+	}
+	pub unsafe fn tls(&self) -> io::Result<Option<img::Tls>> {
+		match self {
+			Self::Elf(elf) => Ok(elf.tls()),
+			Self::MachO(_) => Err(io::Error::new(
+				io::ErrorKind::Unsupported,
+				"thread-local storage of Mach-O images is not supported",
+			)),
+		}
+	}
+}
+
+#[cfg(feature = "images")]
+pub(crate) fn page_size() -> usize {
+	unsafe { c::sysconf(c::_SC_PAGESIZE) as usize }
+}
+
+#[cfg(feature = "images")]
+pub(crate) unsafe fn advise(ranges: &[ops::Range<usize>], advice: img::Advice) -> io::Result<()> {
+	let advice = match advice {
+		img::Advice::WillNeed => c::MADV_WILLNEED,
+		img::Advice::DontNeed => c::MADV_DONTNEED,
+	};
+	for range in ranges {
+		if unsafe { c::madvise(range.start as *mut _, range.len(), advice) } != 0 {
+			return Err(io::Error::last_os_error());
+		}
+	}
+	Ok(())
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[cfg(feature = "images")]
+pub(crate) fn page_stats(ranges: &[ops::Range<usize>]) -> io::Result<img::PageStats> {
+	use std::io::BufRead;
+	let page_size = page_size();
+	let smaps = io::BufReader::new(std::fs::File::open("/proc/self/smaps")?);
+	let mut stats = img::PageStats::default();
+	let mut in_image = false;
+	for line in smaps.lines() {
+		let line = line?;
+		let mut fields = line.split_whitespace();
+		let Some(key) = fields.next() else {
+			continue;
+		};
+		if let Some(key) = key.strip_suffix(':') {
+			if !in_image {
+				continue;
+			}
+			// values are reported in kilobytes.
+			let Some(Ok(kb)) = fields.next().map(str::parse::<usize>) else {
+				continue;
+			};
+			let pages = kb * 1024 / page_size;
+			match key {
+				"Rss" => stats.resident += pages,
+				"Shared_Clean" | "Shared_Dirty" => stats.shared += pages,
+				"Private_Clean" | "Private_Dirty" => stats.private += pages,
+				"AnonHugePages" | "FilePmdMapped" => stats.large += pages,
+				_ => (),
+			}
+		} else if let Some((start, end)) = key.split_once('-')
+			&& let (Ok(start), Ok(end)) = (
+				usize::from_str_radix(start, 16),
+				usize::from_str_radix(end, 16),
+			) {
+			// header line of a new mapping.
+			in_image = ranges
+				.iter()
+				.any(|range| range.start < end && start < range.end);
+			if in_image {
+				stats.mappings += 1;
+			}
+		}
+	}
+	Ok(stats)
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+#[cfg(feature = "images")]
+pub(crate) fn page_stats(_: &[ops::Range<usize>]) -> io::Result<img::PageStats> {
+	Err(io::Error::new(
+		io::ErrorKind::Unsupported,
+		"page statistics are unsupported on this platform",
+	))
+}
+
+#[cfg(feature = "images")]
+pub(crate) unsafe fn map_file(file: &std::fs::File) -> io::Result<(ptr::NonNull<u8>, usize)> {
+	use std::os::fd::AsRawFd;
+	let len = usize::try_from(file.metadata()?.len())
+		.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+	if len == 0 {
+		return Err(io::Error::new(io::ErrorKind::InvalidData, "file is empty"));
+	}
+	let addr = unsafe {
+		c::mmap(
+			ptr::null_mut(),
+			len,
+			c::PROT_READ,
+			c::MAP_PRIVATE,
+			file.as_raw_fd(),
+			0,
+		)
+	};
+	if addr == c::MAP_FAILED {
+		Err(io::Error::last_os_error())
+	} else {
+		ptr::NonNull::new(addr.cast())
+			.map(|addr| (addr, len))
+			.ok_or_else(io::Error::last_os_error)
+	}
+}
+
+#[cfg(feature = "images")]
+pub(crate) unsafe fn unmap_file(addr: ptr::NonNull<u8>, len: usize) {
+	let _ = unsafe { c::munmap(addr.as_ptr().cast(), len) };
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+#[cfg(feature = "images")]
+pub(crate) unsafe fn query_protection(addr: *const u8) -> io::Result<img::Protection> {
+	use std::io::BufRead;
+	let addr = addr as usize;
+	let maps = io::BufReader::new(std::fs::File::open("/proc/self/maps")?);
+	for line in maps.lines() {
+		let line = line?;
+		// format: `start-end perms offset dev inode path`
+		let mut fields = line.split_whitespace();
+		let (Some(range), Some(perms)) = (fields.next(), fields.next()) else {
+			continue;
+		};
+		let Some((start, end)) = range.split_once('-') else {
+			continue;
+		};
+		let (Ok(start), Ok(end)) = (
+			usize::from_str_radix(start, 16),
+			usize::from_str_radix(end, 16),
+		) else {
+			continue;
+		};
+		if (start..end).contains(&addr) {
+			let mut protection = img::Protection::NONE;
+			for (flag, value) in perms.bytes().zip([
+				img::Protection::READ,
+				img::Protection::WRITE,
+				img::Protection::EXECUTE,
+			]) {
+				if flag != b'-' {
+					protection |= value;
+				}
+			}
+			return Ok(protection);
+		}
+	}
+	Err(io::Error::new(
+		io::ErrorKind::NotFound,
+		"address is not mapped",
+	))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+#[cfg(feature = "images")]
+pub(crate) unsafe fn query_protection(_: *const u8) -> io::Result<img::Protection> {
+	Err(io::Error::new(
+		io::ErrorKind::Unsupported,
+		"protection queries are unsupported on this platform",
+	))
+}
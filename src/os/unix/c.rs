@@ -2,6 +2,8 @@
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
 #![allow(non_camel_case_types)]
+// most declarations are only used to inspect images.
+#![cfg_attr(not(feature = "images"), allow(dead_code))]
 
 use std::ffi;
 
@@ -33,6 +35,121 @@ pub struct mach_header_64 {
 	pub reserved: u32,
 }
 
+pub type vm_prot_t = ffi::c_int;
+
+pub const VM_PROT_READ: vm_prot_t = 0x1;
+pub const VM_PROT_WRITE: vm_prot_t = 0x2;
+pub const VM_PROT_EXECUTE: vm_prot_t = 0x4;
+
+pub const LC_SEGMENT: u32 = 0x1;
+pub const LC_SYMTAB: u32 = 0x2;
+pub const LC_ID_DYLIB: u32 = 0xd;
+pub const LC_SEGMENT_64: u32 = 0x19;
+
+pub const FAT_MAGIC: u32 = 0xcafebabe;
+
+pub const CPU_ARCH_ABI64: cpu_type_t = 0x01000000;
+pub const CPU_TYPE_X86: cpu_type_t = 7;
+pub const CPU_TYPE_X86_64: cpu_type_t = CPU_TYPE_X86 | CPU_ARCH_ABI64;
+pub const CPU_TYPE_ARM: cpu_type_t = 12;
+pub const CPU_TYPE_ARM64: cpu_type_t = CPU_TYPE_ARM | CPU_ARCH_ABI64;
+
+pub const N_STAB: u8 = 0xe0;
+pub const N_TYPE: u8 = 0x0e;
+pub const N_EXT: u8 = 0x01;
+pub const N_SECT: u8 = 0x0e;
+
+// fat headers are always big-endian.
+#[repr(C)]
+pub struct fat_header {
+	pub magic: u32,
+	pub nfat_arch: u32,
+}
+
+#[repr(C)]
+pub struct fat_arch {
+	pub cputype: cpu_type_t,
+	pub cpusubtype: cpu_subtype_t,
+	pub offset: u32,
+	pub size: u32,
+	pub align: u32,
+}
+
+#[repr(C)]
+pub struct symtab_command {
+	pub cmd: u32,
+	pub cmdsize: u32,
+	pub symoff: u32,
+	pub nsyms: u32,
+	pub stroff: u32,
+	pub strsize: u32,
+}
+
+#[repr(C)]
+pub struct dylib_command {
+	pub cmd: u32,
+	pub cmdsize: u32,
+	// offset of the name from the start of the command.
+	pub name: u32,
+	pub timestamp: u32,
+	pub current_version: u32,
+	pub compatibility_version: u32,
+}
+
+#[repr(C)]
+pub struct nlist {
+	pub n_strx: u32,
+	pub n_type: u8,
+	pub n_sect: u8,
+	pub n_desc: i16,
+	pub n_value: u32,
+}
+
+#[repr(C)]
+pub struct nlist_64 {
+	pub n_strx: u32,
+	pub n_type: u8,
+	pub n_sect: u8,
+	pub n_desc: u16,
+	pub n_value: u64,
+}
+
+#[repr(C)]
+pub struct load_command {
+	pub cmd: u32,
+	pub cmdsize: u32,
+}
+
+#[repr(C)]
+pub struct segment_command {
+	pub cmd: u32,
+	pub cmdsize: u32,
+	pub segname: [ffi::c_char; 16],
+	pub vmaddr: u32,
+	pub vmsize: u32,
+	pub fileoff: u32,
+	pub filesize: u32,
+	pub maxprot: vm_prot_t,
+	pub initprot: vm_prot_t,
+	pub nsects: u32,
+	pub flags: u32,
+}
+
+#[repr(C)]
+pub struct segment_command_64 {
+	pub cmd: u32,
+	pub cmdsize: u32,
+	pub segname: [ffi::c_char; 16],
+	pub vmaddr: u64,
+	pub vmsize: u64,
+	pub fileoff: u64,
+	pub filesize: u64,
+	pub maxprot: vm_prot_t,
+	pub initprot: vm_prot_t,
+	pub nsects: u32,
+	pub flags: u32,
+}
+
 #[cfg(target_os = "macos")]
 pub type PfnImageCallback = extern "C" fn(mh: *const mach_header, vmaddr_slide: isize);
 
@@ -44,10 +161,18 @@ pub struct Dl_info {
 	pub dli_saddr: *mut ffi::c_void,
 }
 
+#[cfg(not(target_os = "macos"))]
 pub const RTLD_LOCAL: ffi::c_int = 0;
+#[cfg(target_os = "macos")]
+pub const RTLD_LOCAL: ffi::c_int = 0x4;
+pub const RTLD_LAZY: ffi::c_int = 0x1;
 pub const RTLD_NOW: ffi::c_int = 0x2;
-#[cfg(any(target_os = "macos", target_env = "gnu"))]
+#[cfg(any(target_os = "linux", target_env = "gnu"))]
 pub const RTLD_NOLOAD: ffi::c_int = 0x4;
+#[cfg(target_os = "macos")]
+pub const RTLD_NOLOAD: ffi::c_int = 0x10;
+#[cfg(all(target_os = "linux", target_env = "gnu"))]
+pub const RTLD_DEEPBIND: ffi::c_int = 0x8;
 #[cfg(target_env = "gnu")]
 pub const RTLD_DI_LINKMAP: ffi::c_int = 2;
 #[cfg(target_env = "gnu")]
@@ -68,6 +193,95 @@ pub type Elf64_Addr = u64;
 pub const ELFCLASS32: u8 = 1;
 pub const ELFCLASS64: u8 = 2;
 
+pub const EM_386: ElfW_Half = 3;
+pub const EM_ARM: ElfW_Half = 40;
+pub const EM_X86_64: ElfW_Half = 62;
+pub const EM_AARCH64: ElfW_Half = 183;
+pub const EM_RISCV: ElfW_Half = 243;
+
+pub const PT_LOAD: ElfW_Word = 1;
+pub const PT_DYNAMIC: ElfW_Word = 2;
+pub const PT_TLS: ElfW_Word = 7;
+
+pub const DT_NULL: i64 = 0;
+pub const DT_HASH: i64 = 4;
+pub const DT_STRTAB: i64 = 5;
+pub const DT_SYMTAB: i64 = 6;
+pub const DT_SONAME: i64 = 14;
+pub const DT_GNU_HASH: i64 = 0x6ffffef5;
+pub const DT_VERSYM: i64 = 0x6ffffff0;
+pub const DT_VERDEF: i64 = 0x6ffffffc;
+pub const DT_VERDEFNUM: i64 = 0x6ffffffd;
+
+pub const VER_FLG_BASE: ElfW_Half = 0x1;
+
+pub const SHN_UNDEF: ElfW_Half = 0;
+
+pub const STB_GLOBAL: u8 = 1;
+pub const STB_WEAK: u8 = 2;
+pub const STB_GNU_UNIQUE: u8 = 10;
+
+pub const STT_NOTYPE: u8 = 0;
+pub const STT_OBJECT: u8 = 1;
+pub const STT_FUNC: u8 = 2;
+pub const STT_COMMON: u8 = 5;
+pub const STT_TLS: u8 = 6;
+pub const STT_GNU_IFUNC: u8 = 10;
+
+#[repr(C)]
+pub struct Elf32_Dyn {
+	pub d_tag: i32,
+	pub d_un: u32,
+}
+
+#[repr(C)]
+pub struct Elf64_Dyn {
+	pub d_tag: i64,
+	pub d_un: u64,
+}
+
+#[repr(C)]
+pub struct Elf32_Sym {
+	pub st_name: ElfW_Word,
+	pub st_value: Elf32_Addr,
+	pub st_size: ElfW_Word,
+	pub st_info: ffi::c_uchar,
+	pub st_other: ffi::c_uchar,
+	pub st_shndx: ElfW_Half,
+}
+
+#[repr(C)]
+pub struct Elf64_Sym {
+	pub st_name: ElfW_Word,
+	pub st_info: ffi::c_uchar,
+	pub st_other: ffi::c_uchar,
+	pub st_shndx: ElfW_Half,
+	pub st_value: Elf64_Addr,
+	pub st_size: u64,
+}
+
+// version definitions have the same layout for both classes.
+#[repr(C)]
+pub struct ElfW_Verdef {
+	pub vd_version: ElfW_Half,
+	pub vd_flags: ElfW_Half,
+	pub vd_ndx: ElfW_Half,
+	pub vd_cnt: ElfW_Half,
+	pub vd_hash: ElfW_Word,
+	pub vd_aux: ElfW_Word,
+	pub vd_next: ElfW_Word,
+}
+
+#[repr(C)]
+pub struct ElfW_Verdaux {
+	pub vda_name: ElfW_Word,
+	pub vda_next: ElfW_Word,
+}
+
+pub const PF_X: ElfW_Word = 0x1;
+pub const PF_W: ElfW_Word = 0x2;
+pub const PF_R: ElfW_Word = 0x4;
+
 #[repr(C)]
 pub struct Elf32_Ehdr {
 	pub e_ident: [ffi::c_uchar; 16],
@@ -129,7 +343,7 @@ pub struct Elf64_Phdr {
 	pub p_align: Elf64_Xword,
 }
 
-#[cfg(target_env = "gnu")]
+#[cfg(any(target_os = "linux", target_env = "gnu"))]
 #[repr(C)]
 pub struct dl_phdr_info {
 	pub dlpi_addr: ElfW_Addr,
@@ -141,6 +355,52 @@ pub struct dl_phdr_info {
 	pub dlpi_phnum: ElfW_Half,
 }
 
+pub const MADV_WILLNEED: ffi::c_int = 3;
+pub const MADV_DONTNEED: ffi::c_int = 4;
+
+#[cfg(target_os = "macos")]
+pub const _SC_PAGESIZE: ffi::c_int = 29;
+#[cfg(not(target_os = "macos"))]
+pub const _SC_PAGESIZE: ffi::c_int = 30;
+
+pub const PROT_READ: ffi::c_int = 0x1;
+pub const MAP_PRIVATE: ffi::c_int = 0x2;
+pub const MAP_FAILED: *mut ffi::c_void = !0 as *mut ffi::c_void;
+
+unsafe extern "C" {
+	pub fn mmap(
+		addr: *mut ffi::c_void,
+		len: usize,
+		prot: ffi::c_int,
+		flags: ffi::c_int,
+		fd: ffi::c_int,
+		offset: i64,
+	) -> *mut ffi::c_void;
+	pub fn munmap(addr: *mut ffi::c_void, len: usize) -> ffi::c_int;
+	pub fn madvise(addr: *mut ffi::c_void, len: usize, advice: ffi::c_int) -> ffi::c_int;
+	pub fn sysconf(name: ffi::c_int) -> ffi::c_long;
+}
+
+#[cfg(target_os = "linux")]
+pub const O_RDONLY: ffi::c_int = 0;
+#[cfg(target_os = "linux")]
+pub const O_CLOEXEC: ffi::c_int = 0o2000000;
+
+#[cfg(all(target_os = "linux", feature = "sync"))]
+pub const MFD_CLOEXEC: ffi::c_uint = 0x1;
+
+#[cfg(target_os = "linux")]
+unsafe extern "C" {
+	#[cfg(feature = "sync")]
+	pub fn memfd_create(name: *const ffi::c_char, flags: ffi::c_uint) -> ffi::c_int;
+	pub fn openat(
+		dirfd: ffi::c_int,
+		pathname: *const ffi::c_char,
+		flags: ffi::c_int,
+		...
+	) -> ffi::c_int;
+}
+
 unsafe extern "C" {
 	pub fn dlopen(filename: *const ffi::c_char, flag: ffi::c_int) -> *mut ffi::c_void;
 	pub fn dlerror() -> *const ffi::c_char;
@@ -149,6 +409,12 @@ unsafe extern "C" {
 	#[cfg(not(target_os = "aix"))]
 	pub fn dladdr(addr: *const ffi::c_void, info: *mut Dl_info) -> ffi::c_int;
 	#[cfg(target_env = "gnu")]
+	pub fn dlvsym(
+		handle: *mut ffi::c_void,
+		symbol: *const ffi::c_char,
+		version: *const ffi::c_char,
+	) -> *const ffi::c_void;
+	#[cfg(target_env = "gnu")]
 	pub fn dlinfo(
 		handle: *mut ffi::c_void,
 		request: ffi::c_int,
@@ -156,14 +422,14 @@ unsafe extern "C" {
 	) -> ffi::c_int;
 }
 
-#[cfg(target_env = "gnu")]
+#[cfg(any(target_os = "linux", target_env = "gnu"))]
 pub type DlIteratePhdrCallback = unsafe extern "C" fn(
 	info: *mut dl_phdr_info,
 	size: usize,
 	data: *mut ffi::c_void,
 ) -> ffi::c_int;
 
-#[cfg(target_env = "gnu")]
+#[cfg(any(target_os = "linux", target_env = "gnu"))]
 unsafe extern "C" {
 	pub fn dl_iterate_phdr(callback: DlIteratePhdrCallback, data: *mut ffi::c_void) -> ffi::c_int;
 }
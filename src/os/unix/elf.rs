@@ -0,0 +1,589 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use super::c;
+use crate::img::{
+	self,
+	RawImage,
+};
+use std::{
+	io,
+	mem,
+};
+
+/// A class independent ELF program header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Phdr {
+	pub(crate) p_type: c::ElfW_Word,
+	pub(crate) p_flags: c::ElfW_Word,
+	pub(crate) p_offset: usize,
+	pub(crate) p_vaddr: usize,
+	pub(crate) p_filesz: usize,
+	pub(crate) p_memsz: usize,
+	pub(crate) p_align: usize,
+}
+
+impl Phdr {
+	/// Returns the segment type, such as `PT_LOAD`.
+	#[inline]
+	pub fn p_type(&self) -> u32 {
+		self.p_type
+	}
+	/// Returns the segment flags, such as `PF_R`.
+	#[inline]
+	pub fn p_flags(&self) -> u32 {
+		self.p_flags
+	}
+	/// Returns the file offset of the segment.
+	#[inline]
+	pub fn p_offset(&self) -> usize {
+		self.p_offset
+	}
+	/// Returns the virtual address the segment is linked at.
+	#[inline]
+	pub fn p_vaddr(&self) -> usize {
+		self.p_vaddr
+	}
+	/// Returns the size of the segment in the file.
+	#[inline]
+	pub fn p_filesz(&self) -> usize {
+		self.p_filesz
+	}
+	/// Returns the size of the segment in memory.
+	#[inline]
+	pub fn p_memsz(&self) -> usize {
+		self.p_memsz
+	}
+	/// Returns the alignment of the segment.
+	#[inline]
+	pub fn p_align(&self) -> usize {
+		self.p_align
+	}
+}
+
+// hidden versions are only reachable through `dlvsym`.
+pub(crate) const VERSYM_HIDDEN: u16 = 0x8000;
+
+/// Hashes a symbol name for the `DT_GNU_HASH` table.
+pub(crate) fn gnu_hash(name: &[u8]) -> u32 {
+	name.iter()
+		.fold(5381u32, |h, &b| h.wrapping_mul(33).wrapping_add(b as u32))
+}
+
+/// Hashes a symbol name for the `DT_HASH` table.
+pub(crate) fn sysv_hash(name: &[u8]) -> u32 {
+	name.iter().fold(0u32, |h, &b| {
+		let h = (h << 4).wrapping_add(b as u32);
+		(h ^ ((h & 0xf000_0000) >> 24)) & 0x0fff_ffff
+	})
+}
+
+// class independent symbol.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Sym {
+	pub st_name: c::ElfW_Word,
+	pub st_info: u8,
+	pub st_shndx: c::ElfW_Half,
+	pub st_value: usize,
+	pub st_size: usize,
+}
+
+#[derive(Debug)]
+pub(crate) struct Elf {
+	pub raw: RawImage,
+	pub is_64: bool,
+	pub e_machine: c::ElfW_Half,
+	pub phdrs: Vec<Phdr>,
+	// lowest `PT_LOAD` address, which the image base maps to.
+	pub min_vaddr: usize,
+}
+
+impl Elf {
+	pub unsafe fn parse(raw: RawImage) -> io::Result<Self> {
+		unsafe {
+			let ident = raw.read::<[u8; 16]>(0)?;
+			if ident[..4] != [0x7f, b'E', b'L', b'F'] {
+				return Err(io::Error::other("unknown header detected"));
+			}
+			let (is_64, e_machine, e_phoff, e_phnum, e_phentsize) = match ident[4] {
+				c::ELFCLASS32 => {
+					let ehdr = raw.read::<c::Elf32_Ehdr>(0)?;
+					(
+						false,
+						ehdr.e_machine,
+						ehdr.e_phoff as usize,
+						ehdr.e_phnum as usize,
+						ehdr.e_phentsize as usize,
+					)
+				}
+				c::ELFCLASS64 => {
+					let ehdr = raw.read::<c::Elf64_Ehdr>(0)?;
+					(
+						true,
+						ehdr.e_machine,
+						ehdr.e_phoff as usize,
+						ehdr.e_phnum as usize,
+						ehdr.e_phentsize as usize,
+					)
+				}
+				_ => {
+					return Err(io::Error::new(
+						io::ErrorKind::InvalidData,
+						"invalid ELF file",
+					));
+				}
+			};
+			let mut phdrs = Vec::with_capacity(e_phnum);
+			for i in 0..e_phnum {
+				let offset = e_phoff + i * e_phentsize;
+				let phdr = if is_64 {
+					let ph = raw.read::<c::Elf64_Phdr>(offset)?;
+					Phdr {
+						p_type: ph.p_type,
+						p_flags: ph.p_flags,
+						p_offset: ph.p_offset as usize,
+						p_vaddr: ph.p_vaddr as usize,
+						p_filesz: ph.p_filesz as usize,
+						p_memsz: ph.p_memsz as usize,
+						p_align: ph.p_align as usize,
+					}
+				} else {
+					let ph = raw.read::<c::Elf32_Phdr>(offset)?;
+					Phdr {
+						p_type: ph.p_type,
+						p_flags: ph.p_flags,
+						p_offset: ph.p_offset as usize,
+						p_vaddr: ph.p_vaddr as usize,
+						p_filesz: ph.p_filesz as usize,
+						p_memsz: ph.p_memsz as usize,
+						p_align: ph.p_align as usize,
+					}
+				};
+				phdrs.push(phdr);
+			}
+			let min_vaddr = phdrs
+				.iter()
+				.filter(|ph| ph.p_type == c::PT_LOAD)
+				.map(|ph| ph.p_vaddr)
+				.min()
+				.ok_or_else(|| {
+					io::Error::new(io::ErrorKind::InvalidData, "no PT_LOAD segments found")
+				})?;
+			Ok(Self {
+				raw,
+				is_64,
+				e_machine,
+				phdrs,
+				min_vaddr,
+			})
+		}
+	}
+
+	#[inline]
+	pub fn bias(&self) -> usize {
+		(self.raw.ptr as usize).wrapping_sub(self.min_vaddr)
+	}
+
+	pub fn arch(&self) -> img::Arch {
+		match self.e_machine {
+			c::EM_386 => img::Arch::X86,
+			c::EM_X86_64 => img::Arch::X86_64,
+			c::EM_ARM => img::Arch::Arm,
+			c::EM_AARCH64 => img::Arch::AArch64,
+			c::EM_RISCV if self.is_64 => img::Arch::RiscV64,
+			other => img::Arch::Unknown(other as u32),
+		}
+	}
+
+	/// Converts a virtual address to an offset into the raw image.
+	pub fn offset_of(&self, vaddr: usize) -> io::Result<usize> {
+		if self.raw.loaded {
+			vaddr
+				.checked_sub(self.min_vaddr)
+				.ok_or_else(img::raw::out_of_bounds)
+		} else {
+			self.phdrs
+				.iter()
+				.filter(|ph| ph.p_type == c::PT_LOAD)
+				.find(|ph| {
+					// the headers come from the file, so their fields may be anything.
+					vaddr >= ph.p_vaddr
+						&& ph
+							.p_vaddr
+							.checked_add(ph.p_filesz)
+							.is_some_and(|end| vaddr < end)
+				})
+				.and_then(|ph| ph.p_offset.checked_add(vaddr - ph.p_vaddr))
+				.ok_or_else(img::raw::out_of_bounds)
+		}
+	}
+
+	// Some loaders (such as glibc) relocate the dynamic section in place, so pointers
+	// may be either absolute addresses or virtual addresses.
+	fn dyn_vaddr(&self, d_ptr: usize) -> usize {
+		let bias = self.bias();
+		if self.raw.loaded && bias != 0 && d_ptr >= bias {
+			d_ptr - bias
+		} else {
+			d_ptr
+		}
+	}
+
+	pub fn segments(&self) -> Vec<img::Segment> {
+		let bias = self.bias();
+		self.phdrs
+			.iter()
+			.filter(|ph| ph.p_type == c::PT_LOAD)
+			.map(|ph| {
+				let mut protection = img::Protection::NONE;
+				if ph.p_flags & c::PF_R != 0 {
+					protection |= img::Protection::READ;
+				}
+				if ph.p_flags & c::PF_W != 0 {
+					protection |= img::Protection::WRITE;
+				}
+				if ph.p_flags & c::PF_X != 0 {
+					protection |= img::Protection::EXECUTE;
+				}
+				img::Segment {
+					addr: bias.wrapping_add(ph.p_vaddr),
+					len: ph.p_memsz,
+					protection,
+				}
+			})
+			.collect()
+	}
+
+	// ELF has no TLS callbacks, since thread-local variables are initialized from the template.
+	pub fn tls(&self) -> Option<img::Tls> {
+		let ph = self.phdrs.iter().find(|ph| ph.p_type == c::PT_TLS)?;
+		let start = self.bias().wrapping_add(ph.p_vaddr);
+		Some(img::Tls {
+			template: start..start + ph.p_filesz,
+			size: ph.p_memsz,
+			align: ph.p_align.max(1),
+			callbacks: Vec::new(),
+		})
+	}
+
+	/// Returns the entries of the dynamic section as `(d_tag, d_un)` pairs.
+	pub unsafe fn dynamic(&self) -> io::Result<Vec<(i64, usize)>> {
+		let Some(dynamic) = self.phdrs.iter().find(|ph| ph.p_type == c::PT_DYNAMIC) else {
+			return Ok(Vec::new());
+		};
+		let offset = if self.raw.loaded {
+			self.offset_of(dynamic.p_vaddr)?
+		} else {
+			dynamic.p_offset
+		};
+		let mut entries = Vec::new();
+		let entsize = if self.is_64 {
+			mem::size_of::<c::Elf64_Dyn>()
+		} else {
+			mem::size_of::<c::Elf32_Dyn>()
+		};
+		for i in 0..dynamic.p_filesz / entsize {
+			let (d_tag, d_un) = unsafe {
+				if self.is_64 {
+					let d = self.raw.read::<c::Elf64_Dyn>(offset + i * entsize)?;
+					(d.d_tag, d.d_un as usize)
+				} else {
+					let d = self.raw.read::<c::Elf32_Dyn>(offset + i * entsize)?;
+					(d.d_tag as i64, d.d_un as usize)
+				}
+			};
+			if d_tag == c::DT_NULL {
+				break;
+			}
+			entries.push((d_tag, d_un));
+		}
+		Ok(entries)
+	}
+
+	/// Returns the raw offset of the dynamic entry pointer with the given tag.
+	pub unsafe fn dynamic_ptr(&self, tag: i64) -> io::Result<Option<usize>> {
+		unsafe { self.dynamic() }?
+			.into_iter()
+			.find(|(d_tag, _)| *d_tag == tag)
+			.map(|(_, d_ptr)| self.offset_of(self.dyn_vaddr(d_ptr)))
+			.transpose()
+	}
+
+	/// Counts the number of symbols in the dynamic symbol table using the hash tables.
+	unsafe fn dynsym_count(&self) -> io::Result<usize> {
+		unsafe {
+			let at = |offset: usize, add: usize| {
+				offset.checked_add(add).ok_or_else(img::raw::out_of_bounds)
+			};
+			if let Some(hash) = self.dynamic_ptr(c::DT_HASH)? {
+				// nbucket, nchain
+				return Ok(self.raw.read::<u32>(at(hash, 4)?)? as usize);
+			}
+			let Some(gnu_hash) = self.dynamic_ptr(c::DT_GNU_HASH)? else {
+				return Ok(0);
+			};
+			let nbuckets = self.raw.read::<u32>(gnu_hash)? as usize;
+			let symoffset = self.raw.read::<u32>(at(gnu_hash, 4)?)? as usize;
+			let bloom_size = self.raw.read::<u32>(at(gnu_hash, 8)?)? as usize;
+			let word_size = if self.is_64 { 8 } else { 4 };
+			let bloom_len = bloom_size
+				.checked_mul(word_size)
+				.ok_or_else(img::raw::out_of_bounds)?;
+			let buckets = at(at(gnu_hash, 16)?, bloom_len)?;
+			let chains = at(
+				buckets,
+				nbuckets
+					.checked_mul(4)
+					.ok_or_else(img::raw::out_of_bounds)?,
+			)?;
+			let mut last_sym = 0;
+			for i in 0..nbuckets {
+				last_sym = last_sym.max(self.raw.read::<u32>(at(buckets, i * 4)?)? as usize);
+			}
+			if last_sym < symoffset {
+				return Ok(symoffset);
+			}
+			// walk the last chain until the terminating bit is found.
+			let chain = |sym: usize| {
+				(sym - symoffset)
+					.checked_mul(4)
+					.and_then(|offset| chains.checked_add(offset))
+					.ok_or_else(img::raw::out_of_bounds)
+			};
+			while self.raw.read::<u32>(chain(last_sym)?)? & 1 == 0 {
+				last_sym += 1;
+			}
+			Ok(last_sym + 1)
+		}
+	}
+
+	pub unsafe fn sym(&self, symtab: usize, index: usize) -> io::Result<Sym> {
+		unsafe {
+			if self.is_64 {
+				let offset = index
+					.checked_mul(mem::size_of::<c::Elf64_Sym>())
+					.and_then(|offset| symtab.checked_add(offset))
+					.ok_or_else(img::raw::out_of_bounds)?;
+				let sym = self.raw.read::<c::Elf64_Sym>(offset)?;
+				Ok(Sym {
+					st_name: sym.st_name,
+					st_info: sym.st_info,
+					st_shndx: sym.st_shndx,
+					st_value: sym.st_value as usize,
+					st_size: sym.st_size as usize,
+				})
+			} else {
+				let offset = index
+					.checked_mul(mem::size_of::<c::Elf32_Sym>())
+					.and_then(|offset| symtab.checked_add(offset))
+					.ok_or_else(img::raw::out_of_bounds)?;
+				let sym = self.raw.read::<c::Elf32_Sym>(offset)?;
+				Ok(Sym {
+					st_name: sym.st_name,
+					st_info: sym.st_info,
+					st_shndx: sym.st_shndx,
+					st_value: sym.st_value as usize,
+					st_size: sym.st_size as usize,
+				})
+			}
+		}
+	}
+
+	// Converts a dynamic symbol to an export, skipping symbols that aren't exported.
+	unsafe fn to_export(&self, strtab: usize, sym: Sym) -> io::Result<Option<img::Export>> {
+		let bind = sym.st_info >> 4;
+		let kind = match sym.st_info & 0xf {
+			c::STT_FUNC | c::STT_GNU_IFUNC => img::ExportKind::Function,
+			c::STT_OBJECT | c::STT_COMMON => img::ExportKind::Data,
+			c::STT_TLS => img::ExportKind::Tls,
+			c::STT_NOTYPE => img::ExportKind::Unknown,
+			_ => return Ok(None),
+		};
+		if sym.st_shndx == c::SHN_UNDEF
+			|| sym.st_name == 0
+			|| !matches!(bind, c::STB_GLOBAL | c::STB_WEAK | c::STB_GNU_UNIQUE)
+		{
+			return Ok(None);
+		}
+		let name = unsafe { self.raw.read_cstr(strtab + sym.st_name as usize)? };
+		let rva = if kind == img::ExportKind::Tls {
+			sym.st_value
+		} else {
+			sym.st_value.wrapping_sub(self.min_vaddr)
+		};
+		Ok(Some(img::Export {
+			name: Some(name.to_string_lossy().into_owned()),
+			ordinal: None,
+			rva,
+			size: Some(sym.st_size),
+			kind,
+			forwarder: None,
+		}))
+	}
+
+	pub unsafe fn exports(&self) -> io::Result<Vec<img::Export>> {
+		unsafe {
+			let (Some(symtab), Some(strtab)) = (
+				self.dynamic_ptr(c::DT_SYMTAB)?,
+				self.dynamic_ptr(c::DT_STRTAB)?,
+			) else {
+				return Ok(Vec::new());
+			};
+			let count = self.dynsym_count()?;
+			let mut exports = Vec::new();
+			for i in 1..count {
+				if let Some(export) = self.to_export(strtab, self.sym(symtab, i)?)? {
+					exports.push(export);
+				}
+			}
+			Ok(exports)
+		}
+	}
+
+	/// Looks up an export by name using the hash tables, falling back to a linear scan if the
+	/// image has neither.
+	///
+	/// Only the default version of a symbol is considered.
+	pub unsafe fn lookup(&self, name: &str) -> io::Result<Option<img::Export>> {
+		unsafe {
+			let dynamic = self.dynamic()?;
+			let find = |tag| {
+				dynamic
+					.iter()
+					.find(|(d_tag, _)| *d_tag == tag)
+					.map(|(_, d_ptr)| self.offset_of(self.dyn_vaddr(*d_ptr)))
+					.transpose()
+			};
+			let (Some(symtab), Some(strtab)) = (find(c::DT_SYMTAB)?, find(c::DT_STRTAB)?) else {
+				return Ok(None);
+			};
+			let versym = find(c::DT_VERSYM)?;
+			let name = name.as_bytes();
+
+			let matches = |index: usize| -> io::Result<Option<img::Export>> {
+				let sym = self.sym(symtab, index)?;
+				if let Some(versym) = versym
+					&& self.raw.read::<u16>(versym + index * 2)? & VERSYM_HIDDEN != 0
+				{
+					return Ok(None);
+				}
+				if sym.st_name == 0
+					|| self
+						.raw
+						.read_cstr(strtab + sym.st_name as usize)?
+						.to_bytes() != name
+				{
+					return Ok(None);
+				}
+				self.to_export(strtab, sym)
+			};
+
+			if let Some(table) = find(c::DT_GNU_HASH)? {
+				let nbuckets = self.raw.read::<u32>(table)?;
+				let symoffset = self.raw.read::<u32>(table + 4)?;
+				let bloom_size = self.raw.read::<u32>(table + 8)? as usize;
+				if nbuckets == 0 {
+					return Ok(None);
+				}
+				let word_size = if self.is_64 { 8 } else { 4 };
+				let buckets = table + 16 + bloom_size * word_size;
+				let chains = buckets + nbuckets as usize * 4;
+				let hash = gnu_hash(name);
+				let mut index = self
+					.raw
+					.read::<u32>(buckets + (hash % nbuckets) as usize * 4)?;
+				if index < symoffset {
+					return Ok(None);
+				}
+				loop {
+					let chain = self
+						.raw
+						.read::<u32>(chains + (index - symoffset) as usize * 4)?;
+					if chain | 1 == hash | 1
+						&& let Some(export) = matches(index as usize)?
+					{
+						return Ok(Some(export));
+					}
+					if chain & 1 != 0 {
+						return Ok(None);
+					}
+					index += 1;
+				}
+			} else if let Some(table) = find(c::DT_HASH)? {
+				let nbucket = self.raw.read::<u32>(table)?;
+				if nbucket == 0 {
+					return Ok(None);
+				}
+				let buckets = table + 8;
+				let chains = buckets + nbucket as usize * 4;
+				let mut index = self
+					.raw
+					.read::<u32>(buckets + (sysv_hash(name) % nbucket) as usize * 4)?;
+				while index != 0 {
+					if let Some(export) = matches(index as usize)? {
+						return Ok(Some(export));
+					}
+					index = self.raw.read::<u32>(chains + index as usize * 4)?;
+				}
+				Ok(None)
+			} else {
+				Ok(self
+					.exports()?
+					.into_iter()
+					.find(|export| export.name().is_some_and(|n| n.as_bytes() == name)))
+			}
+		}
+	}
+
+	pub unsafe fn version(&self) -> io::Result<img::VersionInfo> {
+		unsafe {
+			let Some(strtab) = self.dynamic_ptr(c::DT_STRTAB)? else {
+				return Ok(img::VersionInfo::default());
+			};
+			let dynamic = self.dynamic()?;
+			let find = |tag| {
+				dynamic
+					.iter()
+					.find(|(d_tag, _)| *d_tag == tag)
+					.map(|(_, d_un)| *d_un)
+			};
+			let name = match find(c::DT_SONAME) {
+				Some(offset) => Some(
+					self.raw
+						.read_cstr(strtab + offset)?
+						.to_string_lossy()
+						.into_owned(),
+				),
+				None => None,
+			};
+			let current = name
+				.as_deref()
+				.and_then(|name| name.split_once(".so."))
+				.and_then(|(_, version)| img::Version::parse_prefix(version));
+			let mut definitions = Vec::new();
+			if let Some(mut verdef) = self.dynamic_ptr(c::DT_VERDEF)? {
+				for _ in 0..find(c::DT_VERDEFNUM).unwrap_or(0) {
+					let vd = self.raw.read::<c::ElfW_Verdef>(verdef)?;
+					// the base definition is the name of the image itself.
+					if vd.vd_flags & c::VER_FLG_BASE == 0 && vd.vd_cnt != 0 {
+						let aux = self
+							.raw
+							.read::<c::ElfW_Verdaux>(verdef + vd.vd_aux as usize)?;
+						let name = self.raw.read_cstr(strtab + aux.vda_name as usize)?;
+						definitions.push(name.to_string_lossy().into_owned());
+					}
+					if vd.vd_next == 0 {
+						break;
+					}
+					verdef += vd.vd_next as usize;
+				}
+			}
+			Ok(img::VersionInfo {
+				current,
+				compatibility: None,
+				name,
+				definitions,
+			})
+		}
+	}
+}
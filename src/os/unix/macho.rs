@@ -0,0 +1,305 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use super::c;
+use crate::img::{
+	self,
+	RawImage,
+};
+use std::{
+	ffi,
+	io,
+	mem,
+};
+
+/// A class independent Mach-O segment load command.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SegmentCommand {
+	pub(crate) vmaddr: usize,
+	pub(crate) vmsize: usize,
+	pub(crate) fileoff: usize,
+	pub(crate) filesize: usize,
+	pub(crate) initprot: c::vm_prot_t,
+}
+
+impl SegmentCommand {
+	/// Returns the virtual address the segment is linked at.
+	#[inline]
+	pub fn vmaddr(&self) -> usize {
+		self.vmaddr
+	}
+	/// Returns the size of the segment in memory.
+	#[inline]
+	pub fn vmsize(&self) -> usize {
+		self.vmsize
+	}
+	/// Returns the file offset of the segment.
+	#[inline]
+	pub fn fileoff(&self) -> usize {
+		self.fileoff
+	}
+	/// Returns the size of the segment in the file.
+	#[inline]
+	pub fn filesize(&self) -> usize {
+		self.filesize
+	}
+	/// Returns the initial memory protection of the segment, such as `VM_PROT_READ`.
+	#[inline]
+	pub fn initprot(&self) -> i32 {
+		self.initprot
+	}
+}
+
+pub(crate) struct MachO {
+	pub raw: RawImage,
+	pub is_64: bool,
+	pub cputype: ffi::c_int,
+	pub segments: Vec<SegmentCommand>,
+	pub symtab: Option<c::symtab_command>,
+	// (offset, command) of `LC_ID_DYLIB`.
+	pub id_dylib: Option<(usize, c::dylib_command)>,
+	// virtual address of the segment that maps the header.
+	pub text_vmaddr: usize,
+}
+
+#[cfg(target_arch = "x86_64")]
+const HOST_CPU_TYPE: ffi::c_int = c::CPU_TYPE_X86_64;
+#[cfg(target_arch = "aarch64")]
+const HOST_CPU_TYPE: ffi::c_int = c::CPU_TYPE_ARM64;
+#[cfg(target_arch = "x86")]
+const HOST_CPU_TYPE: ffi::c_int = c::CPU_TYPE_X86;
+#[cfg(target_arch = "arm")]
+const HOST_CPU_TYPE: ffi::c_int = c::CPU_TYPE_ARM;
+#[cfg(not(any(
+	target_arch = "x86_64",
+	target_arch = "aarch64",
+	target_arch = "x86",
+	target_arch = "arm"
+)))]
+const HOST_CPU_TYPE: ffi::c_int = -1;
+
+impl MachO {
+	pub unsafe fn parse(mut raw: RawImage) -> io::Result<Self> {
+		unsafe {
+			// universal binaries only exist on disk, so select the slice for this host.
+			if !raw.loaded && u32::from_be(raw.read::<u32>(0)?) == c::FAT_MAGIC {
+				let fat = raw.read::<c::fat_header>(0)?;
+				let arch = (0..u32::from_be(fat.nfat_arch) as usize)
+					.map(|i| {
+						raw.read::<c::fat_arch>(
+							mem::size_of::<c::fat_header>() + i * mem::size_of::<c::fat_arch>(),
+						)
+					})
+					.collect::<io::Result<Vec<_>>>()?
+					.into_iter()
+					.find(|arch| i32::from_be(arch.cputype) == HOST_CPU_TYPE)
+					.ok_or_else(|| {
+						io::Error::new(
+							io::ErrorKind::NotFound,
+							"universal binary does not contain the host architecture",
+						)
+					})?;
+				raw = raw.subrange(
+					u32::from_be(arch.offset) as usize,
+					u32::from_be(arch.size) as usize,
+				)?;
+			}
+			let (is_64, cputype, ncmds, cmds_offset) = match raw.read::<u32>(0)? {
+				0xfeedface => {
+					let hdr = raw.read::<c::mach_header>(0)?;
+					(
+						false,
+						hdr.cputype,
+						hdr.ncmds,
+						mem::size_of::<c::mach_header>(),
+					)
+				}
+				0xfeedfacf => {
+					let hdr = raw.read::<c::mach_header_64>(0)?;
+					(
+						true,
+						hdr.cputype,
+						hdr.ncmds,
+						mem::size_of::<c::mach_header_64>(),
+					)
+				}
+				_ => return Err(io::Error::other("unknown header detected")),
+			};
+			let mut segments = Vec::new();
+			let mut symtab = None;
+			let mut id_dylib = None;
+			let mut offset = cmds_offset;
+			for _ in 0..ncmds {
+				let cmd = raw.read::<c::load_command>(offset)?;
+				match cmd.cmd {
+					c::LC_SEGMENT => {
+						let seg = raw.read::<c::segment_command>(offset)?;
+						segments.push(SegmentCommand {
+							vmaddr: seg.vmaddr as usize,
+							vmsize: seg.vmsize as usize,
+							fileoff: seg.fileoff as usize,
+							filesize: seg.filesize as usize,
+							initprot: seg.initprot,
+						});
+					}
+					c::LC_SEGMENT_64 => {
+						let seg = raw.read::<c::segment_command_64>(offset)?;
+						segments.push(SegmentCommand {
+							vmaddr: seg.vmaddr as usize,
+							vmsize: seg.vmsize as usize,
+							fileoff: seg.fileoff as usize,
+							filesize: seg.filesize as usize,
+							initprot: seg.initprot,
+						});
+					}
+					c::LC_SYMTAB => symtab = Some(raw.read::<c::symtab_command>(offset)?),
+					c::LC_ID_DYLIB => {
+						id_dylib = Some((offset, raw.read::<c::dylib_command>(offset)?));
+					}
+					_ => (),
+				}
+				if cmd.cmdsize == 0 {
+					return Err(io::Error::new(
+						io::ErrorKind::InvalidData,
+						"invalid Mach-O load command",
+					));
+				}
+				offset += cmd.cmdsize as usize;
+			}
+			// The segment mapping the start of the file contains the header, which gives the slide.
+			let text_vmaddr = segments
+				.iter()
+				.find(|seg| seg.fileoff == 0 && seg.filesize != 0)
+				.map(|seg| seg.vmaddr)
+				.ok_or_else(|| {
+					io::Error::new(
+						io::ErrorKind::InvalidData,
+						"no Mach-O segment maps the header",
+					)
+				})?;
+			Ok(Self {
+				raw,
+				is_64,
+				cputype,
+				segments,
+				symtab,
+				id_dylib,
+				text_vmaddr,
+			})
+		}
+	}
+
+	pub fn arch(&self) -> img::Arch {
+		match self.cputype {
+			c::CPU_TYPE_X86 => img::Arch::X86,
+			c::CPU_TYPE_X86_64 => img::Arch::X86_64,
+			c::CPU_TYPE_ARM => img::Arch::Arm,
+			c::CPU_TYPE_ARM64 => img::Arch::AArch64,
+			other => img::Arch::Unknown(other as u32),
+		}
+	}
+
+	/// Converts a file offset to an offset into the raw image.
+	pub fn offset_of(&self, fileoff: usize) -> io::Result<usize> {
+		if !self.raw.loaded {
+			return Ok(fileoff);
+		}
+		self.segments
+			.iter()
+			.find(|seg| {
+				// the load commands come from the file, so their fields may be anything.
+				fileoff >= seg.fileoff
+					&& seg
+						.fileoff
+						.checked_add(seg.filesize)
+						.is_some_and(|end| fileoff < end)
+			})
+			.and_then(|seg| {
+				seg.vmaddr
+					.checked_add(fileoff - seg.fileoff)?
+					.checked_sub(self.text_vmaddr)
+			})
+			.ok_or_else(img::raw::out_of_bounds)
+	}
+
+	pub fn segments(&self) -> Vec<img::Segment> {
+		let slide = (self.raw.ptr as usize).wrapping_sub(self.text_vmaddr);
+		self.segments
+			.iter()
+			.map(|seg| {
+				let mut protection = img::Protection::NONE;
+				if seg.initprot & c::VM_PROT_READ != 0 {
+					protection |= img::Protection::READ;
+				}
+				if seg.initprot & c::VM_PROT_WRITE != 0 {
+					protection |= img::Protection::WRITE;
+				}
+				if seg.initprot & c::VM_PROT_EXECUTE != 0 {
+					protection |= img::Protection::EXECUTE;
+				}
+				img::Segment {
+					addr: slide.wrapping_add(seg.vmaddr),
+					len: seg.vmsize,
+					protection,
+				}
+			})
+			.collect()
+	}
+
+	pub unsafe fn exports(&self) -> io::Result<Vec<img::Export>> {
+		let Some(symtab) = &self.symtab else {
+			return Ok(Vec::new());
+		};
+		let symoff = self.offset_of(symtab.symoff as usize)?;
+		let stroff = self.offset_of(symtab.stroff as usize)?;
+		let mut exports = Vec::new();
+		for i in 0..symtab.nsyms as usize {
+			let (n_strx, n_type, n_value) = unsafe {
+				if self.is_64 {
+					let sym = self
+						.raw
+						.read::<c::nlist_64>(symoff + i * mem::size_of::<c::nlist_64>())?;
+					(sym.n_strx, sym.n_type, sym.n_value as usize)
+				} else {
+					let sym = self
+						.raw
+						.read::<c::nlist>(symoff + i * mem::size_of::<c::nlist>())?;
+					(sym.n_strx, sym.n_type, sym.n_value as usize)
+				}
+			};
+			if n_type & c::N_STAB != 0 || n_type & c::N_EXT == 0 || n_type & c::N_TYPE != c::N_SECT
+			{
+				continue;
+			}
+			let name = unsafe { self.raw.read_cstr(stroff + n_strx as usize)? }.to_bytes();
+			// C symbols are prefixed with an underscore.
+			let name = name.strip_prefix(b"_").unwrap_or(name);
+			exports.push(img::Export {
+				name: Some(String::from_utf8_lossy(name).into_owned()),
+				ordinal: None,
+				rva: n_value.wrapping_sub(self.text_vmaddr),
+				size: None,
+				kind: img::ExportKind::Unknown,
+				forwarder: None,
+			});
+		}
+		Ok(exports)
+	}
+
+	pub unsafe fn version(&self) -> io::Result<img::VersionInfo> {
+		// versions are encoded as `xxxx.yy.zz` nibbles.
+		fn decode(version: u32) -> img::Version {
+			img::Version::new(version >> 16, (version >> 8) & 0xff, version & 0xff)
+		}
+		let Some((offset, dylib)) = &self.id_dylib else {
+			return Ok(img::VersionInfo::default());
+		};
+		let name = unsafe { self.raw.read_cstr(offset + dylib.name as usize)? };
+		Ok(img::VersionInfo {
+			current: Some(decode(dylib.current_version)),
+			compatibility: Some(decode(dylib.compatibility_version)),
+			name: Some(name.to_string_lossy().into_owned()),
+			definitions: Vec::new(),
+		})
+	}
+}
@@ -9,32 +9,144 @@ use std::{
 	mem,
 	path,
 	ptr,
+	slice,
+	sync,
 };
 
 use crate::img;
+use crate::scrub::Scrubbed;
+#[cfg(feature = "images")]
 use crate::weak;
 use crate::{
 	Library,
+	LoadError,
+	LoadErrorKind,
+	SandboxDenied,
 	Symbol,
 };
+#[cfg(feature = "images")]
+use std::ops;
 
 mod c;
+#[cfg(feature = "images")]
+pub(crate) mod pe;
 
 fn to_wide(path: &ffi::OsStr) -> Vec<u16> {
 	path.encode_wide().chain(std::iter::once(0u16)).collect()
 }
 
+// Classifies an error of the loader by its code. `ERROR_MOD_NOT_FOUND` is also reported when a
+// dependency is missing, which is assumed when the library itself exists, although the loader
+// doesn't name the dependency.
+fn load_error(path: Option<&path::Path>, err: io::Error) -> io::Error {
+	let Some(code) = err.raw_os_error() else {
+		return err;
+	};
+	let kind = match code {
+		c::ERROR_MOD_NOT_FOUND
+			if path.is_some_and(|path| {
+				path.parent()
+					.is_some_and(|parent| !parent.as_os_str().is_empty())
+					&& path.is_file()
+			}) =>
+		{
+			LoadErrorKind::MissingDependency
+		}
+		c::ERROR_MOD_NOT_FOUND | c::ERROR_FILE_NOT_FOUND | c::ERROR_PATH_NOT_FOUND => {
+			LoadErrorKind::NotFound
+		}
+		// also reported for files that aren't images at all, which don't start with `MZ`.
+		c::ERROR_BAD_EXE_FORMAT
+			if path.is_some_and(|path| {
+				let mut magic = [0u8; 2];
+				std::fs::File::open(path)
+					.and_then(|mut file| io::Read::read_exact(&mut file, &mut magic))
+					.is_ok_and(|()| &magic != b"MZ")
+			}) =>
+		{
+			LoadErrorKind::InvalidFormat
+		}
+		c::ERROR_BAD_EXE_FORMAT => LoadErrorKind::WrongArchitecture,
+		c::ERROR_BAD_FORMAT => LoadErrorKind::InvalidFormat,
+		c::ERROR_PROC_NOT_FOUND | c::ERROR_INVALID_ORDINAL => LoadErrorKind::UndefinedSymbol,
+		_ => LoadErrorKind::Other,
+	};
+	LoadError::error(err.kind(), kind, err.to_string(), None, Some(code))
+}
+
+// Flags of the `OpenFlags` presets.
+pub(crate) const DEFAULT_FLAGS: u32 = 0;
+pub(crate) const PLUGIN_FLAGS: u32 = c::LOAD_LIBRARY_SEARCH_DEFAULT_DIRS;
+pub(crate) const SYSTEM_FLAGS: u32 = c::LOAD_LIBRARY_SEARCH_SYSTEM32;
+pub(crate) const INSPECT_FLAGS: u32 =
+	c::LOAD_LIBRARY_AS_DATAFILE | c::LOAD_LIBRARY_AS_IMAGE_RESOURCE;
+// Windows always binds imports when a library is loaded, so `OpenFlags::lazy` changes nothing.
+pub(crate) const EAGER_FLAGS: u32 = 0;
+pub(crate) const LAZY_FLAGS: u32 = 0;
+
 #[derive(Debug)]
 #[repr(transparent)]
 pub(crate) struct InnerLibrary(pub std::ptr::NonNull<ffi::c_void>);
 
 impl InnerLibrary {
 	pub unsafe fn open(path: &ffi::OsStr) -> io::Result<Self> {
-		let wide_str: Vec<u16> = to_wide(path);
-		let handle = unsafe { c::LoadLibraryExW(wide_str.as_ptr(), ptr::null_mut(), 0) };
-		ptr::NonNull::new(handle)
-			.ok_or_else(io::Error::last_os_error)
-			.map(Self)
+		unsafe { Self::open_with(path, DEFAULT_FLAGS) }
+	}
+
+	pub unsafe fn open_with(path: &ffi::OsStr, flags: u32) -> io::Result<Self> {
+		let wide_str = Scrubbed(to_wide(path));
+		let handle = unsafe { c::LoadLibraryExW(wide_str.as_ptr(), ptr::null_mut(), flags) };
+		ptr::NonNull::new(handle).map(Self).ok_or_else(|| {
+			let err = io::Error::last_os_error();
+			let path = path::Path::new(path);
+			if err.kind() != io::ErrorKind::PermissionDenied {
+				return load_error(Some(path), err);
+			}
+			// the file that was denied is unknown when the library is searched for by name.
+			let denied_path = path
+				.parent()
+				.is_some_and(|parent| !parent.as_os_str().is_empty())
+				.then(|| path.to_path_buf());
+			SandboxDenied::error(denied_path, err.to_string())
+		})
+	}
+
+	// The directory is named by the `\\?\` path of its handle, which the loader takes verbatim, so
+	// the relative path is joined one component at a time instead of being normalized.
+	pub unsafe fn open_at(dir: BorrowedHandle<'_>, path: &ffi::OsStr) -> io::Result<Self> {
+		let mut dir_path = vec![0u16; 260];
+		loop {
+			let len = unsafe {
+				c::GetFinalPathNameByHandleW(
+					dir.as_raw_handle(),
+					dir_path.as_mut_ptr(),
+					dir_path.len() as c::DWORD,
+					c::FILE_NAME_NORMALIZED,
+				)
+			} as usize;
+			match len {
+				0 => return Err(io::Error::last_os_error()),
+				// the length excludes the null terminator on success, and includes it otherwise.
+				len if len < dir_path.len() => {
+					dir_path.truncate(len);
+					break;
+				}
+				len => dir_path.resize(len, 0),
+			}
+		}
+		let mut full_path = Scrubbed(PathBuf::from(ffi::OsString::from_wide(&dir_path)));
+		for component in path::Path::new(path).components() {
+			match component {
+				path::Component::Normal(name) => full_path.0.push(name),
+				path::Component::ParentDir => {
+					full_path.0.pop();
+				}
+				// absolute paths are rejected by `Library::open_at`.
+				path::Component::CurDir | path::Component::Prefix(_) | path::Component::RootDir => {
+				}
+			}
+		}
+		unsafe { Self::open_with(full_path.as_os_str(), DEFAULT_FLAGS) }
 	}
 
 	pub unsafe fn this() -> io::Result<Self> {
@@ -52,14 +164,25 @@ impl InnerLibrary {
 		unsafe { c::GetProcAddress(self.0.as_ptr(), name.as_ptr()).cast() }
 	}
 
-	pub unsafe fn symbol<'a>(&self, name: &str) -> io::Result<*const Symbol> {
+	pub unsafe fn symbol_ordinal(&self, ordinal: u16) -> io::Result<*const Symbol> {
+		// ordinals are passed in place of the name, like `MAKEINTRESOURCEA`.
+		let addr: *const Symbol =
+			unsafe { c::GetProcAddress(self.0.as_ptr(), ordinal as usize as c::PCSTR).cast() };
+		if addr.is_null() {
+			Err(load_error(None, io::Error::last_os_error()))
+		} else {
+			Ok(addr)
+		}
+	}
+
+	pub unsafe fn symbol(&self, name: &str) -> io::Result<*const Symbol> {
 		let c_str = match ffi::CString::new(name) {
-			Ok(s) => s,
+			Ok(s) => Scrubbed(s),
 			Err(err) => return Err(io::Error::new(io::ErrorKind::InvalidData, err)),
 		};
 		let addr = unsafe { self.raw_symbol(&c_str) };
 		if addr.is_null() {
-			Err(io::Error::last_os_error())
+			Err(load_error(None, io::Error::last_os_error()))
 		} else {
 			Ok(addr)
 		}
@@ -122,6 +245,50 @@ impl InnerLibrary {
 		}
 	}
 
+	// Initializers run while the loader lock is held, so acquiring it waits for them to finish.
+	pub(crate) unsafe fn wait_until_initialized(&self) -> io::Result<()> {
+		let (lock, unlock) = loader_lock()?;
+		unsafe {
+			let mut cookie = ptr::null_mut();
+			let status = lock(0, ptr::null_mut(), &mut cookie);
+			if status < 0 {
+				return Err(io::Error::other(format!(
+					"failed to acquire loader lock (NTSTATUS {status:#x})"
+				)));
+			}
+			unlock(0, cookie);
+		}
+		Ok(())
+	}
+
+	// The loader lock is reentrant, so it's held while the reference is taken.
+	pub(crate) unsafe fn try_from_ptr(addr: *mut img::Image) -> io::Result<Option<Self>> {
+		let (lock, unlock) = loader_lock()?;
+		unsafe {
+			let mut disposition = 0;
+			let mut cookie = ptr::null_mut();
+			let status = lock(
+				c::LDR_LOCK_LOADER_LOCK_FLAG_TRY_ONLY,
+				&mut disposition,
+				&mut cookie,
+			);
+			if status < 0 {
+				return Err(io::Error::other(format!(
+					"failed to acquire loader lock (NTSTATUS {status:#x})"
+				)));
+			}
+			if disposition != c::LDR_LOCK_LOADER_LOCK_DISPOSITION_LOCK_ACQUIRED {
+				return Err(io::Error::new(
+					io::ErrorKind::WouldBlock,
+					"loader lock is held by another thread",
+				));
+			}
+			let result = Self::from_ptr(addr);
+			unlock(0, cookie);
+			Ok(result)
+		}
+	}
+
 	#[inline]
 	pub(crate) unsafe fn to_ptr(&self) -> *const img::Image {
 		self.0.as_ptr().cast()
@@ -148,6 +315,67 @@ impl AsRawHandle for Library {
 	}
 }
 
+// Returns `LdrLockLoaderLock` and `LdrUnlockLoaderLock` from ntdll.
+fn loader_lock() -> io::Result<(c::PfnLdrLockLoaderLock, c::PfnLdrUnlockLoaderLock)> {
+	static NTDLL: sync::OnceLock<
+		Option<(Library, c::PfnLdrLockLoaderLock, c::PfnLdrUnlockLoaderLock)>,
+	> = sync::OnceLock::new();
+	match NTDLL.get_or_init(|| unsafe {
+		let lib = Library::open("ntdll.dll").ok()?;
+		let lock: c::PfnLdrLockLoaderLock = mem::transmute(lib.symbol("LdrLockLoaderLock").ok()?);
+		let unlock: c::PfnLdrUnlockLoaderLock =
+			mem::transmute(lib.symbol("LdrUnlockLoaderLock").ok()?);
+		Some((lib, lock, unlock))
+	}) {
+		Some((_, lock, unlock)) => Ok((*lock, *unlock)),
+		None => Err(io::Error::new(
+			io::ErrorKind::Unsupported,
+			"loader lock is unavailable",
+		)),
+	}
+}
+
+// Registers a DLL notification that keeps the global module map up to date.
+#[cfg(feature = "module-map")]
+pub(crate) unsafe fn watch_images() -> bool {
+	unsafe extern "system" fn notify(
+		reason: ffi::c_ulong,
+		data: *const c::LDR_DLL_NOTIFICATION_DATA,
+		_: *mut ffi::c_void,
+	) {
+		let Some(data) = (unsafe { data.as_ref() }) else {
+			return;
+		};
+		let map = img::ModuleMap::unbuilt();
+		match reason {
+			c::LDR_DLL_NOTIFICATION_REASON_LOADED => {
+				let path = unsafe { data.fulldllname.as_ref() }.map(|name| {
+					let wide =
+						unsafe { slice::from_raw_parts(name.buffer, name.length as usize / 2) };
+					PathBuf::from(ffi::OsString::from_wide(wide))
+				});
+				let module =
+					img::Module::new(data.dllbase.cast(), Some(data.sizeofimage as usize), path);
+				map.loaded(module);
+			}
+			c::LDR_DLL_NOTIFICATION_REASON_UNLOADED => map.unloaded(data.dllbase.cast()),
+			_ => {}
+		}
+	}
+	unsafe {
+		let Ok(lib) = Library::open("ntdll.dll") else {
+			return false;
+		};
+		let Ok(register) = lib.symbol("LdrRegisterDllNotification") else {
+			return false;
+		};
+		let register: c::PfnLdrRegisterDllNotification = mem::transmute(register);
+		let mut cookie = ptr::null_mut();
+		// `lib` is never closed, since the notification is never unregistered.
+		register(0, notify, ptr::null_mut(), &mut cookie) >= 0
+	}
+}
+
 pub(crate) unsafe fn base_addr(symbol: *const Symbol) -> *mut img::Image {
 	let mut handle = ptr::null_mut();
 	unsafe {
@@ -161,6 +389,86 @@ pub(crate) unsafe fn base_addr(symbol: *const Symbol) -> *mut img::Image {
 	handle.cast()
 }
 
+#[cfg(feature = "images")]
+struct DbgHelp {
+	_lib: Library,
+	sym_from_addr: c::PfnSymFromAddrW,
+	sym_refresh_module_list: c::PfnSymRefreshModuleList,
+}
+
+// dbghelp is single threaded, so all calls must be serialized.
+#[cfg(feature = "images")]
+static DBGHELP: sync::OnceLock<Option<sync::Mutex<DbgHelp>>> = sync::OnceLock::new();
+
+#[cfg(feature = "images")]
+fn dbghelp() -> Option<&'static sync::Mutex<DbgHelp>> {
+	DBGHELP
+		.get_or_init(|| unsafe {
+			let lib = Library::open("dbghelp.dll").ok()?;
+			let sym_initialize: c::PfnSymInitializeW =
+				mem::transmute(lib.symbol("SymInitializeW").ok()?);
+			let sym_from_addr: c::PfnSymFromAddrW =
+				mem::transmute(lib.symbol("SymFromAddrW").ok()?);
+			let sym_refresh_module_list: c::PfnSymRefreshModuleList =
+				mem::transmute(lib.symbol("SymRefreshModuleList").ok()?);
+			if sym_initialize(c::GetCurrentProcess(), ptr::null(), 1) == 0 {
+				return None;
+			}
+			Some(sync::Mutex::new(DbgHelp {
+				_lib: lib,
+				sym_from_addr,
+				sym_refresh_module_list,
+			}))
+		})
+		.as_ref()
+}
+
+// dbghelp only knows of the modules that were loaded when it was initialized, or last refreshed.
+// It isn't initialized here if it wasn't already.
+#[cfg(feature = "images")]
+pub(crate) fn refresh_symbols() {
+	if let Some(Some(dbghelp)) = DBGHELP.get() {
+		let dbghelp = dbghelp.lock().unwrap_or_else(sync::PoisonError::into_inner);
+		unsafe { (dbghelp.sym_refresh_module_list)(c::GetCurrentProcess()) };
+	}
+}
+
+/// Returns the name and address of the symbol containing `addr` using dbghelp, and whether the
+/// symbol is only the nearest export preceding it.
+#[cfg(feature = "images")]
+pub(crate) unsafe fn nearest_symbol(addr: usize) -> Option<(String, usize, bool)> {
+	const MAX_NAME_LEN: usize = 256;
+	#[repr(C)]
+	struct Buffer {
+		info: c::SYMBOL_INFOW,
+		name: [u16; MAX_NAME_LEN],
+	}
+	let dbghelp = dbghelp()?
+		.lock()
+		.unwrap_or_else(sync::PoisonError::into_inner);
+	unsafe {
+		let mut buf = mem::MaybeUninit::<Buffer>::zeroed();
+		let info = buf.as_mut_ptr().cast::<c::SYMBOL_INFOW>();
+		(*info).sizeofstruct = mem::size_of::<c::SYMBOL_INFOW>() as _;
+		(*info).maxnamelen = MAX_NAME_LEN as _;
+		let mut displacement = 0;
+		if (dbghelp.sym_from_addr)(c::GetCurrentProcess(), addr as u64, &mut displacement, info)
+			== 0
+		{
+			return None;
+		}
+		let len = ((*info).namelen as usize).min(MAX_NAME_LEN);
+		let name = slice::from_raw_parts(ptr::addr_of!((*info).name).cast::<u16>(), len);
+		// without debug information, dbghelp falls back to the export table itself.
+		Some((
+			String::from_utf16_lossy(name),
+			addr.wrapping_sub(displacement as usize),
+			(*info).flags & c::SYMFLAG_EXPORT != 0,
+		))
+	}
+}
+
+#[cfg(feature = "images")]
 pub(crate) unsafe fn load_objects() -> io::Result<Vec<weak::Weak>> {
 	const INITIAL_SIZE: usize = 1000;
 	let mut module_handles = vec![ptr::null_mut::<img::Image>(); INITIAL_SIZE];
@@ -211,6 +519,7 @@ pub(crate) unsafe fn load_objects() -> io::Result<Vec<weak::Weak>> {
 	}
 }
 
+#[cfg(feature = "images")]
 pub(crate) unsafe fn hdr_size(hdr: *const img::Image) -> io::Result<usize> {
 	unsafe {
 		// checks if it's a PE header (fast)
@@ -236,12 +545,322 @@ pub(crate) unsafe fn hdr_size(hdr: *const img::Image) -> io::Result<usize> {
 
 pub(crate) unsafe fn hdr_path(hdr: *const img::Image) -> io::Result<PathBuf> {
 	let Some(nonnull_hdr) = ptr::NonNull::new(hdr as *mut _) else {
-		return Err(io::Error::new(io::ErrorKind::Other, "invalid header"));
+		return Err(io::Error::other("invalid header"));
 	};
 	let lib = mem::ManuallyDrop::new(InnerLibrary(nonnull_hdr));
 	unsafe { lib.path() }
 }
 
+#[cfg(feature = "images")]
+pub(crate) use pe::Pe as Object;
+
+#[cfg(feature = "images")]
+pub(crate) fn page_size() -> usize {
+	c::page_size()
+}
+
+#[cfg(feature = "images")]
+pub(crate) unsafe fn advise(ranges: &[ops::Range<usize>], advice: img::Advice) -> io::Result<()> {
+	match advice {
+		img::Advice::WillNeed => {
+			let entries: Vec<_> = ranges
+				.iter()
+				.map(|range| c::WIN32_MEMORY_RANGE_ENTRY {
+					virtualaddress: range.start as *mut _,
+					numberofbytes: range.len(),
+				})
+				.collect();
+			let result = unsafe {
+				c::PrefetchVirtualMemory(c::GetCurrentProcess(), entries.len(), entries.as_ptr(), 0)
+			};
+			match result {
+				0 => Err(io::Error::last_os_error()),
+				_ => Ok(()),
+			}
+		}
+		img::Advice::DontNeed => {
+			for range in ranges {
+				// unlocking pages that aren't locked removes them from the working set.
+				if unsafe { c::VirtualUnlock(range.start as *const _, range.len()) } == 0 {
+					let err = io::Error::last_os_error();
+					if err.raw_os_error() != Some(c::ERROR_NOT_LOCKED) {
+						return Err(err);
+					}
+				}
+			}
+			Ok(())
+		}
+	}
+}
+
+#[cfg(feature = "images")]
+pub(crate) fn page_stats(ranges: &[ops::Range<usize>]) -> io::Result<img::PageStats> {
+	const VALID: usize = 1 << 0;
+	const SHARED: usize = 1 << 15;
+	const LARGE_PAGE: usize = 1 << 23;
+	let page_size = page_size();
+	let mut entries: Vec<_> = ranges
+		.iter()
+		.flat_map(|range| range.clone().step_by(page_size))
+		.map(|addr| c::PSAPI_WORKING_SET_EX_INFORMATION {
+			virtualaddress: addr as *mut _,
+			virtualattributes: 0,
+		})
+		.collect();
+	let cb = mem::size_of_val(entries.as_slice());
+	let result = unsafe {
+		c::QueryWorkingSetEx(
+			c::GetCurrentProcess(),
+			entries.as_mut_ptr().cast(),
+			cb as c::DWORD,
+		)
+	};
+	if result == 0 {
+		return Err(io::Error::last_os_error());
+	}
+	let mut stats = img::PageStats {
+		mappings: ranges.len(),
+		..Default::default()
+	};
+	for entry in entries {
+		let attributes = entry.virtualattributes;
+		if attributes & VALID == 0 {
+			continue;
+		}
+		stats.resident += 1;
+		if attributes & SHARED != 0 {
+			stats.shared += 1;
+		} else {
+			stats.private += 1;
+		}
+		if attributes & LARGE_PAGE != 0 {
+			stats.large += 1;
+		}
+	}
+	Ok(stats)
+}
+
+#[cfg(feature = "images")]
+pub(crate) unsafe fn map_file(file: &std::fs::File) -> io::Result<(ptr::NonNull<u8>, usize)> {
+	let len = usize::try_from(file.metadata()?.len())
+		.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+	if len == 0 {
+		return Err(io::Error::new(io::ErrorKind::InvalidData, "file is empty"));
+	}
+	unsafe {
+		let mapping = c::CreateFileMappingW(
+			file.as_raw_handle(),
+			ptr::null_mut(),
+			c::PAGE_READONLY,
+			0,
+			0,
+			ptr::null(),
+		);
+		if mapping.is_null() {
+			return Err(io::Error::last_os_error());
+		}
+		let view = c::MapViewOfFile(mapping, c::FILE_MAP_READ, 0, 0, len);
+		// the view keeps the mapping alive.
+		let _ = c::CloseHandle(mapping);
+		ptr::NonNull::new(view.cast())
+			.map(|view| (view, len))
+			.ok_or_else(io::Error::last_os_error)
+	}
+}
+
+#[cfg(feature = "images")]
+pub(crate) unsafe fn unmap_file(addr: ptr::NonNull<u8>, _: usize) {
+	let _ = unsafe { c::UnmapViewOfFile(addr.as_ptr().cast()) };
+}
+
+#[cfg(feature = "images")]
+pub(crate) unsafe fn query_protection(addr: *const u8) -> io::Result<img::Protection> {
+	let mut info = mem::MaybeUninit::<c::MEMORY_BASIC_INFORMATION>::zeroed();
+	let len = unsafe {
+		c::VirtualQuery(
+			addr.cast(),
+			info.as_mut_ptr(),
+			mem::size_of::<c::MEMORY_BASIC_INFORMATION>(),
+		)
+	};
+	if len == 0 {
+		return Err(io::Error::last_os_error());
+	}
+	let info = unsafe { info.assume_init() };
+	if info.state != c::MEM_COMMIT {
+		return Err(io::Error::new(
+			io::ErrorKind::NotFound,
+			"address is not committed",
+		));
+	}
+	// guard and caching modifiers are stored in the upper bits.
+	let protection = match info.protect & 0xff {
+		c::PAGE_READONLY => img::Protection::READ,
+		c::PAGE_READWRITE | c::PAGE_WRITECOPY => img::Protection::READ | img::Protection::WRITE,
+		c::PAGE_EXECUTE => img::Protection::EXECUTE,
+		c::PAGE_EXECUTE_READ => img::Protection::READ | img::Protection::EXECUTE,
+		c::PAGE_EXECUTE_READWRITE | c::PAGE_EXECUTE_WRITECOPY => {
+			img::Protection::READ | img::Protection::WRITE | img::Protection::EXECUTE
+		}
+		_ => img::Protection::NONE,
+	};
+	Ok(protection)
+}
+
+/// A kernel-mode driver loaded in the system.
+///
+/// This object can be obtained through [`loaded_drivers`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Driver {
+	base: usize,
+	path: Option<PathBuf>,
+}
+
+impl Driver {
+	/// Returns the base address of the driver in kernel space.
+	///
+	/// Without `SeDebugPrivilege` this is usually zero.
+	#[inline]
+	pub fn base(&self) -> usize {
+		self.base
+	}
+	/// Returns the path of the driver, such as `\SystemRoot\system32\ntoskrnl.exe`, if known.
+	///
+	/// The path is in the NT namespace, and can't be opened through the Win32 file APIs as is.
+	#[inline]
+	pub fn path(&self) -> Option<&path::Path> {
+		self.path.as_deref()
+	}
+}
+
+/// Enumerates the kernel-mode drivers loaded in the system.
+///
+/// # Errors
+///
+/// May error if the drivers can't be enumerated.
+#[doc(alias = "EnumDeviceDrivers")]
+pub fn loaded_drivers() -> io::Result<Vec<Driver>> {
+	const MAX_PATH: usize = 260;
+	let mut bases: Vec<*mut ffi::c_void> = Vec::new();
+	let mut len_needed: u32 = 0;
+	unsafe {
+		loop {
+			let cb = (bases.len() * mem::size_of::<*mut ffi::c_void>()) as u32;
+			if c::EnumDeviceDrivers(bases.as_mut_ptr(), cb, &mut len_needed) == 0 {
+				return Err(io::Error::last_os_error());
+			}
+			let len = len_needed as usize / mem::size_of::<*mut ffi::c_void>();
+			if len <= bases.len() {
+				bases.truncate(len);
+				break;
+			}
+			// drivers may be loaded between calls, so leave some room to grow.
+			bases.resize(len + 16, ptr::null_mut());
+		}
+		Ok(bases
+			.into_iter()
+			.map(|base| {
+				let mut buffer = vec![0u16; MAX_PATH];
+				let len = c::GetDeviceDriverFileNameW(base, buffer.as_mut_ptr(), MAX_PATH as u32);
+				Driver {
+					base: base as usize,
+					path: (len != 0 && !base.is_null())
+						.then(|| PathBuf::from(ffi::OsString::from_wide(&buffer[..len as usize]))),
+				}
+			})
+			.collect())
+	}
+}
+
+/// Enumerates the names of the DLLs in the `\KnownDlls` object directory.
+///
+/// Known DLLs are mapped from pre-made sections instead of being searched for, so they
+/// can't be substituted by placing a DLL of the same name in the application directory.
+///
+/// # Errors
+///
+/// May error if the object directory can't be opened.
+#[doc(alias = "NtQueryDirectoryObject")]
+pub fn known_dlls() -> io::Result<Vec<String>> {
+	static NTDLL: sync::OnceLock<
+		Option<(
+			Library,
+			c::PfnNtOpenDirectoryObject,
+			c::PfnNtQueryDirectoryObject,
+		)>,
+	> = sync::OnceLock::new();
+	let Some((_, open, query)) = NTDLL.get_or_init(|| unsafe {
+		let lib = Library::open("ntdll.dll").ok()?;
+		let open: c::PfnNtOpenDirectoryObject =
+			mem::transmute(lib.symbol("NtOpenDirectoryObject").ok()?);
+		let query: c::PfnNtQueryDirectoryObject =
+			mem::transmute(lib.symbol("NtQueryDirectoryObject").ok()?);
+		Some((lib, open, query))
+	}) else {
+		return Err(io::Error::new(
+			io::ErrorKind::Unsupported,
+			"object directories are unavailable",
+		));
+	};
+
+	let mut name: Vec<u16> = "\\KnownDlls".encode_utf16().collect();
+	let name = c::UNICODE_STRING {
+		length: (name.len() * 2) as u16,
+		maximumlength: (name.len() * 2) as u16,
+		buffer: name.as_mut_ptr(),
+	};
+	let attributes = c::OBJECT_ATTRIBUTES {
+		length: mem::size_of::<c::OBJECT_ATTRIBUTES>() as _,
+		rootdirectory: ptr::null_mut(),
+		objectname: &name,
+		attributes: c::OBJ_CASE_INSENSITIVE,
+		securitydescriptor: ptr::null_mut(),
+		securityqualityofservice: ptr::null_mut(),
+	};
+	unsafe {
+		let mut handle = ptr::null_mut();
+		let status = open(&mut handle, c::DIRECTORY_QUERY, &attributes);
+		if status < 0 {
+			return Err(io::Error::other(format!(
+				"failed to open \\KnownDlls (NTSTATUS {status:#x})"
+			)));
+		}
+
+		// u64 keeps the buffer aligned for `OBJECT_DIRECTORY_INFORMATION`.
+		let mut buffer = vec![0u64; 512];
+		let mut context = 0;
+		let mut names = Vec::new();
+		let result = loop {
+			let status = query(
+				handle,
+				buffer.as_mut_ptr().cast(),
+				(buffer.len() * mem::size_of::<u64>()) as _,
+				1,
+				0,
+				&mut context,
+				ptr::null_mut(),
+			);
+			if status == c::STATUS_NO_MORE_ENTRIES {
+				break Ok(names);
+			} else if status < 0 {
+				break Err(io::Error::other(format!(
+					"failed to query \\KnownDlls (NTSTATUS {status:#x})"
+				)));
+			}
+			let info = &*buffer.as_ptr().cast::<c::OBJECT_DIRECTORY_INFORMATION>();
+			let to_slice =
+				|s: &c::UNICODE_STRING| slice::from_raw_parts(s.buffer, s.length as usize / 2);
+			// the directory also holds symbolic links, such as `KnownDllPath`.
+			if to_slice(&info.typename) == "Section".encode_utf16().collect::<Vec<_>>() {
+				names.push(String::from_utf16_lossy(to_slice(&info.name)));
+			}
+		};
+		c::CloseHandle(handle);
+		result
+	}
+}
+
+#[cfg(feature = "images")]
 mod tests {
 	#[test]
 	fn test_size() {
@@ -156,6 +156,12 @@ unsafe extern "system" {
 	pub fn GetProcAddress(handle: HMODULE, symbol: PCSTR) -> *const ffi::c_void;
 	pub fn FreeLibrary(hlibmodule: *mut ffi::c_void) -> ffi::c_int;
 	pub fn GetModuleFileNameW(hmodule: HMODULE, lpfilename: PWSTR, nsize: DWORD) -> DWORD;
+	pub fn GetFinalPathNameByHandleW(
+		hfile: HANDLE,
+		lpszfilepath: PWSTR,
+		cchfilepath: DWORD,
+		dwflags: DWORD,
+	) -> DWORD;
 	pub fn GetCurrentProcess() -> HANDLE;
 	#[link_name = "K32EnumProcessModulesEx"]
 	pub fn EnumProcessModulesEx(
@@ -165,6 +171,18 @@ unsafe extern "system" {
 		lpcbneeded: *mut DWORD,
 		dwfilterflag: DWORD,
 	) -> BOOL;
+	#[link_name = "K32EnumDeviceDrivers"]
+	pub fn EnumDeviceDrivers(
+		lpimagebase: *mut *mut ffi::c_void,
+		cb: DWORD,
+		lpcbneeded: *mut DWORD,
+	) -> BOOL;
+	#[link_name = "K32GetDeviceDriverFileNameW"]
+	pub fn GetDeviceDriverFileNameW(
+		imagebase: *mut ffi::c_void,
+		lpfilename: PWSTR,
+		nsize: DWORD,
+	) -> DWORD;
 	#[link_name = "K32GetModuleInformation"]
 	pub fn GetModuleInformation(
 		hprocess: HANDLE,
@@ -180,9 +198,65 @@ unsafe extern "system" {
 		readonly: BOOL,
 	) -> BOOL;
 	pub fn UnMapAndLoad(loadedimage: *mut LOADED_IMAGE) -> BOOL;
+	pub fn VirtualQuery(
+		lpaddress: *const ffi::c_void,
+		lpbuffer: *mut MEMORY_BASIC_INFORMATION,
+		dwlength: usize,
+	) -> usize;
 	fn GetSystemInfo(lpsysteminfo: *mut SYSTEM_INFO);
+	pub fn CreateFileMappingW(
+		hfile: HANDLE,
+		lpfilemappingattributes: *mut ffi::c_void,
+		flprotect: DWORD,
+		dwmaximumsizehigh: DWORD,
+		dwmaximumsizelow: DWORD,
+		lpname: PCWSTR,
+	) -> HANDLE;
+	pub fn MapViewOfFile(
+		hfilemappingobject: HANDLE,
+		dwdesiredaccess: DWORD,
+		dwfileoffsethigh: DWORD,
+		dwfileoffsetlow: DWORD,
+		dwnumberofbytestomap: usize,
+	) -> *mut ffi::c_void;
+	pub fn UnmapViewOfFile(lpbaseaddress: *const ffi::c_void) -> BOOL;
+	pub fn CloseHandle(hobject: HANDLE) -> BOOL;
+	pub fn PrefetchVirtualMemory(
+		hprocess: HANDLE,
+		numberofentries: usize,
+		virtualaddresses: *const WIN32_MEMORY_RANGE_ENTRY,
+		flags: DWORD,
+	) -> BOOL;
+	#[link_name = "K32QueryWorkingSetEx"]
+	pub fn QueryWorkingSetEx(hprocess: HANDLE, pv: *mut ffi::c_void, cb: DWORD) -> BOOL;
+	pub fn VirtualUnlock(lpaddress: *const ffi::c_void, dwsize: usize) -> BOOL;
 }
 
+pub const ERROR_FILE_NOT_FOUND: i32 = 2;
+pub const ERROR_PATH_NOT_FOUND: i32 = 3;
+pub const ERROR_BAD_FORMAT: i32 = 11;
+pub const ERROR_MOD_NOT_FOUND: i32 = 126;
+pub const ERROR_PROC_NOT_FOUND: i32 = 127;
+pub const ERROR_NOT_LOCKED: i32 = 158;
+pub const ERROR_INVALID_ORDINAL: i32 = 182;
+pub const ERROR_BAD_EXE_FORMAT: i32 = 193;
+
+#[repr(C)]
+pub struct PSAPI_WORKING_SET_EX_INFORMATION {
+	pub virtualaddress: *mut ffi::c_void,
+	// bitfield: Valid (0), ShareCount (1..4), Win32Protection (4..15), Shared (15), Node (16..22),
+	// Locked (22), LargePage (23)
+	pub virtualattributes: usize,
+}
+
+#[repr(C)]
+pub struct WIN32_MEMORY_RANGE_ENTRY {
+	pub virtualaddress: *mut ffi::c_void,
+	pub numberofbytes: usize,
+}
+
+pub const FILE_MAP_READ: DWORD = 0x0004;
+
 #[derive(Clone, Copy)]
 #[repr(C)]
 struct SYSTEM_INFO_0_0 {
@@ -196,6 +270,17 @@ union SYSTEM_INFO_0 {
 	pub anonymous: SYSTEM_INFO_0_0,
 }
 
+// thread-safe version of GetSystemInfo's page size.
+pub fn page_size() -> usize {
+	use std::sync::OnceLock;
+	static PAGE_SIZE: OnceLock<usize> = OnceLock::new();
+	*PAGE_SIZE.get_or_init(|| unsafe {
+		let mut sys_info = mem::MaybeUninit::<SYSTEM_INFO>::zeroed();
+		GetSystemInfo(sys_info.as_mut_ptr());
+		sys_info.assume_init().dwpagesize as usize
+	})
+}
+
 #[repr(C)]
 struct SYSTEM_INFO {
 	anonymous: SYSTEM_INFO_0,
@@ -233,9 +318,106 @@ pub struct IMAGE_DOS_HEADER {
 	pub e_lfanew: i32,
 }
 
-const IMAGE_DOS_SIGNATURE: u16 = 0x5A4D;
+pub const IMAGE_DOS_SIGNATURE: u16 = 0x5A4D;
 const IMAGE_DOS_SIGNATURE2: u16 = 0x4D5A;
-const IMAGE_NT_SIGNATURE: u32 = u32::from_le_bytes([b'P', b'E', 0, 0]);
+pub const IMAGE_NT_SIGNATURE: u32 = u32::from_le_bytes([b'P', b'E', 0, 0]);
+
+pub const IMAGE_NT_OPTIONAL_HDR32_MAGIC: WORD = 0x10b;
+pub const IMAGE_NT_OPTIONAL_HDR64_MAGIC: WORD = 0x20b;
+
+pub const IMAGE_DIRECTORY_ENTRY_EXPORT: usize = 0;
+pub const IMAGE_DIRECTORY_ENTRY_RESOURCE: usize = 2;
+pub const IMAGE_DIRECTORY_ENTRY_TLS: usize = 9;
+
+pub const IMAGE_FILE_MACHINE_I386: WORD = 0x014c;
+pub const IMAGE_FILE_MACHINE_ARM: WORD = 0x01c0;
+pub const IMAGE_FILE_MACHINE_ARMNT: WORD = 0x01c4;
+pub const IMAGE_FILE_MACHINE_AMD64: WORD = 0x8664;
+pub const IMAGE_FILE_MACHINE_ARM64: WORD = 0xaa64;
+
+#[repr(C)]
+pub struct IMAGE_EXPORT_DIRECTORY {
+	pub characteristics: DWORD,
+	pub timedatestamp: DWORD,
+	pub majorversion: WORD,
+	pub minorversion: WORD,
+	pub name: DWORD,
+	pub base: DWORD,
+	pub numberoffunctions: DWORD,
+	pub numberofnames: DWORD,
+	pub addressoffunctions: DWORD,
+	pub addressofnames: DWORD,
+	pub addressofnameordinals: DWORD,
+}
+
+// the address fields are pointer sized, so they're `u32` in `IMAGE_TLS_DIRECTORY32`.
+#[repr(C)]
+pub struct IMAGE_TLS_DIRECTORY64 {
+	pub startaddressofrawdata: u64,
+	pub endaddressofrawdata: u64,
+	pub addressofindex: u64,
+	pub addressofcallbacks: u64,
+	pub sizeofzerofill: DWORD,
+	pub characteristics: DWORD,
+}
+
+#[repr(C)]
+pub struct IMAGE_TLS_DIRECTORY32 {
+	pub startaddressofrawdata: DWORD,
+	pub endaddressofrawdata: DWORD,
+	pub addressofindex: DWORD,
+	pub addressofcallbacks: DWORD,
+	pub sizeofzerofill: DWORD,
+	pub characteristics: DWORD,
+}
+
+pub const IMAGE_SCN_ALIGN_MASK: DWORD = 0x00F00000;
+
+pub const RT_VERSION: DWORD = 16;
+pub const IMAGE_RESOURCE_DATA_IS_DIRECTORY: DWORD = 0x80000000;
+
+#[repr(C)]
+pub struct IMAGE_RESOURCE_DIRECTORY {
+	pub characteristics: DWORD,
+	pub timedatestamp: DWORD,
+	pub majorversion: WORD,
+	pub minorversion: WORD,
+	pub numberofnamedentries: WORD,
+	pub numberofidentries: WORD,
+}
+
+#[repr(C)]
+pub struct IMAGE_RESOURCE_DIRECTORY_ENTRY {
+	pub name: DWORD,
+	pub offsettodata: DWORD,
+}
+
+#[repr(C)]
+pub struct IMAGE_RESOURCE_DATA_ENTRY {
+	pub offsettodata: DWORD,
+	pub size: DWORD,
+	pub codepage: DWORD,
+	pub reserved: DWORD,
+}
+
+pub const VS_FFI_SIGNATURE: DWORD = 0xfeef04bd;
+
+#[repr(C)]
+pub struct VS_FIXEDFILEINFO {
+	pub dwsignature: DWORD,
+	pub dwstrucversion: DWORD,
+	pub dwfileversionms: DWORD,
+	pub dwfileversionls: DWORD,
+	pub dwproductversionms: DWORD,
+	pub dwproductversionls: DWORD,
+	pub dwfileflagsmask: DWORD,
+	pub dwfileflags: DWORD,
+	pub dwfileos: DWORD,
+	pub dwfiletype: DWORD,
+	pub dwfilesubtype: DWORD,
+	pub dwfiledatems: DWORD,
+	pub dwfiledatels: DWORD,
+}
 
 // thread-safe version of win32's ImageNtHeader.
 pub unsafe fn ImageNtHeader(base: *mut IMAGE_DOS_HEADER) -> *mut IMAGE_NT_HEADERS {
@@ -279,10 +461,15 @@ pub unsafe fn ImageNtHeader(base: *mut IMAGE_DOS_HEADER) -> *mut IMAGE_NT_HEADER
 	}
 }
 
+pub const LOAD_LIBRARY_AS_DATAFILE: DWORD = 0x00000002;
+pub const LOAD_LIBRARY_AS_IMAGE_RESOURCE: DWORD = 0x00000020;
+pub const LOAD_LIBRARY_SEARCH_SYSTEM32: DWORD = 0x00000800;
+pub const LOAD_LIBRARY_SEARCH_DEFAULT_DIRS: DWORD = 0x00001000;
 pub const GET_MODULE_HANDLE_EX_FLAG_UNCHANGED_REFCOUNT: DWORD = 0x00000002u32;
 pub const GET_MODULE_HANDLE_EX_FLAG_FROM_ADDRESS: DWORD = 0x00000004u32;
 
 pub const LIST_MODULES_ALL: DWORD = 0x03;
+pub const FILE_NAME_NORMALIZED: DWORD = 0x0;
 pub const IMAGE_SIZEOF_SHORT_NAME: usize = 8;
 
 #[repr(C)]
@@ -305,6 +492,34 @@ pub struct IMAGE_SECTION_HEADER {
 	pub characteristics: DWORD,
 }
 
+pub const IMAGE_SCN_MEM_EXECUTE: DWORD = 0x20000000;
+pub const IMAGE_SCN_MEM_READ: DWORD = 0x40000000;
+pub const IMAGE_SCN_MEM_WRITE: DWORD = 0x80000000;
+
+pub const PAGE_NOACCESS: DWORD = 0x01;
+pub const PAGE_READONLY: DWORD = 0x02;
+pub const PAGE_READWRITE: DWORD = 0x04;
+pub const PAGE_WRITECOPY: DWORD = 0x08;
+pub const PAGE_EXECUTE: DWORD = 0x10;
+pub const PAGE_EXECUTE_READ: DWORD = 0x20;
+pub const PAGE_EXECUTE_READWRITE: DWORD = 0x40;
+pub const PAGE_EXECUTE_WRITECOPY: DWORD = 0x80;
+
+pub const MEM_COMMIT: DWORD = 0x1000;
+
+#[repr(C)]
+pub struct MEMORY_BASIC_INFORMATION {
+	pub baseaddress: *mut ffi::c_void,
+	pub allocationbase: *mut ffi::c_void,
+	pub allocationprotect: DWORD,
+	#[cfg(target_pointer_width = "64")]
+	pub partitionid: WORD,
+	pub regionsize: usize,
+	pub state: DWORD,
+	pub protect: DWORD,
+	pub type_: DWORD,
+}
+
 #[repr(C)]
 pub struct LIST_ENTRY {
 	pub flink: *mut LIST_ENTRY,
@@ -329,3 +544,117 @@ pub struct LOADED_IMAGE {
 	pub links: LIST_ENTRY,
 	pub sizeofimage: ffi::c_ulong,
 }
+
+// dbghelp is loaded at run-time, since not every consumer needs it.
+pub type PfnSymInitializeW = unsafe extern "system" fn(
+	hprocess: HANDLE,
+	usersearchpath: PCWSTR,
+	finvadeprocess: BOOL,
+) -> BOOL;
+pub type PfnSymRefreshModuleList = unsafe extern "system" fn(hprocess: HANDLE) -> BOOL;
+pub type PfnSymFromAddrW = unsafe extern "system" fn(
+	hprocess: HANDLE,
+	address: u64,
+	displacement: *mut u64,
+	symbol: *mut SYMBOL_INFOW,
+) -> BOOL;
+
+pub const SYMFLAG_EXPORT: ffi::c_ulong = 0x200;
+
+#[repr(C)]
+pub struct SYMBOL_INFOW {
+	pub sizeofstruct: ffi::c_ulong,
+	pub typeindex: ffi::c_ulong,
+	pub reserved: [u64; 2],
+	pub index: ffi::c_ulong,
+	pub size: ffi::c_ulong,
+	pub modbase: u64,
+	pub flags: ffi::c_ulong,
+	pub value: u64,
+	pub address: u64,
+	pub register: ffi::c_ulong,
+	pub scope: ffi::c_ulong,
+	pub tag: ffi::c_ulong,
+	pub namelen: ffi::c_ulong,
+	pub maxnamelen: ffi::c_ulong,
+	pub name: [u16; 1],
+}
+
+pub type NTSTATUS = i32;
+pub const LDR_LOCK_LOADER_LOCK_FLAG_TRY_ONLY: ffi::c_ulong = 0x2;
+pub const LDR_LOCK_LOADER_LOCK_DISPOSITION_LOCK_ACQUIRED: ffi::c_ulong = 1;
+// undocumented, but stable since Windows XP.
+pub type PfnLdrLockLoaderLock = unsafe extern "system" fn(
+	flags: ffi::c_ulong,
+	disposition: *mut ffi::c_ulong,
+	cookie: *mut *mut ffi::c_void,
+) -> NTSTATUS;
+pub type PfnLdrUnlockLoaderLock =
+	unsafe extern "system" fn(flags: ffi::c_ulong, cookie: *mut ffi::c_void) -> NTSTATUS;
+
+pub const DIRECTORY_QUERY: u32 = 0x0001;
+pub const OBJ_CASE_INSENSITIVE: ffi::c_ulong = 0x0040;
+pub const STATUS_NO_MORE_ENTRIES: NTSTATUS = 0x8000001A_u32 as NTSTATUS;
+
+#[repr(C)]
+pub struct UNICODE_STRING {
+	pub length: u16,
+	pub maximumlength: u16,
+	pub buffer: PWSTR,
+}
+
+#[repr(C)]
+pub struct OBJECT_ATTRIBUTES {
+	pub length: ffi::c_ulong,
+	pub rootdirectory: HANDLE,
+	pub objectname: *const UNICODE_STRING,
+	pub attributes: ffi::c_ulong,
+	pub securitydescriptor: *mut ffi::c_void,
+	pub securityqualityofservice: *mut ffi::c_void,
+}
+
+#[repr(C)]
+pub struct OBJECT_DIRECTORY_INFORMATION {
+	pub name: UNICODE_STRING,
+	pub typename: UNICODE_STRING,
+}
+
+pub type PfnNtOpenDirectoryObject = unsafe extern "system" fn(
+	directoryhandle: *mut HANDLE,
+	desiredaccess: u32,
+	objectattributes: *const OBJECT_ATTRIBUTES,
+) -> NTSTATUS;
+pub type PfnNtQueryDirectoryObject = unsafe extern "system" fn(
+	directoryhandle: HANDLE,
+	buffer: *mut ffi::c_void,
+	length: ffi::c_ulong,
+	returnsingleentry: BOOLEAN,
+	restartscan: BOOLEAN,
+	context: *mut ffi::c_ulong,
+	returnlength: *mut ffi::c_ulong,
+) -> NTSTATUS;
+
+pub const LDR_DLL_NOTIFICATION_REASON_LOADED: ffi::c_ulong = 1;
+pub const LDR_DLL_NOTIFICATION_REASON_UNLOADED: ffi::c_ulong = 2;
+
+// the loaded and unloaded variants of the union have the same layout.
+#[repr(C)]
+pub struct LDR_DLL_NOTIFICATION_DATA {
+	pub flags: ffi::c_ulong,
+	pub fulldllname: *const UNICODE_STRING,
+	pub basedllname: *const UNICODE_STRING,
+	pub dllbase: *mut ffi::c_void,
+	pub sizeofimage: ffi::c_ulong,
+}
+
+pub type PfnLdrDllNotification = unsafe extern "system" fn(
+	notificationreason: ffi::c_ulong,
+	notificationdata: *const LDR_DLL_NOTIFICATION_DATA,
+	context: *mut ffi::c_void,
+);
+pub type PfnLdrRegisterDllNotification = unsafe extern "system" fn(
+	flags: ffi::c_ulong,
+	notificationfunction: PfnLdrDllNotification,
+	context: *mut ffi::c_void,
+	cookie: *mut *mut ffi::c_void,
+) -> NTSTATUS;
@@ -0,0 +1,424 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use super::c;
+use crate::img::{
+	self,
+	RawImage,
+};
+use std::{
+	io,
+	mem,
+};
+
+/// A copyable subset of `IMAGE_SECTION_HEADER`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Section {
+	pub(crate) name: [u8; 8],
+	pub(crate) virtualaddress: usize,
+	pub(crate) virtualsize: usize,
+	pub(crate) pointertorawdata: usize,
+	pub(crate) sizeofrawdata: usize,
+	pub(crate) characteristics: c::DWORD,
+}
+
+impl Section {
+	/// Returns the name of the section, such as `.text`, without trailing nul bytes.
+	#[inline]
+	pub fn name(&self) -> &[u8] {
+		let len = self
+			.name
+			.iter()
+			.position(|&b| b == 0)
+			.unwrap_or(self.name.len());
+		&self.name[..len]
+	}
+	/// Returns the relative virtual address of the section.
+	#[inline]
+	pub fn virtual_address(&self) -> usize {
+		self.virtualaddress
+	}
+	/// Returns the size of the section in memory.
+	#[inline]
+	pub fn virtual_size(&self) -> usize {
+		self.virtualsize
+	}
+	/// Returns the file offset of the section.
+	#[inline]
+	pub fn pointer_to_raw_data(&self) -> usize {
+		self.pointertorawdata
+	}
+	/// Returns the size of the section in the file.
+	#[inline]
+	pub fn size_of_raw_data(&self) -> usize {
+		self.sizeofrawdata
+	}
+	/// Returns the section characteristics, such as `IMAGE_SCN_MEM_READ`.
+	#[inline]
+	pub fn characteristics(&self) -> u32 {
+		self.characteristics
+	}
+}
+
+#[derive(Debug)]
+pub(crate) struct Pe {
+	pub raw: RawImage,
+	pub machine: c::WORD,
+	pub is_64: bool,
+	pub imagebase: usize,
+	pub sizeofheaders: usize,
+	// (virtualaddress, size)
+	pub datadirectory: Vec<(usize, usize)>,
+	pub sections: Vec<Section>,
+}
+
+impl Pe {
+	pub unsafe fn parse(raw: RawImage) -> io::Result<Self> {
+		unsafe {
+			let dos = raw.read::<c::IMAGE_DOS_HEADER>(0)?;
+			if dos.e_magic != c::IMAGE_DOS_SIGNATURE {
+				return Err(io::Error::other("unknown header detected"));
+			}
+			let nt_offset = dos.e_lfanew as usize;
+			if raw.read::<c::DWORD>(nt_offset)? != c::IMAGE_NT_SIGNATURE {
+				return Err(io::Error::new(
+					io::ErrorKind::InvalidData,
+					"invalid PE signature",
+				));
+			}
+			let fileheader_offset = nt_offset + mem::size_of::<c::DWORD>();
+			let fileheader = raw.read::<c::IMAGE_FILE_HEADER>(fileheader_offset)?;
+			let optional_offset = fileheader_offset + mem::size_of::<c::IMAGE_FILE_HEADER>();
+			let magic = raw.read::<c::WORD>(optional_offset)?;
+			let (imagebase, sizeofheaders, numberofrvaandsizes, datadirectory) = match magic {
+				c::IMAGE_NT_OPTIONAL_HDR32_MAGIC => {
+					let opt = raw.read::<c::IMAGE_OPTIONAL_HEADER32>(optional_offset)?;
+					(
+						opt.imagebase as usize,
+						opt.sizeofheaders,
+						opt.numberofrvaandsizes,
+						opt.datadirectory,
+					)
+				}
+				c::IMAGE_NT_OPTIONAL_HDR64_MAGIC => {
+					let opt = raw.read::<c::IMAGE_OPTIONAL_HEADER64>(optional_offset)?;
+					(
+						opt.imagebase as usize,
+						opt.sizeofheaders,
+						opt.numberofrvaandsizes,
+						opt.datadirectory,
+					)
+				}
+				_ => {
+					return Err(io::Error::new(
+						io::ErrorKind::InvalidData,
+						"invalid PE optional header",
+					));
+				}
+			};
+			let datadirectory = datadirectory
+				.iter()
+				.take(numberofrvaandsizes as usize)
+				.map(|dir| (dir.virtualaddress as usize, dir.size as usize))
+				.collect();
+			let sections_offset = optional_offset + fileheader.sizeofoptionalheader as usize;
+			let mut sections = Vec::with_capacity(fileheader.numberofsections as usize);
+			for i in 0..fileheader.numberofsections as usize {
+				let section = raw.read::<c::IMAGE_SECTION_HEADER>(
+					sections_offset + i * mem::size_of::<c::IMAGE_SECTION_HEADER>(),
+				)?;
+				sections.push(Section {
+					name: section.name,
+					virtualaddress: section.virtualaddress as usize,
+					virtualsize: section.misc.virtualsize as usize,
+					pointertorawdata: section.pointertorawdata as usize,
+					sizeofrawdata: section.sizeofrawdata as usize,
+					characteristics: section.characteristics,
+				});
+			}
+			Ok(Self {
+				raw,
+				machine: fileheader.machine,
+				is_64: magic == c::IMAGE_NT_OPTIONAL_HDR64_MAGIC,
+				imagebase,
+				sizeofheaders: sizeofheaders as usize,
+				datadirectory,
+				sections,
+			})
+		}
+	}
+
+	pub fn arch(&self) -> img::Arch {
+		match self.machine {
+			c::IMAGE_FILE_MACHINE_I386 => img::Arch::X86,
+			c::IMAGE_FILE_MACHINE_AMD64 => img::Arch::X86_64,
+			c::IMAGE_FILE_MACHINE_ARM | c::IMAGE_FILE_MACHINE_ARMNT => img::Arch::Arm,
+			c::IMAGE_FILE_MACHINE_ARM64 => img::Arch::AArch64,
+			other => img::Arch::Unknown(other as u32),
+		}
+	}
+
+	/// Returns the address the image was linked to load at.
+	#[inline]
+	pub fn preferred_base(&self) -> usize {
+		self.imagebase
+	}
+
+	fn section_of(&self, rva: usize) -> Option<&Section> {
+		self.sections.iter().find(|section| {
+			let len = section.virtualsize.max(section.sizeofrawdata);
+			(section.virtualaddress..section.virtualaddress + len).contains(&rva)
+		})
+	}
+
+	/// Converts a relative virtual address to an offset into the raw image.
+	pub fn offset_of(&self, rva: usize) -> io::Result<usize> {
+		if self.raw.loaded || rva < self.sizeofheaders {
+			return Ok(rva);
+		}
+		self.section_of(rva)
+			.filter(|section| rva - section.virtualaddress < section.sizeofrawdata)
+			.map(|section| section.pointertorawdata + (rva - section.virtualaddress))
+			.ok_or_else(img::raw::out_of_bounds)
+	}
+
+	pub fn segments(&self) -> Vec<img::Segment> {
+		self.sections
+			.iter()
+			.map(|section| {
+				let mut protection = img::Protection::NONE;
+				if section.characteristics & c::IMAGE_SCN_MEM_READ != 0 {
+					protection |= img::Protection::READ;
+				}
+				if section.characteristics & c::IMAGE_SCN_MEM_WRITE != 0 {
+					protection |= img::Protection::WRITE;
+				}
+				if section.characteristics & c::IMAGE_SCN_MEM_EXECUTE != 0 {
+					protection |= img::Protection::EXECUTE;
+				}
+				let len = match section.virtualsize {
+					0 => section.sizeofrawdata,
+					size => size,
+				};
+				img::Segment {
+					addr: self.raw.ptr as usize + section.virtualaddress,
+					len,
+					protection,
+				}
+			})
+			.collect()
+	}
+
+	pub unsafe fn exports(&self) -> io::Result<Vec<img::Export>> {
+		let Some(&(dir_rva, dir_size)) = self
+			.datadirectory
+			.get(c::IMAGE_DIRECTORY_ENTRY_EXPORT)
+			.filter(|(rva, _)| *rva != 0)
+		else {
+			return Ok(Vec::new());
+		};
+		unsafe {
+			let dir = self
+				.raw
+				.read::<c::IMAGE_EXPORT_DIRECTORY>(self.offset_of(dir_rva)?)?;
+			let functions = self.offset_of(dir.addressoffunctions as usize)?;
+			let mut names = vec![None; dir.numberoffunctions as usize];
+			if dir.numberofnames != 0 {
+				let name_rvas = self.offset_of(dir.addressofnames as usize)?;
+				let ordinals = self.offset_of(dir.addressofnameordinals as usize)?;
+				for i in 0..dir.numberofnames as usize {
+					let name_rva = self.raw.read::<c::DWORD>(name_rvas + i * 4)?;
+					let index = self.raw.read::<c::WORD>(ordinals + i * 2)? as usize;
+					let name = self.raw.read_cstr(self.offset_of(name_rva as usize)?)?;
+					if let Some(slot) = names.get_mut(index) {
+						*slot = Some(name.to_string_lossy().into_owned());
+					}
+				}
+			}
+			let mut exports = Vec::new();
+			for (i, name) in names.into_iter().enumerate() {
+				let rva = self.raw.read::<c::DWORD>(functions + i * 4)? as usize;
+				if rva == 0 {
+					continue;
+				}
+				// forwarded exports point to a string inside the export directory.
+				let forwarder = if (dir_rva..dir_rva + dir_size).contains(&rva) {
+					let forwarder = self.raw.read_cstr(self.offset_of(rva)?)?;
+					Some(forwarder.to_string_lossy().into_owned())
+				} else {
+					None
+				};
+				let kind = match self.section_of(rva) {
+					_ if forwarder.is_some() => img::ExportKind::Unknown,
+					Some(section) if section.characteristics & c::IMAGE_SCN_MEM_EXECUTE != 0 => {
+						img::ExportKind::Function
+					}
+					Some(_) => img::ExportKind::Data,
+					None => img::ExportKind::Unknown,
+				};
+				exports.push(img::Export {
+					name,
+					ordinal: Some(dir.base + i as u32),
+					rva,
+					size: None,
+					kind,
+					forwarder,
+				});
+			}
+			Ok(exports)
+		}
+	}
+
+	pub unsafe fn tls(&self) -> io::Result<Option<img::Tls>> {
+		let Some(&(dir_rva, _)) = self
+			.datadirectory
+			.get(c::IMAGE_DIRECTORY_ENTRY_TLS)
+			.filter(|(rva, _)| *rva != 0)
+		else {
+			return Ok(None);
+		};
+		// the directory holds addresses rather than RVAs, which are relocated once loaded.
+		let base = match self.raw.loaded {
+			true => self.raw.ptr as usize,
+			false => self.imagebase,
+		};
+		let to_rva = |va: u64| {
+			(va as usize)
+				.checked_sub(base)
+				.ok_or_else(img::raw::out_of_bounds)
+		};
+		unsafe {
+			let offset = self.offset_of(dir_rva)?;
+			let (start_va, end_va, callbacks, zerofill, characteristics) = if self.is_64 {
+				let dir = self.raw.read::<c::IMAGE_TLS_DIRECTORY64>(offset)?;
+				(
+					dir.startaddressofrawdata,
+					dir.endaddressofrawdata,
+					dir.addressofcallbacks,
+					dir.sizeofzerofill,
+					dir.characteristics,
+				)
+			} else {
+				let dir = self.raw.read::<c::IMAGE_TLS_DIRECTORY32>(offset)?;
+				(
+					dir.startaddressofrawdata as u64,
+					dir.endaddressofrawdata as u64,
+					dir.addressofcallbacks as u64,
+					dir.sizeofzerofill,
+					dir.characteristics,
+				)
+			};
+			let start = to_rva(start_va)?;
+			let end = to_rva(end_va)?.max(start);
+			// the callbacks are a null terminated array of addresses.
+			let mut addrs = Vec::new();
+			if callbacks != 0 {
+				let array = self.offset_of(to_rva(callbacks)?)?;
+				let width = if self.is_64 { 8 } else { 4 };
+				loop {
+					let offset = array + addrs.len() * width;
+					let va = match self.is_64 {
+						true => self.raw.read::<u64>(offset)?,
+						false => self.raw.read::<u32>(offset)? as u64,
+					};
+					if va == 0 {
+						break;
+					}
+					addrs.push(base + to_rva(va)?);
+				}
+			}
+			let align = match (characteristics & c::IMAGE_SCN_ALIGN_MASK) >> 20 {
+				0 => 1,
+				n => 1 << (n - 1),
+			};
+			Ok(Some(img::Tls {
+				template: base + start..base + end,
+				size: end - start + zerofill as usize,
+				align,
+				callbacks: addrs,
+			}))
+		}
+	}
+
+	// Returns the data offset of the first entry matching `id` in a resource directory.
+	unsafe fn resource_entry(
+		&self,
+		rsrc: usize,
+		dir: usize,
+		id: Option<c::DWORD>,
+	) -> io::Result<Option<c::DWORD>> {
+		unsafe {
+			let header = self.raw.read::<c::IMAGE_RESOURCE_DIRECTORY>(rsrc + dir)?;
+			let entries = rsrc + dir + mem::size_of::<c::IMAGE_RESOURCE_DIRECTORY>();
+			let count = header.numberofnamedentries as usize + header.numberofidentries as usize;
+			for i in 0..count {
+				let entry = self.raw.read::<c::IMAGE_RESOURCE_DIRECTORY_ENTRY>(
+					entries + i * mem::size_of::<c::IMAGE_RESOURCE_DIRECTORY_ENTRY>(),
+				)?;
+				if id.is_none_or(|id| entry.name == id) {
+					return Ok(Some(entry.offsettodata));
+				}
+			}
+			Ok(None)
+		}
+	}
+
+	pub unsafe fn version(&self) -> io::Result<img::VersionInfo> {
+		let mut info = img::VersionInfo::default();
+		unsafe {
+			if let Some(&(dir_rva, _)) = self
+				.datadirectory
+				.get(c::IMAGE_DIRECTORY_ENTRY_EXPORT)
+				.filter(|(rva, _)| *rva != 0)
+			{
+				let dir = self
+					.raw
+					.read::<c::IMAGE_EXPORT_DIRECTORY>(self.offset_of(dir_rva)?)?;
+				let name = self.raw.read_cstr(self.offset_of(dir.name as usize)?)?;
+				info.name = Some(name.to_string_lossy().into_owned());
+			}
+			let Some(&(rsrc_rva, _)) = self
+				.datadirectory
+				.get(c::IMAGE_DIRECTORY_ENTRY_RESOURCE)
+				.filter(|(rva, _)| *rva != 0)
+			else {
+				return Ok(info);
+			};
+			let rsrc = self.offset_of(rsrc_rva)?;
+			// resources are organized as type, name, then language.
+			let mut offset = 0;
+			for id in [Some(c::RT_VERSION), None, None] {
+				if offset & c::IMAGE_RESOURCE_DATA_IS_DIRECTORY != 0 {
+					offset &= !c::IMAGE_RESOURCE_DATA_IS_DIRECTORY;
+				} else if offset != 0 {
+					return Err(io::Error::new(
+						io::ErrorKind::InvalidData,
+						"invalid resource directory",
+					));
+				}
+				match self.resource_entry(rsrc, offset as usize, id)? {
+					Some(next) => offset = next,
+					None => return Ok(info),
+				}
+			}
+			let data = self
+				.raw
+				.read::<c::IMAGE_RESOURCE_DATA_ENTRY>(rsrc + offset as usize)?;
+			// `VS_FIXEDFILEINFO` follows the 16 character "VS_VERSION_INFO" key of `VS_VERSIONINFO`, aligned to 4 bytes.
+			let fixed = self
+				.raw
+				.read::<c::VS_FIXEDFILEINFO>(self.offset_of(data.offsettodata as usize)? + 40)?;
+			if fixed.dwsignature != c::VS_FFI_SIGNATURE {
+				return Err(io::Error::new(
+					io::ErrorKind::InvalidData,
+					"invalid version resource",
+				));
+			}
+			let ms = fixed.dwfileversionms;
+			let ls = fixed.dwfileversionls;
+			info.current =
+				Some(img::Version::new(ms >> 16, ms & 0xffff, ls >> 16).with_build(ls & 0xffff));
+		}
+		Ok(info)
+	}
+}
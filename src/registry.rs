@@ -0,0 +1,132 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#[cfg(feature = "zeroize")]
+use crate::scrub::Scrub;
+use std::{
+	fmt,
+	path,
+	sync::Mutex,
+};
+
+// Entries keyed by the address of their `LibLock`.
+static REGISTRY: Mutex<Vec<(usize, RegistryEntry)>> = Mutex::new(Vec::new());
+
+/// A [`LibLock`](crate::sync::LibLock) recorded in the registry returned by [`registry`].
+#[derive(Debug, Clone)]
+pub struct RegistryEntry {
+	candidates: Vec<path::PathBuf>,
+	loaded: bool,
+	path: Option<path::PathBuf>,
+	error: Option<String>,
+}
+
+impl RegistryEntry {
+	/// Returns the paths the `LibLock` attempted to load from, in order.
+	#[inline]
+	pub fn candidates(&self) -> &[path::PathBuf] {
+		&self.candidates
+	}
+	/// Returns `true` if the library is open.
+	#[inline]
+	pub fn is_loaded(&self) -> bool {
+		self.loaded
+	}
+	/// Returns the candidate the library was opened from, or `None` if it isn't open, or the
+	/// `LibLock` holds the program itself.
+	#[inline]
+	pub fn path(&self) -> Option<&path::Path> {
+		self.path.as_deref()
+	}
+	/// Returns the error the library failed to open with, if it did.
+	#[inline]
+	pub fn error(&self) -> Option<&str> {
+		self.error.as_deref()
+	}
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for RegistryEntry {
+	fn drop(&mut self) {
+		for candidate in &mut self.candidates {
+			candidate.scrub();
+		}
+		self.path.scrub();
+		self.error.scrub();
+	}
+}
+
+impl fmt::Display for RegistryEntry {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		for (i, candidate) in self.candidates.iter().enumerate() {
+			let sep = if i == 0 { "" } else { ", " };
+			write!(f, "{sep}`{}`", candidate.display())?;
+		}
+		if self.candidates.is_empty() {
+			f.write_str("the program")?;
+		}
+		match (&self.error, &self.path) {
+			(Some(err), _) => write!(f, ": failed ({err})"),
+			(None, Some(path)) => write!(f, ": loaded from `{}`", path.display()),
+			(None, None) if self.loaded => f.write_str(": loaded"),
+			(None, None) => f.write_str(": unloaded"),
+		}
+	}
+}
+
+// Records the outcome of opening the library of a `LibLock`, replacing its previous entry.
+pub(crate) fn record_load(
+	id: usize,
+	candidates: Vec<path::PathBuf>,
+	outcome: Result<Option<&path::Path>, String>,
+) {
+	let entry = match outcome {
+		Ok(path) => RegistryEntry {
+			candidates,
+			loaded: true,
+			path: path.map(path::Path::to_path_buf),
+			error: None,
+		},
+		Err(err) => RegistryEntry {
+			candidates,
+			loaded: false,
+			path: None,
+			error: Some(err),
+		},
+	};
+	let mut registry = REGISTRY.lock().unwrap_or_else(|err| err.into_inner());
+	match registry.iter_mut().find(|(key, _)| *key == id) {
+		Some((_, old)) => *old = entry,
+		None => registry.push((id, entry)),
+	}
+}
+
+// Records that the library of a `LibLock` was taken out or unloaded.
+pub(crate) fn record_reset(id: usize) {
+	let mut registry = REGISTRY.lock().unwrap_or_else(|err| err.into_inner());
+	if let Some((_, entry)) = registry.iter_mut().find(|(key, _)| *key == id) {
+		entry.loaded = false;
+		entry.path = None;
+		entry.error = None;
+	}
+}
+
+/// Returns every [`LibLock`](crate::sync::LibLock) that attempted to open its library, along with
+/// whether it did, in the order they were first initialized.
+///
+/// This is meant for diagnostics, such as a page listing every library an application links at
+/// run-time. Since `LibLock`s are constructed in constant contexts, they're recorded when they're
+/// first initialized, so those that were never used aren't listed. Entries are kept after their
+/// `LibLock` is dropped, which is only an issue for `LibLock`s that aren't statics.
+///
+/// # Examples
+///
+/// ```no_run
+/// for entry in dylink::registry() {
+///     println!("{entry}");
+/// }
+/// ```
+pub fn registry() -> Vec<RegistryEntry> {
+	let registry = REGISTRY.lock().unwrap_or_else(|err| err.into_inner());
+	registry.iter().map(|(_, entry)| entry.clone()).collect()
+}
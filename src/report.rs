@@ -0,0 +1,335 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::Library;
+#[cfg(feature = "zeroize")]
+use crate::scrub::Scrub;
+use std::{
+	error,
+	fmt,
+	io,
+	path,
+};
+
+/// A failed attempt to open a library candidate.
+#[derive(Debug)]
+pub struct LoadFailure {
+	index: usize,
+	path: path::PathBuf,
+	error: io::Error,
+}
+
+impl LoadFailure {
+	/// Returns the position of the candidate in the list of candidates.
+	#[inline]
+	pub fn index(&self) -> usize {
+		self.index
+	}
+	/// Returns the path of the candidate.
+	#[inline]
+	pub fn path(&self) -> &path::Path {
+		&self.path
+	}
+	/// Returns the error the candidate failed with.
+	#[inline]
+	pub fn error(&self) -> &io::Error {
+		&self.error
+	}
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for LoadFailure {
+	fn drop(&mut self) {
+		self.path.scrub();
+	}
+}
+
+/// The outcome of opening the first available library out of several candidates.
+///
+/// This object is usually created through [`lib_report`](crate::lib_report).
+#[derive(Debug)]
+pub struct LoadReport {
+	library: Option<(usize, Library)>,
+	failures: Vec<LoadFailure>,
+}
+
+impl LoadReport {
+	/// Attempts to open each candidate in order until one succeeds, recording every failure.
+	///
+	/// Candidates after the first success are not attempted.
+	pub fn open<I, P>(candidates: I) -> Self
+	where
+		I: IntoIterator<Item = P>,
+		P: AsRef<path::Path>,
+	{
+		Self::open_with(candidates, |path| Library::open(path))
+	}
+
+	// Attempts each candidate in order with `open`, until one succeeds.
+	pub(crate) fn open_with<I, P, F>(candidates: I, mut open: F) -> Self
+	where
+		I: IntoIterator<Item = P>,
+		P: AsRef<path::Path>,
+		F: FnMut(&path::Path) -> io::Result<Library>,
+	{
+		let mut failures = Vec::new();
+		for (index, path) in candidates.into_iter().enumerate() {
+			match open(path.as_ref()) {
+				Ok(lib) => {
+					return Self {
+						library: Some((index, lib)),
+						failures,
+					};
+				}
+				Err(error) => failures.push(LoadFailure {
+					index,
+					path: path.as_ref().to_path_buf(),
+					error,
+				}),
+			}
+		}
+		Self {
+			library: None,
+			failures,
+		}
+	}
+
+	/// Returns the position of the candidate that was opened, if any.
+	#[inline]
+	pub fn index(&self) -> Option<usize> {
+		self.library.as_ref().map(|(index, _)| *index)
+	}
+
+	/// Returns the library that was opened, if any.
+	#[inline]
+	pub fn library(&self) -> Option<&Library> {
+		self.library.as_ref().map(|(_, lib)| lib)
+	}
+
+	/// Consumes the report, returning the library that was opened, if any.
+	#[inline]
+	pub fn into_library(self) -> Option<Library> {
+		self.library.map(|(_, lib)| lib)
+	}
+
+	/// Returns the candidates that failed to open, in the order they were attempted.
+	#[inline]
+	pub fn failures(&self) -> &[LoadFailure] {
+		&self.failures
+	}
+
+	// Takes the library out of the report, or returns the report if no candidate could be opened.
+	#[cfg(feature = "sync")]
+	pub(crate) fn into_result(mut self) -> Result<Library, Self> {
+		match self.library.take() {
+			Some((_, lib)) => Ok(lib),
+			None => Err(self),
+		}
+	}
+
+	// Converts a report where no candidate could be opened into an error, of the same kind as the
+	// error of the first candidate.
+	#[cfg(feature = "sync")]
+	pub(crate) fn to_error(&self) -> io::Error {
+		let kind = self
+			.failures
+			.first()
+			.map_or(io::ErrorKind::NotFound, |failure| failure.error.kind());
+		io::Error::new(kind, self.to_string())
+	}
+}
+
+impl fmt::Display for LoadReport {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self.index() {
+			Some(index) => write!(f, "opened candidate {index}")?,
+			None => f.write_str("no candidate could be opened")?,
+		}
+		for (i, failure) in self.failures.iter().enumerate() {
+			let sep = if i == 0 { ", tried " } else { ", " };
+			write!(f, "{sep}`{}` ({})", failure.path.display(), failure.error)?;
+		}
+		Ok(())
+	}
+}
+
+/// An error opening a library that was denied access to its file, which usually means the process
+/// runs in a sandbox, such as one set up with Landlock or seccomp, or an AppContainer.
+///
+/// Such failures are otherwise hard to tell apart from a missing library, since loaders keep
+/// searching past files they can't access. This error is the inner error of an [`io::Error`] of
+/// kind [`PermissionDenied`](io::ErrorKind::PermissionDenied), which can be recovered with
+/// [`io::Error::get_ref`] and [`downcast_ref`](error::Error#method.downcast_ref).
+///
+/// # Examples
+///
+/// ```no_run
+/// use dylink::{Library, SandboxDenied};
+///
+/// let err = Library::open("libfoo.so").unwrap_err();
+/// if let Some(denied) = err.get_ref().and_then(|err| err.downcast_ref::<SandboxDenied>()) {
+///     eprintln!("denied access to {:?}", denied.path());
+/// }
+/// ```
+#[derive(Debug)]
+pub struct SandboxDenied {
+	path: Option<path::PathBuf>,
+	message: String,
+}
+
+impl SandboxDenied {
+	pub(crate) fn error(path: Option<path::PathBuf>, message: String) -> io::Error {
+		io::Error::new(io::ErrorKind::PermissionDenied, Self { path, message })
+	}
+	/// Returns the path that was denied, if it could be determined.
+	///
+	/// When a library is searched for by name, the directories of the search order are probed
+	/// again to find the file that was denied, so this is a best effort.
+	#[inline]
+	pub fn path(&self) -> Option<&path::Path> {
+		self.path.as_deref()
+	}
+	/// Returns the error message reported by the loader.
+	#[inline]
+	pub fn message(&self) -> &str {
+		&self.message
+	}
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for SandboxDenied {
+	fn drop(&mut self) {
+		self.path.scrub();
+		self.message.scrub();
+	}
+}
+
+impl fmt::Display for SandboxDenied {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(&self.message)?;
+		match &self.path {
+			Some(path) => write!(
+				f,
+				" (access to `{}` was denied, possibly by a sandbox)",
+				path.display()
+			),
+			None => f.write_str(" (access was denied, possibly by a sandbox)"),
+		}
+	}
+}
+
+impl error::Error for SandboxDenied {}
+
+/// What an error opening a library, or resolving a symbol, was caused by.
+#[non_exhaustive]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LoadErrorKind {
+	/// The library couldn't be found.
+	NotFound,
+	/// The library was found, but a library it depends on couldn't be.
+	MissingDependency,
+	/// The library was built for another architecture, or word size.
+	WrongArchitecture,
+	/// The file isn't a library the platform can load.
+	InvalidFormat,
+	/// A symbol couldn't be resolved, either one the library imports, or one that was looked up.
+	UndefinedSymbol,
+	/// A symbol was found, but not with the version that was required, such as a library
+	/// requiring a newer glibc.
+	VersionMismatch,
+	/// Any other error.
+	Other,
+}
+
+/// An error opening a library, or resolving a symbol, classified by its cause.
+///
+/// The system loaders only report most failures as a message, which this classifies, so that a
+/// missing dependency can be told apart from a missing library, or a library built for another
+/// architecture. This error is the inner error of the [`io::Error`] returned by [`Library`] and
+/// the loaders of this crate, which can be recovered with [`io::Error::get_ref`] and
+/// [`downcast_ref`](error::Error#method.downcast_ref). Failures of a [`LibLock`] are available
+/// this way from the [`LoadReport`] of [`LibLock::error`]. Failures caused by a sandbox are
+/// reported as [`SandboxDenied`] instead.
+///
+/// Messages are classified on a best effort basis, so a failure the loader reports in an
+/// unexpected way is of kind [`Other`](LoadErrorKind::Other).
+///
+/// [`LibLock`]: crate::sync::LibLock
+/// [`LibLock::error`]: crate::sync::LibLock::error
+///
+/// # Examples
+///
+/// ```no_run
+/// use dylink::{Library, LoadError, LoadErrorKind};
+///
+/// let err = Library::open("libfoo.so").unwrap_err();
+/// if let Some(err) = err.get_ref().and_then(|err| err.downcast_ref::<LoadError>()) {
+///     if err.kind() == LoadErrorKind::MissingDependency {
+///         eprintln!("libfoo.so requires {:?}", err.dependency());
+///     }
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct LoadError {
+	kind: LoadErrorKind,
+	message: String,
+	dependency: Option<String>,
+	os_error: Option<i32>,
+}
+
+impl LoadError {
+	pub(crate) fn error(
+		io_kind: io::ErrorKind,
+		kind: LoadErrorKind,
+		message: String,
+		dependency: Option<String>,
+		os_error: Option<i32>,
+	) -> io::Error {
+		io::Error::new(
+			io_kind,
+			Self {
+				kind,
+				message,
+				dependency,
+				os_error,
+			},
+		)
+	}
+	/// Returns what the error was caused by.
+	#[inline]
+	pub fn kind(&self) -> LoadErrorKind {
+		self.kind
+	}
+	/// Returns the error message reported by the loader, such as the message of `dlerror`.
+	#[inline]
+	pub fn message(&self) -> &str {
+		&self.message
+	}
+	/// Returns the name or path of the library that caused the error if it isn't the library that
+	/// was opened, such as a missing dependency, when the loader reports it.
+	#[inline]
+	pub fn dependency(&self) -> Option<&str> {
+		self.dependency.as_deref()
+	}
+	/// Returns the error code of the platform, which is only reported on Windows.
+	#[inline]
+	pub fn raw_os_error(&self) -> Option<i32> {
+		self.os_error
+	}
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for LoadError {
+	fn drop(&mut self) {
+		self.message.scrub();
+		self.dependency.scrub();
+	}
+}
+
+impl fmt::Display for LoadError {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(&self.message)
+	}
+}
+
+impl error::Error for LoadError {}
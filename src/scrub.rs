@@ -0,0 +1,93 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+// Scrubbing of symbol names and paths before their memory is freed, so that the layout of loaded
+// libraries doesn't linger in the heap. Without the `zeroize` feature, scrubbing does nothing.
+
+use std::{
+	ffi,
+	ops,
+	path,
+};
+#[cfg(feature = "zeroize")]
+use std::{
+	mem,
+	ptr,
+	sync::atomic,
+};
+
+pub(crate) trait Scrub {
+	// Zeroes the heap memory of the value, leaving it empty.
+	fn scrub(&mut self);
+}
+
+// Zeroes the whole allocation, including any spare capacity that may hold old contents.
+#[cfg(feature = "zeroize")]
+fn zero<T: Copy + Default>(mut buf: Vec<T>) {
+	let (data, capacity) = (buf.as_mut_ptr(), buf.capacity());
+	for i in 0..capacity {
+		unsafe { ptr::write_volatile(data.add(i), T::default()) };
+	}
+	// keeps the writes from being elided, since the memory is freed right after.
+	atomic::compiler_fence(atomic::Ordering::SeqCst);
+}
+
+impl<T: Copy + Default> Scrub for Vec<T> {
+	#[inline]
+	fn scrub(&mut self) {
+		#[cfg(feature = "zeroize")]
+		zero(mem::take(self));
+	}
+}
+
+impl Scrub for String {
+	#[inline]
+	fn scrub(&mut self) {
+		#[cfg(feature = "zeroize")]
+		zero(mem::take(self).into_bytes());
+	}
+}
+
+impl Scrub for ffi::CString {
+	#[inline]
+	fn scrub(&mut self) {
+		#[cfg(feature = "zeroize")]
+		zero(mem::take(self).into_bytes_with_nul());
+	}
+}
+
+impl Scrub for path::PathBuf {
+	#[inline]
+	fn scrub(&mut self) {
+		#[cfg(feature = "zeroize")]
+		zero(mem::take(self).into_os_string().into_encoded_bytes());
+	}
+}
+
+impl<T: Scrub> Scrub for Option<T> {
+	#[inline]
+	fn scrub(&mut self) {
+		if let Some(value) = self {
+			value.scrub();
+		}
+	}
+}
+
+// Scrubs a temporary when it goes out of scope.
+#[derive(Debug)]
+pub(crate) struct Scrubbed<T: Scrub>(pub T);
+
+impl<T: Scrub> ops::Deref for Scrubbed<T> {
+	type Target = T;
+	#[inline]
+	fn deref(&self) -> &T {
+		&self.0
+	}
+}
+
+impl<T: Scrub> Drop for Scrubbed<T> {
+	#[inline]
+	fn drop(&mut self) {
+		self.0.scrub();
+	}
+}
@@ -0,0 +1,63 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+// Suggestions of similar names for symbols that failed to resolve.
+
+use std::io;
+
+// The number of names suggested at most.
+const MAX_SUGGESTIONS: usize = 3;
+
+// Returns the Levenshtein distance between two names, ignoring ASCII case.
+fn distance(a: &str, b: &str) -> usize {
+	let b: Vec<u8> = b.bytes().map(|c| c.to_ascii_lowercase()).collect();
+	let mut row: Vec<usize> = (0..=b.len()).collect();
+	for (i, a) in a.bytes().map(|c| c.to_ascii_lowercase()).enumerate() {
+		let mut diagonal = row[0];
+		row[0] = i + 1;
+		for (j, &b) in b.iter().enumerate() {
+			let substitute = diagonal + usize::from(a != b);
+			diagonal = row[j + 1];
+			row[j + 1] = substitute.min(row[j] + 1).min(diagonal + 1);
+		}
+	}
+	row[b.len()]
+}
+
+// Returns the candidates closest to `name`, closest first. Candidates that differ in more than a
+// third of the name, or in more than three characters for short names, are too different to be a
+// typo, or a mismatch of decorations like `_` and `@8`.
+fn closest<'a>(name: &str, candidates: impl IntoIterator<Item = &'a str>) -> Vec<&'a str> {
+	let limit = (name.len() / 3).max(3);
+	let mut matches: Vec<(usize, &str)> = candidates
+		.into_iter()
+		.filter(|candidate| *candidate != name)
+		.map(|candidate| (distance(name, candidate), candidate))
+		.filter(|(distance, _)| *distance <= limit)
+		.collect();
+	matches.sort_unstable();
+	matches.dedup_by_key(|(_, candidate)| *candidate);
+	matches
+		.into_iter()
+		.take(MAX_SUGGESTIONS)
+		.map(|(_, candidate)| candidate)
+		.collect()
+}
+
+// Appends the names closest to `name` to the error, if there are any.
+pub(crate) fn with_suggestions<'a>(
+	err: io::Error,
+	name: &str,
+	candidates: impl IntoIterator<Item = &'a str>,
+) -> io::Error {
+	let names: Vec<String> = closest(name, candidates)
+		.into_iter()
+		.map(|name| format!("`{name}`"))
+		.collect();
+	let names = match names.as_slice() {
+		[] => return err,
+		[one] => one.clone(),
+		[rest @ .., last] => format!("{} or {last}", rest.join(", ")),
+	};
+	io::Error::new(err.kind(), format!("{err} (did you mean {names}?)"))
+}
@@ -9,6 +9,13 @@ use crate::os::unix as imp;
 #[cfg(windows)]
 use crate::os::windows as imp;
 
+/// An opaque symbol exported by a dynamic library.
+///
+/// Symbols are only handled through raw pointers, such as those returned by [`Library::symbol`].
+/// Platform specific functionality is provided through extension traits, such as
+/// [`SymExt`](crate::os::unix::SymExt) on unix.
+///
+/// [`Library::symbol`]: crate::Library::symbol
 #[repr(C)]
 pub struct Symbol {
 	_data: [u8; 0],
@@ -23,3 +30,7 @@ impl Symbol {
 		unsafe { imp::base_addr(this.cast()).as_ref() }
 	}
 }
+
+/// A deprecated alias of [`Symbol`].
+#[deprecated(since = "0.14.0", note = "use `Symbol` instead")]
+pub type Sym = Symbol;
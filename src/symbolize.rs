@@ -0,0 +1,146 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Resolves addresses in the current process to the symbols that contain them.
+//!
+//! Symbols are looked up with `dladdr` on Unix and dbghelp on Windows. When neither
+//! can name the address, the export table of the containing image is scanned instead, which is
+//! [approximate](Location::is_approximate) if the exports don't record their sizes.
+
+#[cfg(feature = "zeroize")]
+use crate::scrub::Scrub;
+use crate::{
+	img,
+	imp,
+};
+use std::{
+	ffi,
+	io,
+	path,
+};
+
+/// The symbolic location of an address.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Location {
+	path: Option<path::PathBuf>,
+	name: Option<String>,
+	offset: usize,
+	approximate: bool,
+}
+
+impl Location {
+	/// Returns the path of the image containing the address, if known.
+	#[inline]
+	pub fn path(&self) -> Option<&path::Path> {
+		self.path.as_deref()
+	}
+
+	/// Returns the name of the symbol containing the address, if any.
+	#[inline]
+	pub fn name(&self) -> Option<&str> {
+		self.name.as_deref()
+	}
+
+	/// Returns the offset of the address from the start of the symbol.
+	///
+	/// If no symbol was found, the offset is relative to the image base instead.
+	#[inline]
+	pub fn offset(&self) -> usize {
+		self.offset
+	}
+
+	/// Returns `true` if the name is of the nearest export preceding the address, rather than of a
+	/// symbol known to contain it.
+	///
+	/// Export tables don't record the sizes of functions on Windows, so an address inside a private
+	/// function is attributed to whichever export precedes it. Such names are only a hint.
+	#[inline]
+	pub fn is_approximate(&self) -> bool {
+		self.approximate
+	}
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for Location {
+	fn drop(&mut self) {
+		self.path.scrub();
+		self.name.scrub();
+	}
+}
+
+/// Resolves an address to its image path, symbol name, and offset.
+///
+/// # Errors
+///
+/// May error if the address doesn't belong to an image loaded in the current process.
+///
+/// # Platform behavior
+///
+/// On Windows the symbol handler of dbghelp is initialized for the current process on first use,
+/// and kept for the lifetime of the process. dbghelp isn't thread-safe, so calls into it are
+/// serialized by an internal lock, and this function may be called from any thread. Other code in
+/// the process using dbghelp directly must not call `SymCleanup`.
+/// If dbghelp isn't available, or has no debug information for the image, the result is based on
+/// the export table and is approximate.
+///
+/// # Examples
+///
+/// ```
+/// # #[cfg(unix)] {
+/// use dylink::{Library, symbolize};
+///
+/// let this = Library::this();
+/// let atoi = this.symbol("atoi").unwrap();
+/// let location = symbolize::resolve(atoi.cast()).unwrap();
+/// assert_eq!(location.name(), Some("atoi"));
+/// assert_eq!(location.offset(), 0);
+/// # }
+/// ```
+pub fn resolve(addr: *const ffi::c_void) -> io::Result<Location> {
+	let image = unsafe { imp::base_addr(addr.cast()).as_ref() }.ok_or_else(|| {
+		io::Error::new(
+			io::ErrorKind::NotFound,
+			"address does not belong to a loaded image",
+		)
+	})?;
+	let path = image.path().ok();
+	if let Some((name, start, approximate)) = unsafe { imp::nearest_symbol(addr as usize) } {
+		return Ok(Location {
+			path,
+			name: Some(name),
+			offset: (addr as usize).wrapping_sub(start),
+			approximate,
+		});
+	}
+	let rva = (addr as usize).wrapping_sub(image as *const img::Image as usize);
+	let nearest = image
+		.exports()
+		.unwrap_or_default()
+		.into_iter()
+		.filter(|export| {
+			matches!(
+				export.kind(),
+				img::ExportKind::Function | img::ExportKind::Unknown
+			) && export.forwarder().is_none()
+				&& export.rva() <= rva
+				&& export
+					.size()
+					.is_none_or(|size| size == 0 || rva < export.rva() + size)
+		})
+		.filter(|export| export.name().is_some())
+		.max_by_key(img::Export::rva);
+	Ok(match nearest {
+		Some(mut export) => Location {
+			path,
+			offset: rva - export.rva(),
+			approximate: export.size().is_none_or(|size| size == 0),
+			name: export.name.take(),
+		},
+		None => Location {
+			path,
+			name: None,
+			offset: rva,
+			approximate: false,
+		},
+	})
+}
@@ -1,25 +1,478 @@
 // SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
 // SPDX-License-Identifier: MIT OR Apache-2.0
 
+//! Libraries loaded once and shared between threads, and the items of the `dylink` macro.
+//!
+//! # Opening libraries and resolving symbols
+//!
+//! Custom loading is split between two traits, which each cover one step, and compose:
+//!
+//! - [`Open`] decides how a [`LibLock`] obtains its [`Library`] from each candidate path, such as
+//!   [`Pinned`], [`Sandboxed`] or [`Embedded`]. The `LibLock` keeps its candidates, reports,
+//!   capabilities, unloading and symbol cache, whatever opened the library.
+//! - [`Loader`] decides how a symbol is resolved by name, and is what the `dylink` macro calls. It's
+//!   implemented by `LibLock` and `Library`, so a custom [`Open`] reaches the macro through the
+//!   `LibLock` it's given to, with `library = LIB`, or `loader = LIB`.
+//!
+//! Implement [`Open`] when loading produces a [`Library`], and [`Loader`] when symbols come from
+//! elsewhere, such as a proc-address function like [`OpenGl`], or a mock in tests. A
+//! `Loader` can't drive a `LibLock`, since it doesn't produce a library to manage.
+//!
+//! # Migrating custom loaders
+//!
+//! Loaders that opened a library and looked symbols up in it themselves implement [`Open`]
+//! instead, returning the `Library`, and are passed to [`LibLock::with_opener`]. Items of the
+//! macro then keep using `library = LIB`. Loaders that resolved symbols without a library
+//! implement [`Loader::symbol`], and are passed with `loader = LOADER`. Both can be wrapped by
+//! [`Logged`] and [`Chain`].
+
 use std::{
-	ffi::CStr,
+	collections::BTreeMap,
+	ffi::{
+		self,
+		CStr,
+	},
+	fmt,
 	io,
-	sync,
+	path,
+	ptr,
+	sync::{
+		self,
+		atomic::{
+			AtomicU32,
+			AtomicUsize,
+			Ordering,
+		},
+	},
+	time,
+};
+
+mod candidates;
+pub use candidates::Candidates;
+mod chain;
+pub use chain::Chain;
+mod egl;
+pub use egl::{
+	EGL,
+	Egl,
+};
+mod embedded;
+pub use embedded::Embedded;
+mod logged;
+pub use logged::{
+	LogEvent,
+	Logged,
+};
+mod opengl;
+pub use opengl::{
+	OPENGL,
+	OpenGl,
+};
+mod pinned;
+pub use pinned::Pinned;
+mod sandboxed;
+pub use sandboxed::Sandboxed;
+mod sha256;
+
+#[cfg(feature = "unload")]
+use std::{
+	cell,
+	ops,
+	sync::atomic::AtomicPtr,
 };
 
 use crate::{
 	Library,
+	LoadReport,
+	OpenFlags,
 	Symbol,
+	scrub::{
+		Scrub,
+		Scrubbed,
+	},
 };
+#[cfg(feature = "images")]
+use crate::{
+	img,
+	suggest,
+};
+
+/// A check for an optional feature of a library.
+///
+/// Capabilities are declared with [`LibLock::with_capabilities`], and are evaluated together the
+/// first time they are queried.
+///
+/// # Examples
+///
+/// ```rust
+/// use dylink::sync::{Capability, Probe};
+/// use std::{ffi, mem};
+///
+/// static CAPS: [Capability; 2] = [
+///     Capability::new("tls1_3", Probe::Symbol("SSL_CTX_set_ciphersuites")),
+///     Capability::new(
+///         "ktls",
+///         Probe::Version {
+///             symbol: "OpenSSL_version_num",
+///             min: 0x3000_0000,
+///             query: |sym| unsafe {
+///                 mem::transmute::<_, extern "C" fn() -> ffi::c_ulong>(sym)() as u64
+///             },
+///         },
+///     ),
+/// ];
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Capability<'a> {
+	name: &'a str,
+	probe: Probe<'a>,
+}
+
+impl<'a> Capability<'a> {
+	/// Constructs a new `Capability` that is available if `probe` succeeds.
+	#[inline]
+	pub const fn new(name: &'a str, probe: Probe<'a>) -> Self {
+		Self { name, probe }
+	}
+	/// Returns the name of the capability.
+	#[inline]
+	pub const fn name(&self) -> &'a str {
+		self.name
+	}
+	/// Returns the probe used to evaluate the capability.
+	#[inline]
+	pub const fn probe(&self) -> &Probe<'a> {
+		&self.probe
+	}
+}
+
+/// The condition a [`Capability`] is evaluated with.
+#[derive(Debug, Clone, Copy)]
+pub enum Probe<'a> {
+	/// The capability is available if the library exports the symbol.
+	Symbol(&'a str),
+	/// The capability is available if the library exports `symbol`, and `query` returns
+	/// at least `min` when passed the address of `symbol`.
+	///
+	/// `query` is usually a closure that transmutes the address into a version function and calls it.
+	Version {
+		symbol: &'a str,
+		min: u64,
+		query: unsafe fn(*const Symbol) -> u64,
+	},
+}
+
+impl Probe<'_> {
+	fn eval(&self, lib: &Library) -> bool {
+		match *self {
+			Self::Symbol(symbol) => lib.symbol(symbol).is_ok(),
+			Self::Version { symbol, min, query } => lib
+				.symbol(symbol)
+				.is_ok_and(|sym| unsafe { query(sym) } >= min),
+		}
+	}
+}
+
+// The library, or the report of every candidate that failed to open.
+type Loaded = Result<Opened, LoadReport>;
+
+// A library opened by a `LibLock`, along with the candidate it was opened from, which is `None`
+// for the program itself, or a library passed to `set`.
+#[derive(Debug)]
+struct Opened {
+	lib: Library,
+	path: Scrubbed<Option<path::PathBuf>>,
+}
+
+/// A way of opening the libraries of a [`LibLock`].
+///
+/// [`System`] opens them with the system loader, like [`Library::open_with`]. Other openers can
+/// verify a library before it's opened, open it from a different location, or return a library
+/// that is already loaded, such as [`Library::this`]. Unlike [`Loader`], which resolves symbols for
+/// the `dylink` macro, this only decides how a `LibLock` obtains its library. See the
+/// [module documentation](self#opening-libraries-and-resolving-symbols) for how the two relate.
+///
+/// # Examples
+///
+/// ```rust
+/// use dylink::{Library, OpenFlags, sync::{LibLock, Open}};
+/// use std::{io, path::Path};
+///
+/// // Only opens libraries from a trusted directory.
+/// struct Trusted;
+/// impl Open for Trusted {
+///     fn open(&self, path: &Path, flags: OpenFlags) -> io::Result<Library> {
+///         if !path.starts_with("/opt/myapp/lib") {
+///             return Err(io::Error::new(io::ErrorKind::PermissionDenied, "untrusted library"));
+///         }
+///         Library::open_with(path, flags)
+///     }
+/// }
+///
+/// static LIBFOO: LibLock<Trusted> = LibLock::with_opener(&["/opt/myapp/lib/libfoo.so"], Trusted);
+/// ```
+pub trait Open {
+	/// Opens the library at `path` with `flags`.
+	///
+	/// # Errors
+	///
+	/// May error if the library can't be opened, in which case the next candidate is tried.
+	fn open(&self, path: &path::Path, flags: OpenFlags) -> io::Result<Library>;
+}
+
+/// The system loader, which is the default [`Open`] of a [`LibLock`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct System;
+
+impl Open for System {
+	#[inline]
+	fn open(&self, path: &path::Path, flags: OpenFlags) -> io::Result<Library> {
+		Library::open_with(path, flags)
+	}
+}
+
+#[cfg(not(feature = "unload"))]
+type ResetCell<T> = sync::OnceLock<T>;
+
+// The cell can be reset through a shared reference by `LibLock::unload`, whose caller guarantees
+// that the library isn't in use.
+#[cfg(feature = "unload")]
+#[derive(Debug)]
+struct ResetCell<T>(cell::UnsafeCell<sync::OnceLock<T>>);
+
+#[cfg(feature = "unload")]
+unsafe impl<T: Send + Sync> Sync for ResetCell<T> {}
+
+#[cfg(feature = "unload")]
+impl<T> ResetCell<T> {
+	const fn new() -> Self {
+		Self(cell::UnsafeCell::new(sync::OnceLock::new()))
+	}
+	fn into_inner(self) -> Option<T> {
+		self.0.into_inner().into_inner()
+	}
+	// Safety: the cell must not be borrowed.
+	unsafe fn reset(&self) -> Option<T> {
+		unsafe { &mut *self.0.get() }.take()
+	}
+}
+
+#[cfg(feature = "unload")]
+impl<T> ops::Deref for ResetCell<T> {
+	type Target = sync::OnceLock<T>;
+	fn deref(&self) -> &Self::Target {
+		unsafe { &*self.0.get() }
+	}
+}
+
+#[cfg(feature = "unload")]
+impl<T> ops::DerefMut for ResetCell<T> {
+	fn deref_mut(&mut self) -> &mut Self::Target {
+		self.0.get_mut()
+	}
+}
+
+/// A snapshot of the state of a [`LibLock`], returned by [`LibLock::status`].
+#[derive(Debug, Clone)]
+pub struct LibStatus {
+	loaded: bool,
+	path: Option<path::PathBuf>,
+	resolved: usize,
+	failed: usize,
+}
+
+impl LibStatus {
+	/// Returns `true` if the library was open.
+	#[inline]
+	pub fn is_loaded(&self) -> bool {
+		self.loaded
+	}
+	/// Returns the candidate the library was opened from.
+	///
+	/// Returns `None` if the library wasn't open, or if the `LibLock` held the program itself, or a
+	/// library passed to [`set`](LibLock::set).
+	#[inline]
+	pub fn path(&self) -> Option<&path::Path> {
+		self.path.as_deref()
+	}
+	/// Returns how many symbols were resolved through the loader since the library was opened.
+	///
+	/// Addresses served by the symbol cache aren't counted, so with the cache enabled, this is the
+	/// number of distinct symbols looked up by name, along with those looked up by ordinal or version.
+	#[inline]
+	pub fn resolved(&self) -> usize {
+		self.resolved
+	}
+	/// Returns how many lookups failed since the library was opened.
+	#[inline]
+	pub fn failed(&self) -> usize {
+		self.failed
+	}
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for LibStatus {
+	fn drop(&mut self) {
+		self.path.scrub();
+	}
+}
+
+impl fmt::Display for LibStatus {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match (self.loaded, &self.path) {
+			(false, _) => f.write_str("not loaded")?,
+			(true, Some(path)) => write!(f, "loaded from `{}`", path.display())?,
+			(true, None) => f.write_str("loaded")?,
+		}
+		write!(
+			f,
+			", {} symbols resolved, {} failed",
+			self.resolved, self.failed
+		)
+	}
+}
+
+/// The outcome of resolving several symbols at once with [`LibLock::preload`].
+#[derive(Debug)]
+pub struct PreloadReport<'n> {
+	resolved: Vec<(&'n str, *const Symbol)>,
+	missing: Vec<(&'n str, io::Error)>,
+}
+
+impl<'n> PreloadReport<'n> {
+	/// Returns the names that resolved, along with their addresses, in the order they were passed.
+	#[inline]
+	pub fn resolved(&self) -> &[(&'n str, *const Symbol)] {
+		&self.resolved
+	}
+	/// Returns the names that failed to resolve, along with their errors, in the order they were
+	/// passed.
+	#[inline]
+	pub fn missing(&self) -> &[(&'n str, io::Error)] {
+		&self.missing
+	}
+	/// Returns `true` if every name resolved.
+	#[inline]
+	pub fn is_complete(&self) -> bool {
+		self.missing.is_empty()
+	}
+}
+
+impl fmt::Display for PreloadReport<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(
+			f,
+			"resolved {} of {} symbols",
+			self.resolved.len(),
+			self.resolved.len() + self.missing.len()
+		)?;
+		for (i, (name, err)) in self.missing.iter().enumerate() {
+			let sep = if i == 0 { ", missing " } else { ", " };
+			write!(f, "{sep}`{name}` ({err})")?;
+		}
+		Ok(())
+	}
+}
+
+// Candidates set at run-time, which are tried before those passed to the constructor, or in place
+// of them.
+#[derive(Debug)]
+struct RuntimeLibs {
+	paths: Vec<path::PathBuf>,
+	replace: bool,
+	// whether the library started to be opened, after which the candidates can't change
+	started: bool,
+}
+
+// Addresses of symbols looked up by name, so that repeated lookups don't go through the loader.
+#[derive(Debug)]
+struct SymbolCache(sync::RwLock<BTreeMap<String, usize>>);
+
+impl SymbolCache {
+	const fn new() -> Self {
+		Self(sync::RwLock::new(BTreeMap::new()))
+	}
+	fn get(&self, name: &str) -> Option<*const Symbol> {
+		let map = self.0.read().unwrap_or_else(|err| err.into_inner());
+		map.get(name).map(|&addr| addr as *const Symbol)
+	}
+	fn insert(&self, name: &str, addr: *const Symbol) {
+		let mut map = self.0.write().unwrap_or_else(|err| err.into_inner());
+		map.insert(name.to_owned(), addr as usize);
+	}
+	fn clear(&self) {
+		let map = std::mem::take(&mut *self.0.write().unwrap_or_else(|err| err.into_inner()));
+		for mut name in map.into_keys() {
+			name.scrub();
+		}
+	}
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for SymbolCache {
+	fn drop(&mut self) {
+		self.clear();
+	}
+}
+
+// The paths a `LibLock` attempts to load from, which are usually string literals.
+#[derive(Debug)]
+enum Libs<'a> {
+	Str(&'a [&'a str]),
+	Path(&'a [&'a path::Path]),
+	Owned(Vec<path::PathBuf>),
+}
+
+impl Libs<'_> {
+	fn to_paths(&self) -> Vec<&path::Path> {
+		match self {
+			Self::Str(libs) => libs.iter().map(path::Path::new).collect(),
+			Self::Path(libs) => libs.to_vec(),
+			Self::Owned(libs) => libs.iter().map(path::PathBuf::as_path).collect(),
+		}
+	}
+}
 
 /// An object providing access to a lazily loaded LibLock on the filesystem.
 ///
-/// This object is designed to be used with [`dylink`](crate::dylink).
+/// This object is designed to be used with [`dylink`](crate::dylink). The library is opened with
+/// `O`, which is the system loader unless constructed with [`with_opener`](Self::with_opener).
 #[derive(Debug)]
-pub struct LibLock<'a> {
-	libs: &'a [&'a str],
+pub struct LibLock<'a, O = System> {
+	libs: Libs<'a>,
 	// LibLock handle
-	hlib: sync::OnceLock<Library>,
+	hlib: ResetCell<Loaded>,
+	caps: &'a [Capability<'a>],
+	// evaluated capabilities
+	caps_cache: ResetCell<Vec<(&'a str, bool)>>,
+	// environment variable holding a path to try first
+	env: Option<&'a str>,
+	// whether failed lookups suggest similar exports
+	#[cfg(feature = "images")]
+	suggest: bool,
+	// addresses of symbols looked up through `symbol`, unless disabled
+	symbols: SymbolCache,
+	cache_symbols: bool,
+	// flags to open the library with, and the flags it was opened with, or all bits if it wasn't
+	// opened from a path
+	flags: AtomicU32,
+	opened_flags: AtomicU32,
+	// lookups made through the loader that resolved, and that failed
+	resolved: AtomicUsize,
+	failed: AtomicUsize,
+	// addresses cached by generated functions, along with the value to reset them to
+	#[cfg(feature = "unload")]
+	resets: sync::Mutex<Vec<(&'a AtomicPtr<ffi::c_void>, usize)>>,
+	// candidates set at run-time
+	runtime: sync::Mutex<RuntimeLibs>,
+	// interval after which a failure to open the library is retried, and the last failure
+	retry: Option<time::Duration>,
+	failure: sync::Mutex<Option<(time::Instant, LoadReport)>>,
+	// called after the library is opened, and before it's unloaded
+	on_load: Option<fn(&Library)>,
+	#[cfg(feature = "unload")]
+	on_unload: Option<fn(&Library)>,
+	opener: O,
 }
 
 impl<'a> LibLock<'a> {
@@ -42,12 +495,370 @@ impl<'a> LibLock<'a> {
 	/// ```
 	#[inline]
 	pub const fn new(libs: &'a [&'a str]) -> Self {
+		Self::with_opener(libs, System)
+	}
+
+	/// Constructs a new `LibLock` with capabilities that can be queried through
+	/// [`capability`](Self::capability).
+	///
+	/// See [`new`](Self::new) for how `libs` is used.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use dylink::sync::{Capability, LibLock, Probe};
+	///
+	/// static LIBSSL: LibLock = LibLock::with_capabilities(
+	///     &["libssl.so.3"],
+	///     &[Capability::new("tls1_3", Probe::Symbol("SSL_CTX_set_ciphersuites"))],
+	/// );
+	/// ```
+	#[inline]
+	pub const fn with_capabilities(libs: &'a [&'a str], caps: &'a [Capability<'a>]) -> Self {
+		Self::with_opener(libs, System).capabilities_of(caps)
+	}
+
+	/// Constructs a new `LibLock` from paths, which unlike [`new`](Self::new) may not be valid
+	/// UTF-8, such as directories chosen by the user.
+	///
+	/// See [`new`](Self::new) for how `paths` is used.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use dylink::sync::LibLock;
+	/// use std::{env, path::Path, sync::LazyLock};
+	///
+	/// static PLUGIN: LazyLock<LibLock> = LazyLock::new(|| {
+	///     let dir = env::current_exe().unwrap().with_file_name("plugins");
+	///     let path: &Path = Box::leak(dir.join("libfoo.so").into_boxed_path());
+	///     LibLock::from_paths(Box::leak(Box::new([path])))
+	/// });
+	/// ```
+	#[inline]
+	pub const fn from_paths(paths: &'a [&'a path::Path]) -> Self {
+		Self::with_libs(Libs::Path(paths), System)
+	}
+
+	/// Constructs a new `LibLock` from a list of candidates composed with [`Candidates`].
+	///
+	/// See [`new`](Self::new) for how the candidates are used.
+	pub fn from_candidates(candidates: &Candidates) -> Self {
+		Self::with_libs(Libs::Owned(candidates.paths().to_vec()), System)
+	}
+}
+
+impl<'a, O: Open> LibLock<'a, O> {
+	/// Constructs a new `LibLock` whose libraries are opened with `opener`.
+	///
+	/// See [`new`](LibLock::new) for how `libs` is used. If `libs` is empty, the program is loaded
+	/// without calling `opener`.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use dylink::{Library, OpenFlags, sync::{LibLock, Open}};
+	/// use std::{io, path::Path};
+	///
+	/// // Resolves symbols from the process, whatever the path, such as when the library is
+	/// // linked statically in some builds.
+	/// struct This;
+	/// impl Open for This {
+	///     fn open(&self, _: &Path, _: OpenFlags) -> io::Result<Library> {
+	///         Ok(Library::this())
+	///     }
+	/// }
+	///
+	/// static LIBFOO: LibLock<This> = LibLock::with_opener(&["libfoo.so.1"], This);
+	/// ```
+	#[inline]
+	pub const fn with_opener(libs: &'a [&'a str], opener: O) -> Self {
+		Self::with_libs(Libs::Str(libs), opener)
+	}
+
+	const fn with_libs(libs: Libs<'a>, opener: O) -> Self {
 		Self {
 			libs,
-			hlib: sync::OnceLock::new(),
+			hlib: ResetCell::new(),
+			caps: &[],
+			caps_cache: ResetCell::new(),
+			env: None,
+			#[cfg(feature = "images")]
+			suggest: false,
+			symbols: SymbolCache::new(),
+			cache_symbols: true,
+			flags: AtomicU32::new(OpenFlags::DEFAULT.bits()),
+			opened_flags: AtomicU32::new(u32::MAX),
+			resolved: AtomicUsize::new(0),
+			failed: AtomicUsize::new(0),
+			#[cfg(feature = "unload")]
+			resets: sync::Mutex::new(Vec::new()),
+			runtime: sync::Mutex::new(RuntimeLibs {
+				paths: Vec::new(),
+				replace: false,
+				started: false,
+			}),
+			retry: None,
+			failure: sync::Mutex::new(None),
+			on_load: None,
+			#[cfg(feature = "unload")]
+			on_unload: None,
+			opener,
+		}
+	}
+
+	/// Replaces the paths passed to the constructor with `paths`, which may not be valid UTF-8.
+	///
+	/// This is the same as [`from_paths`](LibLock::from_paths), for a `LibLock` constructed with
+	/// [`with_opener`](Self::with_opener).
+	#[inline]
+	pub fn candidate_paths(mut self, paths: &'a [&'a path::Path]) -> Self {
+		self.libs = Libs::Path(paths);
+		self
+	}
+
+	/// Replaces the paths passed to the constructor with a list of candidates composed with
+	/// [`Candidates`].
+	///
+	/// This is the same as [`from_candidates`](LibLock::from_candidates), for a `LibLock`
+	/// constructed with [`with_opener`](Self::with_opener).
+	#[inline]
+	pub fn candidates(mut self, candidates: &Candidates) -> Self {
+		self.libs = Libs::Owned(candidates.paths().to_vec());
+		self
+	}
+
+	/// Sets the capabilities that can be queried through [`capability`](Self::capability).
+	///
+	/// This is the same as [`with_capabilities`](LibLock::with_capabilities), for a `LibLock`
+	/// constructed with [`with_opener`](Self::with_opener).
+	#[inline]
+	pub const fn capabilities_of(mut self, caps: &'a [Capability<'a>]) -> Self {
+		self.caps = caps;
+		self
+	}
+
+	/// Sets an environment variable that can override the library path at run-time.
+	///
+	/// If the variable is set when the `LibLock` is initialized, its value is tried first as the
+	/// path of the library, before any of the paths passed to the constructor. This lets users
+	/// point an application at a custom build of a library without recompiling it. If the variable
+	/// is empty, or its path fails to load, the other paths are tried as usual.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use dylink::sync::LibLock;
+	///
+	/// static LIBFOO: LibLock = LibLock::new(&["libfoo.so.1"]).env_override("MYAPP_FOO_PATH");
+	/// ```
+	#[inline]
+	pub const fn env_override(mut self, var: &'a str) -> Self {
+		self.env = Some(var);
+		self
+	}
+
+	/// Makes [`symbol`](Self::symbol) suggest similar names when a symbol fails to resolve.
+	///
+	/// The exports of the library are searched for up to three names that are closest to the one
+	/// requested, which are added to the error, such as "did you mean `SDL_Init`?". This helps with
+	/// typos, and with names whose case or decorations don't match. Since the whole export table is
+	/// searched, it's only done when a lookup fails.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use dylink::sync::LibLock;
+	///
+	/// static LIBSDL: LibLock = LibLock::new(&["libSDL2-2.0.so.0"]).suggest_names();
+	///
+	/// let err = LIBSDL.symbol("SDL_init").unwrap_err();
+	/// assert!(err.to_string().contains("did you mean `SDL_Init`?"));
+	/// ```
+	#[cfg_attr(docsrs, doc(cfg(feature = "images")))]
+	#[cfg(feature = "images")]
+	#[inline]
+	pub const fn suggest_names(mut self) -> Self {
+		self.suggest = true;
+		self
+	}
+
+	/// Makes lookups try to open the library again if it failed to open at least `interval` ago,
+	/// instead of keeping the failure until the `LibLock` is reset.
+	///
+	/// This is meant for libraries that may appear after the process starts, such as plugins
+	/// delivered by an updater. Lookups made within `interval` of a failure return the same error
+	/// without trying the candidates again, which keeps frequently called functions from searching
+	/// the filesystem on every call. Since the failure isn't kept in the cell, [`error`](Self::error)
+	/// returns `None`.
+	///
+	/// Functions generated by [`dylink`](crate::dylink) look their symbol up again on each call
+	/// until it resolves, unless they're bound to a `stub`, an `optional` shim, or a substitute
+	/// returned by `on_error` or the [failure policy](crate::config::Policy), which they keep.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use dylink::sync::LibLock;
+	/// use std::time::Duration;
+	///
+	/// static PLUGIN: LibLock = LibLock::new(&["plugins/libfoo.so"]).retry(Duration::from_secs(5));
+	/// ```
+	#[inline]
+	pub const fn retry(mut self, interval: time::Duration) -> Self {
+		self.retry = Some(interval);
+		self
+	}
+
+	/// Sets a hook called with the library right after it's opened, such as to call an
+	/// initialization function of the library, or to set its allocator.
+	///
+	/// The hook is called while the `LibLock` is being initialized, so other threads looking up
+	/// symbols, including through generated functions, wait until it returns. It must not look up
+	/// symbols through the `LibLock` itself, which would deadlock, but it can through the library it
+	/// is passed. If the hook panics, the panic is propagated to the lookup that opened the library,
+	/// and the `LibLock` is left uninitialized.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use dylink::sync::LibLock;
+	/// use std::mem;
+	///
+	/// static LIBFOO: LibLock = LibLock::new(&["libfoo.so.1"]).on_load(|lib| {
+	///     let init = lib.symbol("foo_init").unwrap();
+	///     unsafe { mem::transmute::<_, unsafe extern "C" fn()>(init)() };
+	/// });
+	/// ```
+	#[inline]
+	pub const fn on_load(mut self, hook: fn(&Library)) -> Self {
+		self.on_load = Some(hook);
+		self
+	}
+
+	/// Sets a hook called with the library right before it's closed by [`unload`](Self::unload),
+	/// such as to call a teardown function of the library.
+	///
+	/// The hook isn't called when the library is taken out of the `LibLock`, or leaked by
+	/// `cr::after_restore`.
+	#[cfg_attr(docsrs, doc(cfg(feature = "unload")))]
+	#[cfg(feature = "unload")]
+	#[inline]
+	pub const fn on_unload(mut self, hook: fn(&Library)) -> Self {
+		self.on_unload = Some(hook);
+		self
+	}
+
+	/// Sets whether [`symbol`](Self::symbol) remembers the addresses it looks up, which it does by
+	/// default.
+	///
+	/// Each address is looked up through the loader once, and later lookups of the same name are
+	/// served from a map, which avoids allocating the name and calling into the loader again. This
+	/// helps layers that dispatch by name at run-time. Failed lookups aren't remembered. The map is
+	/// cleared when the library is taken out of the `LibLock`, or unloaded.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use dylink::sync::LibLock;
+	///
+	/// // symbols are only looked up once by generated functions.
+	/// static LIBFOO: LibLock = LibLock::new(&["libfoo.so.1"]).symbol_cache(false);
+	/// ```
+	#[inline]
+	pub const fn symbol_cache(mut self, enabled: bool) -> Self {
+		self.cache_symbols = enabled;
+		self
+	}
+
+	/// Sets the flags the library is opened with, in place of [`OpenFlags::DEFAULT`].
+	///
+	/// This is mostly useful on Windows, where flags such as
+	/// [`OpenFlags::SYSTEM`] restrict the directories that are searched for the library and its
+	/// dependencies, which prevents [preloading attacks].
+	///
+	/// [preloading attacks]: https://learn.microsoft.com/en-us/windows/win32/dlls/dynamic-link-library-security
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use dylink::{OpenFlags, sync::LibLock};
+	///
+	/// static USER32: LibLock = LibLock::new(&["user32.dll"]).open_flags(OpenFlags::SYSTEM);
+	/// ```
+	#[inline]
+	pub const fn open_flags(mut self, flags: OpenFlags) -> Self {
+		self.flags = AtomicU32::new(flags.bits());
+		self
+	}
+
+	/// Binds the functions of the library when they're first called, rather than when it's opened,
+	/// as with [`OpenFlags::lazy`].
+	///
+	/// This reduces the cost of opening large libraries of which only a few functions are called.
+	/// It does nothing on Windows.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use dylink::sync::LibLock;
+	///
+	/// static LIBCUDA: LibLock = LibLock::new(&["libcuda.so.1"]).lazy_binding();
+	/// ```
+	#[inline]
+	pub const fn lazy_binding(mut self) -> Self {
+		let flags = OpenFlags::from_bits(self.flags.into_inner());
+		self.flags = AtomicU32::new(flags.lazy().bits());
+		self
+	}
+
+	// Used by `dylink` to add flags required by a binding before the library is opened. Errors if
+	// the library was already opened without them.
+	#[doc(hidden)]
+	pub fn require_flags(&self, flags: OpenFlags) -> io::Result<()> {
+		self.flags.fetch_or(flags.bits(), Ordering::Relaxed);
+		self.library()?;
+		let opened = OpenFlags::from_bits(self.opened_flags.load(Ordering::Relaxed));
+		if opened.contains(flags) {
+			Ok(())
+		} else {
+			Err(io::Error::new(
+				io::ErrorKind::InvalidInput,
+				format!(
+					"library was already opened without flags {:#x}",
+					flags.bits() & !opened.bits()
+				),
+			))
 		}
 	}
 
+	/// Evaluates the declared capabilities once, and returns each name along with whether
+	/// it is available.
+	///
+	/// This will lazily initialize the LibLock. If the library couldn't be loaded, every capability
+	/// is unavailable.
+	pub fn capabilities(&self) -> &[(&'a str, bool)] {
+		self.caps_cache.get_or_init(|| {
+			let lib = self.library().ok();
+			self.caps
+				.iter()
+				.map(|cap| (cap.name, lib.is_some_and(|lib| cap.probe.eval(lib))))
+				.collect()
+		})
+	}
+
+	/// Returns `true` if the capability named `name` is available.
+	///
+	/// Undeclared capabilities are never available, and neither are any capabilities if the library
+	/// couldn't be loaded.
+	///
+	/// This will lazily initialize the LibLock.
+	pub fn capability(&self, name: &str) -> bool {
+		self.capabilities()
+			.iter()
+			.any(|&(cap, available)| cap == name && available)
+	}
+
 	/// May block if another thread is currently attempting to initialize the cell.
 	///
 	/// This will lazily initialize the LibLock.
@@ -58,10 +869,6 @@ impl<'a> LibLock<'a> {
 	///
 	/// If the requested symbol does not exist in the dynamic library, then this call will return an error.
 	///
-	/// # Panics
-	///
-	/// Panics if library cannot be initialized
-	///
 	/// # Examples
 	///
 	/// ```no_run
@@ -73,17 +880,47 @@ impl<'a> LibLock<'a> {
 	/// let my_symbol: unsafe extern "C" fn() = unsafe {mem::transmute(sym)};
 	/// ```
 	pub fn symbol(&self, name: &str) -> io::Result<*const Symbol> {
-		let lib = self.hlib.get_or_init(|| {
-			if self.libs.is_empty() {
-				Library::this()
-			} else {
-				self.libs
-					.iter()
-					.find_map(|path| Library::open(path).ok())
-					.unwrap()
+		let lib = self.library()?;
+		if !self.cache_symbols {
+			return self.lookup(lib, name);
+		}
+		if let Some(symbol) = self.symbols.get(name) {
+			return Ok(symbol);
+		}
+		let symbol = self.lookup(lib, name)?;
+		self.symbols.insert(name, symbol);
+		Ok(symbol)
+	}
+
+	fn lookup(&self, lib: &Library, name: &str) -> io::Result<*const Symbol> {
+		#[cfg(feature = "images")]
+		let symbol = lib.symbol(name).map_err(|err| match self.suggest {
+			true => {
+				let exports = lib.to_image().and_then(img::Image::exports);
+				let exports = exports.unwrap_or_default();
+				suggest::with_suggestions(err, name, exports.iter().filter_map(img::Export::name))
 			}
+			false => err,
 		});
-		lib.symbol(name)
+		#[cfg(not(feature = "images"))]
+		let symbol = lib.symbol(name);
+		self.count(symbol)
+	}
+
+	// Identifies the `LibLock` in the registry.
+	#[cfg(feature = "registry")]
+	fn id(&self) -> usize {
+		self as *const Self as usize
+	}
+
+	// Records the outcome of a lookup made through the loader.
+	fn count(&self, symbol: io::Result<*const Symbol>) -> io::Result<*const Symbol> {
+		let counter = match symbol {
+			Ok(_) => &self.resolved,
+			Err(_) => &self.failed,
+		};
+		counter.fetch_add(1, Ordering::Relaxed);
+		symbol
 	}
 
 	/// May block if another thread is currently attempting to initialize the cell. The difference
@@ -99,10 +936,6 @@ impl<'a> LibLock<'a> {
 	///
 	/// If the requested symbol does not exist in the dynamic library, then this call will return an error.
 	///
-	/// # Panics
-	///
-	/// Panics if library cannot be initialized
-	///
 	/// # Examples
 	///
 	/// ```no_run
@@ -114,45 +947,653 @@ impl<'a> LibLock<'a> {
 	/// let my_symbol: unsafe extern "C" fn() = unsafe {mem::transmute(sym)};
 	/// ```
 	pub fn raw_symbol(&self, name: &CStr) -> *const Symbol {
-		let lib = self.hlib.get_or_init(|| {
-			if self.libs.is_empty() {
-				Library::this()
-			} else {
-				self.libs
-					.iter()
-					.find_map(|path| Library::open(path).ok())
-					.unwrap()
+		self.library()
+			.map_or(ptr::null(), |lib| lib.raw_symbol(name))
+	}
+
+	/// May block if another thread is currently attempting to initialize the cell. The difference
+	/// from [`symbol`] is that this function looks up the symbol by its export ordinal.
+	///
+	/// This will lazily initialize the LibLock.
+	///
+	/// [`symbol`]: self::LibLock::symbol
+	///
+	/// # Errors
+	///
+	/// If [`LibLock`] failed to be initialized, then this call will return an error.
+	///
+	/// If no symbol is exported with the ordinal, then this call will return an error.
+	#[cfg_attr(docsrs, doc(cfg(windows)))]
+	#[cfg(windows)]
+	pub fn symbol_ordinal(&self, ordinal: u16) -> io::Result<*const Symbol> {
+		let symbol = self.library()?.symbol_ordinal(ordinal);
+		self.count(symbol)
+	}
+
+	/// May block if another thread is currently attempting to initialize the cell. The difference
+	/// from [`symbol`] is that this function looks up a specific version of the symbol.
+	///
+	/// This will lazily initialize the LibLock.
+	///
+	/// [`symbol`]: self::LibLock::symbol
+	///
+	/// # Errors
+	///
+	/// If [`LibLock`] failed to be initialized, then this call will return an error.
+	///
+	/// If the symbol does not exist with the requested version, then this call will return an error.
+	#[cfg_attr(docsrs, doc(cfg(all(unix, target_env = "gnu"))))]
+	#[cfg(all(unix, target_env = "gnu"))]
+	pub fn symbol_version(&self, name: &str, version: &str) -> io::Result<*const Symbol> {
+		let symbol = self.library()?.symbol_version(name, version);
+		self.count(symbol)
+	}
+
+	// Lazily opens the first library that can be loaded. Failures are kept until the `LibLock` is
+	// reset, unless it retries.
+	fn library(&self) -> io::Result<&Library> {
+		let loaded = match (self.retry, self.hlib.get()) {
+			(Some(interval), None) => return self.library_retry(interval),
+			_ => self.hlib.get_or_init(|| self.open()),
+		};
+		match loaded {
+			Ok(opened) => Ok(&opened.lib),
+			Err(report) => Err(report.to_error()),
+		}
+	}
+
+	// Opens the library without keeping a failure in the cell, so that it can be tried again. The
+	// last failure is kept instead, until the interval elapses.
+	fn library_retry(&self, interval: time::Duration) -> io::Result<&Library> {
+		let mut failure = self.failure.lock().unwrap_or_else(|err| err.into_inner());
+		if let Some(Ok(opened)) = self.hlib.get() {
+			return Ok(&opened.lib);
+		}
+		if let Some((at, report)) = &*failure
+			&& at.elapsed() < interval
+		{
+			return Err(report.to_error());
+		}
+		match self.open() {
+			Ok(opened) => {
+				*failure = None;
+				let _ = self.hlib.set(Ok(opened));
+				drop(failure);
+				self.library()
+			}
+			Err(report) => {
+				let err = report.to_error();
+				*failure = Some((time::Instant::now(), report));
+				// the library can still be opened from other paths.
+				self.runtime
+					.lock()
+					.unwrap_or_else(|err| err.into_inner())
+					.started = false;
+				Err(err)
 			}
+		}
+	}
+
+	// Attempts to open each candidate, trying the path of the environment variable first.
+	fn open(&self) -> Loaded {
+		let flags = OpenFlags::from_bits(self.flags.load(Ordering::Relaxed));
+		let overridden = self
+			.env
+			.and_then(std::env::var_os)
+			.filter(|path| !path.is_empty())
+			.map(path::PathBuf::from);
+		let mut runtime = self.runtime.lock().unwrap_or_else(|err| err.into_inner());
+		runtime.started = true;
+		let mut libs = runtime.paths.clone();
+		if !runtime.replace {
+			libs.extend(self.libs.to_paths().into_iter().map(path::PathBuf::from));
+		}
+		drop(runtime);
+		let fallback = libs.is_empty();
+		let candidates: Vec<path::PathBuf> = overridden.into_iter().chain(libs).collect();
+		let mut opened = None;
+		let report = LoadReport::open_with(&candidates, |path| {
+			let lib = self.opener.open(path, flags)?;
+			opened = Some(path.to_path_buf());
+			Ok(lib)
 		});
-		lib.raw_symbol(name)
+		let loaded = match report.into_result() {
+			Err(_) if fallback => {
+				self.opened_flags.store(u32::MAX, Ordering::Relaxed);
+				Ok(Opened {
+					lib: Library::this(),
+					path: Scrubbed(None),
+				})
+			}
+			loaded => {
+				self.opened_flags.store(flags.bits(), Ordering::Relaxed);
+				loaded.map(|lib| Opened {
+					lib,
+					path: Scrubbed(opened),
+				})
+			}
+		};
+		if let (Some(hook), Ok(opened)) = (self.on_load, &loaded) {
+			hook(&opened.lib);
+		}
+		#[cfg(feature = "registry")]
+		crate::registry::record_load(
+			self.id(),
+			candidates,
+			match &loaded {
+				Ok(opened) => Ok(opened.path.as_deref()),
+				Err(report) => Err(report.to_string()),
+			},
+		);
+		loaded
+	}
+
+	/// Replaces the paths the library is loaded from with `paths`, such as a location read from a
+	/// configuration file or a command line flag.
+	///
+	/// The path of the environment variable set with [`env_override`](Self::env_override) is still
+	/// tried first. If `paths` is empty, the program loads itself.
+	///
+	/// # Errors
+	///
+	/// Returns [`AlreadyExists`](io::ErrorKind::AlreadyExists) if the library was already opened,
+	/// or is being opened.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use dylink::sync::LibLock;
+	///
+	/// static LIBVENDOR: LibLock = LibLock::new(&["libvendor.so.1"]);
+	///
+	/// if let Some(path) = std::env::args_os().nth(1) {
+	///     LIBVENDOR.set_paths([path]).unwrap();
+	/// }
+	/// ```
+	pub fn set_paths<I, P>(&self, paths: I) -> io::Result<()>
+	where
+		I: IntoIterator<Item = P>,
+		P: Into<path::PathBuf>,
+	{
+		self.set_runtime(paths, true)
+	}
+
+	/// Adds `paths` before the paths the library is loaded from, in order.
+	///
+	/// See [`set_paths`](Self::set_paths), which replaces them instead.
+	///
+	/// # Errors
+	///
+	/// Returns [`AlreadyExists`](io::ErrorKind::AlreadyExists) if the library was already opened,
+	/// or is being opened.
+	pub fn prepend_paths<I, P>(&self, paths: I) -> io::Result<()>
+	where
+		I: IntoIterator<Item = P>,
+		P: Into<path::PathBuf>,
+	{
+		self.set_runtime(paths, false)
+	}
+
+	fn set_runtime<I, P>(&self, paths: I, replace: bool) -> io::Result<()>
+	where
+		I: IntoIterator<Item = P>,
+		P: Into<path::PathBuf>,
+	{
+		let mut runtime = self.runtime.lock().unwrap_or_else(|err| err.into_inner());
+		if runtime.started || self.hlib.get().is_some() {
+			return Err(io::Error::new(
+				io::ErrorKind::AlreadyExists,
+				"library was already opened",
+			));
+		}
+		let mut paths: Vec<path::PathBuf> = paths.into_iter().map(Into::into).collect();
+		if !replace {
+			paths.append(&mut runtime.paths);
+		}
+		runtime.paths = paths;
+		runtime.replace |= replace;
+		Ok(())
+	}
+
+	/// Opens the library if it isn't already, and returns it.
+	///
+	/// Libraries are otherwise opened by the first lookup, so this lets an application open them up
+	/// front, such as at startup, and handle a failure there. May block if another thread is
+	/// currently attempting to initialize the cell.
+	///
+	/// # Errors
+	///
+	/// If no candidate could be opened, which is also returned by every later lookup. The candidates
+	/// that failed can be retrieved through [`error`](Self::error).
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use dylink::sync::LibLock;
+	///
+	/// static LIBFOO: LibLock = LibLock::new(&["libfoo.so.1"]);
+	///
+	/// if let Err(err) = LIBFOO.try_init() {
+	///     eprintln!("foo support is disabled: {err}");
+	/// }
+	/// ```
+	#[inline]
+	pub fn try_init(&self) -> io::Result<&Library> {
+		self.library()
+	}
+
+	/// Opens the library if it isn't already, and looks up every symbol in `names`.
+	///
+	/// Unlike looking the symbols up one at a time, every name is tried, so startup code can report
+	/// all of the symbols that are missing at once. The addresses are remembered like those looked
+	/// up through [`symbol`](Self::symbol), unless disabled with
+	/// [`symbol_cache`](Self::symbol_cache).
+	///
+	/// # Errors
+	///
+	/// If [`LibLock`] failed to be initialized, then this call will return an error. Symbols that
+	/// fail to resolve are recorded in the report instead.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use dylink::sync::LibLock;
+	///
+	/// static LIBFOO: LibLock = LibLock::new(&["libfoo.so.1"]);
+	///
+	/// let report = LIBFOO.preload(&["foo_init", "foo_run", "foo_exit"]).unwrap();
+	/// if !report.is_complete() {
+	///     panic!("libfoo is too old: {report}");
+	/// }
+	/// ```
+	pub fn preload<'n>(&self, names: &[&'n str]) -> io::Result<PreloadReport<'n>> {
+		self.library()?;
+		let mut report = PreloadReport {
+			resolved: Vec::with_capacity(names.len()),
+			missing: Vec::new(),
+		};
+		for &name in names {
+			match self.symbol(name) {
+				Ok(symbol) => report.resolved.push((name, symbol)),
+				Err(err) => report.missing.push((name, err)),
+			}
+		}
+		Ok(report)
+	}
+
+	/// Opens the library if it isn't already, and calls `f` with it.
+	///
+	/// This is useful for operations the `LibLock` doesn't provide itself, such as enumerating the
+	/// exports of the library. May block if another thread is currently attempting to initialize
+	/// the cell.
+	///
+	/// # Errors
+	///
+	/// If [`LibLock`] failed to be initialized, then this call will return an error, and `f` isn't
+	/// called.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use dylink::sync::LibLock;
+	///
+	/// static LIBFOO: LibLock = LibLock::new(&["libfoo.so.1"]);
+	///
+	/// let exports = LIBFOO.with(|lib| lib.to_image()?.exports()).unwrap().unwrap();
+	/// ```
+	#[inline]
+	pub fn with<R>(&self, f: impl FnOnce(&Library) -> R) -> io::Result<R> {
+		self.library().map(f)
+	}
+
+	/// Opens the library if it isn't already without blocking the thread polling the future, and
+	/// looks up a symbol.
+	///
+	/// Opening a library may take a long time, such as for large GPU drivers, which would otherwise
+	/// stall the worker thread of an async runtime on the first lookup. The library is opened on a
+	/// thread spawned for the load, which completes the future, so this works with any runtime. Once
+	/// the library is open, this is the same as [`symbol`](Self::symbol).
+	///
+	/// # Errors
+	///
+	/// If [`LibLock`] failed to be initialized, then this call will return an error.
+	///
+	/// If the requested symbol does not exist in the dynamic library, then this call will return an error.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use dylink::sync::LibLock;
+	///
+	/// static LIBVULKAN: LibLock = LibLock::new(&["libvulkan.so.1"]);
+	///
+	/// # async fn f() {
+	/// let sym = LIBVULKAN.symbol_async("vkGetInstanceProcAddr").await.unwrap();
+	/// # }
+	/// ```
+	#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+	#[cfg(feature = "async")]
+	pub async fn symbol_async(&'static self, name: &str) -> io::Result<*const Symbol>
+	where
+		'a: 'static,
+		O: Sync,
+	{
+		if self.hlib.get().is_none() {
+			crate::task::Blocking::spawn(move || self.library().map(drop)).await?;
+		}
+		self.symbol(name)
+	}
+
+	/// Returns the report of the candidates that failed to open, if the `LibLock` failed to be
+	/// initialized.
+	///
+	/// When an environment variable is set with [`env_override`](Self::env_override), its path is
+	/// the first candidate. The failure is kept until the `LibLock` is reset, so later lookups fail
+	/// with the same error without trying the candidates again.
+	///
+	/// Returns `None` if the cell is empty, being initialized, or holds a library. This method never
+	/// blocks.
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use dylink::sync::LibLock;
+	///
+	/// static LIBFOO: LibLock = LibLock::new(&["libfoo.so.2", "libfoo.so"]);
+	///
+	/// assert!(LIBFOO.symbol("foo_init").is_err());
+	/// let report = LIBFOO.error().unwrap();
+	/// assert_eq!(report.failures().len(), 2);
+	/// ```
+	#[inline]
+	pub fn error(&self) -> Option<&LoadReport> {
+		self.hlib.get()?.as_ref().err()
+	}
+
+	/// Returns which candidate the library was opened from, and how many lookups were made through
+	/// it, which helps to tell which of several fallback libraries a machine picked.
+	///
+	/// This doesn't open the library, and never blocks.
+	///
+	/// # Examples
+	///
+	/// ```no_run
+	/// use dylink::sync::LibLock;
+	///
+	/// static LIBGL: LibLock = LibLock::new(&["libGL.so.1", "libGL.so", "libOpenGL.so.0"]);
+	///
+	/// eprintln!("libGL: {}", LIBGL.status());
+	/// ```
+	pub fn status(&self) -> LibStatus {
+		let opened = self.hlib.get().and_then(|loaded| loaded.as_ref().ok());
+		LibStatus {
+			loaded: opened.is_some(),
+			path: opened.and_then(|opened| opened.path.clone()),
+			resolved: self.resolved.load(Ordering::Relaxed),
+			failed: self.failed.load(Ordering::Relaxed),
+		}
 	}
 
 	/// Gets the reference to the underlying value.
 	///
-	/// Returns `None` if the cell is empty, or being initialized. This
+	/// Returns `None` if the cell is empty, being initialized, or failed to be initialized. This
 	/// method never blocks.
 	#[inline]
 	pub fn get(&self) -> Option<&Library> {
-		self.hlib.get()
+		self.hlib.get()?.as_ref().ok().map(|opened| &opened.lib)
 	}
 	/// Takes the value out of this `LibLock`, moving it back to an uninitialized state.
 	///
+	/// Functions generated by [`dylink`](crate::dylink) for this `LibLock` are reset, so that their
+	/// symbols are resolved again when they're next called. Evaluated capabilities, and the error
+	/// of a failed initialization, are also discarded.
+	///
 	/// Has no effect and returns `None` if the `LibLock` hasn't been initialized.
 	///
 	/// Safety is guaranteed by requiring a mutable reference.
 	#[inline]
 	pub fn take(&mut self) -> Option<Library> {
-		self.hlib.take()
+		self.reset_state();
+		self.caps_cache.take();
+		self.hlib.take()?.ok().map(|opened| opened.lib)
 	}
 
 	#[inline]
 	pub fn set(&self, value: Library) -> Result<(), Library> {
-		self.hlib.set(value)
+		let opened = Opened {
+			lib: value,
+			path: Scrubbed(None),
+		};
+		match self.hlib.set(Ok(opened)) {
+			Ok(()) => Ok(()),
+			Err(Ok(opened)) => Err(opened.lib),
+			Err(Err(_)) => unreachable!(),
+		}
 	}
 
 	/// Consumes the `LibLock`, returning the `Library`.
 	#[inline]
 	pub fn into_inner(self) -> Option<Library> {
-		self.hlib.into_inner()
+		self.hlib.into_inner()?.ok().map(|opened| opened.lib)
+	}
+
+	/// Closes the library, and resets every function generated by [`dylink`](crate::dylink) for
+	/// this `LibLock`, so that their symbols are resolved again when they're next called.
+	///
+	/// This lets long-running hosts release a plugin, so that it can be replaced on disk and opened
+	/// again. Evaluated capabilities, and the error of a failed initialization, are also discarded.
+	///
+	/// Has no effect and returns `Ok(())` if the `LibLock` hasn't been initialized.
+	///
+	/// # Errors
+	///
+	/// May error if the library fails to close.
+	///
+	/// # Safety
+	///
+	/// No other thread may be using the `LibLock` or any of its generated functions, and references
+	/// returned by [`get`](Self::get), as well as addresses of symbols, must no longer be used.
+	#[cfg_attr(docsrs, doc(cfg(feature = "unload")))]
+	#[cfg(feature = "unload")]
+	pub unsafe fn unload(&self) -> io::Result<()> {
+		if let (Some(hook), Some(lib)) = (self.on_unload, self.get()) {
+			hook(lib);
+		}
+		match unsafe { self.reset() } {
+			Some(lib) => lib.close(),
+			None => Ok(()),
+		}
+	}
+
+	// Resets every generated function, and takes the library out of the cell.
+	#[cfg(feature = "unload")]
+	pub(crate) unsafe fn reset(&self) -> Option<Library> {
+		self.reset_state();
+		unsafe { self.caps_cache.reset() };
+		unsafe { self.hlib.reset() }?.ok().map(|opened| opened.lib)
+	}
+
+	// Resets every generated function, and discards what was derived from the library, other than
+	// the cells of the library and of the capabilities, which are reset by the caller.
+	fn reset_state(&self) {
+		#[cfg(feature = "unload")]
+		{
+			let resets =
+				std::mem::take(&mut *self.resets.lock().unwrap_or_else(|e| e.into_inner()));
+			for (slot, init) in resets {
+				slot.store(init as *mut ffi::c_void, Ordering::Release);
+			}
+		}
+		#[cfg(feature = "registry")]
+		crate::registry::record_reset(self.id());
+		self.failure
+			.lock()
+			.unwrap_or_else(|e| e.into_inner())
+			.take();
+		self.runtime
+			.lock()
+			.unwrap_or_else(|e| e.into_inner())
+			.started = false;
+		self.symbols.clear();
+		self.resolved.store(0, Ordering::Relaxed);
+		self.failed.store(0, Ordering::Relaxed);
+	}
+
+	// Used by `dylink` to register an address it caches, along with the initial value of the cache.
+	#[doc(hidden)]
+	#[inline]
+	pub fn register_reset(
+		&self,
+		slot: &'a sync::atomic::AtomicPtr<ffi::c_void>,
+		init: *mut ffi::c_void,
+	) {
+		#[cfg(feature = "unload")]
+		self.resets
+			.lock()
+			.unwrap_or_else(|e| e.into_inner())
+			.push((slot, init as usize));
+		#[cfg(not(feature = "unload"))]
+		let _ = (slot, init);
+	}
+}
+
+/// A source of symbols for functions generated by the `dylink` macro.
+///
+/// This is implemented by [`LibLock`] and [`Library`], and can be implemented by other types to
+/// control how symbols are resolved, such as through a sandboxed loader. It's used with the
+/// `loader` argument of the macro.
+///
+/// It's also implemented by references, [`Box`], [`Arc`](sync::Arc), [`Rc`](std::rc::Rc) and
+/// [`LazyLock`](sync::LazyLock) of a loader, so a loader implemented once can be shared, or
+/// constructed at run-time, such as a `static LazyLock<LibLock>`, and still be passed to the macro.
+/// To control how a `LibLock` opens its library rather than how symbols are resolved, implement
+/// [`Open`] instead, as described in the [module documentation](self#migrating-custom-loaders).
+///
+/// # Examples
+///
+/// ```rust
+/// use dylink::{Symbol, sync::{LibLock, Loader}};
+/// use std::io;
+///
+/// static LIBC: LibLock = LibLock::new(&["libc.so.6"]);
+///
+/// // Only allows symbols that are known to be safe to call.
+/// struct Allowed;
+/// impl Loader for Allowed {
+///     fn symbol(&self, name: &str) -> io::Result<*const Symbol> {
+///         match name {
+///             "getpid" | "getppid" => LIBC.symbol(name),
+///             _ => Err(io::Error::new(io::ErrorKind::PermissionDenied, "symbol is not allowed")),
+///         }
+///     }
+/// }
+/// ```
+pub trait Loader {
+	/// Looks up a symbol by name.
+	///
+	/// # Errors
+	///
+	/// May error if the symbol can't be resolved.
+	fn symbol(&self, name: &str) -> io::Result<*const Symbol>;
+
+	/// Looks up a symbol by its export ordinal.
+	///
+	/// # Errors
+	///
+	/// Returns [`Unsupported`](io::ErrorKind::Unsupported) unless implemented. Only libraries of
+	/// Windows export symbols by ordinal, so [`Library`] and [`LibLock`] don't implement it
+	/// elsewhere.
+	fn symbol_ordinal(&self, ordinal: u16) -> io::Result<*const Symbol> {
+		let _ = ordinal;
+		Err(io::Error::new(
+			io::ErrorKind::Unsupported,
+			"loader doesn't support ordinals",
+		))
+	}
+
+	/// Looks up a specific version of a symbol.
+	///
+	/// # Errors
+	///
+	/// Returns [`Unsupported`](io::ErrorKind::Unsupported) unless implemented. Symbol versions are
+	/// only resolved on targets using glibc, so [`Library`] and [`LibLock`] don't implement it
+	/// elsewhere.
+	fn symbol_version(&self, name: &str, version: &str) -> io::Result<*const Symbol> {
+		let _ = (name, version);
+		Err(io::Error::new(
+			io::ErrorKind::Unsupported,
+			"loader doesn't support symbol versions",
+		))
+	}
+}
+
+impl<O: Open> Loader for LibLock<'_, O> {
+	#[inline]
+	fn symbol(&self, name: &str) -> io::Result<*const Symbol> {
+		LibLock::symbol(self, name)
+	}
+	#[cfg(windows)]
+	#[inline]
+	fn symbol_ordinal(&self, ordinal: u16) -> io::Result<*const Symbol> {
+		LibLock::symbol_ordinal(self, ordinal)
+	}
+	#[cfg(all(unix, target_env = "gnu"))]
+	#[inline]
+	fn symbol_version(&self, name: &str, version: &str) -> io::Result<*const Symbol> {
+		LibLock::symbol_version(self, name, version)
+	}
+}
+
+// Forwards the loader of a type that dereferences to one.
+macro_rules! forward_loader {
+	($($ty:ty),* $(,)?) => {$(
+		impl<L: Loader + ?Sized> Loader for $ty {
+			#[inline]
+			fn symbol(&self, name: &str) -> io::Result<*const Symbol> {
+				L::symbol(self, name)
+			}
+			#[inline]
+			fn symbol_ordinal(&self, ordinal: u16) -> io::Result<*const Symbol> {
+				L::symbol_ordinal(self, ordinal)
+			}
+			#[inline]
+			fn symbol_version(&self, name: &str, version: &str) -> io::Result<*const Symbol> {
+				L::symbol_version(self, name, version)
+			}
+		}
+	)*};
+}
+
+forward_loader!(&L, Box<L>, sync::Arc<L>, std::rc::Rc<L>);
+
+impl<L: Loader, F: FnOnce() -> L> Loader for sync::LazyLock<L, F> {
+	#[inline]
+	fn symbol(&self, name: &str) -> io::Result<*const Symbol> {
+		L::symbol(self, name)
+	}
+	#[inline]
+	fn symbol_ordinal(&self, ordinal: u16) -> io::Result<*const Symbol> {
+		L::symbol_ordinal(self, ordinal)
+	}
+	#[inline]
+	fn symbol_version(&self, name: &str, version: &str) -> io::Result<*const Symbol> {
+		L::symbol_version(self, name, version)
+	}
+}
+
+impl Loader for Library {
+	#[inline]
+	fn symbol(&self, name: &str) -> io::Result<*const Symbol> {
+		Library::symbol(self, name)
+	}
+	#[cfg(windows)]
+	#[inline]
+	fn symbol_ordinal(&self, ordinal: u16) -> io::Result<*const Symbol> {
+		Library::symbol_ordinal(self, ordinal)
+	}
+	#[cfg(all(unix, target_env = "gnu"))]
+	#[inline]
+	fn symbol_version(&self, name: &str, version: &str) -> io::Result<*const Symbol> {
+		Library::symbol_version(self, name, version)
 	}
 }
@@ -0,0 +1,166 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use std::{
+	env,
+	ffi,
+	path,
+};
+
+/// A list of paths to load a [`LibLock`](super::LibLock) from, composed from several sources.
+///
+/// Paths are tried in the order they're added. Sources that aren't available, such as an unset
+/// environment variable, add nothing, so the list can be built the same way on every platform.
+///
+/// # Examples
+///
+/// ```rust
+/// use dylink::sync::{Candidates, LibLock};
+/// use std::sync::LazyLock;
+///
+/// // tries `$MYAPP_FOO_PATH`, then `<exe dir>/lib/libfoo.so`, then `libfoo.so` on Linux.
+/// static LIBFOO: LazyLock<LibLock> = LazyLock::new(|| {
+///     let name = Candidates::decorate("foo");
+///     LibLock::from_candidates(
+///         Candidates::new()
+///             .env("MYAPP_FOO_PATH")
+///             .exe_relative(format!("lib/{name}"))
+///             .decorated("foo"),
+///     )
+/// });
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Candidates {
+	paths: Vec<path::PathBuf>,
+}
+
+impl Candidates {
+	/// Creates an empty list of candidates.
+	#[inline]
+	pub const fn new() -> Self {
+		Self { paths: Vec::new() }
+	}
+
+	/// Adds a path or library name as is.
+	pub fn name<S: AsRef<ffi::OsStr>>(&mut self, name: S) -> &mut Self {
+		self.paths.push(path::PathBuf::from(name.as_ref()));
+		self
+	}
+
+	/// Adds the path held by the environment variable `var`, if it's set and not empty.
+	///
+	/// The variable is read when it's added, unlike
+	/// [`LibLock::env_override`](super::LibLock::env_override), which reads it when the library is
+	/// opened.
+	pub fn env<S: AsRef<ffi::OsStr>>(&mut self, var: S) -> &mut Self {
+		if let Some(path) = env::var_os(var).filter(|path| !path.is_empty()) {
+			self.paths.push(path.into());
+		}
+		self
+	}
+
+	/// Adds `path` relative to the directory of the current executable, such as `lib/libfoo.so`
+	/// for a library shipped next to the application.
+	///
+	/// Nothing is added if the path of the executable can't be determined.
+	pub fn exe_relative<P: AsRef<path::Path>>(&mut self, path: P) -> &mut Self {
+		let exe = env::current_exe().ok();
+		if let Some(dir) = exe.as_deref().and_then(path::Path::parent) {
+			self.paths.push(dir.join(path));
+		}
+		self
+	}
+
+	/// Adds `name` decorated the way libraries are named on the current platform, as returned by
+	/// [`decorate`](Self::decorate).
+	pub fn decorated(&mut self, name: &str) -> &mut Self {
+		self.paths.push(Self::decorate(name).into());
+		self
+	}
+
+	/// Adds `name` decorated with each of `versions` in order, as returned by
+	/// [`decorate_version`](Self::decorate_version), followed by `name` without a version.
+	///
+	/// Versions are usually listed from the newest, such as `&["3", "1.1"]` for OpenSSL, and the
+	/// version that was opened can be recovered with [`version_of`](Self::version_of).
+	///
+	/// # Examples
+	///
+	/// ```rust
+	/// use dylink::sync::Candidates;
+	///
+	/// let mut candidates = Candidates::new();
+	/// candidates.versioned("ssl", &["3", "1.1"]);
+	/// // `libssl.so.3`, `libssl.so.1.1`, then `libssl.so` on Linux.
+	/// assert_eq!(candidates.paths().len(), 3);
+	/// ```
+	pub fn versioned(&mut self, name: &str, versions: &[&str]) -> &mut Self {
+		for version in versions {
+			self.paths
+				.push(Self::decorate_version(name, version).into());
+		}
+		self.decorated(name)
+	}
+
+	/// Returns the file name of the library `name` on the current platform, which is `foo.dll` on
+	/// Windows, `libfoo.dylib` on Apple platforms, and `libfoo.so` elsewhere.
+	pub fn decorate(name: &str) -> String {
+		format!(
+			"{}{name}{}",
+			env::consts::DLL_PREFIX,
+			env::consts::DLL_SUFFIX
+		)
+	}
+
+	/// Returns the file name of `version` of the library `name` on the current platform.
+	///
+	/// This follows the usual naming of each platform, which is `foo-1_2.dll` on Windows, with dots
+	/// replaced by underscores, `libfoo.1.2.dylib` on Apple platforms, and `libfoo.so.1.2`
+	/// elsewhere.
+	pub fn decorate_version(name: &str, version: &str) -> String {
+		if cfg!(windows) {
+			format!("{name}-{}.dll", version.replace('.', "_"))
+		} else if cfg!(target_vendor = "apple") {
+			format!("lib{name}.{version}.dylib")
+		} else {
+			format!(
+				"{}{name}{}.{version}",
+				env::consts::DLL_PREFIX,
+				env::consts::DLL_SUFFIX
+			)
+		}
+	}
+
+	/// Returns the version of the library `name` that `path` names, as decorated by
+	/// [`decorate_version`](Self::decorate_version), such as `1.1` for `libssl.so.1.1` on Linux.
+	///
+	/// Returns `None` if the file name of `path` isn't a versioned name of the library. This is
+	/// meant for the path a [`LibLock`](super::LibLock) opened, as returned by
+	/// [`LibStatus::path`](super::LibStatus::path).
+	pub fn version_of(name: &str, path: &path::Path) -> Option<String> {
+		let file_name = path.file_name()?.to_str()?;
+		let version = if cfg!(windows) {
+			let version = file_name.strip_prefix(name)?.strip_prefix('-')?;
+			version.strip_suffix(".dll")?.replace('_', ".")
+		} else if cfg!(target_vendor = "apple") {
+			let version = file_name.strip_prefix("lib")?.strip_prefix(name)?;
+			version
+				.strip_prefix('.')?
+				.strip_suffix(".dylib")?
+				.to_owned()
+		} else {
+			let version = file_name
+				.strip_prefix(env::consts::DLL_PREFIX)?
+				.strip_prefix(name)?
+				.strip_prefix(env::consts::DLL_SUFFIX)?;
+			version.strip_prefix('.')?.to_owned()
+		};
+		(!version.is_empty()).then_some(version)
+	}
+
+	/// Returns the candidates in the order they're tried.
+	#[inline]
+	pub fn paths(&self) -> &[path::PathBuf] {
+		&self.paths
+	}
+}
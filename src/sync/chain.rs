@@ -0,0 +1,146 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use super::{
+	Loader,
+	Open,
+};
+use crate::{
+	Library,
+	OpenFlags,
+	Symbol,
+};
+use std::{
+	fmt::Write,
+	io,
+	path,
+};
+
+/// An [`Open`] or [`Loader`] trying a tuple of openers or loaders in order, until one succeeds.
+///
+/// Candidates of a [`LibLock`](super::LibLock) can only vary paths, while a chain varies how each
+/// path is opened, such as trying a sandboxed directory before the system search. Likewise, a chain
+/// of loaders passed to the `loader` of the `dylink` macro resolves each symbol from the first
+/// loader exporting it. [`open_indexed`](Self::open_indexed) and
+/// [`symbol_indexed`](Self::symbol_indexed) report which link succeeded.
+///
+/// If every link fails, the error is of the kind of the first failure, and lists every failure.
+/// Chains of up to 4 links are supported, and longer chains can be nested.
+///
+/// # Examples
+///
+/// ```rust
+/// use dylink::sync::{Chain, LibLock, Sandboxed, System};
+///
+/// // opens `libfoo.so` from the bundled directory, or else from the system search.
+/// static LIBFOO: LibLock<Chain<(Sandboxed, System)>> = LibLock::with_opener(
+///     &["/opt/myapp/lib/libfoo.so", "libfoo.so"],
+///     Chain::new((Sandboxed::new(&["/opt/myapp/lib"]), System)),
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Chain<T> {
+	links: T,
+}
+
+impl<T> Chain<T> {
+	/// Constructs a chain trying each of `links` in order.
+	#[inline]
+	pub const fn new(links: T) -> Self {
+		Self { links }
+	}
+	/// Returns the links of the chain.
+	#[inline]
+	pub const fn links(&self) -> &T {
+		&self.links
+	}
+}
+
+// Combines the errors of every link into one.
+fn chain_error(errors: Vec<io::Error>) -> io::Error {
+	let kind = errors
+		.first()
+		.map_or(io::ErrorKind::NotFound, io::Error::kind);
+	let mut message = String::from("every link of the chain failed");
+	for (i, err) in errors.iter().enumerate() {
+		let sep = if i == 0 { ": " } else { "; " };
+		let _ = write!(message, "{sep}{i}: {err}");
+	}
+	io::Error::new(kind, message)
+}
+
+macro_rules! chain {
+	($($link:ident . $index:tt),+) => {
+		impl<$($link: Open),+> Chain<($($link,)+)> {
+			/// Opens the library at `path` with the first opener that succeeds, returning its index
+			/// along with the library.
+			///
+			/// # Errors
+			///
+			/// Errors if every opener failed.
+			pub fn open_indexed(
+				&self,
+				path: &path::Path,
+				flags: OpenFlags,
+			) -> io::Result<(usize, Library)> {
+				let mut errors = Vec::new();
+				$(match self.links.$index.open(path, flags) {
+					Ok(lib) => return Ok(($index, lib)),
+					Err(err) => errors.push(err),
+				})+
+				Err(chain_error(errors))
+			}
+		}
+
+		impl<$($link: Open),+> Open for Chain<($($link,)+)> {
+			#[inline]
+			fn open(&self, path: &path::Path, flags: OpenFlags) -> io::Result<Library> {
+				self.open_indexed(path, flags).map(|(_, lib)| lib)
+			}
+		}
+
+		impl<$($link: Loader),+> Chain<($($link,)+)> {
+			/// Resolves the symbol `name` with the first loader exporting it, returning its index
+			/// along with the address.
+			///
+			/// # Errors
+			///
+			/// Errors if every loader failed.
+			pub fn symbol_indexed(&self, name: &str) -> io::Result<(usize, *const Symbol)> {
+				let mut errors = Vec::new();
+				$(match self.links.$index.symbol(name) {
+					Ok(addr) => return Ok(($index, addr)),
+					Err(err) => errors.push(err),
+				})+
+				Err(chain_error(errors))
+			}
+		}
+
+		impl<$($link: Loader),+> Loader for Chain<($($link,)+)> {
+			#[inline]
+			fn symbol(&self, name: &str) -> io::Result<*const Symbol> {
+				self.symbol_indexed(name).map(|(_, addr)| addr)
+			}
+			fn symbol_ordinal(&self, ordinal: u16) -> io::Result<*const Symbol> {
+				let mut errors = Vec::new();
+				$(match self.links.$index.symbol_ordinal(ordinal) {
+					Ok(addr) => return Ok(addr),
+					Err(err) => errors.push(err),
+				})+
+				Err(chain_error(errors))
+			}
+			fn symbol_version(&self, name: &str, version: &str) -> io::Result<*const Symbol> {
+				let mut errors = Vec::new();
+				$(match self.links.$index.symbol_version(name, version) {
+					Ok(addr) => return Ok(addr),
+					Err(err) => errors.push(err),
+				})+
+				Err(chain_error(errors))
+			}
+		}
+	};
+}
+
+chain!(A.0, B.1);
+chain!(A.0, B.1, C.2);
+chain!(A.0, B.1, C.2, D.3);
@@ -0,0 +1,103 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use super::{
+	LibLock,
+	Loader,
+	opengl::proc_address,
+};
+use crate::Symbol;
+use std::{
+	ffi,
+	io,
+	mem,
+};
+
+#[cfg(windows)]
+const LIBEGL_NAMES: &[&str] = &["libEGL.dll"];
+#[cfg(target_vendor = "apple")]
+const LIBEGL_NAMES: &[&str] = &["libEGL.dylib"];
+#[cfg(not(any(windows, target_vendor = "apple")))]
+const LIBEGL_NAMES: &[&str] = &["libEGL.so.1", "libEGL.so"];
+
+static LIBEGL: LibLock = LibLock::new(LIBEGL_NAMES);
+
+/// The EGL library of the system, which is `libEGL.so.1`, or the `libEGL` of ANGLE on Windows and
+/// macOS.
+///
+/// This is the loader of items declared with `#[dylink(egl)]`.
+pub static EGL: Egl<&LibLock> = Egl::new(&LIBEGL);
+
+type GetCurrentContext = unsafe extern "system" fn() -> *mut ffi::c_void;
+
+/// A [`Loader`] resolving the functions of EGL, and of its client APIs such as OpenGL ES, through
+/// `eglGetProcAddress`.
+///
+/// Functions of EGL itself are resolved from the exports of the EGL library first. Functions of
+/// client APIs are only resolved while an EGL context is current on the calling thread, since
+/// implementations may return no address, or one for another client API, before a context was
+/// made current. Until then, they fail to resolve, so items of the macro can be called again once
+/// a context is current.
+///
+/// Before EGL 1.5, `eglGetProcAddress` may only return extension functions, and core functions
+/// of client APIs must be resolved from their own library. A [`Chain`](super::Chain) falls back to
+/// that library.
+///
+/// # Examples
+///
+/// ```rust
+/// use dylink::sync::{Chain, EGL, Egl, LibLock};
+///
+/// static LIBGLESV2: LibLock = LibLock::new(&["libGLESv2.so.2"]);
+/// static GLES: Chain<(&Egl<&LibLock>, &LibLock)> = Chain::new((&EGL, &LIBGLESV2));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Egl<L> {
+	lib: L,
+}
+
+impl<L> Egl<L> {
+	/// Constructs a loader resolving functions through the EGL library `lib`.
+	#[inline]
+	pub const fn new(lib: L) -> Self {
+		Self { lib }
+	}
+	/// Returns the loader of the EGL library.
+	#[inline]
+	pub const fn library(&self) -> &L {
+		&self.lib
+	}
+}
+
+impl<L: Loader> Egl<L> {
+	/// Returns whether an EGL context is current on the calling thread.
+	///
+	/// # Errors
+	///
+	/// Errors if the EGL library couldn't be loaded.
+	pub fn is_current(&self) -> io::Result<bool> {
+		let addr = self.lib.symbol("eglGetCurrentContext")?;
+		let get_current_context: GetCurrentContext = unsafe { mem::transmute(addr) };
+		Ok(!unsafe { get_current_context() }.is_null())
+	}
+}
+
+impl<L: Loader> Loader for Egl<L> {
+	fn symbol(&self, name: &str) -> io::Result<*const Symbol> {
+		if name.starts_with("egl") {
+			return self
+				.lib
+				.symbol(name)
+				.or_else(|err| proc_address(&self.lib, "eglGetProcAddress", name).ok_or(err));
+		}
+		if !self.is_current()? {
+			return Err(io::Error::other(format!(
+				"`{name}` can't be resolved until an EGL context is current"
+			)));
+		}
+		match proc_address(&self.lib, "eglGetProcAddress", name) {
+			Some(addr) => Ok(addr),
+			None => self.lib.symbol(name),
+		}
+	}
+}
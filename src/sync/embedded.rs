@@ -0,0 +1,146 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use super::Open;
+#[cfg(windows)]
+use super::sha256;
+use crate::{
+	Library,
+	OpenFlags,
+};
+#[cfg(not(target_os = "linux"))]
+use std::fs;
+use std::{
+	io,
+	path,
+};
+
+/// An [`Open`] loading libraries embedded in the program, such as with [`include_bytes`].
+///
+/// Each candidate of the [`LibLock`](super::LibLock) names an embedded library, which is written to
+/// a file only the current user can access, and opened from there. Candidates that aren't embedded
+/// fail with [`NotFound`](io::ErrorKind::NotFound), so the next candidate is tried.
+///
+/// # Platform behavior
+///
+/// On Linux, the library is written to an anonymous file created with `memfd_create`, so nothing
+/// is left on the filesystem. On other Unix platforms, it's written to a new directory in the
+/// temporary directory, which is removed right after the library was opened. Windows can't remove
+/// the files of loaded libraries, so the library is extracted to a directory of the temporary
+/// directory named after its SHA-256 digest instead, and the file is reused by later loads as
+/// long as its digest matches.
+///
+/// # Examples
+///
+/// ```no_run
+/// use dylink::sync::{Embedded, LibLock};
+///
+/// // usually `include_bytes!("../plugins/libfoo.so")`.
+/// const LIBFOO_SO: &[u8] = &[];
+///
+/// static LIBFOO: LibLock<Embedded> =
+///     LibLock::with_opener(&["libfoo.so"], Embedded::new(&[("libfoo.so", LIBFOO_SO)]));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Embedded<'a> {
+	libs: &'a [(&'a str, &'a [u8])],
+}
+
+impl<'a> Embedded<'a> {
+	/// Constructs an opener for libraries embedded as bytes, keyed by the file name they're
+	/// extracted to.
+	#[inline]
+	pub const fn new(libs: &'a [(&'a str, &'a [u8])]) -> Self {
+		Self { libs }
+	}
+
+	// Returns the library embedded as `path`.
+	fn find(&self, path: &path::Path) -> io::Result<(&'a str, &'a [u8])> {
+		let &(name, bytes) = self
+			.libs
+			.iter()
+			.find(|(name, _)| path::Path::new(name) == path)
+			.ok_or_else(|| {
+				io::Error::new(
+					io::ErrorKind::NotFound,
+					format!("`{}` isn't embedded", path.display()),
+				)
+			})?;
+		// the name is joined to a directory, so it can't be a path.
+		if path::Path::new(name).file_name() != Some(name.as_ref()) {
+			return Err(io::Error::new(
+				io::ErrorKind::InvalidInput,
+				format!("`{name}` isn't a file name"),
+			));
+		}
+		Ok((name, bytes))
+	}
+}
+
+impl Open for Embedded<'_> {
+	#[cfg(target_os = "linux")]
+	fn open(&self, path: &path::Path, flags: OpenFlags) -> io::Result<Library> {
+		let (name, bytes) = self.find(path)?;
+		unsafe { crate::imp::InnerLibrary::open_bytes(name, bytes, flags.bits()) }.map(Library)
+	}
+
+	#[cfg(all(unix, not(target_os = "linux")))]
+	fn open(&self, path: &path::Path, flags: OpenFlags) -> io::Result<Library> {
+		use std::{
+			io::Write,
+			os::unix::fs::{
+				DirBuilderExt,
+				OpenOptionsExt,
+			},
+			sync::atomic::{
+				AtomicUsize,
+				Ordering,
+			},
+		};
+		static COUNT: AtomicUsize = AtomicUsize::new(0);
+
+		let (name, bytes) = self.find(path)?;
+		// a new directory is created for every library, so its file can't have been tampered with.
+		let dir = loop {
+			let count = COUNT.fetch_add(1, Ordering::Relaxed);
+			let dir = std::env::temp_dir().join(format!("dylink-{}-{count}", std::process::id()));
+			match fs::DirBuilder::new().mode(0o700).create(&dir) {
+				Ok(()) => break dir,
+				Err(err) if err.kind() == io::ErrorKind::AlreadyExists => continue,
+				Err(err) => return Err(err),
+			}
+		};
+		let file = dir.join(name);
+		let result = fs::OpenOptions::new()
+			.write(true)
+			.create_new(true)
+			.mode(0o700)
+			.open(&file)
+			.and_then(|mut file| file.write_all(bytes))
+			.and_then(|()| Library::open_with(&file, flags));
+		// the loaded image keeps its own mapping, so the file is removed right away.
+		let _ = fs::remove_file(&file);
+		let _ = fs::remove_dir(&dir);
+		result
+	}
+
+	#[cfg(windows)]
+	fn open(&self, path: &path::Path, flags: OpenFlags) -> io::Result<Library> {
+		let (name, bytes) = self.find(path)?;
+		let digest = sha256::digest(bytes)?;
+		let dir = std::env::temp_dir().join(format!("dylink-{}", sha256::to_hex(&digest)));
+		fs::create_dir_all(&dir)?;
+		let file = dir.join(name);
+		// the file is rewritten unless it was extracted before, and wasn't changed since.
+		let extracted = fs::File::open(&file).and_then(sha256::digest);
+		if extracted.ok() != Some(digest) {
+			let temp = dir.join(format!("{name}.{}.tmp", std::process::id()));
+			fs::write(&temp, bytes)
+				.and_then(|()| fs::rename(&temp, &file))
+				.inspect_err(|_| {
+					let _ = fs::remove_file(&temp);
+				})?;
+		}
+		Library::open_with(&file, flags)
+	}
+}
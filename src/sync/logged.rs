@@ -0,0 +1,177 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use super::{
+	Loader,
+	Open,
+};
+use crate::{
+	Library,
+	OpenFlags,
+	Symbol,
+};
+use std::{
+	fmt,
+	io,
+	path,
+	time,
+};
+
+// What a `LogEvent` is about.
+#[derive(Debug)]
+enum Subject<'a> {
+	Library(&'a path::Path, OpenFlags),
+	Symbol(&'a str),
+}
+
+/// An attempt to open a library or resolve a symbol through [`Logged`].
+#[derive(Debug)]
+pub struct LogEvent<'a> {
+	subject: Subject<'a>,
+	address: Option<*const Symbol>,
+	error: Option<&'a io::Error>,
+	elapsed: time::Duration,
+}
+
+impl LogEvent<'_> {
+	/// Returns the path of the library, or `None` if a symbol was resolved.
+	#[inline]
+	pub fn path(&self) -> Option<&path::Path> {
+		match self.subject {
+			Subject::Library(path, _) => Some(path),
+			Subject::Symbol(_) => None,
+		}
+	}
+	/// Returns the flags the library was opened with, or `None` if a symbol was resolved.
+	#[inline]
+	pub fn flags(&self) -> Option<OpenFlags> {
+		match self.subject {
+			Subject::Library(_, flags) => Some(flags),
+			Subject::Symbol(_) => None,
+		}
+	}
+	/// Returns the name of the symbol, or `None` if a library was opened.
+	///
+	/// Symbols resolved by ordinal are named `#n`, and those resolved by version `name@version`.
+	#[inline]
+	pub fn symbol(&self) -> Option<&str> {
+		match self.subject {
+			Subject::Library(..) => None,
+			Subject::Symbol(name) => Some(name),
+		}
+	}
+	/// Returns the address the symbol resolved to, or `None` if it failed to resolve, or if a
+	/// library was opened.
+	#[inline]
+	pub fn address(&self) -> Option<*const Symbol> {
+		self.address
+	}
+	/// Returns the error if the attempt failed.
+	#[inline]
+	pub fn error(&self) -> Option<&io::Error> {
+		self.error
+	}
+	/// Returns the time the attempt took.
+	#[inline]
+	pub fn elapsed(&self) -> time::Duration {
+		self.elapsed
+	}
+}
+
+impl fmt::Display for LogEvent<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match (&self.subject, self.error) {
+			(Subject::Library(path, _), None) => write!(f, "opened `{}`", path.display())?,
+			(Subject::Library(path, _), Some(err)) => {
+				write!(f, "failed to open `{}`: {err}", path.display())?
+			}
+			(Subject::Symbol(name), None) => match self.address {
+				Some(addr) => write!(f, "`{name}` resolved to {addr:p}")?,
+				None => write!(f, "`{name}` resolved")?,
+			},
+			(Subject::Symbol(name), Some(err)) => write!(f, "`{name}` failed to resolve: {err}")?,
+		}
+		write!(f, " ({:?})", self.elapsed)
+	}
+}
+
+/// An [`Open`] or [`Loader`] reporting every library it opens and every symbol it resolves to a
+/// hook, along with whether it succeeded and how long it took.
+///
+/// The wrapped opener or loader is used as is, so logging can be added to a
+/// [`LibLock`](super::LibLock) by only changing its opener, or to items of the `dylink` macro by
+/// only changing their `loader`. The hook may forward events to any logger, and may be called from
+/// any thread, while the library is being opened.
+///
+/// # Examples
+///
+/// ```rust
+/// use dylink::sync::{LibLock, Logged, System};
+///
+/// static LIBFOO: LibLock<Logged<System>> =
+///     LibLock::with_opener(&["libfoo.so"], Logged::new(System, |event| eprintln!("{event}")));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Logged<T> {
+	inner: T,
+	hook: fn(&LogEvent<'_>),
+}
+
+impl<T> Logged<T> {
+	/// Wraps `inner`, reporting what it does to `hook`.
+	#[inline]
+	pub const fn new(inner: T, hook: fn(&LogEvent<'_>)) -> Self {
+		Self { inner, hook }
+	}
+	/// Returns the wrapped opener or loader.
+	#[inline]
+	pub const fn inner(&self) -> &T {
+		&self.inner
+	}
+
+	fn resolved(
+		&self,
+		name: &str,
+		resolve: impl FnOnce(&T) -> io::Result<*const Symbol>,
+	) -> io::Result<*const Symbol> {
+		let start = time::Instant::now();
+		let result = resolve(&self.inner);
+		(self.hook)(&LogEvent {
+			subject: Subject::Symbol(name),
+			address: result.as_ref().ok().copied(),
+			error: result.as_ref().err(),
+			elapsed: start.elapsed(),
+		});
+		result
+	}
+}
+
+impl<T: Open> Open for Logged<T> {
+	fn open(&self, path: &path::Path, flags: OpenFlags) -> io::Result<Library> {
+		let start = time::Instant::now();
+		let result = self.inner.open(path, flags);
+		(self.hook)(&LogEvent {
+			subject: Subject::Library(path, flags),
+			address: None,
+			error: result.as_ref().err(),
+			elapsed: start.elapsed(),
+		});
+		result
+	}
+}
+
+impl<T: Loader> Loader for Logged<T> {
+	fn symbol(&self, name: &str) -> io::Result<*const Symbol> {
+		self.resolved(name, |inner| inner.symbol(name))
+	}
+	fn symbol_ordinal(&self, ordinal: u16) -> io::Result<*const Symbol> {
+		self.resolved(&format!("#{ordinal}"), |inner| {
+			inner.symbol_ordinal(ordinal)
+		})
+	}
+	fn symbol_version(&self, name: &str, version: &str) -> io::Result<*const Symbol> {
+		self.resolved(&format!("{name}@{version}"), |inner| {
+			inner.symbol_version(name, version)
+		})
+	}
+}
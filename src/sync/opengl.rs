@@ -0,0 +1,145 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use super::{
+	LibLock,
+	Loader,
+};
+use crate::{
+	Symbol,
+	scrub::Scrubbed,
+};
+use std::{
+	ffi,
+	io,
+	mem,
+	sync::OnceLock,
+};
+
+// Functions returning the addresses of GL functions, tried in order. macOS has none, since its GL
+// framework exports every function.
+#[cfg(windows)]
+const GET_PROC_ADDRESS: &[&str] = &["wglGetProcAddress", "eglGetProcAddress"];
+#[cfg(target_vendor = "apple")]
+const GET_PROC_ADDRESS: &[&str] = &[];
+#[cfg(not(any(windows, target_vendor = "apple")))]
+const GET_PROC_ADDRESS: &[&str] = &[
+	"glXGetProcAddressARB",
+	"glXGetProcAddress",
+	"eglGetProcAddress",
+];
+
+#[cfg(windows)]
+const LIBGL_NAMES: &[&str] = &["opengl32.dll"];
+#[cfg(target_vendor = "apple")]
+const LIBGL_NAMES: &[&str] = &["/System/Library/Frameworks/OpenGL.framework/OpenGL"];
+#[cfg(not(any(windows, target_vendor = "apple")))]
+const LIBGL_NAMES: &[&str] = &["libGL.so.1", "libGL.so"];
+
+static LIBGL: LibLock = LibLock::new(LIBGL_NAMES);
+
+/// The OpenGL library of the system, which is `opengl32.dll` on Windows, the `OpenGL` framework on
+/// macOS, and `libGL.so.1` elsewhere.
+///
+/// This is the loader of items declared with `#[dylink(opengl)]`.
+pub static OPENGL: OpenGl<&LibLock> = OpenGl::new(&LIBGL);
+
+type GetProcAddress = unsafe extern "system" fn(*const ffi::c_char) -> *const Symbol;
+
+// Returns the address of `name` from the proc-address function `get_proc_address` of `lib`, if it
+// knows the function.
+pub(super) fn proc_address<L: Loader>(
+	lib: &L,
+	get_proc_address: &str,
+	name: &str,
+) -> Option<*const Symbol> {
+	let addr = lib.symbol(get_proc_address).ok()?;
+	let get_proc_address: GetProcAddress = unsafe { mem::transmute(addr) };
+	let c_str = Scrubbed(ffi::CString::new(name).ok()?);
+	let addr = unsafe { get_proc_address(c_str.as_ptr()) };
+	// some drivers of Windows return 1, 2, 3 or -1 for functions they don't have.
+	#[cfg(windows)]
+	if matches!(addr as isize, 1..=3 | -1) {
+		return None;
+	}
+	(!addr.is_null()).then_some(addr)
+}
+
+/// A [`Loader`] resolving OpenGL functions through the proc-address function of the platform,
+/// falling back to the exports of the GL library.
+///
+/// Most GL functions aren't exported by the GL library, and must be queried from the driver with
+/// `wglGetProcAddress`, `glXGetProcAddressARB` or `eglGetProcAddress`, whichever the library
+/// exports. On Windows, `wglGetProcAddress` doesn't return the functions of OpenGL 1.1, which are
+/// only exported by `opengl32.dll`, and some drivers return small integers instead of null for
+/// functions they don't have, which is handled here.
+///
+/// On Windows, the addresses depend on the context that is current, so items resolving through this
+/// loader must first be called once a context is current, and every context should have the same
+/// pixel format, since the macro caches the addresses.
+///
+/// # Examples
+///
+/// ```rust
+/// use dylink::sync::{LibLock, OpenGl};
+///
+/// static LIBGLES: LibLock = LibLock::new(&["libGLESv2.so.2"]);
+/// static GLES: OpenGl<&LibLock> = OpenGl::new(&LIBGLES);
+/// ```
+#[derive(Debug)]
+pub struct OpenGl<L> {
+	lib: L,
+	// the proc-address function the library exports, once it was found.
+	get_proc_address: OnceLock<&'static str>,
+}
+
+impl<L> OpenGl<L> {
+	/// Constructs a loader resolving GL functions from `lib`.
+	#[inline]
+	pub const fn new(lib: L) -> Self {
+		Self {
+			lib,
+			get_proc_address: OnceLock::new(),
+		}
+	}
+	/// Returns the loader of the GL library.
+	#[inline]
+	pub const fn library(&self) -> &L {
+		&self.lib
+	}
+}
+
+impl<L: Loader> OpenGl<L> {
+	// Returns the address of `name` from the proc-address function, if the library has one that
+	// knows the function.
+	fn proc_address(&self, name: &str) -> Option<*const Symbol> {
+		let get_proc_address = match self.get_proc_address.get() {
+			Some(&get_proc_address) => get_proc_address,
+			None => {
+				let found = GET_PROC_ADDRESS
+					.iter()
+					.copied()
+					.find(|name| self.lib.symbol(name).is_ok())?;
+				*self.get_proc_address.get_or_init(|| found)
+			}
+		};
+		proc_address(&self.lib, get_proc_address, name)
+	}
+}
+
+impl<L: Loader> Loader for OpenGl<L> {
+	fn symbol(&self, name: &str) -> io::Result<*const Symbol> {
+		match self.proc_address(name) {
+			Some(addr) => Ok(addr),
+			None => self.lib.symbol(name),
+		}
+	}
+	#[inline]
+	fn symbol_ordinal(&self, ordinal: u16) -> io::Result<*const Symbol> {
+		self.lib.symbol_ordinal(ordinal)
+	}
+	#[inline]
+	fn symbol_version(&self, name: &str, version: &str) -> io::Result<*const Symbol> {
+		self.lib.symbol_version(name, version)
+	}
+}
@@ -0,0 +1,164 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use super::{
+	Open,
+	System,
+	sha256,
+};
+use crate::{
+	Library,
+	OpenFlags,
+};
+use std::{
+	fs,
+	io,
+	path,
+};
+
+/// An [`Open`] that only opens libraries matching a SHA-256 digest they're pinned to.
+///
+/// Each candidate of the [`LibLock`](super::LibLock) is pinned to the hexadecimal digest of its
+/// file, as printed by `sha256sum`. Candidates that aren't pinned are refused with
+/// [`PermissionDenied`](io::ErrorKind::PermissionDenied), which includes the path of an
+/// [`env_override`](super::LibLock::env_override) unless it's pinned as well, and files that don't
+/// match their digest are refused with [`InvalidData`](io::ErrorKind::InvalidData). Either way the
+/// next candidate is tried.
+///
+/// Every candidate is hashed before it's opened, and the file is then opened again by path. A bare
+/// name, like `libfoo.so`, is searched for by the system loader, and can't be hashed before it's
+/// opened, so it's refused with [`PermissionDenied`](io::ErrorKind::PermissionDenied) as well.
+///
+/// Only the file as it was before the open is checked. If it's replaced between the hash and the
+/// open, the replacement is loaded, and its initializers run before anything can reject it. With
+/// [`verify_loaded`](Self::verify_loaded), the file the library was loaded from is also hashed
+/// after it's opened, which detects the replacement and refuses the library, but only once its
+/// code already ran. Pinning therefore guards against files that were tampered with ahead of time,
+/// and libraries should be kept in directories that can't be written by untrusted users.
+///
+/// # Examples
+///
+/// ```no_run
+/// use dylink::sync::{LibLock, Pinned};
+///
+/// static LIBFOO: LibLock<Pinned> = LibLock::with_opener(
+///     &["/opt/myapp/lib/libfoo.so"],
+///     Pinned::new(&[(
+///         "/opt/myapp/lib/libfoo.so",
+///         "2c26b46b68ffc68ff99b453c1d30413413422d706483bfa0f98a5e886266e7ae",
+///     )]),
+/// );
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Pinned<'a, O = System> {
+	pins: &'a [(&'a str, &'a str)],
+	verify_loaded: bool,
+	opener: O,
+}
+
+impl<'a> Pinned<'a> {
+	/// Constructs an opener pinning each path to a digest, which opens libraries with the system
+	/// loader.
+	#[inline]
+	pub const fn new(pins: &'a [(&'a str, &'a str)]) -> Self {
+		Self::with_opener(pins, System)
+	}
+
+	/// Returns the SHA-256 digest of the file at `path` in lowercase hexadecimal, which is what a
+	/// path is pinned to.
+	///
+	/// # Errors
+	///
+	/// May error if the file can't be read.
+	pub fn digest<P: AsRef<path::Path>>(path: P) -> io::Result<String> {
+		let digest = sha256::digest(fs::File::open(path)?)?;
+		Ok(sha256::to_hex(&digest))
+	}
+}
+
+impl<'a, O> Pinned<'a, O> {
+	/// Constructs an opener pinning each path to a digest, which opens libraries with `opener`
+	/// once they're verified.
+	#[inline]
+	pub const fn with_opener(pins: &'a [(&'a str, &'a str)], opener: O) -> Self {
+		Self {
+			pins,
+			verify_loaded: false,
+			opener,
+		}
+	}
+
+	/// Also hashes the file the library was loaded from after it's opened, in case the file
+	/// changed between the first hash and the library being opened.
+	///
+	/// A library refused this way was already loaded, so its initializers already ran.
+	#[inline]
+	pub const fn verify_loaded(mut self) -> Self {
+		self.verify_loaded = true;
+		self
+	}
+
+	// Returns the digest `path` is pinned to.
+	fn pin(&self, path: &path::Path) -> io::Result<[u8; 32]> {
+		let (_, hex) = self
+			.pins
+			.iter()
+			.find(|(pinned, _)| path::Path::new(pinned) == path)
+			.ok_or_else(|| {
+				io::Error::new(
+					io::ErrorKind::PermissionDenied,
+					format!("`{}` isn't pinned", path.display()),
+				)
+			})?;
+		let invalid = || {
+			io::Error::new(
+				io::ErrorKind::InvalidInput,
+				format!("the digest of `{}` isn't valid", path.display()),
+			)
+		};
+		if hex.len() != 64 || !hex.bytes().all(|byte| byte.is_ascii_hexdigit()) {
+			return Err(invalid());
+		}
+		let mut digest = [0u8; 32];
+		for (byte, pair) in digest.iter_mut().zip(hex.as_bytes().chunks_exact(2)) {
+			let pair = str::from_utf8(pair).map_err(|_| invalid())?;
+			*byte = u8::from_str_radix(pair, 16).map_err(|_| invalid())?;
+		}
+		Ok(digest)
+	}
+}
+
+// Hashes the file at `path`, and compares it with `expected`.
+fn verify(path: &path::Path, expected: &[u8; 32]) -> io::Result<()> {
+	if sha256::digest(fs::File::open(path)?)? == *expected {
+		Ok(())
+	} else {
+		Err(io::Error::new(
+			io::ErrorKind::InvalidData,
+			format!("`{}` doesn't match its pinned digest", path.display()),
+		))
+	}
+}
+
+impl<O: Open> Open for Pinned<'_, O> {
+	fn open(&self, path: &path::Path, flags: OpenFlags) -> io::Result<Library> {
+		// bare names are searched for by the loader, so the file can't be hashed before it's opened.
+		if path.parent().is_none_or(|dir| dir.as_os_str().is_empty()) {
+			return Err(io::Error::new(
+				io::ErrorKind::PermissionDenied,
+				format!("`{}` must be a path to be pinned", path.display()),
+			));
+		}
+		let expected = self.pin(path)?;
+		verify(path, &expected)?;
+		let lib = self.opener.open(path, flags)?;
+		if self.verify_loaded {
+			let loaded = lib.to_image().and_then(|image| image.path());
+			if let Err(err) = loaded.and_then(|loaded| verify(&loaded, &expected)) {
+				let _ = lib.close();
+				return Err(err);
+			}
+		}
+		Ok(lib)
+	}
+}
@@ -0,0 +1,92 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use super::{
+	Open,
+	System,
+};
+use crate::{
+	Library,
+	OpenFlags,
+};
+use std::{
+	fs,
+	io,
+	path,
+};
+
+/// An [`Open`] that only opens libraries inside a list of allowed directories.
+///
+/// This enforces the advice of [`Library::open`] against [preloading attacks]. Each candidate of
+/// the [`LibLock`](super::LibLock) is canonicalized, resolving symbolic links, and refused with
+/// [`PermissionDenied`](io::ErrorKind::PermissionDenied) unless the file it resolves to is inside
+/// one of the directories, so a link can't escape them. The canonical path is then opened, rather
+/// than the candidate. Bare names, like `libfoo.so`, are always refused, since the system loader
+/// would search for them elsewhere. Relative paths are resolved from the current directory, like
+/// the loader does.
+///
+/// [preloading attacks]: https://learn.microsoft.com/en-us/windows/win32/dlls/dynamic-link-library-security
+///
+/// # Examples
+///
+/// ```no_run
+/// use dylink::sync::{LibLock, Sandboxed};
+///
+/// static LIBFOO: LibLock<Sandboxed> =
+///     LibLock::with_opener(&["/opt/myapp/lib/libfoo.so"], Sandboxed::new(&["/opt/myapp/lib"]));
+/// ```
+#[derive(Debug, Clone, Copy)]
+pub struct Sandboxed<'a, O = System> {
+	dirs: &'a [&'a str],
+	opener: O,
+}
+
+impl<'a> Sandboxed<'a> {
+	/// Constructs an opener allowing libraries inside `dirs`, which opens them with the system
+	/// loader.
+	#[inline]
+	pub const fn new(dirs: &'a [&'a str]) -> Self {
+		Self::with_opener(dirs, System)
+	}
+}
+
+impl<'a, O> Sandboxed<'a, O> {
+	/// Constructs an opener allowing libraries inside `dirs`, which opens them with `opener`.
+	#[inline]
+	pub const fn with_opener(dirs: &'a [&'a str], opener: O) -> Self {
+		Self { dirs, opener }
+	}
+
+	/// Returns the canonical path of `path` if it's inside an allowed directory.
+	///
+	/// # Errors
+	///
+	/// Returns [`PermissionDenied`](io::ErrorKind::PermissionDenied) if `path` is a bare name, or
+	/// resolves outside of the allowed directories, and may error if it can't be canonicalized.
+	pub fn check(&self, path: &path::Path) -> io::Result<path::PathBuf> {
+		let denied = || {
+			io::Error::new(
+				io::ErrorKind::PermissionDenied,
+				format!("`{}` is outside of the allowed directories", path.display()),
+			)
+		};
+		if path.parent().is_none_or(|dir| dir.as_os_str().is_empty()) {
+			return Err(denied());
+		}
+		let real = fs::canonicalize(path)?;
+		// directories that don't exist can't contain anything.
+		let allowed = self
+			.dirs
+			.iter()
+			.filter_map(|dir| fs::canonicalize(dir).ok())
+			.any(|dir| real.starts_with(dir));
+		if allowed { Ok(real) } else { Err(denied()) }
+	}
+}
+
+impl<O: Open> Open for Sandboxed<'_, O> {
+	fn open(&self, path: &path::Path, flags: OpenFlags) -> io::Result<Library> {
+		let real = self.check(path)?;
+		self.opener.open(&real, flags)
+	}
+}
@@ -0,0 +1,111 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+use crate::LoadReport;
+use std::{
+	future::Future,
+	path,
+	pin::Pin,
+	sync::{
+		Arc,
+		Mutex,
+	},
+	task::{
+		Context,
+		Poll,
+		Waker,
+	},
+	thread,
+};
+
+// The result of a blocking call, along with the waker of the task awaiting it.
+struct Shared<T> {
+	result: Option<T>,
+	waker: Option<Waker>,
+}
+
+// A future resolved by a call made on its own thread, so that loading a library doesn't block the
+// thread polling it. This doesn't depend on any particular runtime.
+pub(crate) struct Blocking<T> {
+	shared: Arc<Mutex<Shared<T>>>,
+}
+
+impl<T: Send + 'static> Blocking<T> {
+	pub(crate) fn spawn<F: FnOnce() -> T + Send + 'static>(f: F) -> Self {
+		let shared = Arc::new(Mutex::new(Shared {
+			result: None,
+			waker: None,
+		}));
+		let task = Arc::clone(&shared);
+		let f = Arc::new(Mutex::new(Some(f)));
+		let spawned = thread::Builder::new().name("dylink-load".into()).spawn({
+			let f = Arc::clone(&f);
+			move || {
+				let f = f.lock().unwrap_or_else(|err| err.into_inner()).take();
+				if let Some(f) = f {
+					complete(&task, f());
+				}
+			}
+		});
+		// the call is made on the current thread if no thread can be spawned.
+		if spawned.is_err() {
+			let f = f.lock().unwrap_or_else(|err| err.into_inner()).take();
+			if let Some(f) = f {
+				complete(&shared, f());
+			}
+		}
+		Self { shared }
+	}
+}
+
+fn complete<T>(shared: &Mutex<Shared<T>>, result: T) {
+	let mut shared = shared.lock().unwrap_or_else(|err| err.into_inner());
+	shared.result = Some(result);
+	if let Some(waker) = shared.waker.take() {
+		waker.wake();
+	}
+}
+
+impl<T> Future for Blocking<T> {
+	type Output = T;
+	fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<T> {
+		let mut shared = self.shared.lock().unwrap_or_else(|err| err.into_inner());
+		match shared.result.take() {
+			Some(result) => Poll::Ready(result),
+			None => {
+				shared.waker = Some(cx.waker().clone());
+				Poll::Pending
+			}
+		}
+	}
+}
+
+/// Opens the first library that can be loaded out of `candidates`, without blocking the thread
+/// polling the future.
+///
+/// This is the asynchronous counterpart of [`LoadReport::open`]. Loading a library may take a long
+/// time, such as for large GPU drivers, which would otherwise stall the worker thread of an async
+/// runtime. The candidates are opened on a thread spawned for the load, which completes the
+/// future, so this works with any runtime.
+///
+/// # Examples
+///
+/// ```no_run
+/// # async fn f() {
+/// let report = dylink::load_async(["libvulkan.so.1", "libvulkan.so"]).await;
+/// if report.library().is_none() {
+///     eprintln!("could not find Vulkan: {report}");
+/// }
+/// # }
+/// ```
+pub fn load_async<I, P>(candidates: I) -> impl Future<Output = LoadReport> + Send + 'static
+where
+	I: IntoIterator<Item = P>,
+	P: AsRef<path::Path>,
+{
+	let candidates: Vec<path::PathBuf> = candidates
+		.into_iter()
+		.map(|path| path.as_ref().to_path_buf())
+		.collect();
+	Blocking::spawn(move || LoadReport::open(candidates))
+}
@@ -0,0 +1,100 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Utilities for testing code that uses dynamically loaded libraries.
+
+use crate::{
+	Symbol,
+	scrub::{
+		Scrub,
+		Scrubbed,
+	},
+	sync::Loader,
+};
+use std::{
+	collections::BTreeMap,
+	io,
+	sync::RwLock,
+};
+
+/// A [`Loader`] resolving symbols that were defined by tests, instead of loading a library.
+///
+/// Items generated by the `dylink` macro with `loader` pointing at a mock call the functions
+/// defined with [`define`](Self::define), so code using them can be tested without the real
+/// library being present. Symbols that weren't defined fail to resolve with
+/// [`NotFound`](io::ErrorKind::NotFound).
+///
+/// # Examples
+///
+/// ```rust
+/// use dylink::test::MockLoader;
+///
+/// static MOCK: MockLoader = MockLoader::new();
+///
+/// extern "C" fn get_last_error() -> u32 {
+///     5
+/// }
+///
+/// MOCK.define("GetLastError", get_last_error as *const ());
+/// # use dylink::sync::Loader;
+/// assert!(MOCK.symbol("GetLastError").is_ok());
+/// ```
+#[derive(Debug, Default)]
+pub struct MockLoader {
+	symbols: RwLock<BTreeMap<String, usize>>,
+}
+
+impl MockLoader {
+	/// Constructs a mock without any symbols.
+	#[inline]
+	pub const fn new() -> Self {
+		Self {
+			symbols: RwLock::new(BTreeMap::new()),
+		}
+	}
+
+	/// Defines the symbol `name` at `address`, replacing its previous definition.
+	///
+	/// Items of the `dylink` macro that already resolved the symbol keep their previous address.
+	pub fn define(&self, name: &str, address: *const ()) -> &Self {
+		let mut symbols = self.symbols.write().unwrap_or_else(|err| err.into_inner());
+		symbols.insert(name.to_owned(), address as usize);
+		self
+	}
+
+	/// Removes the definition of the symbol `name`, returning whether it was defined.
+	pub fn undefine(&self, name: &str) -> bool {
+		let mut symbols = self.symbols.write().unwrap_or_else(|err| err.into_inner());
+		let removed = symbols.remove_entry(name);
+		removed.map(|(name, _)| Scrubbed(name)).is_some()
+	}
+
+	/// Removes every definition.
+	pub fn clear(&self) {
+		let symbols =
+			std::mem::take(&mut *self.symbols.write().unwrap_or_else(|err| err.into_inner()));
+		for mut name in symbols.into_keys() {
+			name.scrub();
+		}
+	}
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for MockLoader {
+	fn drop(&mut self) {
+		self.clear();
+	}
+}
+
+impl Loader for MockLoader {
+	fn symbol(&self, name: &str) -> io::Result<*const Symbol> {
+		let symbols = self.symbols.read().unwrap_or_else(|err| err.into_inner());
+		match symbols.get(name) {
+			Some(&address) => Ok(address as *const Symbol),
+			None => Err(io::Error::new(
+				io::ErrorKind::NotFound,
+				format!("symbol `{name}` isn't defined by the mock"),
+			)),
+		}
+	}
+}
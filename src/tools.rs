@@ -0,0 +1,329 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Utilities for inspecting built libraries, such as from CI jobs or build scripts.
+
+use crate::img;
+use std::{
+	collections::HashMap,
+	error,
+	fmt::{
+		self,
+		Write,
+	},
+	io,
+	path,
+};
+
+/// A machine-readable format for [`dump_exports`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ExportFormat {
+	/// A JSON array with one object per export.
+	Json,
+	/// CSV with a header row, and one row per export.
+	Csv,
+}
+
+const FIELDS: [&str; 6] = ["name", "ordinal", "rva", "size", "kind", "forwarder"];
+
+/// Lists the exports of the library at `path` in a machine-readable format.
+///
+/// The library is parsed from disk instead of being loaded, so its initializers aren't run, and
+/// libraries built for other architectures can be listed. Exports are sorted by name and then
+/// ordinal, one per line, so that listings of different builds can be compared with a text diff.
+///
+/// Each export has the fields `name`, `ordinal`, `rva`, `size`, `kind`, and `forwarder`, which
+/// correspond to the methods of [`Export`](img::Export). Fields that are unknown are `null` in
+/// JSON, or empty in CSV.
+///
+/// # Errors
+///
+/// May error if the file can't be read, or isn't a supported image format.
+///
+/// # Examples
+///
+/// ```no_run
+/// use dylink::tools::{self, ExportFormat};
+///
+/// let listing = tools::dump_exports("target/release/libplugin.so", ExportFormat::Csv)?;
+/// std::fs::write("exports.csv", listing)?;
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn dump_exports<P: AsRef<path::Path>>(path: P, format: ExportFormat) -> io::Result<String> {
+	let mut exports = img::File::open(path)?.exports()?;
+	exports.sort_unstable_by(|a, b| (a.name(), a.ordinal()).cmp(&(b.name(), b.ordinal())));
+
+	let mut out = String::new();
+	match format {
+		ExportFormat::Json => {
+			out.push('[');
+			for (i, export) in exports.iter().enumerate() {
+				out.push_str(if i == 0 { "\n\t{" } else { ",\n\t{" });
+				for (j, (key, value)) in FIELDS.iter().zip(fields(export)).enumerate() {
+					if j != 0 {
+						out.push_str(", ");
+					}
+					let _ = write!(out, "\"{key}\": ");
+					match value {
+						Field::Str(s) => json_str(&mut out, s),
+						Field::Int(n) => {
+							let _ = write!(out, "{n}");
+						}
+						Field::None => out.push_str("null"),
+					}
+				}
+				out.push('}');
+			}
+			out.push_str(if exports.is_empty() { "]\n" } else { "\n]\n" });
+		}
+		ExportFormat::Csv => {
+			out.push_str(&FIELDS.join(","));
+			out.push('\n');
+			for export in &exports {
+				for (j, value) in fields(export).into_iter().enumerate() {
+					if j != 0 {
+						out.push(',');
+					}
+					match value {
+						Field::Str(s) => csv_str(&mut out, s),
+						Field::Int(n) => {
+							let _ = write!(out, "{n}");
+						}
+						Field::None => {}
+					}
+				}
+				out.push('\n');
+			}
+		}
+	}
+	Ok(out)
+}
+
+enum Field<'a> {
+	Str(&'a str),
+	Int(usize),
+	None,
+}
+
+fn kind_str(kind: img::ExportKind) -> &'static str {
+	match kind {
+		img::ExportKind::Function => "function",
+		img::ExportKind::Data => "data",
+		img::ExportKind::Tls => "tls",
+		img::ExportKind::Unknown => "unknown",
+	}
+}
+
+fn fields(export: &img::Export) -> [Field<'_>; 6] {
+	let int = |n: Option<usize>| n.map_or(Field::None, Field::Int);
+	[
+		export.name().map_or(Field::None, Field::Str),
+		int(export.ordinal().map(|n| n as usize)),
+		Field::Int(export.rva()),
+		int(export.size()),
+		Field::Str(kind_str(export.kind())),
+		export.forwarder().map_or(Field::None, Field::Str),
+	]
+}
+
+fn json_str(out: &mut String, s: &str) {
+	out.push('"');
+	for c in s.chars() {
+		match c {
+			'"' => out.push_str("\\\""),
+			'\\' => out.push_str("\\\\"),
+			'\n' => out.push_str("\\n"),
+			'\r' => out.push_str("\\r"),
+			'\t' => out.push_str("\\t"),
+			c if c.is_control() => {
+				let _ = write!(out, "\\u{:04x}", c as u32);
+			}
+			c => out.push(c),
+		}
+	}
+	out.push('"');
+}
+
+// Fields are only quoted if needed, since symbol names rarely contain special characters.
+fn csv_str(out: &mut String, s: &str) {
+	if s.contains([',', '"', '\n', '\r']) {
+		out.push('"');
+		out.push_str(&s.replace('"', "\"\""));
+		out.push('"');
+	} else {
+		out.push_str(s);
+	}
+}
+
+/// A breaking change between two builds of a library, found by [`abi_check`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AbiBreak {
+	/// An export is no longer present.
+	Removed {
+		/// The name of the export, or `#` followed by its ordinal.
+		name: String,
+	},
+	/// An export refers to a different kind of item, such as data instead of a function.
+	KindChanged {
+		/// The name of the export, or `#` followed by its ordinal.
+		name: String,
+		/// The kind in the old build.
+		old: img::ExportKind,
+		/// The kind in the new build.
+		new: img::ExportKind,
+	},
+	/// An export was assigned a different ordinal, which breaks callers that import it by ordinal.
+	OrdinalChanged {
+		/// The name of the export.
+		name: String,
+		/// The ordinal in the old build.
+		old: u32,
+		/// The ordinal in the new build.
+		new: u32,
+	},
+	/// An exported variable changed size, so its layout is likely to have changed.
+	SizeChanged {
+		/// The name of the export, or `#` followed by its ordinal.
+		name: String,
+		/// The size in the old build.
+		old: usize,
+		/// The size in the new build.
+		new: usize,
+	},
+}
+
+impl AbiBreak {
+	/// Returns the name of the export, or `#` followed by its ordinal if it has no name.
+	pub fn name(&self) -> &str {
+		match self {
+			Self::Removed { name }
+			| Self::KindChanged { name, .. }
+			| Self::OrdinalChanged { name, .. }
+			| Self::SizeChanged { name, .. } => name,
+		}
+	}
+}
+
+impl fmt::Display for AbiBreak {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			Self::Removed { name } => write!(f, "`{name}` was removed"),
+			Self::KindChanged { name, old, new } => write!(
+				f,
+				"`{name}` changed from {} to {}",
+				kind_str(*old),
+				kind_str(*new)
+			),
+			Self::OrdinalChanged { name, old, new } => {
+				write!(f, "`{name}` changed from ordinal {old} to {new}")
+			}
+			Self::SizeChanged { name, old, new } => {
+				write!(f, "`{name}` changed from {old} to {new} bytes")
+			}
+		}
+	}
+}
+
+/// The breaking changes between two builds of a library, returned by [`abi_check`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AbiBreaks {
+	breaks: Vec<AbiBreak>,
+}
+
+impl AbiBreaks {
+	/// Returns the breaking changes, sorted by name.
+	#[inline]
+	pub fn breaks(&self) -> &[AbiBreak] {
+		&self.breaks
+	}
+}
+
+impl fmt::Display for AbiBreaks {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{} breaking change(s)", self.breaks.len())?;
+		for (i, abi_break) in self.breaks.iter().enumerate() {
+			let sep = if i == 0 { ": " } else { ", " };
+			write!(f, "{sep}{abi_break}")?;
+		}
+		Ok(())
+	}
+}
+
+impl error::Error for AbiBreaks {}
+
+/// Compares the exports of two builds of a library, returning the changes that would break
+/// existing callers.
+///
+/// An export that was removed, changed kind, changed ordinal, or is a variable that changed size is
+/// considered breaking, while new exports are not. Exports without a name are matched by ordinal.
+/// Function signatures aren't recorded in images, so changes to them can't be detected.
+///
+/// # Errors
+///
+/// Returns every breaking change that was found.
+///
+/// # Examples
+///
+/// ```no_run
+/// use dylink::{img::File, tools};
+///
+/// let old = File::open("release/v1/plugin.dll")?.exports()?;
+/// let new = File::open("target/release/plugin.dll")?.exports()?;
+/// if let Err(breaks) = tools::abi_check(&old, &new) {
+///     panic!("{breaks}");
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+pub fn abi_check(old: &[img::Export], new: &[img::Export]) -> Result<(), AbiBreaks> {
+	fn key(export: &img::Export) -> Option<String> {
+		match (export.name(), export.ordinal()) {
+			(Some(name), _) => Some(name.to_owned()),
+			(None, Some(ordinal)) => Some(format!("#{ordinal}")),
+			(None, None) => None,
+		}
+	}
+	let new: HashMap<String, &img::Export> = new
+		.iter()
+		.filter_map(|export| Some((key(export)?, export)))
+		.collect();
+
+	let mut breaks = Vec::new();
+	for old in old {
+		let Some(name) = key(old) else {
+			continue;
+		};
+		let Some(new) = new.get(&name) else {
+			breaks.push(AbiBreak::Removed { name });
+			continue;
+		};
+		if old.kind() != new.kind() {
+			breaks.push(AbiBreak::KindChanged {
+				name,
+				old: old.kind(),
+				new: new.kind(),
+			});
+		} else if let (Some(old), Some(new)) = (old.ordinal(), new.ordinal())
+			&& old != new
+			&& !name.starts_with('#')
+		{
+			breaks.push(AbiBreak::OrdinalChanged { name, old, new });
+		} else if let (Some(old_size), Some(new_size)) = (old.size(), new.size())
+			&& old_size != new_size
+			&& matches!(old.kind(), img::ExportKind::Data | img::ExportKind::Tls)
+		{
+			breaks.push(AbiBreak::SizeChanged {
+				name,
+				old: old_size,
+				new: new_size,
+			});
+		}
+	}
+	if breaks.is_empty() {
+		Ok(())
+	} else {
+		breaks.sort_by(|a, b| a.name().cmp(b.name()));
+		Err(AbiBreaks { breaks })
+	}
+}
@@ -0,0 +1,128 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Tracing of the symbols resolved by items generated with [`dylink`](crate::dylink).
+//!
+//! With the `trace` feature, generated items report every resolution of their symbol, whether it
+//! succeeded or not, to the hook set with [`set_hook`]. This answers which library an item actually
+//! bound to without attaching a debugger. No events are reported until a hook is set.
+//!
+//! # Examples
+//!
+//! ```
+//! use dylink::trace;
+//!
+//! trace::set_hook(|event| eprintln!("{event}"));
+//! ```
+
+use crate::Symbol;
+use std::{
+	fmt,
+	io,
+	path,
+	sync::RwLock,
+	time,
+};
+
+static HOOK: RwLock<Option<fn(&Event<'_>)>> = RwLock::new(None);
+
+/// The resolution of a symbol by a generated item.
+#[derive(Debug)]
+pub struct Event<'a> {
+	library: &'a str,
+	item: &'a str,
+	names: &'a [&'a str],
+	result: &'a io::Result<*const Symbol>,
+	elapsed: time::Duration,
+}
+
+impl Event<'_> {
+	/// Returns the path of the `library` or `loader` the item resolves its symbol from, as written
+	/// in the attribute.
+	#[inline]
+	pub fn library(&self) -> &str {
+		self.library
+	}
+	/// Returns the name of the generated item.
+	#[inline]
+	pub fn item(&self) -> &str {
+		self.item
+	}
+	/// Returns the symbol names tried, in order, or `#n` when bound by `link_ordinal`.
+	#[inline]
+	pub fn names(&self) -> &[&str] {
+		self.names
+	}
+	/// Returns the address the symbol resolved to, or `None` if it failed to resolve.
+	#[inline]
+	pub fn address(&self) -> Option<*const Symbol> {
+		self.result.as_ref().ok().copied()
+	}
+	/// Returns the error if the symbol failed to resolve.
+	#[inline]
+	pub fn error(&self) -> Option<&io::Error> {
+		self.result.as_ref().err()
+	}
+	/// Returns the path of the image containing the resolved address.
+	///
+	/// This is looked up when called, so it's `None` if the symbol failed to resolve, or if the
+	/// image was unloaded since.
+	pub fn path(&self) -> Option<path::PathBuf> {
+		Symbol::image(self.address()?)?.path().ok()
+	}
+	/// Returns the time it took to resolve the symbol, including opening the library if this was
+	/// its first use.
+	#[inline]
+	pub fn elapsed(&self) -> time::Duration {
+		self.elapsed
+	}
+}
+
+impl fmt::Display for Event<'_> {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{}: `{}`", self.library, self.item)?;
+		match self.result {
+			Ok(addr) => {
+				write!(f, " bound to {:p}", *addr)?;
+				if let Some(path) = self.path() {
+					write!(f, " in {}", path.display())?;
+				}
+			}
+			Err(err) => write!(f, " failed to resolve: {err}")?,
+		}
+		write!(f, " ({:?})", self.elapsed)
+	}
+}
+
+/// Sets the hook called whenever a generated item resolves its symbol, replacing the previous hook.
+///
+/// The hook may be called from any thread, and while the library of the item is being loaded, so
+/// it shouldn't resolve symbols through generated items itself.
+pub fn set_hook(hook: fn(&Event<'_>)) {
+	*HOOK.write().unwrap_or_else(|err| err.into_inner()) = Some(hook);
+}
+
+/// Removes the hook, returning it if one was set.
+pub fn take_hook() -> Option<fn(&Event<'_>)> {
+	HOOK.write().unwrap_or_else(|err| err.into_inner()).take()
+}
+
+#[doc(hidden)]
+pub fn resolved(
+	library: &str,
+	item: &str,
+	names: &[&str],
+	result: &io::Result<*const Symbol>,
+	start: time::Instant,
+) {
+	let hook = *HOOK.read().unwrap_or_else(|err| err.into_inner());
+	if let Some(hook) = hook {
+		hook(&Event {
+			library,
+			item,
+			names,
+			result,
+			elapsed: start.elapsed(),
+		});
+	}
+}
@@ -0,0 +1,275 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Resolution of Vulkan functions through `vkGetInstanceProcAddr` and `vkGetDeviceProcAddr`.
+//!
+//! The Vulkan loader only exports a few functions, and every other function must be queried for the
+//! instance or device it's called with. Items declared with `#[dylink(vulkan)]` resolve through
+//! [`VULKAN`], which queries global functions, such as `vkCreateInstance`, without an instance,
+//! functions of devices from the devices registered with [`register_device`], and other functions
+//! from the instances registered with [`register_instance`].
+//!
+//! # Lifecycle
+//!
+//! Register an instance right after `vkCreateInstance` succeeded, and unregister it right before
+//! `vkDestroyInstance` is called, and likewise for devices with `vkCreateDevice` and
+//! `vkDestroyDevice`. Items of the macro cache the address they first resolved to, so
+//! functions of an instance should only be called once it was registered, and the addresses are
+//! only valid for the instance they were resolved with, and its children. Applications creating
+//! several instances should resolve their functions through a [`Vulkan`] loader per instance, or
+//! query them themselves.
+//!
+//! # Examples
+//!
+//! ```no_run
+//! use dylink::{dylink, vk};
+//! use std::ffi;
+//!
+//! #[dylink(vulkan)]
+//! extern "system" {
+//!     fn vkCreateInstance(
+//!         create_info: *const ffi::c_void,
+//!         allocator: *const ffi::c_void,
+//!         instance: *mut vk::VkInstance,
+//!     ) -> i32;
+//!     fn vkDestroyInstance(instance: vk::VkInstance, allocator: *const ffi::c_void);
+//! }
+//!
+//! # let create_info = std::ptr::null();
+//! let mut instance = std::ptr::null_mut();
+//! if unsafe { vkCreateInstance(create_info, std::ptr::null(), &mut instance) } == 0 {
+//!     vk::register_instance(instance);
+//!     // ...
+//!     vk::unregister_instance(instance);
+//!     unsafe { vkDestroyInstance(instance, std::ptr::null()) };
+//! }
+//! ```
+
+use crate::{
+	Symbol,
+	scrub::Scrubbed,
+	sync::{
+		LibLock,
+		Loader,
+	},
+};
+use std::{
+	ffi,
+	io,
+	mem,
+	sync::{
+		RwLock,
+		atomic::{
+			AtomicPtr,
+			Ordering,
+		},
+	},
+};
+
+/// A raw `VkInstance` handle.
+pub type VkInstance = *mut ffi::c_void;
+/// A raw `VkDevice` handle.
+pub type VkDevice = *mut ffi::c_void;
+
+type GetInstanceProcAddr =
+	unsafe extern "system" fn(VkInstance, *const ffi::c_char) -> *const Symbol;
+type GetDeviceProcAddr = unsafe extern "system" fn(VkDevice, *const ffi::c_char) -> *const Symbol;
+
+// handles are stored as addresses, so the lists can be shared between threads.
+static INSTANCES: RwLock<Vec<usize>> = RwLock::new(Vec::new());
+static DEVICES: RwLock<Vec<usize>> = RwLock::new(Vec::new());
+
+fn register(handles: &RwLock<Vec<usize>>, handle: usize) {
+	let mut handles = handles.write().unwrap_or_else(|err| err.into_inner());
+	if !handles.contains(&handle) {
+		handles.push(handle);
+	}
+}
+
+fn unregister(handles: &RwLock<Vec<usize>>, handle: usize) -> bool {
+	let mut handles = handles.write().unwrap_or_else(|err| err.into_inner());
+	let len = handles.len();
+	handles.retain(|&registered| registered != handle);
+	handles.len() != len
+}
+
+// Returns the registered handles, most recent first.
+fn registered(handles: &RwLock<Vec<usize>>) -> Vec<usize> {
+	let handles = handles.read().unwrap_or_else(|err| err.into_inner());
+	handles.iter().rev().copied().collect()
+}
+
+/// Registers `instance`, so that functions of instances resolve through it.
+///
+/// The instance must be unregistered before it's destroyed, since functions are resolved through
+/// it until then. Registering an instance twice has no effect.
+pub fn register_instance(instance: VkInstance) {
+	register(&INSTANCES, instance as usize);
+}
+
+/// Unregisters `instance`, returning whether it was registered.
+pub fn unregister_instance(instance: VkInstance) -> bool {
+	unregister(&INSTANCES, instance as usize)
+}
+
+/// Registers `device`, so that functions of devices resolve through it with `vkGetDeviceProcAddr`.
+///
+/// Functions resolved through a device are called without the dispatch of the Vulkan loader, and
+/// are only valid for that device. The device must be unregistered before it's destroyed.
+/// Registering a device twice has no effect.
+pub fn register_device(device: VkDevice) {
+	register(&DEVICES, device as usize);
+}
+
+/// Unregisters `device`, returning whether it was registered.
+pub fn unregister_device(device: VkDevice) -> bool {
+	unregister(&DEVICES, device as usize)
+}
+
+#[cfg(windows)]
+const LIBVULKAN_NAMES: &[&str] = &["vulkan-1.dll"];
+#[cfg(target_vendor = "apple")]
+const LIBVULKAN_NAMES: &[&str] = &["libvulkan.dylib", "libvulkan.1.dylib", "libMoltenVK.dylib"];
+#[cfg(not(any(windows, target_vendor = "apple")))]
+const LIBVULKAN_NAMES: &[&str] = &["libvulkan.so.1", "libvulkan.so"];
+
+static LIBVULKAN: LibLock = LibLock::new(LIBVULKAN_NAMES);
+
+/// The Vulkan loader of the system, which is `vulkan-1.dll` on Windows, `libvulkan.so.1` on Linux,
+/// and `libvulkan.dylib` or MoltenVK on macOS.
+///
+/// This is the loader of items declared with `#[dylink(vulkan)]`.
+pub static VULKAN: Vulkan<&LibLock> = Vulkan::new(&LIBVULKAN);
+
+/// A [`Loader`] resolving Vulkan functions through the `vkGetInstanceProcAddr` of a Vulkan loader.
+///
+/// Global functions are resolved without an instance, functions of devices from the registered
+/// devices, and other functions from the registered instances, most recent first. The
+/// `vkGetDeviceProcAddr` of the driver is queried from a registered instance the first time it's
+/// needed, and is kept for later lookups.
+#[derive(Debug)]
+pub struct Vulkan<L> {
+	lib: L,
+	// `vkGetDeviceProcAddr`, once it was queried.
+	get_device_proc_addr: AtomicPtr<Symbol>,
+}
+
+impl<L> Vulkan<L> {
+	/// Constructs a loader resolving functions through the Vulkan loader `lib`.
+	#[inline]
+	pub const fn new(lib: L) -> Self {
+		Self {
+			lib,
+			get_device_proc_addr: AtomicPtr::new(std::ptr::null_mut()),
+		}
+	}
+	/// Returns the loader of the Vulkan loader library.
+	#[inline]
+	pub const fn library(&self) -> &L {
+		&self.lib
+	}
+}
+
+impl<L: Loader> Vulkan<L> {
+	/// Resolves the function `name` of `instance` through `vkGetInstanceProcAddr`, or a global
+	/// function if `instance` is null.
+	///
+	/// # Errors
+	///
+	/// Errors if the Vulkan loader couldn't be loaded, or if it doesn't know the function.
+	///
+	/// # Safety
+	///
+	/// `instance` must be null, or an instance created by this Vulkan loader that wasn't destroyed.
+	pub unsafe fn instance_proc_addr(
+		&self,
+		instance: VkInstance,
+		name: &str,
+	) -> io::Result<*const Symbol> {
+		let addr = self.lib.symbol("vkGetInstanceProcAddr")?;
+		let get_instance_proc_addr: GetInstanceProcAddr = unsafe { mem::transmute(addr) };
+		let c_str = match ffi::CString::new(name) {
+			Ok(s) => Scrubbed(s),
+			Err(err) => return Err(io::Error::new(io::ErrorKind::InvalidData, err)),
+		};
+		let addr = unsafe { get_instance_proc_addr(instance, c_str.as_ptr()) };
+		if addr.is_null() {
+			Err(io::Error::new(
+				io::ErrorKind::NotFound,
+				format!("`{name}` isn't resolved by `vkGetInstanceProcAddr`"),
+			))
+		} else {
+			Ok(addr)
+		}
+	}
+
+	/// Resolves the function `name` of `device` through `vkGetDeviceProcAddr`.
+	///
+	/// `vkGetDeviceProcAddr` is queried with `vkGetInstanceProcAddr` from the most recently
+	/// registered instance the first time, or else taken from the exports of the Vulkan loader.
+	///
+	/// # Errors
+	///
+	/// Errors if the Vulkan loader couldn't be loaded, or if the device doesn't know the function.
+	///
+	/// # Safety
+	///
+	/// `device` must be a device created by this Vulkan loader that wasn't destroyed, and registered
+	/// instances must not have been destroyed.
+	pub unsafe fn device_proc_addr(
+		&self,
+		device: VkDevice,
+		name: &str,
+	) -> io::Result<*const Symbol> {
+		let mut addr = self.get_device_proc_addr.load(Ordering::Acquire);
+		if addr.is_null() {
+			let queried = registered(&INSTANCES).into_iter().find_map(|instance| {
+				unsafe { self.instance_proc_addr(instance as VkInstance, "vkGetDeviceProcAddr") }
+					.ok()
+			});
+			addr = match queried {
+				Some(addr) => addr,
+				None => self.lib.symbol("vkGetDeviceProcAddr")?,
+			}
+			.cast_mut();
+			self.get_device_proc_addr.store(addr, Ordering::Release);
+		}
+		let get_device_proc_addr: GetDeviceProcAddr = unsafe { mem::transmute(addr) };
+		let c_str = match ffi::CString::new(name) {
+			Ok(s) => Scrubbed(s),
+			Err(err) => return Err(io::Error::new(io::ErrorKind::InvalidData, err)),
+		};
+		let addr = unsafe { get_device_proc_addr(device, c_str.as_ptr()) };
+		if addr.is_null() {
+			Err(io::Error::new(
+				io::ErrorKind::NotFound,
+				format!("`{name}` isn't resolved by `vkGetDeviceProcAddr`"),
+			))
+		} else {
+			Ok(addr)
+		}
+	}
+}
+
+impl<L: Loader> Loader for Vulkan<L> {
+	fn symbol(&self, name: &str) -> io::Result<*const Symbol> {
+		let err = match unsafe { self.instance_proc_addr(std::ptr::null_mut(), name) } {
+			Ok(addr) => return Ok(addr),
+			Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+			Err(err) => err,
+		};
+		// `vkGetDeviceProcAddr` returns null for functions that aren't of devices.
+		let from_device = registered(&DEVICES)
+			.into_iter()
+			.find_map(|device| unsafe { self.device_proc_addr(device as VkDevice, name) }.ok());
+		if let Some(addr) = from_device {
+			return Ok(addr);
+		}
+		registered(&INSTANCES)
+			.into_iter()
+			.find_map(|instance| {
+				unsafe { self.instance_proc_addr(instance as VkInstance, name) }.ok()
+			})
+			.ok_or(err)
+	}
+}
@@ -4,6 +4,9 @@
 use crate::Library;
 use crate::img;
 use crate::os;
+#[cfg(feature = "zeroize")]
+use crate::scrub::Scrub;
+use std::io;
 use std::path;
 use std::ptr;
 
@@ -31,6 +34,13 @@ impl Default for Weak {
 	}
 }
 
+#[cfg(feature = "zeroize")]
+impl Drop for Weak {
+	fn drop(&mut self) {
+		self.path_name.scrub();
+	}
+}
+
 impl Weak {
 	/// Constructs a new `Weak`, without allocating any memory. Calling [`upgrade`] on the return value always gives [`None`].
 	///
@@ -58,6 +68,14 @@ impl Weak {
 	///
 	/// Returns [`None`] if the inner value has since been dropped.
 	///
+	/// # Blocking
+	///
+	/// Taking a reference requires the loader lock, so this may block while another thread is
+	/// loading or unloading a library, including while its initializers run. Use [`try_upgrade`]
+	/// from threads that must not wait on the loader.
+	///
+	/// [`try_upgrade`]: Weak::try_upgrade
+	///
 	/// # Examples
 	///
 	/// ```no_run
@@ -74,6 +92,27 @@ impl Weak {
 		unsafe { imp::InnerLibrary::from_ptr(self.base_addr.cast_mut()) }.map(Library)
 	}
 
+	/// Attempts to upgrade the `Weak` pointer to a [`Library`] without waiting on the loader lock.
+	///
+	/// Returns `Ok(None)` if the inner value has since been dropped. Unlike [`upgrade`], the image
+	/// is never loaded again if it was unloaded.
+	///
+	/// [`upgrade`]: Weak::upgrade
+	///
+	/// # Errors
+	///
+	/// Returns an error of kind [`WouldBlock`](io::ErrorKind::WouldBlock) if the loader lock is
+	/// held by another thread.
+	///
+	/// # Platform behavior
+	///
+	/// The loader lock can only be polled on Windows. On other platforms this may still block
+	/// while another thread holds the loader lock.
+	pub fn try_upgrade(&self) -> io::Result<Option<Library>> {
+		unsafe { imp::InnerLibrary::try_from_ptr(self.base_addr.cast_mut()) }
+			.map(|inner| inner.map(Library))
+	}
+
 	/// Returns the base address of the image.
 	///
 	/// The pointer is only valid if there are some strong references to the image.
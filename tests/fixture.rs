@@ -0,0 +1,1427 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+#[path = "fixtures/mod.rs"]
+mod fixtures;
+
+use dylink::*;
+use std::{
+	ffi,
+	sync::LazyLock,
+};
+
+static FIXTURE: LazyLock<sync::LibLock> = LazyLock::new(|| sync::LibLock::new(fixtures::names()));
+
+fn open() -> Library {
+	Library::open(fixtures::path()).unwrap()
+}
+
+#[test]
+fn test_fixture_symbol() {
+	let lib = open();
+	let add = lib.symbol("fixture_add").unwrap();
+	let add: extern "C" fn(ffi::c_int, ffi::c_int) -> ffi::c_int =
+		unsafe { std::mem::transmute(add) };
+	assert_eq!(add(2, 3), 5);
+
+	let data = lib.symbol("fixture_data").unwrap();
+	assert_eq!(unsafe { *data.cast::<ffi::c_int>() }, 42);
+	assert!(lib.symbol("fixture_missing").is_err());
+
+	let image = Symbol::image(data).unwrap();
+	assert!(std::ptr::eq(image, lib.to_image().unwrap()));
+	assert_eq!(
+		image.path().unwrap().file_name(),
+		fixtures::path().file_name()
+	);
+	lib.close().unwrap();
+}
+
+#[test]
+fn test_fixture_packed() {
+	let lib = open();
+	let image = lib.to_image().unwrap();
+	assert_eq!(image.packed().unwrap(), None);
+	assert!(!image.to_bytes().unwrap().is_empty());
+	lib.close().unwrap();
+}
+
+#[test]
+fn test_fixture_macro() {
+	#[dylink(library = FIXTURE)]
+	extern "C" {
+		fn fixture_add(a: ffi::c_int, b: ffi::c_int) -> ffi::c_int;
+		static mut fixture_data: ffi::c_int;
+	}
+
+	#[dylink(library = FIXTURE, link_name = "fixture_name")]
+	extern "C" fn name() -> *const ffi::c_char;
+
+	#[dylink(library = FIXTURE, link_prefix = "fixture_")]
+	extern "C" {
+		fn add(a: ffi::c_int, b: ffi::c_int) -> ffi::c_int;
+	}
+
+	unsafe {
+		assert_eq!(fixture_add(40, 2), 42);
+		assert_eq!(add(1, 2), 3);
+		assert_eq!(*fixture_data(), 42);
+		assert_eq!(ffi::CStr::from_ptr(name()), c"fixture");
+	}
+}
+
+#[test]
+fn test_fixture_variadic() {
+	#[dylink(library = FIXTURE)]
+	extern "C" {
+		fn fixture_sum(count: ffi::c_int, ...) -> ffi::c_int;
+	}
+
+	#[dylink(library = FIXTURE, checked)]
+	extern "C" {
+		fn fixture_missing(count: ffi::c_int, ...);
+	}
+
+	unsafe {
+		assert_eq!(fixture_sum()(3, 1, 2, 3), 6);
+		assert_eq!(fixture_sum()(0), 0);
+		assert!(fixture_missing().is_err());
+	}
+}
+
+#[test]
+fn test_fixture_link_case() {
+	#[dylink(library = FIXTURE, link_case = "pascal")]
+	extern "C" {
+		fn fixture_sub(a: ffi::c_int, b: ffi::c_int) -> ffi::c_int;
+	}
+
+	unsafe {
+		assert_eq!(fixture_sub(5, 3), 2);
+	}
+}
+
+#[test]
+fn test_fixture_impl() {
+	#[derive(Clone, Copy)]
+	#[repr(transparent)]
+	struct Num(ffi::c_int);
+
+	#[dylink(library = FIXTURE, link_prefix = "fixture_")]
+	impl Num {
+		extern "C" fn add(self, b: ffi::c_int) -> ffi::c_int;
+
+		fn get(self) -> ffi::c_int {
+			self.0
+		}
+	}
+
+	let num = Num(40);
+	unsafe {
+		assert_eq!(num.add(2), 42);
+	}
+	assert_eq!(num.get(), 40);
+}
+
+#[test]
+fn test_fixture_loader() {
+	use std::{
+		io,
+		sync::atomic::{
+			AtomicUsize,
+			Ordering,
+		},
+	};
+	static LOOKUPS: AtomicUsize = AtomicUsize::new(0);
+	struct Prefixed;
+	impl sync::Loader for Prefixed {
+		fn symbol(&self, name: &str) -> io::Result<*const Symbol> {
+			LOOKUPS.fetch_add(1, Ordering::Relaxed);
+			FIXTURE.symbol(&format!("fixture_{name}"))
+		}
+	}
+
+	#[dylink(loader = Prefixed)]
+	extern "C" {
+		fn add(a: ffi::c_int, b: ffi::c_int) -> ffi::c_int;
+	}
+
+	unsafe {
+		assert_eq!(add(20, 22), 42);
+		assert_eq!(add(1, 1), 2);
+	}
+	assert_eq!(LOOKUPS.load(Ordering::Relaxed), 1);
+
+	#[dylink(loader = FIXTURE)]
+	extern "C" {
+		fn fixture_add(a: ffi::c_int, b: ffi::c_int) -> ffi::c_int;
+	}
+
+	assert_eq!(unsafe { fixture_add(2, 3) }, 5);
+}
+
+#[test]
+fn test_fixture_unload() {
+	static LIB: LazyLock<sync::LibLock> = LazyLock::new(|| sync::LibLock::new(fixtures::names()));
+
+	#[dylink(library = LIB, link_prefix = "fixture_")]
+	extern "C" {
+		fn add(a: ffi::c_int, b: ffi::c_int) -> ffi::c_int;
+	}
+
+	unsafe {
+		assert_eq!(add(20, 22), 42);
+		LIB.unload().unwrap();
+		assert!(LIB.get().is_none());
+		LIB.unload().unwrap();
+		assert_eq!(add(1, 1), 2);
+	}
+	assert!(LIB.get().is_some());
+}
+
+#[test]
+fn test_fixture_take() {
+	use std::sync::atomic::{
+		AtomicPtr,
+		Ordering,
+	};
+	static SLOT: AtomicPtr<ffi::c_void> = AtomicPtr::new(std::ptr::null_mut());
+
+	let mut lib = sync::LibLock::new(fixtures::names());
+	let add = lib.symbol("fixture_add").unwrap();
+	SLOT.store(add.cast_mut().cast(), Ordering::Relaxed);
+	lib.register_reset(&SLOT, std::ptr::null_mut());
+
+	lib.take().unwrap().close().unwrap();
+	assert!(SLOT.load(Ordering::Relaxed).is_null());
+	assert!(lib.get().is_none());
+}
+
+#[test]
+fn test_fixture_after_restore() {
+	static LIB: LazyLock<sync::LibLock> = LazyLock::new(|| sync::LibLock::new(fixtures::names()));
+
+	#[dylink(library = LIB, link_prefix = "fixture_")]
+	extern "C" {
+		fn add(a: ffi::c_int, b: ffi::c_int) -> ffi::c_int;
+	}
+
+	unsafe {
+		assert_eq!(add(20, 22), 42);
+		cr::after_restore(&[&LIB]).unwrap();
+		assert!(LIB.get().is_none());
+		assert_eq!(add(1, 1), 2);
+	}
+	assert!(LIB.get().is_some());
+}
+
+#[test]
+fn test_fixture_env_override() {
+	static LIB: sync::LibLock =
+		sync::LibLock::new(&["dylink_missing_library"]).env_override("DYLINK_TEST_FIXTURE_PATH");
+
+	unsafe { std::env::set_var("DYLINK_TEST_FIXTURE_PATH", fixtures::path()) };
+	assert!(LIB.symbol("fixture_add").is_ok());
+}
+
+#[test]
+fn test_fixture_from_paths() {
+	static LIB: LazyLock<sync::LibLock> =
+		LazyLock::new(|| sync::LibLock::from_paths(fixtures::paths()));
+
+	let add = LIB.symbol("fixture_add").unwrap();
+	assert_eq!(LIB.symbol("fixture_add").unwrap(), add);
+	assert!(LIB.symbol("fixture_missing").is_err());
+	assert!(LIB.with(|lib| lib.symbol("fixture_data").is_ok()).unwrap());
+
+	let report = LIB
+		.preload(&["fixture_add", "fixture_missing", "fixture_data"])
+		.unwrap();
+	assert!(!report.is_complete());
+	assert_eq!(report.resolved()[0], ("fixture_add", add));
+	assert_eq!(report.resolved()[1].0, "fixture_data");
+	assert_eq!(report.missing()[0].0, "fixture_missing");
+	assert!(
+		report
+			.to_string()
+			.starts_with("resolved 2 of 3 symbols, missing `fixture_missing`")
+	);
+
+	let status = LIB.status();
+	assert!(status.is_loaded());
+	assert_eq!(status.path(), Some(fixtures::path()));
+	assert_eq!((status.resolved(), status.failed()), (2, 2));
+
+	assert!(registry().iter().any(|entry| {
+		entry.is_loaded()
+			&& entry.path() == Some(fixtures::path())
+			&& entry.candidates() == [fixtures::path()]
+	}));
+}
+
+#[test]
+fn test_fixture_candidates() {
+	unsafe { std::env::set_var("DYLINK_TEST_CANDIDATES_PATH", fixtures::path()) };
+	let mut candidates = sync::Candidates::new();
+	candidates
+		.name("dylink_missing_library")
+		.env("DYLINK_TEST_CANDIDATES_PATH")
+		.env("DYLINK_TEST_CANDIDATES_UNSET")
+		.decorated("dylink_missing");
+	assert_eq!(candidates.paths().len(), 3);
+	assert_eq!(candidates.paths()[1], fixtures::path());
+	assert!(
+		candidates.paths()[2]
+			.to_str()
+			.unwrap()
+			.contains("dylink_missing")
+	);
+
+	let lib = sync::LibLock::from_candidates(&candidates);
+	assert!(lib.symbol("fixture_add").is_ok());
+	assert!(lib.error().is_none());
+
+	let mut candidates = sync::Candidates::new();
+	candidates.versioned("fixture", &["9", "1.2.3"]);
+	let names = candidates.paths();
+	assert_eq!(names.len(), 3);
+	assert_eq!(
+		names[2],
+		std::path::Path::new(&sync::Candidates::decorate("fixture"))
+	);
+	assert_eq!(
+		sync::Candidates::version_of("fixture", &names[1]).as_deref(),
+		Some("1.2.3")
+	);
+	assert_eq!(sync::Candidates::version_of("fixture", &names[2]), None);
+	#[cfg(target_os = "linux")]
+	assert_eq!(
+		sync::Candidates::version_of("fixture", fixtures::path()).as_deref(),
+		Some("1.2.3")
+	);
+}
+
+#[test]
+fn test_fixture_async() {
+	use std::{
+		sync::Arc,
+		task::{
+			Context,
+			Poll,
+			Wake,
+		},
+		thread,
+	};
+
+	struct Unpark(thread::Thread);
+	impl Wake for Unpark {
+		fn wake(self: Arc<Self>) {
+			self.0.unpark();
+		}
+	}
+
+	fn block_on<F: Future>(future: F) -> F::Output {
+		let waker = Arc::new(Unpark(thread::current())).into();
+		let mut cx = Context::from_waker(&waker);
+		let mut future = std::pin::pin!(future);
+		loop {
+			match future.as_mut().poll(&mut cx) {
+				Poll::Ready(output) => return output,
+				Poll::Pending => thread::park(),
+			}
+		}
+	}
+
+	static LIB: LazyLock<sync::LibLock> = LazyLock::new(|| sync::LibLock::new(fixtures::names()));
+
+	let report = block_on(load_async(["dylink_missing_library", fixtures::path_str()]));
+	assert_eq!(report.index(), Some(1));
+	assert!(block_on(LIB.symbol_async("fixture_add")).is_ok());
+	assert!(LIB.get().is_some());
+	assert!(block_on(LIB.symbol_async("fixture_missing")).is_err());
+}
+
+#[test]
+fn test_fixture_load_hooks() {
+	use std::sync::atomic::{
+		AtomicI32,
+		Ordering,
+	};
+
+	static LOADED: AtomicI32 = AtomicI32::new(0);
+	static UNLOADED: AtomicI32 = AtomicI32::new(0);
+
+	static LIB: LazyLock<sync::LibLock> = LazyLock::new(|| {
+		sync::LibLock::new(fixtures::names())
+			.on_load(|lib| {
+				let data = lib.symbol("fixture_data").unwrap();
+				LOADED.store(unsafe { *data.cast::<ffi::c_int>() }, Ordering::Relaxed);
+			})
+			.on_unload(|_| {
+				UNLOADED.fetch_add(1, Ordering::Relaxed);
+			})
+	});
+
+	#[dylink(library = LIB)]
+	extern "C" {
+		fn fixture_add(a: ffi::c_int, b: ffi::c_int) -> ffi::c_int;
+	}
+
+	assert_eq!(unsafe { fixture_add(1, 2) }, 3);
+	assert_eq!(LOADED.load(Ordering::Relaxed), 42);
+	unsafe { LIB.unload() }.unwrap();
+	assert_eq!(UNLOADED.load(Ordering::Relaxed), 1);
+	unsafe { LIB.unload() }.unwrap();
+	assert_eq!(UNLOADED.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn test_fixture_retry() {
+	use std::time::Duration;
+
+	static LIB: sync::LibLock = sync::LibLock::new(&["dylink_missing_library"])
+		.env_override("DYLINK_TEST_RETRY_PATH")
+		.retry(Duration::ZERO);
+	static LATER: sync::LibLock = sync::LibLock::new(&["dylink_missing_library"])
+		.env_override("DYLINK_TEST_RETRY_PATH")
+		.retry(Duration::from_secs(3600));
+
+	assert!(LIB.symbol("fixture_add").is_err());
+	assert!(LATER.symbol("fixture_add").is_err());
+	assert!(LIB.error().is_none());
+	unsafe { std::env::set_var("DYLINK_TEST_RETRY_PATH", fixtures::path()) };
+	assert!(LIB.symbol("fixture_add").is_ok());
+	assert!(LATER.symbol("fixture_add").is_err());
+}
+
+#[test]
+fn test_fixture_set_paths() {
+	static LIB: sync::LibLock = sync::LibLock::new(&["dylink_missing_library"]);
+	static PREPENDED: sync::LibLock = sync::LibLock::new(&["dylink_missing_library"]);
+
+	LIB.set_paths(["dylink_missing_library2"]).unwrap();
+	LIB.set_paths([fixtures::path()]).unwrap();
+	assert!(LIB.symbol("fixture_add").is_ok());
+	let err = LIB.set_paths([fixtures::path()]).unwrap_err();
+	assert_eq!(err.kind(), std::io::ErrorKind::AlreadyExists);
+
+	PREPENDED.prepend_paths([fixtures::path()]).unwrap();
+	assert!(PREPENDED.symbol("fixture_add").is_ok());
+	assert!(PREPENDED.prepend_paths([fixtures::path()]).is_err());
+}
+
+#[test]
+fn test_fixture_pinned() {
+	use std::io;
+	use sync::{
+		Open,
+		Pinned,
+	};
+
+	let path = std::env::temp_dir().join(format!("dylink_pinned_{}", std::process::id()));
+	std::fs::write(&path, "abc").unwrap();
+	let digest = Pinned::digest(&path);
+	std::fs::remove_file(&path).unwrap();
+	assert_eq!(
+		digest.unwrap(),
+		"ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+	);
+
+	let digest = Pinned::digest(fixtures::path()).unwrap();
+	let pins = [(fixtures::path_str(), digest.as_str())];
+	let pinned = Pinned::new(&pins).verify_loaded();
+	let lib = pinned.open(fixtures::path(), OpenFlags::default()).unwrap();
+	assert!(lib.symbol("fixture_add").is_ok());
+
+	let pins = [(fixtures::path_str(), &*"0".repeat(64))];
+	let err = Pinned::new(&pins)
+		.open(fixtures::path(), OpenFlags::default())
+		.unwrap_err();
+	assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+	let err = Pinned::new(&[])
+		.open(fixtures::path(), OpenFlags::default())
+		.unwrap_err();
+	assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+	// bare names can't be hashed before they're loaded, even if pinned.
+	let pins = [(fixtures::FILE_NAME, digest.as_str())];
+	let err = Pinned::new(&pins)
+		.open(fixtures::FILE_NAME.as_ref(), OpenFlags::default())
+		.unwrap_err();
+	assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+}
+
+#[test]
+fn test_fixture_sandboxed() {
+	use std::io;
+	use sync::{
+		Open,
+		Sandboxed,
+	};
+
+	let dir = fixtures::path().parent().unwrap().to_str().unwrap();
+	let dirs = [dir];
+	let sandbox = Sandboxed::new(&dirs);
+	let lib = sandbox
+		.open(fixtures::path(), OpenFlags::default())
+		.unwrap();
+	assert!(lib.symbol("fixture_add").is_ok());
+	let err = sandbox
+		.open(fixtures::FILE_NAME.as_ref(), OpenFlags::default())
+		.unwrap_err();
+	assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+
+	// a link inside an allowed directory can't escape it.
+	#[cfg(unix)]
+	{
+		let outside = std::env::temp_dir().join(format!("dylink_sandboxed_{}", std::process::id()));
+		std::fs::create_dir_all(&outside).unwrap();
+		let link = outside.join(fixtures::FILE_NAME);
+		let _ = std::fs::remove_file(&link);
+		std::os::unix::fs::symlink(fixtures::path(), &link).unwrap();
+		let dirs = [outside.to_str().unwrap()];
+		let err = Sandboxed::new(&dirs)
+			.open(&link, OpenFlags::default())
+			.unwrap_err();
+		std::fs::remove_dir_all(&outside).unwrap();
+		assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+	}
+}
+
+#[test]
+fn test_fixture_libloading() {
+	static LIB: LazyLock<libloading::Library> =
+		LazyLock::new(|| unsafe { libloading::Library::new(fixtures::path()) }.unwrap());
+
+	#[dylink(loader = LIB)]
+	extern "C" {
+		fn fixture_add(a: ffi::c_int, b: ffi::c_int) -> ffi::c_int;
+	}
+
+	assert_eq!(unsafe { fixture_add(4, 5) }, 9);
+	let lib = Library::from(unsafe { libloading::Library::new(fixtures::path()) }.unwrap());
+	assert!(lib.symbol("fixture_add").is_ok());
+	let lib = libloading::Library::from(lib);
+	assert!(unsafe { lib.get::<*const Symbol>(b"fixture_add") }.is_ok());
+}
+
+#[test]
+fn test_fixture_logged() {
+	use std::sync::Mutex;
+	use sync::{
+		Logged,
+		System,
+	};
+
+	static EVENTS: Mutex<Vec<String>> = Mutex::new(Vec::new());
+	fn log(event: &sync::LogEvent<'_>) {
+		EVENTS.lock().unwrap().push(event.to_string());
+	}
+	static LIB: LazyLock<sync::LibLock<Logged<System>>> = LazyLock::new(|| {
+		sync::LibLock::with_opener(
+			Box::leak(Box::new(["dylink_missing_library", fixtures::path_str()])),
+			Logged::new(System, log),
+		)
+	});
+	static LOGGED: Logged<&LazyLock<sync::LibLock<Logged<System>>>> = Logged::new(&LIB, log);
+
+	#[dylink(loader = LOGGED)]
+	extern "C" {
+		fn fixture_add(a: ffi::c_int, b: ffi::c_int) -> ffi::c_int;
+	}
+
+	assert_eq!(unsafe { fixture_add(1, 1) }, 2);
+	let events = EVENTS.lock().unwrap();
+	assert_eq!(events.len(), 3);
+	assert!(events[0].starts_with("failed to open `dylink_missing_library`"));
+	assert!(events[1].starts_with(&format!("opened `{}`", fixtures::path_str())));
+	assert!(events[2].starts_with("`fixture_add` resolved to"));
+}
+
+#[test]
+fn test_fixture_chain() {
+	use sync::{
+		Chain,
+		Open,
+		Sandboxed,
+		System,
+	};
+
+	let dirs = [fixtures::path().parent().unwrap().to_str().unwrap()];
+	let chain = Chain::new((Sandboxed::new(&[]), Sandboxed::new(&dirs), System));
+	let (index, lib) = chain
+		.open_indexed(fixtures::path(), OpenFlags::default())
+		.unwrap();
+	assert_eq!(index, 1);
+	let err = chain
+		.open("dylink_missing_library".as_ref(), OpenFlags::default())
+		.unwrap_err();
+	assert_eq!(err.kind(), std::io::ErrorKind::PermissionDenied);
+
+	let chain = Chain::new((&*FIXTURE, lib));
+	assert_eq!(chain.symbol_indexed("fixture_add").unwrap().0, 0);
+	assert!(chain.symbol_indexed("fixture_missing").is_err());
+}
+
+#[test]
+fn test_fixture_lazy() {
+	static LIB: LazyLock<sync::LibLock> =
+		LazyLock::new(|| sync::LibLock::new(fixtures::names()).lazy_binding());
+
+	let flags = OpenFlags::DEFAULT.lazy();
+	assert_eq!(flags.lazy(), flags);
+	#[cfg(unix)]
+	assert_ne!(flags, OpenFlags::DEFAULT);
+	let lib = Library::open_with(fixtures::path(), flags).unwrap();
+	assert!(lib.symbol("fixture_add").is_ok());
+	assert!(LIB.symbol("fixture_add").is_ok());
+}
+
+#[test]
+fn test_fixture_embedded() {
+	use sync::{
+		Embedded,
+		Open,
+	};
+
+	let bytes: &[u8] = std::fs::read(fixtures::path()).unwrap().leak();
+	let other: &[u8] = std::fs::read(fixtures::other_path()).unwrap().leak();
+	let libs = [
+		("libembedded_fixture.so", bytes),
+		("libembedded_other.so", other),
+		("../escape.so", bytes),
+	];
+	let embedded = Embedded::new(&libs);
+	let lib = embedded
+		.open("libembedded_fixture.so".as_ref(), OpenFlags::default())
+		.unwrap();
+	assert!(lib.symbol("fixture_add").is_ok());
+	// a second library isn't taken for the first one, which is still loaded.
+	let other = embedded
+		.open("libembedded_other.so".as_ref(), OpenFlags::default())
+		.unwrap();
+	assert!(other.symbol("fixture_other").is_ok());
+	assert!(lib.symbol("fixture_other").is_err());
+	let err = embedded
+		.open("libmissing.so".as_ref(), OpenFlags::default())
+		.unwrap_err();
+	assert_eq!(err.kind(), std::io::ErrorKind::NotFound);
+	let err = embedded
+		.open("../escape.so".as_ref(), OpenFlags::default())
+		.unwrap_err();
+	assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+}
+
+#[test]
+fn test_fixture_opengl() {
+	use sync::{
+		Loader,
+		OpenGl,
+	};
+
+	#[dylink(opengl)]
+	unsafe extern "system" {
+		fn glGetString(name: u32) -> *const u8;
+	}
+	let _: unsafe extern "system" fn(u32) -> *const u8 = glGetString;
+
+	// the fixture has no proc-address function, so functions resolve from its exports.
+	let gl = OpenGl::new(&*FIXTURE);
+	assert_eq!(
+		gl.symbol("fixture_add").unwrap(),
+		FIXTURE.symbol("fixture_add").unwrap()
+	);
+	assert!(gl.symbol("fixture_missing").is_err());
+}
+
+#[test]
+fn test_fixture_egl() {
+	use sync::{
+		Egl,
+		Loader,
+	};
+
+	#[dylink(egl)]
+	unsafe extern "system" {
+		fn eglGetError() -> i32;
+	}
+	let _: unsafe extern "system" fn() -> i32 = eglGetError;
+
+	// the fixture has no `eglGetCurrentContext`, so client functions fail to resolve.
+	let egl = Egl::new(&*FIXTURE);
+	assert!(egl.is_current().is_err());
+	assert!(egl.symbol("fixture_add").is_err());
+}
+
+#[test]
+fn test_fixture_load_error() {
+	static LIB: sync::LibLock =
+		sync::LibLock::new(&["dylink_missing_library", "dylink_missing_library2"]);
+
+	let err = LIB.try_init().unwrap_err();
+	assert_eq!(
+		LIB.symbol("fixture_add").unwrap_err().to_string(),
+		err.to_string()
+	);
+	let report = LIB.error().unwrap();
+	assert!(LIB.get().is_none());
+	assert_eq!(report.failures().len(), 2);
+	assert_eq!(err.kind(), report.failures()[0].error().kind());
+	assert!(err.to_string().contains("`dylink_missing_library`"));
+	assert!(err.to_string().contains("`dylink_missing_library2`"));
+	let load_err = report.failures()[0]
+		.error()
+		.get_ref()
+		.and_then(|err| err.downcast_ref::<LoadError>())
+		.unwrap();
+	assert_eq!(load_err.kind(), LoadErrorKind::NotFound);
+	assert_eq!(load_err.dependency(), None);
+
+	let err = open().symbol("dylink_missing_symbol").unwrap_err();
+	let load_err = err
+		.get_ref()
+		.and_then(|err| err.downcast_ref::<LoadError>());
+	assert_eq!(load_err.unwrap().kind(), LoadErrorKind::UndefinedSymbol);
+	let err = Library::open(concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml")).unwrap_err();
+	let load_err = err
+		.get_ref()
+		.and_then(|err| err.downcast_ref::<LoadError>());
+	assert_eq!(load_err.unwrap().kind(), LoadErrorKind::InvalidFormat);
+
+	let entry = registry()
+		.into_iter()
+		.find(|entry| {
+			entry
+				.candidates()
+				.iter()
+				.any(|path| path.ends_with("dylink_missing_library2"))
+		})
+		.unwrap();
+	assert_eq!(entry.error(), Some(report.to_string().as_str()));
+}
+
+#[test]
+fn test_fixture_opener() {
+	use std::{
+		path,
+		sync::atomic::{
+			AtomicUsize,
+			Ordering,
+		},
+	};
+
+	static OPENED: AtomicUsize = AtomicUsize::new(0);
+
+	// opens the fixture in place of any path.
+	struct Fixture;
+	impl sync::Open for Fixture {
+		fn open(&self, _: &path::Path, flags: OpenFlags) -> std::io::Result<Library> {
+			OPENED.fetch_add(1, Ordering::Relaxed);
+			Library::open_with(fixtures::path(), flags)
+		}
+	}
+
+	static LIB: sync::LibLock<Fixture> =
+		sync::LibLock::with_opener(&["dylink_missing_library"], Fixture);
+
+	#[dylink(library = LIB)]
+	extern "C" {
+		fn fixture_add(a: ffi::c_int, b: ffi::c_int) -> ffi::c_int;
+	}
+
+	assert!(LIB.try_init().is_ok());
+	assert_eq!(unsafe { fixture_add(2, 3) }, 5);
+	assert!(LIB.symbol("fixture_data").is_ok());
+	assert_eq!(OPENED.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn test_fixture_win32_flags() {
+	static LIB: LazyLock<sync::LibLock> =
+		LazyLock::new(|| sync::LibLock::new(fixtures::names()).open_flags(OpenFlags::PLUGIN));
+
+	#[dylink(library = LIB, win32_flags = "LOAD_LIBRARY_SEARCH_DLL_LOAD_DIR | LOAD_LIBRARY_SEARCH_SYSTEM32")]
+	extern "C" {
+		fn fixture_add(a: ffi::c_int, b: ffi::c_int) -> ffi::c_int;
+	}
+
+	unsafe {
+		assert_eq!(fixture_add(2, 3), 5);
+	}
+}
+
+#[test]
+fn test_fixture_stub() {
+	extern "C" fn no_missing(a: ffi::c_int) -> ffi::c_int {
+		-a
+	}
+
+	#[dylink(library = FIXTURE, stub = no_missing)]
+	extern "C" fn fixture_missing(a: ffi::c_int) -> ffi::c_int;
+
+	extern "C" fn no_add(_: ffi::c_int, _: ffi::c_int) -> ffi::c_int {
+		-1
+	}
+
+	#[dylink(library = FIXTURE, link_name = "fixture_add", stub = no_add)]
+	extern "C" fn add(a: ffi::c_int, b: ffi::c_int) -> ffi::c_int;
+
+	#[dylink(library = FIXTURE, link_name = "fixture_missing", fallback = no_add)]
+	extern "C" fn missing_add(a: ffi::c_int, b: ffi::c_int) -> ffi::c_int;
+
+	unsafe {
+		assert_eq!(fixture_missing(1), -1);
+		assert_eq!(fixture_missing(2), -2);
+		assert_eq!(add(1, 2), 3);
+		assert_eq!(missing_add(1, 2), -1);
+	}
+}
+
+#[test]
+fn test_fixture_link_name_any() {
+	#[dylink(library = FIXTURE, link_name = any("fixture_missing", "fixture_add"))]
+	extern "C" fn add(a: ffi::c_int, b: ffi::c_int) -> ffi::c_int;
+
+	#[dylink(library = FIXTURE, link_name = any("fixture_missing", "missing"), checked)]
+	extern "C" fn missing();
+
+	unsafe {
+		assert_eq!(add(1, 2), 3);
+		assert!(missing().is_err());
+	}
+}
+
+#[cfg(not(target_env = "msvc"))]
+#[test]
+fn test_fixture_cxx_name() {
+	#[dylink(library = FIXTURE, cxx_name = "fixture::Math::add(int, int) const")]
+	extern "C" fn math_add(this: *const ffi::c_void, a: ffi::c_int, b: ffi::c_int) -> ffi::c_int;
+
+	#[dylink(library = FIXTURE, cxx_name = "_ZNK7fixture4Math3addEii")]
+	extern "C" fn mangled_add(this: *const ffi::c_void, a: ffi::c_int, b: ffi::c_int)
+	-> ffi::c_int;
+
+	#[dylink(library = FIXTURE, cxx_name = "fixture::count(const char*, const char*)")]
+	extern "C" fn count(a: *const ffi::c_char, b: *const ffi::c_char) -> ffi::c_int;
+
+	unsafe {
+		assert_eq!(math_add(std::ptr::null(), 1, 2), 3);
+		assert_eq!(mangled_add(std::ptr::null(), 3, 4), 7);
+		assert_eq!(count(c"ab".as_ptr(), c"cde".as_ptr()), 5);
+	}
+}
+
+#[test]
+fn test_fixture_mod() {
+	#[dylink(library = FIXTURE, link_prefix = "fixture_", eager)]
+	mod fixture {
+		use super::*;
+
+		extern "C" {
+			pub fn add(a: ffi::c_int, b: ffi::c_int) -> ffi::c_int;
+		}
+		extern "C-unwind" {
+			pub fn name() -> *const ffi::c_char;
+			pub static data: ffi::c_int;
+		}
+	}
+
+	fixture::preload().unwrap();
+	unsafe {
+		assert_eq!(fixture::add(2, 3), 5);
+		assert_eq!(ffi::CStr::from_ptr(fixture::name()), c"fixture");
+		assert_eq!(*fixture::data(), 42);
+	}
+}
+
+#[test]
+fn test_fixture_eager() {
+	mod fixture {
+		use super::*;
+
+		#[dylink(library = FIXTURE, eager)]
+		extern "C" {
+			pub fn fixture_add(a: ffi::c_int, b: ffi::c_int) -> ffi::c_int;
+			pub static fixture_data: ffi::c_int;
+		}
+	}
+	mod missing {
+		use super::*;
+
+		#[dylink(library = FIXTURE, eager)]
+		extern "C" {
+			pub fn fixture_missing();
+		}
+	}
+
+	fixture::preload().unwrap();
+	assert!(missing::preload().is_err());
+	unsafe {
+		assert_eq!(fixture::fixture_add(1, 1), 2);
+		assert_eq!(*fixture::fixture_data(), 42);
+	}
+}
+
+#[test]
+fn test_fixture_shared() {
+	#[dylink(library = FIXTURE, shared)]
+	extern "C-unwind" {
+		fn fixture_add(a: ffi::c_int, b: ffi::c_int) -> ffi::c_int;
+		static fixture_data: ffi::c_int;
+		fn fixture_missing();
+	}
+
+	unsafe {
+		assert_eq!(fixture_add(2, 2), 4);
+		// resolved along with `fixture_add`.
+		assert!(
+			!__DYLINK_fixture_data
+				.load(std::sync::atomic::Ordering::Acquire)
+				.is_null()
+		);
+		assert_eq!(*fixture_data(), 42);
+	}
+	assert!(std::panic::catch_unwind(|| unsafe { fixture_missing() }).is_err());
+}
+
+#[test]
+fn test_fixture_probe() {
+	#[dylink(library = FIXTURE, probe)]
+	extern "C" {
+		fn fixture_add(a: ffi::c_int, b: ffi::c_int) -> ffi::c_int;
+		fn fixture_missing();
+	}
+
+	assert!(fixture_add::is_loaded());
+	assert!(!fixture_missing::is_loaded());
+	assert!(fixture_missing::addr().is_none());
+	let addr = fixture_add::addr().unwrap();
+	assert_eq!(
+		addr.as_ptr() as *const Symbol,
+		FIXTURE.symbol("fixture_add").unwrap()
+	);
+	unsafe {
+		assert_eq!(fixture_add(1, 2), 3);
+	}
+}
+
+#[test]
+fn test_fixture_table() {
+	#[dylink(table = FixtureFns, link_prefix = "fixture_")]
+	extern "C" {
+		fn add(a: ffi::c_int, b: ffi::c_int) -> ffi::c_int;
+		fn name() -> *const ffi::c_char;
+	}
+
+	#[dylink(table = MissingFns)]
+	extern "C" {
+		fn fixture_missing();
+	}
+
+	let lib = open();
+	let fns = FixtureFns::load(&lib).unwrap();
+	unsafe {
+		assert_eq!(fns.add(2, 2), 4);
+		assert_eq!(ffi::CStr::from_ptr(fns.name()), c"fixture");
+	}
+	assert!(MissingFns::load(&lib).is_err());
+	lib.close().unwrap();
+}
+
+#[test]
+fn test_fixture_derive_table() {
+	#[derive(DylinkTable)]
+	struct FixtureApi {
+		fixture_add: Option<extern "C" fn(ffi::c_int, ffi::c_int) -> ffi::c_int>,
+		#[dylink(name = "FixtureSub")]
+		sub: Option<extern "C" fn(ffi::c_int, ffi::c_int) -> ffi::c_int>,
+		fixture_missing: Option<extern "C" fn()>,
+	}
+
+	let lib = open();
+	let api = FixtureApi::load(&lib);
+	assert_eq!(api.fixture_add.unwrap()(2, 2), 4);
+	assert_eq!(api.sub.unwrap()(5, 3), 2);
+	assert!(api.fixture_missing.is_none());
+	lib.close().unwrap();
+
+	let api = FixtureApi::load_from(&FIXTURE);
+	assert_eq!(api.fixture_add.unwrap()(1, 2), 3);
+	assert!(api.fixture_missing.is_none());
+}
+
+#[test]
+fn test_fixture_on_error() {
+	use std::sync::atomic::{
+		AtomicUsize,
+		Ordering,
+	};
+	static ERRORS: AtomicUsize = AtomicUsize::new(0);
+	extern "C" fn fallback() -> ffi::c_int {
+		-1
+	}
+	fn handler(err: &std::io::Error) -> Option<*const Symbol> {
+		assert!(err.to_string().contains("fixture_missing"));
+		ERRORS.fetch_add(1, Ordering::Relaxed);
+		Some(fallback as *const Symbol)
+	}
+
+	#[dylink(library = FIXTURE, on_error = handler)]
+	extern "C" {
+		fn fixture_missing() -> ffi::c_int;
+		fn fixture_add(a: ffi::c_int, b: ffi::c_int) -> ffi::c_int;
+	}
+
+	unsafe {
+		assert_eq!(fixture_missing(), -1);
+		assert_eq!(fixture_missing(), -1);
+		assert_eq!(fixture_add(1, 2), 3);
+	}
+	assert_eq!(ERRORS.load(Ordering::Relaxed), 1);
+}
+
+#[test]
+fn test_fixture_checked() {
+	#[dylink(library = FIXTURE, checked)]
+	extern "C" {
+		fn fixture_add(a: ffi::c_int, b: ffi::c_int) -> ffi::c_int;
+		fn fixture_missing();
+		static fixture_data: ffi::c_int;
+	}
+
+	unsafe {
+		assert_eq!(fixture_add(40, 2).unwrap(), 42);
+		assert_eq!(*fixture_data().unwrap(), 42);
+		assert!(fixture_missing().is_err());
+		assert!(fixture_missing().is_err());
+	}
+}
+
+#[test]
+fn test_fixture_capabilities() {
+	use sync::{
+		Capability,
+		Probe,
+	};
+	fn version(sym: *const Symbol) -> u64 {
+		let version: extern "C" fn() -> ffi::c_int = unsafe { std::mem::transmute(sym) };
+		version() as u64
+	}
+	static CAPS: [Capability; 4] = [
+		Capability::new("add", Probe::Symbol("fixture_add")),
+		Capability::new("missing", Probe::Symbol("fixture_missing")),
+		Capability::new(
+			"v1",
+			Probe::Version {
+				symbol: "fixture_version",
+				min: 10000,
+				query: version,
+			},
+		),
+		Capability::new(
+			"v2",
+			Probe::Version {
+				symbol: "fixture_version",
+				min: 20000,
+				query: version,
+			},
+		),
+	];
+	let lib = sync::LibLock::with_capabilities(fixtures::names(), &CAPS);
+	assert!(lib.capability("add"));
+	assert!(!lib.capability("missing"));
+	assert!(lib.capability("v1"));
+	assert!(!lib.capability("v2"));
+	assert!(!lib.capability("undeclared"));
+	assert_eq!(lib.capabilities().len(), 4);
+
+	let missing = sync::LibLock::with_capabilities(&["libdylink_missing.so"], &CAPS);
+	assert!(!missing.capability("add"));
+	assert!(
+		missing
+			.capabilities()
+			.iter()
+			.all(|&(_, available)| !available)
+	);
+}
+
+#[test]
+fn test_fixture_exports() {
+	let lib = open();
+	let image = lib.to_image().unwrap();
+	let exports = image.exports().unwrap();
+	let find = |name| exports.iter().find(|e| e.name() == Some(name));
+
+	let add = find("fixture_add").unwrap();
+	assert_eq!(
+		image as *const img::Image as usize + add.rva(),
+		lib.symbol("fixture_add").unwrap() as usize
+	);
+	assert!(find("fixture_name").is_some());
+	assert!(find("fixture_data").is_some());
+	assert!(find("fixture_missing").is_none());
+
+	// Mach-O symbol tables don't record symbol kinds.
+	if cfg!(not(target_os = "macos")) {
+		assert_eq!(add.kind(), img::ExportKind::Function);
+		assert_eq!(find("fixture_data").unwrap().kind(), img::ExportKind::Data);
+	}
+	if cfg!(all(unix, not(target_os = "macos"))) {
+		assert_eq!(find("fixture_tls").unwrap().kind(), img::ExportKind::Tls);
+		assert!(find("fixture_weak").is_some());
+	}
+	if let Some(forward) = find("fixture_forward") {
+		assert_eq!(forward.forwarder(), Some("kernel32.GetLastError"));
+	}
+
+	let file = img::File::open(fixtures::path()).unwrap();
+	assert_eq!(file.arch().unwrap(), image.arch().unwrap());
+	let mut names: Vec<_> = exports.iter().filter_map(img::Export::name).collect();
+	let file_exports = file.exports().unwrap();
+	let mut file_names: Vec<_> = file_exports.iter().filter_map(img::Export::name).collect();
+	names.sort_unstable();
+	file_names.sort_unstable();
+	assert_eq!(names, file_names);
+	lib.close().unwrap();
+}
+
+#[test]
+fn test_fixture_dump_exports() {
+	use tools::ExportFormat;
+	let json = tools::dump_exports(fixtures::path(), ExportFormat::Json).unwrap();
+	assert!(json.starts_with("[\n\t{\"name\": "));
+	assert!(json.contains("{\"name\": \"fixture_add\", "));
+
+	let csv = tools::dump_exports(fixtures::path(), ExportFormat::Csv).unwrap();
+	let mut lines = csv.lines();
+	assert_eq!(lines.next(), Some("name,ordinal,rva,size,kind,forwarder"));
+	let add = lines.find(|line| line.starts_with("fixture_add,")).unwrap();
+	assert_eq!(add.split(',').count(), 6);
+	assert_eq!(csv.lines().count(), json.lines().count() - 1);
+}
+
+#[test]
+fn test_fixture_abi_check() {
+	let old = img::File::open(fixtures::path())
+		.unwrap()
+		.exports()
+		.unwrap();
+	assert!(tools::abi_check(&old, &old).is_ok());
+
+	let new: Vec<_> = old
+		.iter()
+		.filter(|e| e.name() != Some("fixture_add"))
+		.cloned()
+		.collect();
+	assert!(tools::abi_check(&new, &old).is_ok());
+	let breaks = tools::abi_check(&old, &new).unwrap_err();
+	assert_eq!(
+		breaks.breaks(),
+		[tools::AbiBreak::Removed {
+			name: "fixture_add".into()
+		}]
+	);
+	assert_eq!(
+		breaks.to_string(),
+		"1 breaking change(s): `fixture_add` was removed"
+	);
+}
+
+#[cfg(unix)]
+#[test]
+fn test_fixture_version() {
+	let (major, minor, patch) = fixtures::VERSION;
+	let lib = open();
+	let info = lib.to_image().unwrap().version().unwrap();
+	assert_eq!(info.current(), Some(img::Version::new(major, minor, patch)));
+	assert_eq!(
+		img::File::open(fixtures::path())
+			.unwrap()
+			.version()
+			.unwrap(),
+		info
+	);
+	lib.close().unwrap();
+}
+
+#[test]
+fn test_fixture_segments() {
+	let lib = open();
+	let image = lib.to_image().unwrap();
+	let segments = image.segments().unwrap();
+	let add = lib.symbol("fixture_add").unwrap() as usize;
+	let text = segments
+		.iter()
+		.find(|seg| seg.range().contains(&add))
+		.unwrap();
+	assert!(text.protection().is_executable());
+	assert!(!text.protection().is_writable());
+	image.advise(img::Advice::WillNeed).unwrap();
+	assert_eq!(
+		image.perf_report().unwrap().base(),
+		image as *const _ as usize
+	);
+	lib.close().unwrap();
+}
+
+#[test]
+fn test_fixture_symbolize() {
+	let lib = open();
+	let add = lib.symbol("fixture_add").unwrap();
+	let location = symbolize::resolve(add.cast()).unwrap();
+	assert_eq!(location.name(), Some("fixture_add"));
+	assert_eq!(location.offset(), 0);
+	lib.close().unwrap();
+}
+
+#[test]
+fn test_fixture_module_map() {
+	let lib = open();
+	let map = img::ModuleMap::global();
+	if !map.is_watched() {
+		map.refresh().unwrap();
+	}
+	let add = lib.symbol("fixture_add").unwrap() as usize;
+	let module = map.lookup(add).unwrap();
+	assert_eq!(module.base(), lib.to_image().unwrap() as *const _);
+	assert_eq!(
+		module.path().unwrap().file_name(),
+		fixtures::path().file_name()
+	);
+	assert!(map.generation() > 0);
+	assert!(map.lookup(0).is_none());
+
+	// a guard keeps its snapshot while the map is republished.
+	let modules = map.modules();
+	map.refresh().unwrap();
+	map.refresh().unwrap();
+	assert!(map.generation() > modules.generation());
+	assert_eq!(modules.lookup(add).unwrap().base(), module.base());
+	drop(modules);
+	drop(module);
+	lib.close().unwrap();
+}
+
+#[test]
+fn test_fixture_flush_caches() {
+	let map = img::ModuleMap::global();
+	let lib = open();
+	flush_caches().unwrap();
+	let add = lib.symbol("fixture_add").unwrap() as usize;
+	assert!(map.lookup(add).is_some());
+	lib.close().unwrap();
+}
+
+#[test]
+fn test_fixture_report() {
+	let report = lib_report!["fixture_missing", fixtures::path_str()];
+	assert_eq!(report.index(), Some(1));
+	assert_eq!(report.failures().len(), 1);
+	let lib = report.into_library().unwrap();
+
+	let images = img::Images::now().unwrap();
+	let diff = img::Images::diff(&img::Images::from(Vec::new()), &images);
+	assert!(
+		diff.loaded()
+			.iter()
+			.any(|weak| std::ptr::eq(weak.to_ptr(), lib.to_image().unwrap()))
+	);
+	lib.close().unwrap();
+}
+
+#[test]
+fn test_fixture_trace() {
+	use std::sync::atomic::{
+		AtomicBool,
+		Ordering,
+	};
+	static TRACED: AtomicBool = AtomicBool::new(false);
+	fn hook(event: &trace::Event<'_>) {
+		// other tests may resolve symbols while the hook is set.
+		if event.item() == "traced_add" {
+			assert_eq!(event.library(), "FIXTURE");
+			assert_eq!(event.names(), ["fixture_add"]);
+			assert!(event.address().is_some());
+			assert_eq!(
+				event.path().unwrap().file_name(),
+				fixtures::path().file_name()
+			);
+			assert!(event.to_string().contains("`traced_add` bound to"));
+			TRACED.store(true, Ordering::Relaxed);
+		}
+	}
+
+	#[dylink(library = FIXTURE, link_name = "fixture_add")]
+	extern "C" fn traced_add(a: ffi::c_int, b: ffi::c_int) -> ffi::c_int;
+
+	trace::set_hook(hook);
+	assert_eq!(unsafe { traced_add(1, 1) }, 2);
+	let _ = trace::take_hook();
+	assert!(TRACED.load(Ordering::Relaxed));
+}
+
+#[test]
+fn test_fixture_nullable() {
+	#[dylink(library = FIXTURE, link_prefix = "fixture_", nullable)]
+	extern "C" {
+		fn name() -> *const ffi::c_char;
+		fn add(a: ffi::c_int, b: ffi::c_int) -> ffi::c_int;
+	}
+
+	#[dylink(table = NullableFns, link_prefix = "fixture_", nullable)]
+	extern "C" {
+		fn name() -> *const ffi::c_char;
+	}
+
+	let lib = open();
+	let fns = NullableFns::load(&lib).unwrap();
+	unsafe {
+		let name_ptr: Option<std::ptr::NonNull<ffi::c_char>> = name();
+		assert_eq!(ffi::CStr::from_ptr(name_ptr.unwrap().as_ptr()), c"fixture");
+		assert_eq!(add(1, 2), 3);
+		assert!(fns.name().is_some());
+	}
+	lib.close().unwrap();
+}
+
+#[test]
+fn test_fixture_concurrent_first_call() {
+	use std::sync::Barrier;
+
+	#[dylink(library = FIXTURE, link_prefix = "fixture_")]
+	extern "C" {
+		fn add(a: ffi::c_int, b: ffi::c_int) -> ffi::c_int;
+		static data: ffi::c_int;
+	}
+
+	mod relaxed {
+		use super::*;
+
+		#[dylink(library = FIXTURE, link_prefix = "fixture_", ordering = "relaxed")]
+		extern "C" {
+			pub fn add(a: ffi::c_int, b: ffi::c_int) -> ffi::c_int;
+			pub static data: ffi::c_int;
+		}
+
+		#[dylink(library = FIXTURE, link_name = "fixture_add", ordering = "relaxed", checked)]
+		pub extern "C" fn checked_add(a: ffi::c_int, b: ffi::c_int) -> ffi::c_int;
+	}
+
+	const THREADS: usize = 8;
+	let barrier = Barrier::new(THREADS);
+	std::thread::scope(|scope| {
+		for i in 0..THREADS as ffi::c_int {
+			let barrier = &barrier;
+			scope.spawn(move || {
+				barrier.wait();
+				unsafe {
+					assert_eq!(add(i, 1), i + 1);
+					assert_eq!(*data(), 42);
+					assert_eq!(relaxed::add(i, 2), i + 2);
+					assert_eq!(*relaxed::data(), 42);
+					assert_eq!(relaxed::checked_add(i, 3).unwrap(), i + 3);
+				}
+			});
+		}
+	});
+}
+
+#[test]
+fn test_fixture_global() {
+	let lib = open();
+	let data = unsafe { lib.global::<ffi::c_int>("fixture_data") }.unwrap();
+	assert_eq!(data.read(), 42);
+	assert_eq!(
+		data.as_ptr().cast_const(),
+		lib.symbol("fixture_data").unwrap().cast()
+	);
+
+	#[cfg(target_os = "linux")]
+	{
+		let err = unsafe { lib.global::<[ffi::c_int; 2]>("fixture_data") }.unwrap_err();
+		assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+	}
+	assert!(unsafe { lib.global::<ffi::c_int>("fixture_missing") }.is_err());
+	lib.close().unwrap();
+}
+
+#[test]
+fn test_fixture_failure_policy() {
+	use dylink::config::{
+		self,
+		Policy,
+	};
+	extern "C" fn fallback() -> ffi::c_int {
+		-1
+	}
+	// other tests rely on missing symbols panicking, so only this one is substituted.
+	fn hook(err: &std::io::Error) -> Option<*const Symbol> {
+		err.to_string()
+			.contains("`policy_missing`")
+			.then_some(fallback as *const Symbol)
+	}
+
+	#[dylink(library = FIXTURE)]
+	extern "C" fn policy_missing() -> ffi::c_int;
+
+	config::set_failure_policy(Policy::ErrorHook(hook));
+	assert!(matches!(config::failure_policy(), Policy::ErrorHook(_)));
+	assert_eq!(unsafe { policy_missing() }, -1);
+	config::set_failure_policy(Policy::Panic);
+}
+
+#[test]
+fn test_fixture_optional() {
+	#[dylink(library = FIXTURE, link_prefix = "fixture_", optional)]
+	extern "C" {
+		fn add(a: ffi::c_int, b: ffi::c_int) -> ffi::c_int;
+		fn added_later(a: ffi::c_int) -> ffi::c_int;
+		fn added_later_ptr() -> *const ffi::c_char;
+		fn added_later_unit();
+	}
+
+	#[dylink(library = FIXTURE, optional, probe)]
+	extern "C" fn fixture_missing_probed() -> ffi::c_int;
+
+	unsafe {
+		assert_eq!(add(1, 2), 3);
+		assert_eq!(added_later(1), 0);
+		assert!(added_later_ptr().is_null());
+		added_later_unit();
+		assert_eq!(fixture_missing_probed(), 0);
+	}
+	assert!(add::resolved());
+	assert!(!added_later::resolved());
+	assert!(!fixture_missing_probed::resolved());
+	assert!(!fixture_missing_probed::is_loaded());
+}
+
+#[test]
+fn test_fixture_bind_hook() {
+	use std::sync::atomic::{
+		AtomicUsize,
+		Ordering,
+	};
+	static BOUND: AtomicUsize = AtomicUsize::new(0);
+	fn hook(event: &BindEvent<'_>) {
+		// other tests bind items concurrently, so only this one is counted.
+		if event.item() == "bound_add" {
+			assert_eq!(event.names(), ["fixture_add"]);
+			assert_eq!(
+				event.path().unwrap().file_name(),
+				fixtures::path().file_name()
+			);
+			BOUND.fetch_add(1, Ordering::Relaxed);
+		}
+	}
+
+	#[dylink(library = FIXTURE, link_name = "fixture_add")]
+	extern "C" fn bound_add(a: ffi::c_int, b: ffi::c_int) -> ffi::c_int;
+
+	set_bind_hook(hook);
+	unsafe {
+		assert_eq!(bound_add(1, 1), 2);
+		assert_eq!(bound_add(2, 2), 4);
+	}
+	let _ = take_bind_hook();
+	assert_eq!(BOUND.load(Ordering::Relaxed), 1);
+}
@@ -0,0 +1,73 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+// A tiny library exporting one of each kind of symbol the integration tests inspect.
+
+#include <stdarg.h>
+
+#if defined(_WIN32)
+#define EXPORT __declspec(dllexport)
+#else
+#define EXPORT __attribute__((visibility("default")))
+#endif
+
+EXPORT int fixture_add(int a, int b) { return a + b; }
+
+// Only in the other fixture, built with `FIXTURE_OTHER`, to tell two libraries apart.
+#if defined(FIXTURE_OTHER)
+EXPORT int fixture_other(void) { return 1; }
+#endif
+
+EXPORT const char *fixture_name(void) { return "fixture"; }
+
+// Named in `PascalCase`, like Win32 functions.
+EXPORT int FixtureSub(int a, int b) { return a - b; }
+
+EXPORT int fixture_data = 42;
+
+// Sums `count` integers passed as variadic arguments.
+EXPORT int fixture_sum(int count, ...) {
+	va_list args;
+	va_start(args, count);
+	int sum = 0;
+	for (int i = 0; i < count; i++) sum += va_arg(args, int);
+	va_end(args);
+	return sum;
+}
+
+// Encodes the library version as `major * 10000 + minor * 100 + patch`.
+EXPORT int fixture_version(void) { return 10203; }
+
+#if !defined(_WIN32)
+EXPORT __thread int fixture_tls = 7;
+
+EXPORT __attribute__((weak)) int fixture_weak(void) { return 1; }
+#endif
+
+#if !defined(_MSC_VER)
+#define STR(x) #x
+#define XSTR(x) STR(x)
+#define ASM_NAME(name) __asm__(XSTR(__USER_LABEL_PREFIX__) name)
+
+// Stand-ins for `fixture::Math::add(int, int) const` and `fixture::count(const char*, const char*)`
+// under their Itanium mangled names.
+EXPORT int fixture_math_add(const void *self, int a, int b) ASM_NAME("_ZNK7fixture4Math3addEii");
+int fixture_math_add(const void *self, int a, int b) { return a + b; }
+
+EXPORT int fixture_count(const char *a, const char *b) ASM_NAME("_ZN7fixture5countEPKcS1_");
+int fixture_count(const char *a, const char *b) {
+	int n = 0;
+	while (*a++) n++;
+	while (*b++) n++;
+	return n;
+}
+
+// A name that isn't an identifier, which becomes the same identifier as `fixture_dotted`.
+EXPORT int fixture_dotted(void) { return 1; }
+EXPORT int fixture_dotted_name(void) ASM_NAME("fixture.dotted");
+int fixture_dotted_name(void) { return 2; }
+#endif
+
+#if defined(_MSC_VER)
+#pragma comment(linker, "/export:fixture_forward=kernel32.GetLastError")
+#endif
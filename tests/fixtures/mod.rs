@@ -0,0 +1,135 @@
+// SPDX-FileCopyrightText: 2022-2026 Jonathan A. Thomason <contact@jonathan-thomason.com>
+// SPDX-License-Identifier: MIT OR Apache-2.0
+
+//! Builds the fixture library from `fixture.c` with the system C compiler.
+//!
+//! The compiler can be overridden with the `CC` environment variable.
+
+#![allow(dead_code)]
+
+use std::{
+	env,
+	ffi::OsString,
+	fs,
+	path::{
+		Path,
+		PathBuf,
+	},
+	process::Command,
+	sync::OnceLock,
+};
+
+/// The version the fixture is linked with on unix.
+pub const VERSION: (u32, u32, u32) = (1, 2, 3);
+
+#[cfg(target_os = "linux")]
+pub const FILE_NAME: &str = "libfixture.so.1.2.3";
+#[cfg(target_os = "macos")]
+pub const FILE_NAME: &str = "libfixture.dylib";
+#[cfg(windows)]
+pub const FILE_NAME: &str = "fixture.dll";
+#[cfg(not(any(target_os = "linux", target_os = "macos", windows)))]
+pub const FILE_NAME: &str = "libfixture.so";
+
+/// Returns the path of the fixture library, building it on first use.
+pub fn path() -> &'static Path {
+	static PATH: OnceLock<PathBuf> = OnceLock::new();
+	PATH.get_or_init(build)
+}
+
+/// Returns the path of another fixture library, which also exports `fixture_other`, building it on
+/// first use. It's in the same directory as the fixture.
+pub fn other_path() -> &'static Path {
+	static PATH: OnceLock<PathBuf> = OnceLock::new();
+	PATH.get_or_init(|| {
+		let out = path().with_file_name(format!("other_{FILE_NAME}"));
+		compile(&out, Some("FIXTURE_OTHER"));
+		out
+	})
+}
+
+/// Returns the path of the fixture library as a `'static` string, for use with `LibLock`.
+pub fn path_str() -> &'static str {
+	static PATH: OnceLock<String> = OnceLock::new();
+	PATH.get_or_init(|| {
+		path()
+			.to_str()
+			.expect("non UTF-8 target directory")
+			.to_owned()
+	})
+}
+
+/// Returns the path of the fixture library as the only candidate of a `LibLock`.
+pub fn names() -> &'static [&'static str] {
+	static NAMES: OnceLock<[&str; 1]> = OnceLock::new();
+	NAMES.get_or_init(|| [path_str()])
+}
+
+/// Returns the path of the fixture library as the only candidate of `LibLock::from_paths`.
+pub fn paths() -> &'static [&'static Path] {
+	static PATHS: OnceLock<[&Path; 1]> = OnceLock::new();
+	PATHS.get_or_init(|| [path()])
+}
+
+fn build() -> PathBuf {
+	// test binaries may run concurrently, so each one builds its own copy.
+	let dir =
+		Path::new(env!("CARGO_TARGET_TMPDIR")).join(format!("fixture-{}", std::process::id()));
+	fs::create_dir_all(&dir).unwrap();
+	let out = dir.join(FILE_NAME);
+	compile(&out, None);
+	out
+}
+
+fn compile(out: &Path, define: Option<&str>) {
+	// the source is embedded, since this module is also used by the tests of other packages.
+	let src = out.with_file_name("fixture.c");
+	fs::write(&src, include_str!("fixture.c")).unwrap();
+	let status = compilers()
+		.into_iter()
+		.find_map(|cc| command(&cc, &src, out, define).status().ok())
+		.expect("no C compiler found, set `CC` to build the test fixture");
+	assert!(status.success(), "failed to build test fixture");
+}
+
+fn compilers() -> Vec<OsString> {
+	if let Some(cc) = env::var_os("CC") {
+		return vec![cc];
+	}
+	if cfg!(windows) {
+		vec!["cl".into(), "clang".into(), "gcc".into()]
+	} else {
+		vec!["cc".into()]
+	}
+}
+
+fn command(cc: &OsString, src: &Path, out: &Path, define: Option<&str>) -> Command {
+	let mut cmd = Command::new(cc);
+	if cfg!(windows) && Path::new(cc).file_stem().is_some_and(|s| s == "cl") {
+		if let Some(define) = define {
+			cmd.arg(format!("/D{define}"));
+		}
+		let mut fe = OsString::from("/Fe:");
+		fe.push(out);
+		let mut fo = OsString::from("/Fo:");
+		fo.push(out.with_extension("obj"));
+		cmd.args(["/nologo", "/LD"]).arg(src).arg(fe).arg(fo);
+		return cmd;
+	}
+	if let Some(define) = define {
+		cmd.arg(format!("-D{define}"));
+	}
+	cmd.arg("-shared").arg("-o").arg(out).arg(src);
+	let (major, minor, patch) = VERSION;
+	if cfg!(target_os = "macos") {
+		cmd.args(["-dynamiclib", "-install_name"])
+			.arg(out)
+			.arg("-current_version")
+			.arg(format!("{major}.{minor}.{patch}"))
+			.args(["-compatibility_version", "1.0.0"]);
+	} else if cfg!(unix) {
+		let soname = out.file_name().unwrap().to_str().unwrap();
+		cmd.arg("-fPIC").arg(format!("-Wl,-soname,{soname}"));
+	}
+	cmd
+}
@@ -127,3 +127,226 @@ fn test_downgrade_upgrade() {
 
 	assert!(strong_clone.is_some());
 }
+
+#[test]
+fn test_segments() {
+	let this = Library::this();
+	let segments = this.to_image().unwrap().segments().unwrap();
+	assert!(segments.iter().any(|seg| seg.protection().is_executable()));
+	for seg in segments {
+		assert!(!seg.is_empty());
+		if cfg!(any(target_os = "linux", windows)) && seg.protection().is_executable() {
+			assert!(seg.query().unwrap().is_executable());
+		}
+	}
+}
+
+#[test]
+fn test_advise() {
+	use dylink::img::Advice;
+	let this = Library::this();
+	let image = this.to_image().unwrap();
+	image.advise(Advice::WillNeed).unwrap();
+	image.advise(Advice::DontNeed).unwrap();
+	// the image must remain usable after its pages are released.
+	assert!(image.segments().is_ok());
+}
+
+#[test]
+fn test_perf_report() {
+	let this = Library::this();
+	let report = this.to_image().unwrap().perf_report().unwrap();
+	assert!(report.page_size().is_power_of_two());
+	if cfg!(any(target_os = "linux", windows)) {
+		let pages = report.pages().unwrap();
+		assert!(pages.mappings() > 0);
+		assert!(pages.resident() >= pages.large());
+	}
+}
+
+#[test]
+fn test_images_diff() {
+	let now = img::Images::now().unwrap();
+	assert!(img::Images::diff(&now, &now.clone()).is_empty());
+
+	let empty = img::Images::from(Vec::new());
+	let diff = img::Images::diff(&empty, &now);
+	assert_eq!(diff.loaded().len(), now.len());
+	assert!(diff.unloaded().is_empty());
+}
+
+#[test]
+fn test_wait_until_initialized() {
+	let lib = Library::this();
+	lib.wait_until_initialized().unwrap();
+}
+
+#[test]
+fn test_lib_report() {
+	let report = lib_report!["does_not_exist_1", "does_not_exist_2"];
+	assert!(report.library().is_none());
+	assert_eq!(report.index(), None);
+	assert_eq!(report.failures().len(), 2);
+	assert_eq!(report.failures()[1].index(), 1);
+	assert!(report.to_string().contains("does_not_exist_2"));
+}
+
+#[test]
+fn test_try_upgrade() {
+	let this = Library::this();
+	let weak = Library::downgrade(&this).unwrap();
+	let lib = weak.try_upgrade().unwrap().unwrap();
+	assert!(std::ptr::eq(
+		lib.to_image().unwrap(),
+		this.to_image().unwrap()
+	));
+	assert!(Weak::new().try_upgrade().unwrap().is_none());
+}
+
+#[test]
+fn test_mock_loader() {
+	use std::io;
+	use sync::Loader;
+
+	static MOCK: test::MockLoader = test::MockLoader::new();
+	extern "C" fn fake_add(a: i32, b: i32) -> i32 {
+		a + b
+	}
+
+	#[dylink(loader = MOCK)]
+	extern "C" {
+		fn mocked_add(a: i32, b: i32) -> i32;
+	}
+
+	MOCK.define("mocked_add", fake_add as *const ());
+	assert_eq!(unsafe { mocked_add(3, 1) }, 4);
+	assert!(MOCK.undefine("mocked_add"));
+	let err = MOCK.symbol("mocked_add").unwrap_err();
+	assert_eq!(err.kind(), io::ErrorKind::NotFound);
+}
+
+#[test]
+fn test_mock_egl() {
+	use std::{
+		ffi,
+		sync::atomic::{
+			AtomicBool,
+			Ordering,
+		},
+	};
+	use sync::{
+		Egl,
+		Loader,
+	};
+
+	static MOCK: test::MockLoader = test::MockLoader::new();
+	static CURRENT: AtomicBool = AtomicBool::new(false);
+	extern "system" fn get_current_context() -> *mut ffi::c_void {
+		CURRENT.load(Ordering::Relaxed) as usize as *mut ffi::c_void
+	}
+	extern "system" fn get_proc_address(name: *const ffi::c_char) -> *const () {
+		match unsafe { ffi::CStr::from_ptr(name) }.to_bytes() {
+			b"glClear" => get_proc_address as *const (),
+			_ => std::ptr::null(),
+		}
+	}
+	MOCK.define("eglGetCurrentContext", get_current_context as *const ())
+		.define("eglGetProcAddress", get_proc_address as *const ());
+
+	let egl = Egl::new(&MOCK);
+	assert!(!egl.is_current().unwrap());
+	assert!(egl.symbol("eglGetProcAddress").is_ok());
+	assert!(egl.symbol("glClear").is_err());
+	CURRENT.store(true, Ordering::Relaxed);
+	assert_eq!(
+		egl.symbol("glClear").unwrap(),
+		get_proc_address as *const Symbol
+	);
+	assert!(egl.symbol("glMissing").is_err());
+}
+
+#[test]
+fn test_mock_vulkan() {
+	use std::{
+		ffi,
+		sync::atomic::{
+			AtomicUsize,
+			Ordering,
+		},
+	};
+	use sync::Loader;
+
+	static MOCK: test::MockLoader = test::MockLoader::new();
+	static INSTANCE: u8 = 0;
+	static DEVICE: u8 = 0;
+	static BOOTSTRAPS: AtomicUsize = AtomicUsize::new(0);
+	extern "system" fn create_instance() {}
+	extern "system" fn destroy_instance() {}
+	extern "system" fn queue_submit() {}
+	extern "system" fn get_device_proc_addr(
+		device: vk::VkDevice,
+		name: *const ffi::c_char,
+	) -> *const () {
+		let is_device = device == &DEVICE as *const u8 as vk::VkDevice;
+		match (unsafe { ffi::CStr::from_ptr(name) }.to_bytes(), is_device) {
+			(b"vkQueueSubmit", true) => queue_submit as *const (),
+			_ => std::ptr::null(),
+		}
+	}
+	extern "system" fn get_instance_proc_addr(
+		instance: vk::VkInstance,
+		name: *const ffi::c_char,
+	) -> *const () {
+		let is_instance = instance == &INSTANCE as *const u8 as vk::VkInstance;
+		match (unsafe { ffi::CStr::from_ptr(name) }.to_bytes(), is_instance) {
+			(b"vkCreateInstance", _) => create_instance as *const (),
+			(b"vkDestroyInstance", true) => destroy_instance as *const (),
+			(b"vkGetDeviceProcAddr", true) => {
+				BOOTSTRAPS.fetch_add(1, Ordering::Relaxed);
+				get_device_proc_addr as *const ()
+			}
+			_ => std::ptr::null(),
+		}
+	}
+	MOCK.define("vkGetInstanceProcAddr", get_instance_proc_addr as *const ());
+
+	#[dylink(vulkan)]
+	unsafe extern "system" {
+		fn vkEnumerateInstanceVersion(version: *mut u32) -> i32;
+	}
+	let _: unsafe extern "system" fn(*mut u32) -> i32 = vkEnumerateInstanceVersion;
+
+	let instance = &INSTANCE as *const u8 as vk::VkInstance;
+	let vulkan = vk::Vulkan::new(&MOCK);
+	assert_eq!(
+		vulkan.symbol("vkCreateInstance").unwrap(),
+		create_instance as *const Symbol
+	);
+	assert!(vulkan.symbol("vkDestroyInstance").is_err());
+	vk::register_instance(instance);
+	assert_eq!(
+		vulkan.symbol("vkDestroyInstance").unwrap(),
+		destroy_instance as *const Symbol
+	);
+
+	// device functions bootstrap `vkGetDeviceProcAddr` from the instance once.
+	let device = &DEVICE as *const u8 as vk::VkDevice;
+	assert!(vulkan.symbol("vkQueueSubmit").is_err());
+	vk::register_device(device);
+	for _ in 0..2 {
+		assert_eq!(
+			vulkan.symbol("vkQueueSubmit").unwrap(),
+			queue_submit as *const Symbol
+		);
+	}
+	assert_eq!(
+		vulkan.symbol("vkDestroyInstance").unwrap(),
+		destroy_instance as *const Symbol
+	);
+	assert_eq!(BOOTSTRAPS.load(Ordering::Relaxed), 1);
+	assert!(vk::unregister_device(device));
+
+	assert!(vk::unregister_instance(instance));
+	assert!(!vk::unregister_instance(instance));
+	assert!(vulkan.symbol("vkDestroyInstance").is_err());
+}
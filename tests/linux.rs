@@ -4,33 +4,8 @@
 #![cfg(target_os = "linux")]
 use dylink::*;
 
-static LIB_X11: sync::LibLock = sync::LibLock::new(&["libX11.so.6"]);
-
-#[test]
-fn test_linux_x11() {
-	use std::ffi::{
-		CStr,
-		c_char,
-		c_void,
-	};
-
-	type Display = c_void;
-
-	#[dylink(library = LIB_X11)]
-	extern "C-unwind" {
-		fn XOpenDisplay(display_name: *const c_char) -> *mut Display;
-		fn XCloseDisplay(display: *mut Display);
-	}
-
-	unsafe {
-		let display_name = CStr::from_bytes_until_nul(b"foo\0").unwrap();
-		let disp = XOpenDisplay(display_name.as_ptr());
-		if !disp.is_null() {
-			println!("display created successfully.\nnow destroying...");
-			XCloseDisplay(disp);
-		}
-	}
-}
+#[path = "fixtures/mod.rs"]
+mod fixtures;
 
 #[test]
 fn test_atoi_linux() {
@@ -44,14 +19,26 @@ fn test_atoi_linux() {
 		fn atoi(s: *const c_char) -> c_int;
 	}
 
-	let five = unsafe { atoi(b"5\0".as_ptr().cast()) };
+	let five = unsafe { atoi(c"5".as_ptr()) };
 	assert_eq!(five, 5);
 }
 
+#[test]
+fn test_validate() {
+	use std::ffi::c_int;
+	static LIBC: sync::LibLock = sync::LibLock::new(&["libc.so.6"]);
+	#[dylink(library = LIBC, validate = "libc.so.6")]
+	extern "C" {
+		fn getpid() -> c_int;
+	}
+
+	assert_eq!(unsafe { getpid() } as u32, std::process::id());
+}
+
 #[test]
 fn test_sym_hdr() {
-	let lib = Library::open("libX11.so.6").unwrap();
-	let sym = lib.symbol("XOpenDisplay").unwrap();
+	let lib = Library::open(fixtures::path()).unwrap();
+	let sym = lib.symbol("fixture_add").unwrap();
 	let base = Symbol::image(sym);
 	assert!(base.is_some());
 	lib.close().unwrap();
@@ -59,7 +46,7 @@ fn test_sym_hdr() {
 
 #[test]
 fn test_path() {
-	let lib = Library::open("libX11.so.6").unwrap();
+	let lib = Library::open(fixtures::path()).unwrap();
 	let path = lib.to_image().unwrap().path();
 	assert!(path.is_ok());
 	lib.close().unwrap();
@@ -108,7 +95,7 @@ fn elf_span_32(data: &[u8]) -> Option<usize> {
 		max_end = max_end.max(sh_offset + sh_size);
 	}
 
-	Some(max_end as usize)
+	Some(max_end)
 }
 
 fn elf_span_64(data: &[u8]) -> Option<usize> {
@@ -183,3 +170,245 @@ fn to_bytes_returns_full_image_size() {
 		let _ = lib.close();
 	}
 }
+
+#[cfg(target_env = "gnu")]
+#[test]
+fn test_version() {
+	let this = Library::this();
+	let atoi = this.symbol("atoi").unwrap();
+	let info = Symbol::image(atoi).unwrap().version().unwrap();
+	assert_eq!(info.name(), Some("libc.so.6"));
+	assert_eq!(info.current(), Some(img::Version::new(6, 0, 0)));
+	assert!(info.definitions().iter().any(|d| d.starts_with("GLIBC_2.")));
+}
+
+#[test]
+fn test_elf_view() {
+	let this = Library::this();
+	let atoi = this.symbol("atoi").unwrap();
+	let image = Symbol::image(atoi).unwrap();
+	assert!(image.as_macho().is_none());
+	let view = image.as_elf().unwrap();
+	assert_eq!(view.is_64(), cfg!(target_pointer_width = "64"));
+	// PT_LOAD
+	assert!(view.program_headers().iter().any(|ph| ph.p_type() == 1));
+	assert!(!view.dynamic().unwrap().is_empty());
+	let export = view.lookup("atoi").unwrap().unwrap();
+	assert_eq!(
+		image as *const img::Image as usize + export.rva(),
+		atoi as usize
+	);
+
+	let file = img::File::open(image.path().unwrap()).unwrap();
+	assert_eq!(
+		file.as_elf().unwrap().program_headers(),
+		view.program_headers()
+	);
+}
+
+#[test]
+fn test_elf_lookup() {
+	let lib = Library::open(fixtures::path()).unwrap();
+	let image = lib.to_image().unwrap();
+	let view = image.as_elf().unwrap();
+	let add = view.lookup("fixture_add").unwrap().unwrap();
+	assert_eq!(
+		image as *const img::Image as usize + add.rva(),
+		lib.symbol("fixture_add").unwrap() as usize
+	);
+	assert_eq!(add.kind(), img::ExportKind::Function);
+	assert!(view.lookup("fixture_missing").unwrap().is_none());
+
+	let file = img::File::open(fixtures::path()).unwrap();
+	assert_eq!(
+		file.as_elf().unwrap().lookup("fixture_add").unwrap(),
+		Some(add)
+	);
+	lib.close().unwrap();
+}
+
+#[cfg(target_pointer_width = "64")]
+#[test]
+fn test_file_malformed() {
+	let mut bytes = std::fs::read(fixtures::path()).unwrap();
+	let field = |bytes: &[u8], at: usize, len: usize| {
+		let mut buf = [0u8; 8];
+		buf[..len].copy_from_slice(&bytes[at..at + len]);
+		u64::from_le_bytes(buf) as usize
+	};
+	// moves the first `PT_LOAD` segment to the end of the address space.
+	let (phoff, phentsize, phnum) = (
+		field(&bytes, 0x20, 8),
+		field(&bytes, 0x36, 2),
+		field(&bytes, 0x38, 2),
+	);
+	let phdr = (0..phnum)
+		.map(|i| phoff + i * phentsize)
+		.find(|&phdr| field(&bytes, phdr, 4) == 1)
+		.unwrap();
+	bytes[phdr + 0x10..phdr + 0x18].copy_from_slice(&(u64::MAX - 0x10).to_le_bytes());
+	let path = fixtures::path().with_file_name("libmalformed.so");
+	std::fs::write(&path, bytes).unwrap();
+
+	// the image may fail to parse, but mustn't panic.
+	let file = img::File::open(&path).unwrap();
+	let _ = file.exports();
+	let _ = file.as_elf().unwrap().lookup("fixture_add");
+}
+
+#[cfg(target_env = "gnu")]
+#[test]
+fn test_macro_static() {
+	static LIBC: sync::LibLock = sync::LibLock::new(&["libc.so.6"]);
+
+	#[dylink(library = LIBC)]
+	extern "C" {
+		static environ: *const *const std::ffi::c_char;
+	}
+
+	unsafe {
+		assert!(!(*environ()).is_null());
+	}
+}
+
+// `struct timespec` is two 64-bit fields on 64-bit targets.
+#[cfg(all(target_env = "gnu", target_pointer_width = "64"))]
+#[test]
+fn test_symbol_version() {
+	static LIBC: sync::LibLock = sync::LibLock::new(&["libc.so.6"]);
+
+	#[dylink(library = LIBC, version = "GLIBC_2.17")]
+	extern "C" fn clock_gettime(clockid: std::ffi::c_int, tp: *mut [i64; 2]) -> std::ffi::c_int;
+
+	let mut tp = [0; 2];
+	unsafe {
+		assert_eq!(clock_gettime(1, &mut tp), 0);
+	}
+	assert_ne!(tp, [0; 2]);
+
+	let lib = Library::open("libc.so.6").unwrap();
+	assert_eq!(
+		lib.symbol_version("clock_gettime", "GLIBC_2.17").unwrap(),
+		LIBC.symbol_version("clock_gettime", "GLIBC_2.17").unwrap()
+	);
+	assert!(lib.symbol_version("clock_gettime", "GLIBC_0.0").is_err());
+	lib.close().unwrap();
+}
+
+#[test]
+fn test_loader_symbol_ordinal() {
+	use sync::Loader;
+	static LIBC: sync::LibLock = sync::LibLock::new(&["libc.so.6"]);
+
+	let loader = std::sync::Arc::new(&LIBC);
+	let err = loader.symbol_ordinal(1).unwrap_err();
+	assert_eq!(err.kind(), std::io::ErrorKind::Unsupported);
+	#[cfg(target_env = "gnu")]
+	assert_eq!(
+		loader
+			.symbol_version("clock_gettime", "GLIBC_2.17")
+			.unwrap(),
+		LIBC.symbol_version("clock_gettime", "GLIBC_2.17").unwrap()
+	);
+}
+
+#[test]
+fn test_find_symbol_in_maps() {
+	let this = Library::this();
+	assert_eq!(
+		os::linux::find_symbol_in_maps("atoi"),
+		Some(this.symbol("atoi").unwrap())
+	);
+	let lib = Library::open(fixtures::path()).unwrap();
+	assert_eq!(
+		os::linux::find_symbol_in_maps("fixture_add"),
+		Some(lib.symbol("fixture_add").unwrap())
+	);
+	assert_eq!(os::linux::find_symbol_in_maps("fixture_missing"), None);
+	lib.close().unwrap();
+}
+
+#[test]
+fn test_tls() {
+	let lib = Library::open(fixtures::path()).unwrap();
+	let image = lib.to_image().unwrap();
+	let tls = image.tls().unwrap().unwrap();
+	assert_eq!(tls.template().len(), size_of::<std::ffi::c_int>());
+	assert_eq!(
+		unsafe { *(tls.template().start as *const std::ffi::c_int) },
+		7
+	);
+	assert!(tls.size() >= tls.template().len());
+	assert!(image.tls_callbacks().unwrap().is_empty());
+	lib.close().unwrap();
+}
+
+#[test]
+fn test_open_flags() {
+	// libc is always loaded, so it can be inspected without running its initializers.
+	let libc = Library::open_with("libc.so.6", OpenFlags::INSPECT).unwrap();
+	assert!(libc.symbol("atoi").is_ok());
+
+	let lib = Library::open_with(fixtures::path(), OpenFlags::PLUGIN).unwrap();
+	assert!(lib.symbol("fixture_add").is_ok());
+	assert!(OpenFlags::PLUGIN.contains(OpenFlags::DEFAULT));
+}
+
+#[test]
+fn test_suggest_names() {
+	static LIBC: sync::LibLock = sync::LibLock::new(&["libc.so.6"]).suggest_names();
+	let err = LIBC.symbol("ATOI").unwrap_err();
+	assert!(err.to_string().contains("did you mean `atoi`"), "{err}");
+}
+
+#[test]
+fn test_open_at() {
+	let path = fixtures::path();
+	let dir = std::fs::File::open(path.parent().unwrap()).unwrap();
+	let lib = Library::open_at(&dir, path.file_name().unwrap()).unwrap();
+	assert!(lib.symbol("fixture_add").is_ok());
+	// a second library of the directory isn't taken for the first one, which is still loaded.
+	let other = fixtures::other_path().file_name().unwrap();
+	let other = Library::open_at(&dir, other).unwrap();
+	assert!(other.symbol("fixture_other").is_ok());
+	assert!(lib.symbol("fixture_other").is_err());
+	other.close().unwrap();
+	lib.close().unwrap();
+
+	let err = Library::open_at(&dir, path).unwrap_err();
+	assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+	assert!(Library::open_at(&dir, "missing.so").is_err());
+}
+
+#[test]
+fn test_sandbox_denied() {
+	// a missing library isn't mistaken for one that was denied.
+	let err = Library::open("libdylink_missing.so").unwrap_err();
+	assert_ne!(err.kind(), std::io::ErrorKind::PermissionDenied);
+	assert!(
+		err.get_ref()
+			.and_then(|err| err.downcast_ref::<SandboxDenied>())
+			.is_none()
+	);
+
+	// the loader fails to open a directory, without it being denied.
+	let dir = fixtures::path().parent().unwrap().to_path_buf();
+	let err = Library::open(dir).unwrap_err();
+	assert_ne!(err.kind(), std::io::ErrorKind::PermissionDenied);
+}
+
+#[test]
+fn test_open_multiarch() {
+	let dir = std::path::Path::new(env!("CARGO_TARGET_TMPDIR"))
+		.join(format!("multiarch-{}", std::process::id()));
+	let arch_dir = dir.join(env!("DYLINK_MULTIARCH"));
+	std::fs::create_dir_all(&arch_dir).unwrap();
+	std::fs::copy(fixtures::path(), arch_dir.join("libfixture.so")).unwrap();
+
+	let path = dir.join("libfixture.so");
+	assert!(OpenOptions::new().open(&path).is_err());
+	let lib = OpenOptions::new().multiarch(true).open(&path).unwrap();
+	assert!(lib.symbol("fixture_add").is_ok());
+	lib.close().unwrap();
+	std::fs::remove_dir_all(dir).unwrap();
+}
@@ -13,3 +13,44 @@ fn test_unix_sym_info() {
 	let info = Symbol::info(symbol);
 	assert!(info.is_ok());
 }
+
+#[cfg(not(target_os = "aix"))]
+#[test]
+fn test_unix_exports() {
+	use dylink::{
+		Symbol,
+		img,
+	};
+	let this = dylink::Library::this();
+	let symbol = this.symbol("atoi").unwrap();
+	let image = Symbol::image(symbol).unwrap();
+	let exports = image.exports().unwrap();
+	let atoi = exports.iter().find(|e| e.name() == Some("atoi")).unwrap();
+	assert_eq!(
+		image as *const img::Image as usize + atoi.rva(),
+		symbol as usize
+	);
+
+	let file = img::File::open(image.path().unwrap()).unwrap();
+	assert_eq!(file.magic(), unsafe { &*image.magic() });
+	assert_eq!(file.arch().unwrap(), image.arch().unwrap());
+	assert!(
+		file.exports()
+			.unwrap()
+			.iter()
+			.any(|e| e.name() == Some("atoi"))
+	);
+}
+
+#[cfg(not(target_os = "aix"))]
+#[test]
+fn test_symbolize() {
+	use dylink::symbolize;
+	let this = dylink::Library::this();
+	let atoi = this.symbol("atoi").unwrap();
+	let location = symbolize::resolve(atoi.cast::<u8>().wrapping_add(1).cast()).unwrap();
+	assert_eq!(location.name(), Some("atoi"));
+	assert_eq!(location.offset(), 1);
+	assert!(location.path().is_some());
+	assert!(!location.is_approximate());
+}
@@ -4,6 +4,9 @@
 #![cfg(windows)]
 use dylink::*;
 
+#[path = "fixtures/mod.rs"]
+mod fixtures;
+
 static KERNEL32: sync::LibLock = sync::LibLock::new(&["Kernel32.dll"]);
 
 #[test]
@@ -45,8 +48,8 @@ fn test_macro_impl() {
 
 #[test]
 fn test_sym_img() {
-	let lib = Library::open("Kernel32.dll").unwrap();
-	let sym = lib.symbol("SetLastError").unwrap();
+	let lib = Library::open(fixtures::path()).unwrap();
+	let sym = lib.symbol("fixture_add").unwrap();
 	let base = Symbol::image(sym);
 	assert!(base.is_some());
 	lib.close().unwrap();
@@ -54,8 +57,49 @@ fn test_sym_img() {
 
 #[test]
 fn test_path() {
-	let lib = Library::open("Kernel32.dll").unwrap();
+	let lib = Library::open(fixtures::path()).unwrap();
 	let path = lib.to_image().unwrap().path();
 	assert!(path.is_ok());
 	lib.close().unwrap();
 }
+
+#[test]
+fn test_exports() {
+	let lib = Library::open("Kernel32.dll").unwrap();
+	let image = lib.to_image().unwrap();
+	let exports = image.exports().unwrap();
+	assert!(exports.iter().any(|e| e.name() == Some("SetLastError")));
+
+	let file = img::File::open(image.path().unwrap()).unwrap();
+	assert_eq!(file.arch().unwrap(), image.arch().unwrap());
+	assert_eq!(file.exports().unwrap(), exports);
+	lib.close().unwrap();
+}
+
+#[test]
+fn test_symbol_ordinal() {
+	let lib = Library::open(fixtures::path()).unwrap();
+	let exports = lib.to_image().unwrap().exports().unwrap();
+	let add = exports
+		.iter()
+		.find(|e| e.name() == Some("fixture_add"))
+		.unwrap();
+	let ordinal = add.ordinal().unwrap() as u16;
+	assert_eq!(
+		lib.symbol_ordinal(ordinal).unwrap(),
+		lib.symbol("fixture_add").unwrap()
+	);
+	lib.close().unwrap();
+}
+
+#[test]
+fn test_known_dlls() {
+	let known = os::windows::known_dlls().unwrap();
+	assert!(
+		known
+			.iter()
+			.any(|name| name.eq_ignore_ascii_case("kernel32.dll"))
+	);
+	let drivers = os::windows::loaded_drivers().unwrap();
+	assert!(!drivers.is_empty());
+}